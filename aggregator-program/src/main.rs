@@ -0,0 +1,22 @@
+//! The aggregator program: verifies a batch of compressed proofs of the Fibonacci program and
+//! commits either all of their public values or only the Merkle root of them, so that a single
+//! SNARK settles the whole batch on-chain.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::batch::AggregationInput;
+use sha2::{Digest, Sha256};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<AggregationInput>();
+
+    // Each proof is read by the verifier from the proofs the host wrote to the stdin, in order;
+    // it is checked against the digest of the public values it is claimed to commit.
+    for public_values in &input.public_values {
+        let digest: [u8; 32] = Sha256::digest(public_values).into();
+        sp1_zkvm::lib::verify::verify_sp1_proof(&input.vkey_digest, &digest);
+    }
+
+    sp1_zkvm::io::commit_slice(&input.batch_public_values().to_abi_bytes());
+}
@@ -0,0 +1,15 @@
+//! The BLS program: verifies an aggregate BLS12-381 signature of a message by a set of public
+//! keys with the SP1 precompiles, and commits the aggregate key, the hash of the keys and the
+//! hash of the message.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::bls::BlsInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<BlsInput>();
+
+    let public_values = input.verify().expect("the aggregate signature must be valid");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
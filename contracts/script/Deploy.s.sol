@@ -40,9 +40,11 @@ contract Deploy is Script {
         // Get network name from environment or default to sepolia
         string memory network = vm.envOr("NETWORK", string("sepolia"));
         
-        // Get the verifier address for the current network
-        address verifierAddress = verifierAddresses[network];
+        // Get the verifier address for the current network, unless one was resolved and checked
+        // on-chain with `cli verifier --update-env`
+        address verifierAddress = vm.envOr("SP1_VERIFIER_ADDRESS", verifierAddresses[network]);
         require(verifierAddress != address(0), "Unsupported network");
+        require(verifierAddress.code.length > 0, "No contract at the verifier address");
         
         console.log("Deploying to network:", network);
         console.log("Using SP1 Verifier at:", verifierAddress);
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Script, console} from "forge-std/Script.sol";
+import {FibonacciOracle} from "../src/FibonacciOracle.sol";
+
+contract DeployOracle is Script {
+    function run() external {
+        // Get the program verification key from environment
+        bytes32 programVKey = vm.envOr("FIBONACCI_PROGRAM_VKEY", bytes32(0));
+        require(programVKey != bytes32(0), "FIBONACCI_PROGRAM_VKEY must be set in .env");
+
+        // Default to the SP1 Verifier Gateway
+        address verifier = vm.envOr("VERIFIER_ADDRESS", address(0x3B6041173B80E77f038f3F2C0f9744f04837185e));
+
+        console.log("==============================================");
+        console.log("Deploying FibonacciOracle Contract");
+        console.log("==============================================");
+        console.log("Program VKey:", vm.toString(programVKey));
+        console.log("SP1 Verifier:", verifier);
+
+        vm.startBroadcast();
+
+        FibonacciOracle oracle = new FibonacciOracle(verifier, programVKey);
+
+        vm.stopBroadcast();
+
+        console.log("Contract Address:", address(oracle));
+        console.log("Block Number:", block.number);
+
+        console.log("\nNext steps:");
+        console.log("1. Set FIBONACCI_ORACLE_ADDRESS in .env");
+        console.log("2. Start the fulfiller: cd ../script && cargo run --release --bin fulfill");
+    }
+}
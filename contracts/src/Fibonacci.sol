@@ -2,13 +2,13 @@
 pragma solidity ^0.8.20;
 
 import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
-
-/// @notice Struct representing the public values from the SP1 program
-struct PublicValuesStruct {
-    uint32 n;
-    uint32 a;
-    uint32 b;
-}
+import {
+    PublicValuesStruct,
+    PublicValuesLib,
+    MAX_N,
+    STATUS_OK,
+    STATUS_OVERFLOW
+} from "./generated/PublicValues.sol";
 
 /// @title Enhanced Fibonacci Verifier
 /// @author Succinct Labs (Enhanced)
@@ -34,6 +34,18 @@ contract Fibonacci {
     /// @notice Mapping to store the latest Fibonacci values for each n
     mapping(uint32 => PublicValuesStruct) public fibonacciResults;
 
+    /// @notice The status of the latest proven rejection of each n (zero if there is none)
+    mapping(uint32 => uint8) public rejectedInputs;
+
+    /// @notice The verified result of each recurrence, by `PublicValuesLib.recurrenceId`
+    mapping(bytes32 => uint64) public recurrenceResults;
+
+    /// @notice Mapping to store verified recurrences, by `PublicValuesLib.recurrenceId`
+    mapping(bytes32 => bool) public verifiedRecurrences;
+
+    /// @notice Nonces that have already been settled (replay protection)
+    mapping(uint64 => bool) public usedNonces;
+
     /// @notice Total number of proofs verified
     uint256 public totalProofsVerified;
 
@@ -46,13 +58,34 @@ contract Fibonacci {
         bytes32 proofHash
     );
 
+    event FibonacciRejected(
+        address indexed verifier,
+        uint32 indexed n,
+        uint8 status,
+        bytes32 proofHash
+    );
+
+    event RecurrenceVerified(
+        address indexed verifier,
+        bytes32 indexed recurrenceId,
+        uint32 n,
+        uint64 result,
+        bytes32 proofHash
+    );
+
     event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
 
     /// @notice Custom errors
     error InvalidProof();
     error AlreadyVerified(uint32 n);
     error InvalidInput(uint32 n);
+    error WrongChain(uint64 chainId);
+    error WrongConsumer(address consumer);
+    error NonceAlreadyUsed(uint64 nonce);
+    error ProofExpired(uint64 validUntil);
     error OnlyOwner();
+    error NotFibonacci();
+    error NotRecurrence();
 
     /// @notice Modifier to restrict access to owner only
     modifier onlyOwner() {
@@ -70,6 +103,8 @@ contract Fibonacci {
     }
 
     /// @notice The main entrypoint for verifying Fibonacci proofs
+    /// @dev A proof that the program rejected its input (see the `STATUS_*` constants) is settled
+    ///      too: it is recorded in `rejectedInputs` and returns zero values.
     /// @param _publicValues The encoded public values from the SP1 program
     /// @param _proofBytes The encoded SP1 proof
     /// @return n The input number
@@ -79,21 +114,29 @@ contract Fibonacci {
         bytes calldata _publicValues,
         bytes calldata _proofBytes
     ) public returns (uint32 n, uint32 fibN1, uint32 fibN) {
-        // Verify the SP1 proof
-        try ISP1Verifier(verifier).verifyProof(fibonacciProgramVKey, _publicValues, _proofBytes) {
-            // Proof is valid, continue
-        } catch {
-            revert InvalidProof();
+        PublicValuesStruct memory publicValues = _verifyAndDecode(_publicValues, _proofBytes);
+
+        // Only the Fibonacci sequence is recorded here (no other seeds); recurrences are settled
+        // with `verifyRecurrenceProof`
+        if (publicValues.modulus != 0 || publicValues.initialValues.length != 0) {
+            revert NotFibonacci();
         }
 
-        // Decode the public values
-        PublicValuesStruct memory publicValues = abi.decode(_publicValues, (PublicValuesStruct));
-        
-        // Basic input validation
-        if (publicValues.n > 10000) {
+        // Basic input validation (the program commits a rejection instead of values it refused
+        // to compute)
+        bool rejected = publicValues.status != STATUS_OK && publicValues.status != STATUS_OVERFLOW;
+        if (!rejected && publicValues.n > MAX_N) {
             revert InvalidInput(publicValues.n);
         }
 
+        bytes32 proofHash = keccak256(_proofBytes);
+        if (rejected) {
+            rejectedInputs[publicValues.n] = publicValues.status;
+            totalProofsVerified++;
+            emit FibonacciRejected(msg.sender, publicValues.n, publicValues.status, proofHash);
+            return (publicValues.n, 0, 0);
+        }
+
         // Optional: Prevent duplicate verifications
         // if (verifiedNumbers[publicValues.n]) {
         //     revert AlreadyVerified(publicValues.n);
@@ -105,7 +148,6 @@ contract Fibonacci {
         totalProofsVerified++;
 
         // Emit event for indexing
-        bytes32 proofHash = keccak256(_proofBytes);
         emit FibonacciVerified(
             msg.sender,
             publicValues.n,
@@ -117,6 +159,73 @@ contract Fibonacci {
         return (publicValues.n, publicValues.a, publicValues.b);
     }
 
+    /// @notice Verify a proof of a linear recurrence evaluated by the SP1 program
+    /// @param _publicValues The encoded public values from the SP1 program
+    /// @param _proofBytes The encoded SP1 proof
+    /// @return recurrenceId The id the result is recorded under
+    /// @return result The value x_n of the recurrence
+    function verifyRecurrenceProof(
+        bytes calldata _publicValues,
+        bytes calldata _proofBytes
+    ) external returns (bytes32 recurrenceId, uint64 result) {
+        PublicValuesStruct memory publicValues = _verifyAndDecode(_publicValues, _proofBytes);
+        if (publicValues.modulus == 0) {
+            revert NotRecurrence();
+        }
+
+        recurrenceId = PublicValuesLib.recurrenceId(publicValues);
+        result = publicValues.result;
+        recurrenceResults[recurrenceId] = result;
+        verifiedRecurrences[recurrenceId] = true;
+        totalProofsVerified++;
+
+        emit RecurrenceVerified(
+            msg.sender,
+            recurrenceId,
+            publicValues.n,
+            result,
+            keccak256(_proofBytes)
+        );
+    }
+
+    /// @notice Verify the proof and decode its public values, rejecting values this contract
+    ///         must not settle
+    function _verifyAndDecode(
+        bytes calldata _publicValues,
+        bytes calldata _proofBytes
+    ) internal returns (PublicValuesStruct memory publicValues) {
+        // Verify the SP1 proof
+        try ISP1Verifier(verifier).verifyProof(fibonacciProgramVKey, _publicValues, _proofBytes) {
+            // Proof is valid, continue
+        } catch {
+            revert InvalidProof();
+        }
+
+        // Decode the public values
+        publicValues = PublicValuesLib.decode(_publicValues);
+
+        // Reject proofs bound to another chain or consumer contract
+        if (publicValues.chainId != 0 && publicValues.chainId != block.chainid) {
+            revert WrongChain(publicValues.chainId);
+        }
+        if (publicValues.consumer != address(0) && publicValues.consumer != address(this)) {
+            revert WrongConsumer(publicValues.consumer);
+        }
+
+        // Reject stale proofs
+        if (publicValues.validUntil != 0 && block.timestamp > publicValues.validUntil) {
+            revert ProofExpired(publicValues.validUntil);
+        }
+
+        // Reject a second settlement of the same request
+        if (publicValues.nonce != 0) {
+            if (usedNonces[publicValues.nonce]) {
+                revert NonceAlreadyUsed(publicValues.nonce);
+            }
+            usedNonces[publicValues.nonce] = true;
+        }
+    }
+
     /// @notice View function to check if a Fibonacci number has been verified
     /// @param n The input number to check
     /// @return verified Whether the number has been verified
@@ -132,6 +241,21 @@ contract Fibonacci {
         }
     }
 
+    /// @notice Check values against the range hash of a verified proof
+    /// @param n The input number of the proof
+    /// @param start The index of the first value
+    /// @param values The Fibonacci numbers from `start` up to `n`
+    /// @return Whether the proof for `n` committed the hash of exactly these values
+    function isVerifiedRange(uint32 n, uint32 start, uint32[] calldata values)
+        external
+        view
+        returns (bool)
+    {
+        bytes32 rangeHash = fibonacciResults[n].rangeHash;
+        return verifiedNumbers[n] && rangeHash != bytes32(0)
+            && rangeHash == PublicValuesLib.rangeHash(start, values);
+    }
+
     /// @notice Batch verify multiple Fibonacci proofs
     /// @param publicValuesArray Array of encoded public values
     /// @param proofBytesArray Array of encoded proofs
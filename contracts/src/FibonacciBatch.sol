@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
+import {
+    PublicValuesStruct,
+    PublicValuesLib,
+    MAX_N,
+    STATUS_OK,
+    STATUS_OVERFLOW
+} from "./generated/PublicValues.sol";
+
+/// @notice The public values of the aggregator program (`fibonacci_lib::batch`)
+struct BatchPublicValuesStruct {
+    bytes32 programVKeyDigest;
+    uint32 count;
+    bytes32 root;
+    bytes[] publicValues;
+}
+
+/// @title Batched Fibonacci Verifier
+/// @notice Settles a batch of Fibonacci proofs with a single proof of the aggregator program.
+///         A batch commits either the public values of every item, which are all recorded at
+///         once, or only their Merkle root, after which each item is recorded on demand with a
+///         proof of inclusion instead of a SNARK.
+contract FibonacciBatch {
+    /// @notice The address of the SP1 verifier contract (or gateway).
+    address public immutable verifier;
+
+    /// @notice The verification key of the aggregator program.
+    bytes32 public immutable aggregatorProgramVKey;
+
+    /// @notice The digest of the Fibonacci program's vkey the aggregated proofs must be of
+    ///         (`SP1VerifyingKey::hash_u32`, as big-endian words).
+    bytes32 public immutable fibonacciProgramVKeyDigest;
+
+    /// @notice The Merkle roots of settled batches
+    mapping(bytes32 => bool) public settledRoots;
+
+    /// @notice Mapping to store verified Fibonacci results
+    mapping(uint32 => bool) public verifiedNumbers;
+
+    /// @notice Mapping to store the latest Fibonacci values for each n
+    mapping(uint32 => PublicValuesStruct) public fibonacciResults;
+
+    /// @notice The status of the latest proven rejection of each n (zero if there is none)
+    mapping(uint32 => uint8) public rejectedInputs;
+
+    /// @notice Nonces that have already been settled (replay protection)
+    mapping(uint64 => bool) public usedNonces;
+
+    /// @notice Events
+    event BatchSettled(bytes32 indexed root, uint32 count, bytes32 proofHash);
+    event FibonacciVerified(uint32 indexed n, uint32 fibN1, uint32 fibN, bytes32 indexed root);
+    event FibonacciRejected(uint32 indexed n, uint8 status, bytes32 indexed root);
+
+    /// @notice Custom errors
+    error InvalidProof();
+    error WrongProgram(bytes32 programVKeyDigest);
+    error UnknownRoot(bytes32 root);
+    error InvalidInclusionProof();
+    error InvalidInput(uint32 n);
+    error WrongChain(uint64 chainId);
+    error WrongConsumer(address consumer);
+    error NonceAlreadyUsed(uint64 nonce);
+    error ProofExpired(uint64 validUntil);
+    error NotFibonacci();
+
+    /// @param _verifier The address of the SP1 verifier contract
+    /// @param _aggregatorProgramVKey The verification key of the aggregator program
+    /// @param _fibonacciProgramVKeyDigest The vkey digest of the Fibonacci program
+    constructor(address _verifier, bytes32 _aggregatorProgramVKey, bytes32 _fibonacciProgramVKeyDigest) {
+        verifier = _verifier;
+        aggregatorProgramVKey = _aggregatorProgramVKey;
+        fibonacciProgramVKeyDigest = _fibonacciProgramVKeyDigest;
+    }
+
+    /// @notice Settle a batch proved by the aggregator program
+    /// @dev A batch with a zero root records its items right away; otherwise only the root is
+    ///      stored, and items are recorded with `proveItem`.
+    /// @param _publicValues The encoded public values of the aggregator program
+    /// @param _proofBytes The encoded SP1 proof
+    /// @return count The number of items in the batch
+    function settleBatch(bytes calldata _publicValues, bytes calldata _proofBytes)
+        external
+        returns (uint32 count)
+    {
+        try ISP1Verifier(verifier).verifyProof(aggregatorProgramVKey, _publicValues, _proofBytes) {
+            // Proof is valid, continue
+        } catch {
+            revert InvalidProof();
+        }
+
+        BatchPublicValuesStruct memory batch = abi.decode(_publicValues, (BatchPublicValuesStruct));
+        if (batch.programVKeyDigest != fibonacciProgramVKeyDigest) {
+            revert WrongProgram(batch.programVKeyDigest);
+        }
+
+        if (batch.root == bytes32(0)) {
+            for (uint256 i = 0; i < batch.publicValues.length; i++) {
+                _record(batch.publicValues[i], bytes32(0));
+            }
+        } else {
+            settledRoots[batch.root] = true;
+        }
+        emit BatchSettled(batch.root, batch.count, keccak256(_proofBytes));
+        return batch.count;
+    }
+
+    /// @notice Record one item of a batch settled as a Merkle root
+    /// @param root The root of the batch
+    /// @param _publicValues The encoded public values of the item
+    /// @param proof The siblings of the item's leaf, from the bottom up (saved by the
+    ///        `aggregate` script as `item_<index>_n<n>.json`)
+    /// @return n The input number
+    /// @return fibN1 The (n-1)th Fibonacci number
+    /// @return fibN The nth Fibonacci number
+    function proveItem(bytes32 root, bytes calldata _publicValues, bytes32[] calldata proof)
+        external
+        returns (uint32 n, uint32 fibN1, uint32 fibN)
+    {
+        if (!settledRoots[root]) {
+            revert UnknownRoot(root);
+        }
+        bytes32 hash = keccak256(_publicValues);
+        for (uint256 i = 0; i < proof.length; i++) {
+            hash = _node(hash, proof[i]);
+        }
+        if (hash != root) {
+            revert InvalidInclusionProof();
+        }
+        return _record(_publicValues, root);
+    }
+
+    /// @notice Check and record the public values of one Fibonacci proof, as `Fibonacci` does
+    function _record(bytes memory _publicValues, bytes32 root)
+        internal
+        returns (uint32, uint32, uint32)
+    {
+        PublicValuesStruct memory publicValues = PublicValuesLib.decode(_publicValues);
+        if (publicValues.modulus != 0 || publicValues.initialValues.length != 0) {
+            revert NotFibonacci();
+        }
+
+        bool rejected = publicValues.status != STATUS_OK && publicValues.status != STATUS_OVERFLOW;
+        if (!rejected && publicValues.n > MAX_N) {
+            revert InvalidInput(publicValues.n);
+        }
+        if (publicValues.chainId != 0 && publicValues.chainId != block.chainid) {
+            revert WrongChain(publicValues.chainId);
+        }
+        if (publicValues.consumer != address(0) && publicValues.consumer != address(this)) {
+            revert WrongConsumer(publicValues.consumer);
+        }
+        if (publicValues.validUntil != 0 && block.timestamp > publicValues.validUntil) {
+            revert ProofExpired(publicValues.validUntil);
+        }
+        if (publicValues.nonce != 0) {
+            if (usedNonces[publicValues.nonce]) {
+                revert NonceAlreadyUsed(publicValues.nonce);
+            }
+            usedNonces[publicValues.nonce] = true;
+        }
+
+        if (rejected) {
+            rejectedInputs[publicValues.n] = publicValues.status;
+            emit FibonacciRejected(publicValues.n, publicValues.status, root);
+            return (publicValues.n, 0, 0);
+        }
+        verifiedNumbers[publicValues.n] = true;
+        fibonacciResults[publicValues.n] = publicValues;
+        emit FibonacciVerified(publicValues.n, publicValues.a, publicValues.b, root);
+        return (publicValues.n, publicValues.a, publicValues.b);
+    }
+
+    /// @notice The parent of two nodes of a batch's Merkle tree (hashed in sorted order)
+    function _node(bytes32 a, bytes32 b) internal pure returns (bytes32) {
+        return a < b ? keccak256(abi.encodePacked(a, b)) : keccak256(abi.encodePacked(b, a));
+    }
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
+import {
+    PublicValuesStruct,
+    PublicValuesLib,
+    MAX_N,
+    STATUS_OK,
+    STATUS_OVERFLOW
+} from "./generated/PublicValues.sol";
+
+/// @title Fibonacci Proving Oracle
+/// @notice Consumers request Fibonacci numbers on-chain; an off-chain fulfiller (see the `fulfill`
+///         binary) generates the SP1 proof and settles it with `fulfillProof`.
+contract FibonacciOracle {
+    /// @notice A pending or fulfilled request
+    struct Request {
+        uint32 n;
+        bool exists;
+        bool fulfilled;
+        uint32 result;
+    }
+
+    /// @notice The address of the SP1 verifier contract
+    address public immutable verifier;
+
+    /// @notice The verification key for the fibonacci program
+    bytes32 public immutable fibonacciProgramVKey;
+
+    /// @notice The id of the most recent request
+    uint64 public lastRequestId;
+
+    /// @notice All requests by id
+    mapping(uint64 => Request) public requests;
+
+    /// @notice Events
+    event ProofRequested(uint32 n, uint64 indexed requestId);
+    event ProofFulfilled(uint64 indexed requestId, uint32 n, uint32 fibN);
+
+    /// @notice Custom errors
+    error InvalidInput(uint32 n);
+    error UnknownRequest(uint64 requestId);
+    error AlreadyFulfilled(uint64 requestId);
+    error MismatchedPublicValues(uint64 requestId);
+
+    /// @notice Constructor
+    /// @param _verifier The address of the SP1 verifier contract
+    /// @param _fibonacciProgramVKey The verification key for the fibonacci program
+    constructor(address _verifier, bytes32 _fibonacciProgramVKey) {
+        verifier = _verifier;
+        fibonacciProgramVKey = _fibonacciProgramVKey;
+    }
+
+    /// @notice Request a proof of the n'th Fibonacci number
+    /// @param n The input number
+    /// @return requestId The id the fulfiller must bind the proof to
+    function requestProof(uint32 n) external returns (uint64 requestId) {
+        if (n > MAX_N) revert InvalidInput(n);
+
+        requestId = ++lastRequestId;
+        requests[requestId] = Request({n: n, exists: true, fulfilled: false, result: 0});
+        emit ProofRequested(n, requestId);
+    }
+
+    /// @notice Settle a request with a proof whose public values are bound to it
+    /// @param requestId The request being fulfilled
+    /// @param publicValues The encoded public values from the SP1 program
+    /// @param proofBytes The encoded SP1 proof
+    function fulfillProof(
+        uint64 requestId,
+        bytes calldata publicValues,
+        bytes calldata proofBytes
+    ) external {
+        Request storage request = requests[requestId];
+        if (!request.exists) revert UnknownRequest(requestId);
+        if (request.fulfilled) revert AlreadyFulfilled(requestId);
+
+        ISP1Verifier(verifier).verifyProof(fibonacciProgramVKey, publicValues, proofBytes);
+
+        // The proof must answer this exact request on this chain and contract, with a Fibonacci
+        // value
+        PublicValuesStruct memory values = PublicValuesLib.decode(publicValues);
+        if (
+            values.n != request.n || values.nonce != requestId || values.chainId != block.chainid
+                || values.consumer != address(this)
+                || (values.status != STATUS_OK && values.status != STATUS_OVERFLOW)
+                || values.modulus != 0 || values.initialValues.length != 0
+        ) {
+            revert MismatchedPublicValues(requestId);
+        }
+
+        request.fulfilled = true;
+        request.result = values.b;
+        emit ProofFulfilled(requestId, values.n, values.b);
+    }
+}
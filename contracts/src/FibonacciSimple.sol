@@ -2,13 +2,7 @@
 pragma solidity ^0.8.20;
 
 import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
-
-/// @notice Struct representing the public values from the SP1 program
-struct PublicValuesStruct {
-    uint32 n;
-    uint32 a;
-    uint32 b;
-}
+import {PublicValuesStruct, PublicValuesLib, STATUS_OK, STATUS_OVERFLOW} from "./generated/PublicValues.sol";
 
 /// @title Simple Fibonacci Verifier using SP1VerifierGateway
 contract FibonacciSimple {
@@ -20,6 +14,15 @@ contract FibonacciSimple {
     
     /// @notice Event emitted when a proof is verified
     event ProofVerified(uint32 indexed n, uint32 fibN);
+
+    /// @notice Event emitted when a proof that the program rejected n is verified
+    event ProofRejected(uint32 indexed n, uint8 status);
+
+    /// @notice Errors for proofs bound to another chain or consumer contract
+    error WrongChain(uint64 chainId);
+    error WrongConsumer(address consumer);
+    error ProofExpired(uint64 validUntil);
+    error NotFibonacci();
     
     /// @notice Constructor
     constructor(bytes32 _programVKey) {
@@ -35,10 +38,20 @@ contract FibonacciSimple {
         ISP1Verifier(SP1_VERIFIER).verifyProof(fibonacciProgramVKey, publicValues, proofBytes);
         
         // Decode the public values
-        PublicValuesStruct memory values = abi.decode(publicValues, (PublicValuesStruct));
+        PublicValuesStruct memory values = PublicValuesLib.decode(publicValues);
+
+        // Reject proofs bound to another chain or consumer contract
+        if (values.chainId != 0 && values.chainId != block.chainid) revert WrongChain(values.chainId);
+        if (values.consumer != address(0) && values.consumer != address(this)) revert WrongConsumer(values.consumer);
+        if (values.validUntil != 0 && block.timestamp > values.validUntil) revert ProofExpired(values.validUntil);
+        if (values.modulus != 0 || values.initialValues.length != 0) revert NotFibonacci();
         
         // Emit event
-        emit ProofVerified(values.n, values.b);
+        if (values.status != STATUS_OK && values.status != STATUS_OVERFLOW) {
+            emit ProofRejected(values.n, values.status);
+        } else {
+            emit ProofVerified(values.n, values.b);
+        }
     }
     
     /// @notice Get the program verification key
@@ -53,7 +66,7 @@ contract FibonacciSimple {
     
     /// @notice Test function to decode public values without verification
     function testDecodePublicValues(bytes calldata publicValues) external pure returns (uint32 n, uint32 a, uint32 b) {
-        PublicValuesStruct memory values = abi.decode(publicValues, (PublicValuesStruct));
+        PublicValuesStruct memory values = PublicValuesLib.decode(publicValues);
         return (values.n, values.a, values.b);
     }
 } 
\ No newline at end of file
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT
+// Generated by `cargo run --bin cli -- codegen` from `fibonacci-lib`. Do not edit.
+pragma solidity ^0.8.20;
+
+/// @notice The largest `n` the SP1 program accepts
+uint32 constant MAX_N = 10000;
+
+/// @notice The largest number of values the SP1 program commits in a single range
+uint32 constant MAX_RANGE_LEN = 1000;
+
+/// @notice The largest order of a recurrence the SP1 program evaluates
+uint32 constant MAX_ORDER = 16;
+
+/// @notice The outcomes the SP1 program commits in `status`
+uint8 constant STATUS_OK = 0;
+uint8 constant STATUS_INPUT_TOO_LARGE = 1;
+uint8 constant STATUS_OVERFLOW = 2;
+uint8 constant STATUS_INVALID_RANGE = 3;
+uint8 constant STATUS_INVALID_RECURRENCE = 4;
+
+/// @notice Struct representing the public values from the SP1 program
+struct PublicValuesStruct {
+    uint32 n;
+    uint32 a;
+    uint32 b;
+    uint32[] fibValues;
+    uint64 chainId;
+    address consumer;
+    uint64 nonce;
+    uint64 validUntil;
+    uint8 status;
+    bytes32 rangeHash;
+    uint64[] coefficients;
+    uint64[] initialValues;
+    uint64 modulus;
+    uint64 result;
+}
+
+/// @notice Helpers for the public values committed by the SP1 program
+library PublicValuesLib {
+    /// @notice Decode the ABI-encoded public values
+    function decode(bytes memory publicValues) internal pure returns (PublicValuesStruct memory) {
+        return abi.decode(publicValues, (PublicValuesStruct));
+    }
+
+    /// @notice The hash the SP1 program commits in `rangeHash` for `values` = F(start..=n)
+    function rangeHash(uint32 start, uint32[] memory values) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(start, values));
+    }
+
+    /// @notice The id of the recurrence and index whose result the public values commit
+    function recurrenceId(PublicValuesStruct memory values) internal pure returns (bytes32) {
+        return keccak256(abi.encode(values.coefficients, values.initialValues, values.modulus, values.n));
+    }
+}
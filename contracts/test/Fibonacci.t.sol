@@ -4,6 +4,9 @@ pragma solidity ^0.8.20;
 import {Test, console} from "forge-std/Test.sol";
 import {stdJson} from "forge-std/StdJson.sol";
 import {Fibonacci} from "../src/Fibonacci.sol";
+import {
+    PublicValuesStruct, PublicValuesLib, MAX_N, STATUS_INPUT_TOO_LARGE
+} from "../src/generated/PublicValues.sol";
 import {SP1VerifierGateway} from "@sp1-contracts/SP1VerifierGateway.sol";
 
 struct SP1ProofFixtureJson {
@@ -57,6 +60,100 @@ contract FibonacciGroth16Test is Test {
 
         fibonacci.verifyFibonacciProof(fixture.publicValues, fakeProof);
     }
+
+    function test_RejectedInputProof() public {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+
+        vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
+
+        PublicValuesStruct memory rejection;
+        rejection.n = MAX_N + 1;
+        rejection.status = STATUS_INPUT_TOO_LARGE;
+
+        (uint32 n, uint32 a, uint32 b) = fibonacci.verifyFibonacciProof(abi.encode(rejection), fixture.proof);
+        assert(n == MAX_N + 1);
+        assert(a == 0 && b == 0);
+        assert(fibonacci.rejectedInputs(MAX_N + 1) == STATUS_INPUT_TOO_LARGE);
+        assert(!fibonacci.verifiedNumbers(MAX_N + 1));
+    }
+
+    function test_VerifiedRangeHash() public {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+
+        vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
+
+        uint32[] memory values = new uint32[](3);
+        values[0] = 2;
+        values[1] = 3;
+        values[2] = 5;
+        PublicValuesStruct memory range;
+        range.n = 5;
+        range.a = 3;
+        range.b = 5;
+        range.rangeHash = PublicValuesLib.rangeHash(3, values);
+
+        fibonacci.verifyFibonacciProof(abi.encode(range), fixture.proof);
+        assert(fibonacci.isVerifiedRange(5, 3, values));
+        assert(!fibonacci.isVerifiedRange(5, 2, values));
+        values[2] = 6;
+        assert(!fibonacci.isVerifiedRange(5, 3, values));
+    }
+
+    function test_RecurrenceProof() public {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+
+        vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
+
+        // Lucas numbers: L(10) = 123
+        PublicValuesStruct memory lucas;
+        lucas.n = 10;
+        lucas.coefficients = new uint64[](2);
+        lucas.coefficients[0] = 1;
+        lucas.coefficients[1] = 1;
+        lucas.initialValues = new uint64[](2);
+        lucas.initialValues[0] = 2;
+        lucas.initialValues[1] = 1;
+        lucas.modulus = 1000000007;
+        lucas.result = 123;
+
+        (bytes32 id, uint64 result) = fibonacci.verifyRecurrenceProof(abi.encode(lucas), fixture.proof);
+        assert(id == PublicValuesLib.recurrenceId(lucas));
+        assert(result == 123);
+        assert(fibonacci.verifiedRecurrences(id));
+        assert(fibonacci.recurrenceResults(id) == 123);
+        assert(!fibonacci.verifiedNumbers(10));
+    }
+
+    function testRevert_RecurrenceAsFibonacci() public {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+
+        vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
+
+        PublicValuesStruct memory recurrence;
+        recurrence.n = 10;
+        recurrence.modulus = 7;
+
+        vm.expectRevert(Fibonacci.NotFibonacci.selector);
+        fibonacci.verifyFibonacciProof(abi.encode(recurrence), fixture.proof);
+    }
+
+    function testRevert_LucasAsFibonacci() public {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+
+        vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
+
+        // Lucas numbers: seeds 2, 1
+        PublicValuesStruct memory lucas;
+        lucas.n = 10;
+        lucas.a = 123;
+        lucas.b = 199;
+        lucas.initialValues = new uint64[](2);
+        lucas.initialValues[0] = 2;
+        lucas.initialValues[1] = 1;
+
+        vm.expectRevert(Fibonacci.NotFibonacci.selector);
+        fibonacci.verifyFibonacciProof(abi.encode(lucas), fixture.proof);
+    }
 }
 
 
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import {Test} from "forge-std/Test.sol";
+import {FibonacciBatch, BatchPublicValuesStruct} from "../src/FibonacciBatch.sol";
+import {PublicValuesStruct} from "../src/generated/PublicValues.sol";
+import {SP1VerifierGateway} from "@sp1-contracts/SP1VerifierGateway.sol";
+
+contract FibonacciBatchTest is Test {
+    bytes32 constant AGGREGATOR_VKEY = bytes32(uint256(1));
+    bytes32 constant PROGRAM_DIGEST = bytes32(uint256(2));
+
+    address verifier;
+    FibonacciBatch public batch;
+
+    function setUp() public {
+        verifier = address(new SP1VerifierGateway(address(1)));
+        batch = new FibonacciBatch(verifier, AGGREGATOR_VKEY, PROGRAM_DIGEST);
+        vm.mockCall(verifier, abi.encodeWithSelector(SP1VerifierGateway.verifyProof.selector), abi.encode(true));
+    }
+
+    function item(uint32 n, uint32 a, uint32 b) internal pure returns (bytes memory) {
+        PublicValuesStruct memory values;
+        values.n = n;
+        values.a = a;
+        values.b = b;
+        return abi.encode(values);
+    }
+
+    function node(bytes32 a, bytes32 b) internal pure returns (bytes32) {
+        return a < b ? keccak256(abi.encodePacked(a, b)) : keccak256(abi.encodePacked(b, a));
+    }
+
+    function settle(bytes32 root, bytes[] memory items) internal {
+        BatchPublicValuesStruct memory values;
+        values.programVKeyDigest = PROGRAM_DIGEST;
+        values.count = 3;
+        values.root = root;
+        values.publicValues = items;
+        batch.settleBatch(abi.encode(values), hex"00");
+    }
+
+    function test_SettleAllItems() public {
+        bytes[] memory items = new bytes[](3);
+        items[0] = item(10, 34, 55);
+        items[1] = item(11, 55, 89);
+        items[2] = item(12, 89, 144);
+        settle(bytes32(0), items);
+
+        assert(batch.verifiedNumbers(10) && batch.verifiedNumbers(11) && batch.verifiedNumbers(12));
+    }
+
+    function test_ProveItemsOfMerkleRoot() public {
+        // Three leaves: the third is promoted to the second level unchanged.
+        bytes32 leaf0 = keccak256(item(10, 34, 55));
+        bytes32 leaf1 = keccak256(item(11, 55, 89));
+        bytes32 leaf2 = keccak256(item(12, 89, 144));
+        bytes32 root = node(node(leaf0, leaf1), leaf2);
+        settle(root, new bytes[](0));
+        assert(!batch.verifiedNumbers(10));
+
+        bytes32[] memory proof = new bytes32[](2);
+        proof[0] = leaf0;
+        proof[1] = leaf2;
+        (uint32 n, uint32 a, uint32 b) = batch.proveItem(root, item(11, 55, 89), proof);
+        assert(n == 11 && a == 55 && b == 89);
+        assert(batch.verifiedNumbers(11));
+
+        bytes32[] memory promoted = new bytes32[](1);
+        promoted[0] = node(leaf0, leaf1);
+        batch.proveItem(root, item(12, 89, 144), promoted);
+        assert(batch.verifiedNumbers(12));
+    }
+
+    function testRevert_ItemNotInBatch() public {
+        bytes32 leaf0 = keccak256(item(10, 34, 55));
+        bytes32 leaf1 = keccak256(item(11, 55, 89));
+        bytes32 root = node(leaf0, leaf1);
+        settle(root, new bytes[](0));
+
+        bytes32[] memory proof = new bytes32[](1);
+        proof[0] = leaf0;
+        vm.expectRevert(FibonacciBatch.InvalidInclusionProof.selector);
+        batch.proveItem(root, item(11, 55, 90), proof);
+    }
+
+    function testRevert_UnknownRoot() public {
+        bytes32 root = keccak256("unsettled");
+        vm.expectRevert(abi.encodeWithSelector(FibonacciBatch.UnknownRoot.selector, root));
+        batch.proveItem(root, item(10, 34, 55), new bytes32[](0));
+    }
+
+    function testRevert_WrongProgram() public {
+        BatchPublicValuesStruct memory values;
+        values.programVKeyDigest = bytes32(uint256(3));
+        values.root = bytes32(uint256(4));
+        vm.expectRevert(abi.encodeWithSelector(FibonacciBatch.WrongProgram.selector, values.programVKeyDigest));
+        batch.settleBatch(abi.encode(values), hex"00");
+    }
+}
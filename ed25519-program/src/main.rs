@@ -0,0 +1,15 @@
+//! The Ed25519 program: verifies an Ed25519 signature over a message and commits the public key
+//! and the hash of the message. The curve arithmetic runs on the SP1 precompiles through the
+//! patched `curve25519-dalek`.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::ed25519::Ed25519Input;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<Ed25519Input>();
+
+    let public_values = input.verify().expect("the signature must be valid");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
@@ -0,0 +1,29 @@
+//! Hashes its input with SHA-256 and Keccak-256 and commits both digests.
+//!
+//! The program is built twice: as `hashing-program` with the vanilla `sha2` and `tiny-keccak`
+//! crates, and as `hashing-program-patched` with the SP1 patches that route them through the
+//! zkVM's precompiles. `cli hash-bench` executes both to compare their cycles.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sha2::{Digest, Sha256};
+use tiny_keccak::{Hasher, Keccak};
+
+pub fn main() {
+    let data = sp1_zkvm::io::read_vec();
+
+    println!("cycle-tracker-report-start: sha256");
+    let sha256 = Sha256::digest(&data);
+    println!("cycle-tracker-report-end: sha256");
+
+    println!("cycle-tracker-report-start: keccak256");
+    let mut keccak = Keccak::v256();
+    keccak.update(&data);
+    let mut keccak256 = [0u8; 32];
+    keccak.finalize(&mut keccak256);
+    println!("cycle-tracker-report-end: keccak256");
+
+    sp1_zkvm::io::commit_slice(&sha256);
+    sp1_zkvm::io::commit_slice(&keccak256);
+}
@@ -0,0 +1,14 @@
+//! The header chain program: verifies that a sequence of Ethereum block headers link by parent
+//! hash and commits the first and last hashes and heights and the total difficulty.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::headers::HeaderChainInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<HeaderChainInput>();
+
+    let public_values = input.verify().expect("the headers must form a chain");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
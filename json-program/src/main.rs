@@ -0,0 +1,14 @@
+//! The JSON program: extracts a field from a private JSON document and commits the hash of the
+//! document, the path of the field and the hash of its value, so a property of the document (e.g.
+//! an API response) can be proven without revealing the rest of it.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::json::JsonInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<JsonInput>();
+
+    sp1_zkvm::io::commit_slice(&input.public_values().to_abi_bytes());
+}
@@ -0,0 +1,14 @@
+//! The JWT program: verifies the RS256 or ES256 signature of a JWT and its expiry at a given
+//! time, and commits the hash of the public key and of the selected claims.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::jwt::JwtInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<JwtInput>();
+
+    let public_values = input.verify().expect("the token must be valid");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
@@ -0,0 +1,65 @@
+//! Benchmarks of the logic shared by the program and the scripts.
+//!
+//! Run with `cargo bench -p fibonacci-lib`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fibonacci_lib::recurrence::{Recurrence, MAX_ORDER};
+use fibonacci_lib::{
+    compute_public_values, fibonacci, fibonacci_fast_doubling, ProgramInput, PublicValuesStruct,
+    MAX_N, MAX_RANGE_LEN,
+};
+
+/// The inputs each benchmark is run with.
+const SIZES: [u32; 4] = [10, 100, 1000, MAX_N];
+
+fn bench_fibonacci(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci");
+    for n in SIZES {
+        group.bench_with_input(BenchmarkId::new("naive", n), &n, |b, &n| {
+            b.iter(|| fibonacci(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("fast_doubling", n), &n, |b, &n| {
+            b.iter(|| fibonacci_fast_doubling(black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_abi(c: &mut Criterion) {
+    let mut group = c.benchmark_group("public_values_abi");
+    for n in SIZES {
+        // Commit the longest range allowed, so the size of `fibValues` grows with `n`.
+        let range_start = n.saturating_sub(MAX_RANGE_LEN - 1);
+        let input = ProgramInput { range_start: Some(range_start), ..ProgramInput::new(n) };
+        let public_values = compute_public_values(&input);
+        let encoded = public_values.to_abi_bytes();
+        group.bench_with_input(BenchmarkId::new("encode", n), &public_values, |b, values| {
+            b.iter(|| black_box(values).to_abi_bytes())
+        });
+        group.bench_with_input(BenchmarkId::new("decode", n), &encoded, |b, encoded| {
+            b.iter(|| PublicValuesStruct::from_abi_bytes(black_box(encoded)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_recurrence(c: &mut Criterion) {
+    let mut group = c.benchmark_group("recurrence");
+    // The cost grows with the order, so compare Fibonacci with the largest order allowed.
+    let order = MAX_ORDER as usize;
+    let fibonacci = Recurrence::fibonacci(1_000_000_007);
+    let largest =
+        Recurrence { coefficients: vec![1; order], initial: vec![1; order], modulus: u64::MAX };
+    for n in SIZES {
+        group.bench_with_input(BenchmarkId::new("order_2", n), &n, |b, &n| {
+            b.iter(|| fibonacci.evaluate(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new(format!("order_{}", order), n), &n, |b, &n| {
+            b.iter(|| largest.evaluate(black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_fibonacci, bench_abi, bench_recurrence);
+criterion_main!(benches);
@@ -0,0 +1,205 @@
+//! Batches of Fibonacci proofs verified together by the aggregator program, and the Merkle tree
+//! it can commit to instead of the individual public values.
+//!
+//! The tree hashes pairs of nodes in sorted order (as OpenZeppelin's `MerkleProof` does), so a
+//! proof of inclusion is just the list of siblings from the leaf up. A level with an odd number of
+//! nodes promotes its last node unchanged.
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Bytes, B256};
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// The public values of the aggregator program: the Fibonacci proofs it verified, either
+    /// one by one (`publicValues`) or as the Merkle root of their public values (`root`, with
+    /// `publicValues` left empty).
+    #[derive(Serialize, Deserialize)]
+    struct BatchPublicValuesStruct {
+        bytes32 programVKeyDigest;
+        uint32 count;
+        bytes32 root;
+        bytes[] publicValues;
+    }
+}
+
+impl BatchPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the aggregator program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the aggregator program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// The inputs passed from the host to the aggregator program, followed by one compressed proof
+/// of the Fibonacci program for each entry of `public_values`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregationInput {
+    /// The digest of the Fibonacci program's vkey (`SP1VerifyingKey::hash_u32`).
+    pub vkey_digest: [u32; 8],
+    /// The public values of each proof, in order.
+    pub public_values: Vec<Vec<u8>>,
+    /// Commit only the Merkle root of the public values instead of the values themselves.
+    pub merkle_root: bool,
+}
+
+impl AggregationInput {
+    /// The public values the aggregator program commits for this input.
+    pub fn batch_public_values(&self) -> BatchPublicValuesStruct {
+        let leaves: Vec<_> = self.public_values.iter().map(|values| leaf_hash(values)).collect();
+        let (root, public_values) = if self.merkle_root {
+            (merkle_root(&leaves), Vec::new())
+        } else {
+            (B256::ZERO, self.public_values.iter().cloned().map(Bytes::from).collect())
+        };
+        BatchPublicValuesStruct {
+            programVKeyDigest: vkey_digest_bytes(&self.vkey_digest),
+            count: self.public_values.len() as u32,
+            root,
+            publicValues: public_values,
+        }
+    }
+}
+
+/// The vkey digest as the aggregator program commits it: its words, big-endian.
+pub fn vkey_digest_bytes(digest: &[u32; 8]) -> B256 {
+    let mut bytes = [0u8; 32];
+    for (i, word) in digest.iter().enumerate() {
+        bytes[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    B256::from(bytes)
+}
+
+/// The leaf of the public values of one proof. Public values are always longer than the 64
+/// bytes an inner node hashes, so a leaf can never be passed off as an inner node.
+pub fn leaf_hash(public_values: &[u8]) -> B256 {
+    keccak256(public_values)
+}
+
+/// The Merkle root of `leaves` (zero if there are none).
+pub fn merkle_root(leaves: &[B256]) -> B256 {
+    let mut level = leaves.to_vec();
+    if level.is_empty() {
+        return B256::ZERO;
+    }
+    while level.len() > 1 {
+        level = parents(&level);
+    }
+    level[0]
+}
+
+/// The siblings proving that the leaf at `index` is in the tree of `leaves`, from the bottom up,
+/// or `None` if there is no such leaf.
+pub fn merkle_proof(leaves: &[B256], mut index: usize) -> Option<Vec<B256>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut level = leaves.to_vec();
+    let mut proof = Vec::new();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = parents(&level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Whether `proof` proves that `leaf` is in the tree with `root`.
+pub fn verify_merkle_proof(leaf: B256, proof: &[B256], root: B256) -> bool {
+    proof.iter().fold(leaf, |hash, sibling| node(hash, *sibling)) == root
+}
+
+/// The level of the tree above `level`.
+fn parents(level: &[B256]) -> Vec<B256> {
+    level.chunks(2).map(|pair| pair.get(1).map_or(pair[0], |b| node(pair[0], *b))).collect()
+}
+
+/// The parent of two nodes.
+fn node(a: B256, b: B256) -> B256 {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut pair = [0u8; 64];
+    pair[..32].copy_from_slice(low.as_slice());
+    pair[32..].copy_from_slice(high.as_slice());
+    keccak256(pair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<B256> {
+        (0..count).map(|i| leaf_hash(&[i as u8; 96])).collect()
+    }
+
+    #[test]
+    fn root_of_small_trees() {
+        assert_eq!(merkle_root(&[]), B256::ZERO);
+        let [a, b, c] = leaves(3).try_into().unwrap();
+        assert_eq!(merkle_root(&[a]), a);
+        assert_eq!(merkle_root(&[a, b]), node(a, b));
+        assert_eq!(merkle_root(&[b, a]), node(a, b));
+        // The odd node is promoted unchanged.
+        assert_eq!(merkle_root(&[a, b, c]), node(node(a, b), c));
+    }
+
+    #[test]
+    fn node_hashes_the_sorted_pair() {
+        let [a, b] = leaves(2).try_into().unwrap();
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        assert_eq!(node(a, b), keccak256([low.as_slice(), high.as_slice()].concat()));
+    }
+
+    #[test]
+    fn every_proof_verifies() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+            let root = merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof(&leaves, index).unwrap();
+                assert!(verify_merkle_proof(*leaf, &proof, root), "leaf {} of {}", index, count);
+            }
+            assert_eq!(merkle_proof(&leaves, count), None);
+        }
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let leaves = leaves(5);
+        let root = merkle_root(&leaves);
+        let proof = merkle_proof(&leaves, 2).unwrap();
+        assert!(!verify_merkle_proof(leaves[3], &proof, root));
+        assert!(!verify_merkle_proof(leaves[2], &proof, B256::ZERO));
+        assert!(!verify_merkle_proof(leaves[2], &proof[1..], root));
+        let mut tampered = proof.clone();
+        tampered[0] = B256::repeat_byte(1);
+        assert!(!verify_merkle_proof(leaves[2], &tampered, root));
+    }
+
+    #[test]
+    fn batch_public_values_commit_values_or_root() {
+        let mut input = AggregationInput {
+            vkey_digest: [1, 2, 3, 4, 5, 6, 7, 8],
+            public_values: vec![vec![1; 96], vec![2; 96], vec![3; 96]],
+            merkle_root: false,
+        };
+        let values = input.batch_public_values();
+        assert_eq!(values.count, 3);
+        assert_eq!(values.root, B256::ZERO);
+        assert_eq!(values.publicValues.len(), 3);
+        assert_eq!(values.programVKeyDigest[..8], [0, 0, 0, 1, 0, 0, 0, 2]);
+
+        input.merkle_root = true;
+        let values = input.batch_public_values();
+        let leaves: Vec<_> = input.public_values.iter().map(|values| leaf_hash(values)).collect();
+        assert_eq!(values.root, merkle_root(&leaves));
+        assert!(values.publicValues.is_empty());
+        let decoded = BatchPublicValuesStruct::from_abi_bytes(&values.to_abi_bytes()).unwrap();
+        assert_eq!(decoded.root, values.root);
+    }
+}
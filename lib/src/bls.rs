@@ -0,0 +1,125 @@
+//! Verifying an aggregate BLS12-381 signature, as the BLS program does to prove that a set of
+//! keys (e.g. the validators of an attestation) all signed a message.
+//!
+//! Signatures follow the Ethereum consensus layer: public keys are compressed G1 points (48
+//! bytes), signatures compressed G2 points (96 bytes), and messages are hashed to G2 with the
+//! proof-of-possession ciphersuite [`DST`]. Every key signs the same message, so the aggregate
+//! signature is checked against the sum of the keys with a single pairing check.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Bytes};
+use alloy_sol_types::{sol, SolType};
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt};
+use serde::{Deserialize, Serialize};
+
+/// The domain separation tag of the `BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_` ciphersuite,
+/// which Ethereum signs with.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+sol! {
+    /// The public values of the BLS program.
+    #[derive(Serialize, Deserialize)]
+    struct BlsPublicValuesStruct {
+        bytes aggregatePublicKey;
+        bytes32 publicKeysHash;
+        uint32 signerCount;
+        bytes32 messageHash;
+    }
+}
+
+/// The input passed from the host to the BLS program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlsInput {
+    /// The 48-byte compressed public keys of the signers.
+    pub public_keys: Vec<Vec<u8>>,
+    /// The 96-byte compressed aggregate signature (see [`aggregate_signatures`]).
+    pub signature: Vec<u8>,
+    pub message: Vec<u8>,
+}
+
+impl BlsInput {
+    /// Verify that every key signed the message, and return the public values the BLS program
+    /// commits if they did. `publicKeysHash` is the keccak256 hash of the keys concatenated in
+    /// order, and `aggregatePublicKey` their compressed sum.
+    pub fn verify(&self) -> Result<BlsPublicValuesStruct, String> {
+        if self.public_keys.is_empty() {
+            return Err("there are no public keys".into());
+        }
+        let mut aggregate = G1Projective::identity();
+        for (index, key) in self.public_keys.iter().enumerate() {
+            aggregate += decode_public_key(key)
+                .map_err(|err| format!("invalid public key {}: {}", index, err))?;
+        }
+        let aggregate = G1Affine::from(aggregate);
+        let signature = decode_signature(&self.signature)?;
+        let hash = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v0_9::Sha256>>>::hash_to_curve(
+            &self.message,
+            DST,
+        );
+        // e(aggregate, H(m)) == e(g1, signature), checked as a product with one final
+        // exponentiation.
+        let check = multi_miller_loop(&[
+            (&aggregate, &G2Prepared::from(G2Affine::from(hash))),
+            (&-G1Affine::generator(), &G2Prepared::from(signature)),
+        ])
+        .final_exponentiation();
+        if check != Gt::identity() {
+            return Err("invalid signature".into());
+        }
+        Ok(BlsPublicValuesStruct {
+            aggregatePublicKey: Bytes::copy_from_slice(&aggregate.to_compressed()),
+            publicKeysHash: keccak256(self.public_keys.concat()),
+            signerCount: self.public_keys.len() as u32,
+            messageHash: keccak256(&self.message),
+        })
+    }
+}
+
+impl BlsPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the BLS program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the BLS program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// Aggregate compressed signatures of the same message into one.
+pub fn aggregate_signatures(signatures: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+    if signatures.is_empty() {
+        return Err("there are no signatures".into());
+    }
+    let mut aggregate = G2Projective::identity();
+    for (index, signature) in signatures.iter().enumerate() {
+        aggregate += decode_signature(signature)
+            .map_err(|err| format!("signature {}: {}", index, err))?;
+    }
+    Ok(G2Affine::from(aggregate).to_compressed().to_vec())
+}
+
+/// Decode a compressed public key, which must be in the subgroup and not the identity.
+fn decode_public_key(key: &[u8]) -> Result<G1Affine, String> {
+    let key: [u8; 48] =
+        key.try_into().map_err(|_| format!("a public key is 48 bytes, got {}", key.len()))?;
+    let key = Option::<G1Affine>::from(G1Affine::from_compressed(&key))
+        .ok_or("not a point of the subgroup")?;
+    if bool::from(key.is_identity()) {
+        return Err("the identity is not a valid public key".into());
+    }
+    Ok(key)
+}
+
+/// Decode a compressed signature, which must be in the subgroup.
+fn decode_signature(signature: &[u8]) -> Result<G2Affine, String> {
+    let signature: [u8; 96] = signature
+        .try_into()
+        .map_err(|_| format!("a signature is 96 bytes, got {}", signature.len()))?;
+    Option::<G2Affine>::from(G2Affine::from_compressed(&signature))
+        .ok_or_else(|| "invalid signature: not a point of the subgroup".into())
+}
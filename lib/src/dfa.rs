@@ -0,0 +1,120 @@
+//! Matching private text against a public regular expression, as the regex program does.
+//!
+//! Compiling a regex is far too expensive to do in the program, so the host compiles it into a
+//! [`Dfa`] and the program only runs the DFA over the text. The program commits the hash of the
+//! pattern and of the DFA alongside the result: anyone can compile the pattern again and check
+//! that it gives the committed DFA, so the host cannot substitute another one.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256};
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// The public values of the regex program.
+    #[derive(Serialize, Deserialize)]
+    struct RegexPublicValuesStruct {
+        bytes32 patternHash;
+        bytes32 dfaHash;
+        bytes32 textHash;
+        bool matched;
+    }
+}
+
+/// A deterministic finite automaton over bytes, which reports whether the text contains a match
+/// of its pattern.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dfa {
+    pub start: u32,
+    /// The next state of each state on each byte: `transitions[state * 256 + byte]`.
+    pub transitions: Vec<u32>,
+    /// Whether reaching each state means a match was found.
+    pub accepting: Vec<bool>,
+    /// Whether ending the text in each state completes a match (e.g. of a pattern ending in `$`).
+    pub accepting_at_end: Vec<bool>,
+}
+
+impl Dfa {
+    /// The number of states.
+    pub fn states(&self) -> usize {
+        self.accepting.len()
+    }
+
+    /// Check that the tables are consistent, so that running the DFA cannot go out of bounds.
+    pub fn validate(&self) -> Result<(), String> {
+        let states = self.states();
+        if self.transitions.len() != states * 256 || self.accepting_at_end.len() != states {
+            return Err(format!("the tables do not describe the same {} states", states));
+        }
+        if let Some(state) =
+            core::iter::once(&self.start).chain(&self.transitions).find(|s| **s as usize >= states)
+        {
+            return Err(format!("state {} does not exist", state));
+        }
+        Ok(())
+    }
+
+    /// Whether `text` contains a match. The DFA must be valid.
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        let mut state = self.start as usize;
+        for &byte in text {
+            if self.accepting[state] {
+                return true;
+            }
+            state = self.transitions[state * 256 + byte as usize] as usize;
+        }
+        self.accepting[state] || self.accepting_at_end[state]
+    }
+
+    /// The hash of the DFA: keccak256 of its start state, number of states and transitions as
+    /// little-endian `u32`s, followed by one byte per state for each of `accepting` and
+    /// `accepting_at_end`.
+    pub fn hash(&self) -> B256 {
+        let mut bytes = Vec::with_capacity(8 + self.transitions.len() * 4 + self.states() * 2);
+        bytes.extend(self.start.to_le_bytes());
+        bytes.extend((self.states() as u32).to_le_bytes());
+        for state in &self.transitions {
+            bytes.extend(state.to_le_bytes());
+        }
+        bytes.extend(self.accepting.iter().map(|accepting| *accepting as u8));
+        bytes.extend(self.accepting_at_end.iter().map(|accepting| *accepting as u8));
+        keccak256(bytes)
+    }
+}
+
+/// The input passed from the host to the regex program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegexInput {
+    pub pattern: String,
+    /// The DFA the host compiled `pattern` into.
+    pub dfa: Dfa,
+    /// The private text to match.
+    pub text: Vec<u8>,
+}
+
+impl RegexInput {
+    /// Run the DFA over the text, and return the public values the regex program commits.
+    pub fn public_values(&self) -> Result<RegexPublicValuesStruct, String> {
+        self.dfa.validate()?;
+        Ok(RegexPublicValuesStruct {
+            patternHash: keccak256(self.pattern.as_bytes()),
+            dfaHash: self.dfa.hash(),
+            textHash: keccak256(&self.text),
+            matched: self.dfa.is_match(&self.text),
+        })
+    }
+}
+
+impl RegexPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the regex program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the regex program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
@@ -0,0 +1,64 @@
+//! Verifying an Ed25519 signature, as the Ed25519 program does to prove that a message was signed
+//! by a key, with the signature itself kept private.
+//!
+//! The program commits the public key and the keccak256 hash of the message. It only commits for
+//! a valid signature: an invalid one makes it panic, so it cannot be proven.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256};
+use alloy_sol_types::{sol, SolType};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// The public values of the Ed25519 program.
+    #[derive(Serialize, Deserialize)]
+    struct Ed25519PublicValuesStruct {
+        bytes32 publicKey;
+        bytes32 messageHash;
+    }
+}
+
+/// The input passed from the host to the Ed25519 program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ed25519Input {
+    /// The 32-byte compressed public key.
+    pub public_key: [u8; 32],
+    /// The 64-byte signature.
+    pub signature: Vec<u8>,
+    pub message: Vec<u8>,
+}
+
+impl Ed25519Input {
+    /// Verify the signature (strictly, rejecting weak keys and malleable signatures), and return
+    /// the public values the Ed25519 program commits if it is valid.
+    pub fn verify(&self) -> Result<Ed25519PublicValuesStruct, String> {
+        let key = VerifyingKey::from_bytes(&self.public_key)
+            .map_err(|err| format!("invalid public key: {}", err))?;
+        let signature: [u8; 64] = self
+            .signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("a signature is 64 bytes, got {}", self.signature.len()))?;
+        key.verify_strict(&self.message, &Signature::from_bytes(&signature))
+            .map_err(|err| format!("invalid signature: {}", err))?;
+        Ok(Ed25519PublicValuesStruct {
+            publicKey: B256::from(self.public_key),
+            messageHash: keccak256(&self.message),
+        })
+    }
+}
+
+impl Ed25519PublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the Ed25519 program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the Ed25519 program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
@@ -0,0 +1,114 @@
+//! Verifying a chain of Ethereum block headers, as the header chain program does: each header
+//! must be the parent of the next, so trusting the last hash (e.g. a checkpoint a light client
+//! already knows) extends that trust to every header in the chain.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Bytes, B256, U256};
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+use crate::rlp::list_items;
+
+/// The number of fields every header has (up to `nonce`); later forks appended more.
+pub const BASE_HEADER_FIELDS: usize = 15;
+
+sol! {
+    /// The public values of the header chain program.
+    #[derive(Serialize, Deserialize)]
+    struct HeaderChainPublicValuesStruct {
+        bytes32 firstHash;
+        bytes32 lastHash;
+        uint64 firstNumber;
+        uint64 lastNumber;
+        uint256 totalDifficulty;
+    }
+}
+
+/// The input passed from the host to the header chain program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderChainInput {
+    /// The RLP-encoded headers of consecutive blocks, oldest first.
+    pub headers: Vec<Bytes>,
+}
+
+/// The fields of a header the program checks.
+struct Header {
+    hash: B256,
+    parent_hash: B256,
+    difficulty: U256,
+    number: u64,
+}
+
+impl HeaderChainInput {
+    /// Check that each header is the parent of the next, and return the public values the header
+    /// chain program commits if they are. `totalDifficulty` is the sum of the difficulties of
+    /// the headers, which stops growing at the merge.
+    pub fn verify(&self) -> Result<HeaderChainPublicValuesStruct, String> {
+        let mut headers = self.headers.iter().map(|header| decode_header(header));
+        let first = headers.next().ok_or("the chain has no headers")??;
+        let mut last_hash = first.hash;
+        let mut last_number = first.number;
+        let mut total_difficulty = first.difficulty;
+        for header in headers {
+            let header = header?;
+            if header.parent_hash != last_hash {
+                return Err(format!(
+                    "block {} does not follow block {}: its parent is {}, not {}",
+                    header.number, last_number, header.parent_hash, last_hash
+                ));
+            }
+            if header.number != last_number + 1 {
+                return Err(format!("block {} follows block {}", header.number, last_number));
+            }
+            total_difficulty = total_difficulty
+                .checked_add(header.difficulty)
+                .ok_or("the total difficulty overflows")?;
+            last_hash = header.hash;
+            last_number = header.number;
+        }
+        Ok(HeaderChainPublicValuesStruct {
+            firstHash: first.hash,
+            lastHash: last_hash,
+            firstNumber: first.number,
+            lastNumber: last_number,
+            totalDifficulty: total_difficulty,
+        })
+    }
+}
+
+impl HeaderChainPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the header chain program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the header chain program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// Decode the fields of an RLP-encoded header the program checks.
+fn decode_header(encoded: &[u8]) -> Result<Header, String> {
+    let items = list_items(encoded).map_err(|err| format!("invalid header: {}", err))?;
+    if items.len() < BASE_HEADER_FIELDS {
+        return Err(format!(
+            "a header has at least {} fields, got {}",
+            BASE_HEADER_FIELDS,
+            items.len()
+        ));
+    }
+    let parent_hash = &items[0];
+    if parent_hash.list || parent_hash.payload.len() != 32 {
+        return Err("the parent hash of a header is 32 bytes".into());
+    }
+    let number = items[8].integer()?;
+    Ok(Header {
+        hash: keccak256(encoded),
+        parent_hash: B256::from_slice(parent_hash.payload),
+        difficulty: items[7].integer()?,
+        number: number.try_into().map_err(|_| format!("block number {} is too large", number))?,
+    })
+}
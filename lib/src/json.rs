@@ -0,0 +1,124 @@
+//! Extracting a field from a private JSON document, as the JSON program does to prove what an API
+//! response said without revealing the rest of it.
+//!
+//! The document stays private: the program commits the keccak256 hash of its bytes, the path of
+//! the field (a JSON Pointer, e.g. `/data/0/price`) and the hash of the field's value. Whoever
+//! knows the value checks it against `valueHash` with [`value_hash`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256};
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+sol! {
+    /// The public values of the JSON program.
+    #[derive(Serialize, Deserialize)]
+    struct JsonPublicValuesStruct {
+        bytes32 documentHash;
+        string path;
+        uint8 status;
+        bytes32 valueHash;
+    }
+}
+
+/// The outcome the JSON program commits in `status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u8)]
+pub enum JsonStatus {
+    /// The field was found, and `valueHash` is the hash of its value.
+    Found = 0,
+    /// The document has no field at the path; `valueHash` is zero.
+    Missing = 1,
+    /// The document is not valid JSON; `valueHash` is zero.
+    InvalidDocument = 2,
+}
+
+impl JsonStatus {
+    /// Every status, in the order of their codes.
+    pub const ALL: [Self; 3] = [Self::Found, Self::Missing, Self::InvalidDocument];
+
+    /// The status with the given code, if any.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| *status as u8 == code)
+    }
+
+    /// The name of the status.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Found => "FOUND",
+            Self::Missing => "MISSING",
+            Self::InvalidDocument => "INVALID_DOCUMENT",
+        }
+    }
+}
+
+/// The input passed from the host to the JSON program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonInput {
+    /// The document, as the exact bytes that are hashed (e.g. the body of an API response).
+    pub document: Vec<u8>,
+    /// The JSON Pointer (RFC 6901) of the field; the empty string is the whole document.
+    pub path: String,
+}
+
+impl JsonInput {
+    /// The public values the JSON program commits for this input.
+    pub fn public_values(&self) -> JsonPublicValuesStruct {
+        let (status, hash) = match serde_json::from_slice::<Value>(&self.document) {
+            Err(_) => (JsonStatus::InvalidDocument, B256::ZERO),
+            Ok(document) => match document.pointer(&self.path) {
+                Some(value) => (JsonStatus::Found, value_hash(value)),
+                None => (JsonStatus::Missing, B256::ZERO),
+            },
+        };
+        JsonPublicValuesStruct {
+            documentHash: keccak256(&self.document),
+            path: self.path.clone(),
+            status: status as u8,
+            valueHash: hash,
+        }
+    }
+}
+
+impl JsonPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the JSON program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the JSON program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+
+    /// The committed status, or `None` for an unknown code.
+    pub fn status(&self) -> Option<JsonStatus> {
+        JsonStatus::from_code(self.status)
+    }
+}
+
+/// The hash of a JSON value: keccak256 of its compact serialization with the keys of every object
+/// sorted, so that it does not depend on the formatting or key order of the document. A string
+/// value is hashed with its quotes, e.g. `keccak256("\"ok\"")`.
+pub fn value_hash(value: &Value) -> B256 {
+    let canonical =
+        serde_json::to_vec(&sort_keys(value.clone())).expect("JSON values always serialize");
+    keccak256(canonical)
+}
+
+/// Sort the keys of every object in `value`, whether or not `serde_json`'s `preserve_order`
+/// feature is enabled by another crate.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(key, value)| (key, sort_keys(value))).collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
@@ -0,0 +1,371 @@
+//! Verifying a JWT, as the JWT program does to prove a credential: that a token signed by a given
+//! key was valid at a given time, and what some of its claims are, without revealing the token.
+//!
+//! The program commits the hash of the public key, the time the token was checked at, its expiry
+//! and the [`value_hash`] of each selected claim. The caller chooses the time, so a contract
+//! settling the proof compares it with `block.timestamp`.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256};
+use alloy_sol_types::{sol, SolType};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use p256::ecdsa::signature::Verifier as _;
+use p256::pkcs8::DecodePublicKey as _;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+
+use crate::json::value_hash;
+
+sol! {
+    /// The public values of the JWT program.
+    #[derive(Serialize, Deserialize)]
+    struct JwtPublicValuesStruct {
+        bytes32 publicKeyHash;
+        uint8 algorithm;
+        uint64 timestamp;
+        uint64 expiresAt;
+        string[] claimNames;
+        bytes32[] claimHashes;
+    }
+}
+
+/// The signature algorithms the JWT program accepts, committed in `algorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum JwtAlgorithm {
+    /// RSASSA-PKCS1-v1_5 with SHA-256.
+    Rs256 = 1,
+    /// ECDSA on P-256 with SHA-256.
+    Es256 = 2,
+}
+
+impl JwtAlgorithm {
+    /// The algorithm named by the `alg` header, if the program accepts it.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "RS256" => Some(Self::Rs256),
+            "ES256" => Some(Self::Es256),
+            _ => None,
+        }
+    }
+
+    /// The algorithm with the given code, if any.
+    pub fn from_code(code: u8) -> Option<Self> {
+        [Self::Rs256, Self::Es256].into_iter().find(|algorithm| *algorithm as u8 == code)
+    }
+
+    /// The `alg` header of the algorithm.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rs256 => "RS256",
+            Self::Es256 => "ES256",
+        }
+    }
+}
+
+/// The input passed from the host to the JWT program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwtInput {
+    /// The compact token, `header.payload.signature`.
+    pub token: String,
+    /// The DER-encoded `SubjectPublicKeyInfo` of the signing key.
+    pub public_key: Vec<u8>,
+    /// The Unix timestamp to check the token's `exp` and `nbf` against.
+    pub timestamp: u64,
+    /// The claims to commit the hash of, e.g. `sub`; each must be in the token.
+    pub claims: Vec<String>,
+}
+
+impl JwtInput {
+    /// Verify the token, and return the public values the JWT program commits if it is valid at
+    /// `timestamp`.
+    pub fn verify(&self) -> Result<JwtPublicValuesStruct, String> {
+        let parts: Vec<&str> = self.token.split('.').collect();
+        let [header, payload, signature] = parts[..] else {
+            return Err(format!("a JWT has 3 parts, got {}", parts.len()));
+        };
+        let header: Value = decode_json(header, "header")?;
+        let algorithm = header["alg"]
+            .as_str()
+            .and_then(JwtAlgorithm::from_name)
+            .ok_or_else(|| format!("unsupported algorithm {}", header["alg"]))?;
+        let signature =
+            URL_SAFE_NO_PAD.decode(signature).map_err(|err| format!("invalid signature: {}", err))?;
+        let signed = &self.token[..self.token.len() - parts[2].len() - 1];
+        verify_signature(algorithm, &self.public_key, signed.as_bytes(), &signature)?;
+
+        let payload: Value = decode_json(payload, "payload")?;
+        let expires_at = payload["exp"].as_u64().ok_or("the token has no `exp` claim")?;
+        if self.timestamp >= expires_at {
+            return Err(format!("the token expired at {}", expires_at));
+        }
+        if let Some(not_before) = payload["nbf"].as_u64() {
+            if self.timestamp < not_before {
+                return Err(format!("the token is not valid before {}", not_before));
+            }
+        }
+        let claim_hashes = self
+            .claims
+            .iter()
+            .map(|claim| {
+                payload.get(claim).map(value_hash).ok_or_else(|| format!("no `{}` claim", claim))
+            })
+            .collect::<Result<Vec<B256>, String>>()?;
+        Ok(JwtPublicValuesStruct {
+            publicKeyHash: keccak256(&self.public_key),
+            algorithm: algorithm as u8,
+            timestamp: self.timestamp,
+            expiresAt: expires_at,
+            claimNames: self.claims.clone(),
+            claimHashes: claim_hashes,
+        })
+    }
+}
+
+impl JwtPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the JWT program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the JWT program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// Decode a base64url-encoded JSON part of a token.
+fn decode_json(part: &str, name: &str) -> Result<Value, String> {
+    let bytes = URL_SAFE_NO_PAD.decode(part).map_err(|err| format!("invalid {}: {}", name, err))?;
+    serde_json::from_slice(&bytes).map_err(|err| format!("invalid {}: {}", name, err))
+}
+
+/// Verify a signature over `signed` with a DER-encoded public key.
+fn verify_signature(
+    algorithm: JwtAlgorithm,
+    public_key: &[u8],
+    signed: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    let key_error = |err: String| format!("invalid {} key: {}", algorithm.name(), err);
+    let signature_error = |err: String| format!("invalid signature: {}", err);
+    match algorithm {
+        JwtAlgorithm::Rs256 => {
+            let key = rsa::RsaPublicKey::from_public_key_der(public_key)
+                .map_err(|err| key_error(err.to_string()))?;
+            let signature = rsa::pkcs1v15::Signature::try_from(signature)
+                .map_err(|err| signature_error(err.to_string()))?;
+            rsa::pkcs1v15::VerifyingKey::<Sha256>::new(key)
+                .verify(signed, &signature)
+                .map_err(|err| signature_error(err.to_string()))
+        }
+        JwtAlgorithm::Es256 => {
+            let key = p256::ecdsa::VerifyingKey::from_public_key_der(public_key)
+                .map_err(|err| key_error(err.to_string()))?;
+            let signature = p256::ecdsa::Signature::from_slice(signature)
+                .map_err(|err| signature_error(err.to_string()))?;
+            key.verify(signed, &signature).map_err(|err| signature_error(err.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use base64::engine::general_purpose::STANDARD;
+    use p256::pkcs8::EncodePublicKey as _;
+    use rsa::pkcs1::DecodeRsaPrivateKey as _;
+    use rsa::signature::{SignatureEncoding as _, Signer as _};
+    use serde_json::json;
+
+    /// A 2048-bit RSA key generated for these tests, as base64 PKCS#1 DER.
+    const RSA_KEY: &str = concat!(
+        "MIIEogIBAAKCAQEAtku1KZO2s7kfA9jrpG3JMSfEqOPZ1Ca0Ps5ImUmiK/mAFeghQK0Iim/RHXDvPHpbkk47eSKN",
+        "ZHgyzG1qkENmqB8BTAy4TqMbSLyJzXDQCIayOTB2sLI1uCVraInotpUHwG4krGlaZYHDgAWYqC3kFpN32fkFvZxS",
+        "f62btihd4MjggUIUascSNE5C4ERVMqyaWqdoC7Ax/OHw4ycAJrdDQKZlJHBEefAmeFTdEJ2zhfl+tPsacmaOIcrI",
+        "WlQ41I8fUF1BM0y/Hx/s9UGnR2ODHqmuuKHxD0HdntEYIgJfc2XE1QJmZ6sDegSya3TXaOs3eVZ+ospcwnk7/jJh",
+        "y5yGNQIDAQABAoIBABsDvSUcLscTd/UqAEGtw1L1gb9FhNJbqfpkt31/2/iIUOLuM39SJSHe0C58vfdIaqmTzlx1",
+        "Ml7hMSX6UQBv/JaNgbOzbty4byKoTgOT5EBfNaoj9HLa9fsEEJa9WS81hqCNsgGa2zqPsFkYezbxFgwCbpBuxtsC",
+        "f3dMUvb7pG3bFfdD1OplSHYP0wgwcb0XXi7ZFK2ArI3dvMH9NjwU2RPogTXVoaZe9kAAUaCXMgXXoSH9D2P+81jV",
+        "yWnLsGAzL2CMcKoFXaGZF/5dVvjPlP46vKaM0f1xI3vvkMRGcwPv/KpaQrKA8lTV0ruWZKr88PN2O40G1Wk1yZ1Q",
+        "MEqYSUECgYEA/+eacg4weYKvAtta2747nKWJx8Bio39m4ac+xbc3veEnghbL1jPNMAo15neTI9FnOLPL2KVT2YlW",
+        "1UvuI4WT4MRlJyoWR1iodiX+Szp30IdkAXUl61+/+cLD80vH3uTZ6biI0AVEyAe++xWHOfERLksuxh4KYPXi6+Cy",
+        "m+g6p+sCgYEAtl0WO4CW8WIdiMUi/fK5p+3cKZwjHewg8o/+XTINg3SKj5dffSP1gMpPa9G+lAA7NbmJDXoIY9Y1",
+        "eMLFjTFdhbk0zRucVCo+Roiw33kjARfpcwdYysrWovzd/sdG9l87SVYSVl0+CfMcm3aVdqGP6mHXMXlMyFhPzo6w",
+        "wjrhIl8CgYAP2G1txocl3vurN53moV7bkjx3y+fPq+HY8xwl7fRbo4dovLSebNK1uMwhmyHXQ+4aSrfCkGoivChO",
+        "SMdUF79SWZbR1/9NJ6Y2up+GVPZ6SQfeNzzD2Efw1SiA9h5IJO+BU1XVW54orKIctfaLleu0bB9XUSDfZBpK9Lza",
+        "foSzRQKBgDi064PnMYiv5NUxz/a9soOIkGyT88uLMJectREPzmnxJGEQRg4oz1+UCWxSlXt6Mu862WZKay94XbpG",
+        "fp2wGw/JDqU1D8CQPjs6qB1lkr/1So+NWkv9FQ8rqHBgUS2tYUclr6ARcIgxg9TH5CQ2iwTsFbl5qamm5HIrpsVO",
+        "/9DrAoGAckZs6rb1h1j4/xB3rB18ZkqiNcMLPj2MN3wnDQbxQi1/4bLEVI7Nuql6EKpC8E0rTg4ae3OXisJp0Yat",
+        "oaxiCOwQpY1dDS2dyq3UrEQP9JafZny7njZ5i53U3DwVjZWmXsCqF4lNA/RnSMudCCJpKCEuatLt/JDioyR9qiH6",
+        "Or0=",
+    );
+
+    fn encode(value: &Value) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(value).unwrap())
+    }
+
+    fn es256_key(seed: u8) -> p256::ecdsa::SigningKey {
+        p256::ecdsa::SigningKey::from_slice(&[seed; 32]).unwrap()
+    }
+
+    fn es256_public_key(key: &p256::ecdsa::SigningKey) -> Vec<u8> {
+        p256::PublicKey::from(key.verifying_key()).to_public_key_der().unwrap().into_vec()
+    }
+
+    /// A token with the header `alg` over `payload`, signed with the test key of `algorithm`,
+    /// to be checked at 1000 with that key, committing the `sub` claim.
+    fn signed(algorithm: JwtAlgorithm, alg: &str, payload: Value) -> JwtInput {
+        let signed =
+            format!("{}.{}", encode(&json!({ "alg": alg, "typ": "JWT" })), encode(&payload));
+        let (signature, public_key) = match algorithm {
+            JwtAlgorithm::Rs256 => {
+                let key =
+                    rsa::RsaPrivateKey::from_pkcs1_der(&STANDARD.decode(RSA_KEY).unwrap()).unwrap();
+                let public_key = key.to_public_key().to_public_key_der().unwrap().into_vec();
+                let signature: rsa::pkcs1v15::Signature =
+                    rsa::pkcs1v15::SigningKey::<Sha256>::new(key).sign(signed.as_bytes());
+                (signature.to_vec(), public_key)
+            }
+            JwtAlgorithm::Es256 => {
+                let key = es256_key(7);
+                let public_key = es256_public_key(&key);
+                let signature: p256::ecdsa::Signature = key.sign(signed.as_bytes());
+                (signature.to_vec(), public_key)
+            }
+        };
+        JwtInput {
+            token: format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature)),
+            public_key,
+            timestamp: 1_000,
+            claims: vec!["sub".to_string()],
+        }
+    }
+
+    fn valid(algorithm: JwtAlgorithm) -> JwtInput {
+        signed(algorithm, algorithm.name(), json!({ "sub": "alice", "nbf": 500, "exp": 2_000 }))
+    }
+
+    fn error(input: &JwtInput) -> String {
+        match input.verify() {
+            Ok(_) => panic!("the token should be rejected"),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn accepts_valid_tokens() {
+        for algorithm in [JwtAlgorithm::Rs256, JwtAlgorithm::Es256] {
+            let input = valid(algorithm);
+            let public_values = input.verify().unwrap();
+            assert_eq!(public_values.publicKeyHash, keccak256(&input.public_key));
+            assert_eq!(JwtAlgorithm::from_code(public_values.algorithm), Some(algorithm));
+            assert_eq!(public_values.timestamp, 1_000);
+            assert_eq!(public_values.expiresAt, 2_000);
+            assert_eq!(public_values.claimNames, ["sub"]);
+            assert_eq!(public_values.claimHashes, [value_hash(&json!("alice"))]);
+            let bytes = public_values.to_abi_bytes();
+            assert_eq!(
+                JwtPublicValuesStruct::from_abi_bytes(&bytes).unwrap().to_abi_bytes(),
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_bad_signatures() {
+        for algorithm in [JwtAlgorithm::Rs256, JwtAlgorithm::Es256] {
+            // Another payload under the same signature.
+            let mut input = valid(algorithm);
+            let parts: Vec<&str> = input.token.split('.').collect();
+            let payload = encode(&json!({ "sub": "mallory", "exp": 2_000 }));
+            input.token = format!("{}.{}.{}", parts[0], payload, parts[2]);
+            assert!(error(&input).starts_with("invalid signature"), "{}", error(&input));
+
+            // A truncated signature.
+            let mut input = valid(algorithm);
+            input.token.truncate(input.token.len() - 8);
+            assert!(error(&input).starts_with("invalid signature"), "{}", error(&input));
+        }
+
+        // Another key of the same algorithm.
+        let mut input = valid(JwtAlgorithm::Es256);
+        input.public_key = es256_public_key(&es256_key(8));
+        assert!(error(&input).starts_with("invalid signature"), "{}", error(&input));
+    }
+
+    #[test]
+    fn rejects_unsupported_and_mismatched_algorithms() {
+        for alg in ["HS256", "none", "es256"] {
+            let input = signed(JwtAlgorithm::Es256, alg, json!({ "sub": "alice", "exp": 2_000 }));
+            assert!(error(&input).starts_with("unsupported algorithm"), "{}", error(&input));
+        }
+        // An ES256 token claiming to be RS256 is checked against the key as an RSA key.
+        let input = signed(JwtAlgorithm::Es256, "RS256", json!({ "sub": "alice", "exp": 2_000 }));
+        assert!(error(&input).starts_with("invalid RS256 key"), "{}", error(&input));
+        let input = signed(JwtAlgorithm::Rs256, "ES256", json!({ "sub": "alice", "exp": 2_000 }));
+        assert!(error(&input).starts_with("invalid ES256 key"), "{}", error(&input));
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        let token = valid(JwtAlgorithm::Es256).token;
+        let parts: Vec<&str> = token.split('.').collect();
+        for (token, expected) in [
+            (format!("{}.{}", parts[0], parts[1]), "a JWT has 3 parts, got 2"),
+            (format!("{}.", token), "a JWT has 3 parts, got 4"),
+            (format!("*.{}.{}", parts[1], parts[2]), "invalid header"),
+            (format!("{}.{}.{}", encode(&json!("ES256")), parts[1], parts[2]), "unsupported"),
+            (format!("{}.{}.*", parts[0], parts[1]), "invalid signature"),
+        ] {
+            let input = JwtInput { token, ..valid(JwtAlgorithm::Es256) };
+            assert!(error(&input).starts_with(expected), "{}", error(&input));
+        }
+        // A payload that is not JSON, signed correctly.
+        let mut input = valid(JwtAlgorithm::Es256);
+        let signed = format!("{}.{}", parts[0], URL_SAFE_NO_PAD.encode("not json"));
+        let signature: p256::ecdsa::Signature = es256_key(7).sign(signed.as_bytes());
+        input.token = format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature.to_vec()));
+        assert!(error(&input).starts_with("invalid payload"), "{}", error(&input));
+    }
+
+    #[test]
+    fn checks_expiry_and_not_before() {
+        let input = valid(JwtAlgorithm::Es256);
+        for (timestamp, ok) in [(499, false), (500, true), (1_999, true), (2_000, false)] {
+            let input = JwtInput { timestamp, ..input.clone() };
+            assert_eq!(input.verify().is_ok(), ok, "timestamp {}", timestamp);
+        }
+        assert_eq!(
+            error(&JwtInput { timestamp: 2_000, ..input.clone() }),
+            "the token expired at 2000"
+        );
+        assert_eq!(error(&JwtInput { timestamp: 0, ..input }), "the token is not valid before 500");
+
+        let input = signed(JwtAlgorithm::Es256, "ES256", json!({ "sub": "alice" }));
+        assert_eq!(error(&input), "the token has no `exp` claim");
+        let input = signed(JwtAlgorithm::Es256, "ES256", json!({ "sub": "alice", "exp": "2000" }));
+        assert_eq!(error(&input), "the token has no `exp` claim");
+    }
+
+    #[test]
+    fn requires_the_selected_claims() {
+        let payload = json!({ "sub": "alice", "exp": 2_000, "roles": ["admin"] });
+        let input = signed(JwtAlgorithm::Rs256, "RS256", payload);
+        let claims = vec!["roles".to_string(), "sub".to_string()];
+        let public_values = JwtInput { claims, ..input.clone() }.verify().unwrap();
+        assert_eq!(
+            public_values.claimHashes,
+            [value_hash(&json!(["admin"])), value_hash(&json!("alice"))]
+        );
+        let public_values = JwtInput { claims: Vec::new(), ..input.clone() }.verify().unwrap();
+        assert!(public_values.claimNames.is_empty() && public_values.claimHashes.is_empty());
+        let input = JwtInput { claims: vec!["email".to_string()], ..input };
+        assert_eq!(error(&input), "no `email` claim");
+    }
+}
@@ -1,11 +1,483 @@
-use alloy_sol_types::sol;
+//! The types and logic shared by the Fibonacci program and the scripts.
+//!
+//! The crate is `no_std` (with `alloc`) when its default `std` feature is disabled.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use alloy_primitives::{hex, keccak256, Address, B256};
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+pub mod batch;
+#[cfg(feature = "bls")]
+pub mod bls;
+pub mod dfa;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
+pub mod headers;
+pub mod json;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+pub mod recurrence;
+pub mod recursion;
+mod rlp;
+pub mod stats;
+pub mod storage;
+pub mod stream;
+
+use recurrence::Recurrence;
+
+/// The largest `n` the program accepts, overridable at build time with `FIBONACCI_MAX_N`.
+pub const MAX_N: u32 = limit(option_env!("FIBONACCI_MAX_N"), 10000);
+
+/// The largest number of Fibonacci values the program commits in a single range, overridable at
+/// build time with `FIBONACCI_MAX_RANGE_LEN`.
+pub const MAX_RANGE_LEN: u32 = limit(option_env!("FIBONACCI_MAX_RANGE_LEN"), 1000);
+
+/// The file descriptor the program writes its log lines to, so the host can collect them apart
+/// from its own output instead of having them interleaved on stdout.
+pub const GUEST_LOG_FD: u32 = 1000;
+
+/// Parse a limit set at build time, or fall back to `default`. Fails the build if it is not a
+/// number.
+const fn limit(value: Option<&str>, default: u32) -> u32 {
+    let Some(value) = value else {
+        return default;
+    };
+    let digits = value.as_bytes();
+    assert!(!digits.is_empty(), "build-time limits must be numbers");
+    let mut limit = 0u32;
+    let mut i = 0;
+    while i < digits.len() {
+        assert!(digits[i].is_ascii_digit(), "build-time limits must be numbers");
+        limit = limit * 10 + (digits[i] - b'0') as u32;
+        i += 1;
+    }
+    limit
+}
 
 sol! {
     /// The public values encoded as a struct that can be easily deserialized inside Solidity.
+    #[derive(Serialize, Deserialize)]
     struct PublicValuesStruct {
         uint32 n;
         uint32 a;
         uint32 b;
+        uint32[] fibValues;
+        uint64 chainId;
+        address consumer;
+        uint64 nonce;
+        uint64 validUntil;
+        uint8 status;
+        bytes32 rangeHash;
+        uint64[] coefficients;
+        uint64[] initialValues;
+        uint64 modulus;
+        uint64 result;
+    }
+}
+
+/// The outcome the program commits in `status`.
+///
+/// An input the program rejects is not a panic: the program commits the rejection (with `n` and
+/// the bindings, but no values), so it can be proven and settled like any other result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u8)]
+pub enum Status {
+    /// The values were computed.
+    Ok = 0,
+    /// `n` is greater than [`MAX_N`]; nothing was computed.
+    InputTooLarge = 1,
+    /// The values were computed, but wrapped around because they do not fit in a `uint32`.
+    Overflow = 2,
+    /// The range start is greater than `n`, or the range is longer than [`MAX_RANGE_LEN`];
+    /// nothing was computed.
+    InvalidRange = 3,
+    /// The recurrence is malformed or exceeds [`recurrence::MAX_ORDER`]; nothing was computed.
+    InvalidRecurrence = 4,
+}
+
+impl Status {
+    /// Every status, in the order of their codes.
+    pub const ALL: [Self; 5] = [
+        Self::Ok,
+        Self::InputTooLarge,
+        Self::Overflow,
+        Self::InvalidRange,
+        Self::InvalidRecurrence,
+    ];
+
+    /// The status with the given code, if any.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| *status as u8 == code)
+    }
+
+    /// The name of the status, as in the generated Solidity constants (`STATUS_<NAME>`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::InputTooLarge => "INPUT_TOO_LARGE",
+            Self::Overflow => "OVERFLOW",
+            Self::InvalidRange => "INVALID_RANGE",
+            Self::InvalidRecurrence => "INVALID_RECURRENCE",
+        }
+    }
+
+    /// The name of the status with the given code (as read from the chain), or the code if this
+    /// version does not know it.
+    pub fn name_of(code: u8) -> String {
+        match Self::from_code(code) {
+            Some(status) => status.name().into(),
+            None => format!("unknown status {}", code),
+        }
+    }
+
+    /// Whether the program refused the input instead of computing its values.
+    pub fn is_rejected(self) -> bool {
+        matches!(self, Self::InputTooLarge | Self::InvalidRange | Self::InvalidRecurrence)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// The smallest `n` whose committed values wrap around: `fibonacci(47).1` is `F(48)`, the first
+/// Fibonacci number that does not fit in a `u32`. See [`first_overflowing_n`] for other seeds.
+pub const FIRST_OVERFLOWING_N: u32 = 47;
+
+/// A Fibonacci-like sequence: each value is the sum of the previous two, starting from its seeds.
+///
+/// Parsed from `fibonacci`, `lucas` or the seeds themselves, e.g. `3,7`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Sequence {
+    /// Seeds `0, 1`.
+    #[default]
+    Fibonacci,
+    /// Seeds `2, 1`.
+    Lucas,
+    /// Any other seeds.
+    Seeded(u32, u32),
+}
+
+impl Sequence {
+    /// The first two values of the sequence.
+    pub fn seeds(self) -> [u32; 2] {
+        match self {
+            Self::Fibonacci => [0, 1],
+            Self::Lucas => [2, 1],
+            Self::Seeded(x0, x1) => [x0, x1],
+        }
+    }
+
+    /// The sequence starting from `seeds`.
+    pub fn from_seeds(seeds: [u32; 2]) -> Self {
+        match seeds {
+            [0, 1] => Self::Fibonacci,
+            [2, 1] => Self::Lucas,
+            [x0, x1] => Self::Seeded(x0, x1),
+        }
+    }
+
+    /// The seeds the program is given for this sequence: `None` for the Fibonacci sequence, which
+    /// it computes by default.
+    pub fn input_seeds(self) -> Option<[u32; 2]> {
+        (self != Self::Fibonacci).then(|| self.seeds())
+    }
+}
+
+impl FromStr for Sequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "fibonacci" => return Ok(Self::Fibonacci),
+            "lucas" => return Ok(Self::Lucas),
+            _ => {}
+        }
+        let seed = |seed: &str| {
+            seed.trim().parse::<u32>().map_err(|_| format!("invalid seed `{}` in `{}`", seed, s))
+        };
+        let (x0, x1) = s.split_once(',').ok_or_else(|| {
+            format!("expected `fibonacci`, `lucas` or two seeds like `3,7`, got `{}`", s)
+        })?;
+        Ok(Self::from_seeds([seed(x0)?, seed(x1)?]))
+    }
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fibonacci => write!(f, "Fibonacci"),
+            Self::Lucas => write!(f, "Lucas"),
+            Self::Seeded(x0, x1) => write!(f, "Seeded[{}, {}]", x0, x1),
+        }
+    }
+}
+
+/// The inputs passed from the host to the program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProgramInput {
+    /// The index of the Fibonacci number to compute.
+    pub n: u32,
+    /// If set, every Fibonacci number from this index up to `n` is also committed.
+    pub range_start: Option<u32>,
+    /// Commit the hash of the range (see [`range_hash`]) instead of its values, so the public
+    /// values stay small however long the range is.
+    #[serde(default)]
+    pub range_hash: bool,
+    /// If set, the proof can only be settled on the chain with this id.
+    pub chain_id: Option<u64>,
+    /// If set, the proof can only be settled by the consumer contract at this address.
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub consumer: Option<Address>,
+    /// If set, a request id that lets the consumer reject a second settlement of the same proof.
+    pub nonce: Option<u64>,
+    /// If set, the unix timestamp after which the proof must no longer be settled.
+    pub valid_until: Option<u64>,
+    /// If set, compute `x_n` of this recurrence instead of the n'th Fibonacci number.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// If set, compute the sequence starting from these two values instead of `0, 1` (see
+    /// [`Sequence`]). They are committed as `initialValues`.
+    #[serde(default)]
+    pub seeds: Option<[u32; 2]>,
+}
+
+impl ProgramInput {
+    /// Create an input that only computes the n'th Fibonacci number.
+    pub fn new(n: u32) -> Self {
+        Self { n, ..Default::default() }
+    }
+
+    /// Check that the input is within the limits enforced by the program.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.n > MAX_N {
+            return Err(format!("Input too large: maximum allowed is {}", MAX_N));
+        }
+        if let Some(start) = self.range_start {
+            if start > self.n {
+                return Err(format!("Range start {} is greater than n = {}", start, self.n));
+            }
+            if self.n - start + 1 > MAX_RANGE_LEN {
+                return Err(format!("Range too long: maximum allowed is {} values", MAX_RANGE_LEN));
+            }
+        } else if self.range_hash {
+            return Err("A range hash needs a range start".to_string());
+        }
+        if let Some(recurrence) = &self.recurrence {
+            if self.range_start.is_some() {
+                return Err("A range cannot be committed for a recurrence".to_string());
+            }
+            if self.seeds.is_some() {
+                return Err("A recurrence takes initial values, not seeds".to_string());
+            }
+            recurrence.validate()?;
+        }
+        Ok(())
+    }
+
+    /// The status the program rejects the input with, if it does: the same checks as
+    /// [`ProgramInput::validate`].
+    pub fn rejection(&self) -> Option<Status> {
+        match self.validate() {
+            Ok(()) => None,
+            Err(_) if self.n > MAX_N => Some(Status::InputTooLarge),
+            Err(_) if self.recurrence.is_some() => Some(Status::InvalidRecurrence),
+            Err(_) => Some(Status::InvalidRange),
+        }
+    }
+}
+
+impl PublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+
+    /// The index of the first value in `fibValues`, if a range was committed.
+    pub fn range_start(&self) -> Option<u32> {
+        if self.fibValues.is_empty() {
+            None
+        } else {
+            Some(self.n + 1 - self.fibValues.len() as u32)
+        }
+    }
+
+    /// Whether the range was committed as its [`range_hash`] rather than its values. Its start is
+    /// not committed, so whoever checks the values against the hash must know it.
+    pub fn has_range_hash(&self) -> bool {
+        !self.rangeHash.is_zero()
+    }
+
+    /// Whether the values are those of a recurrence (in `result`) rather than of the Fibonacci
+    /// sequence.
+    pub fn is_recurrence(&self) -> bool {
+        self.modulus != 0
+    }
+
+    /// The sequence `a`, `b` and `fibValues` are values of, from the seeds committed in
+    /// `initialValues`; `None` for a recurrence or seeds that are not two `u32`s.
+    pub fn sequence(&self) -> Option<Sequence> {
+        if self.is_recurrence() {
+            return None;
+        }
+        match self.initialValues[..] {
+            [] => Some(Sequence::Fibonacci),
+            [x0, x1] => Some(Sequence::from_seeds([x0.try_into().ok()?, x1.try_into().ok()?])),
+            _ => None,
+        }
+    }
+
+    /// Whether the proof may be settled by `consumer` on the chain with id `chain_id`.
+    ///
+    /// A zero `chainId` or `consumer` means the public values are not bound to a chain or
+    /// contract respectively.
+    pub fn is_valid_for(&self, chain_id: u64, consumer: Address) -> bool {
+        (self.chainId == 0 || self.chainId == chain_id)
+            && (self.consumer.is_zero() || self.consumer == consumer)
+    }
+
+    /// Whether the proof has expired at the unix timestamp `now`.
+    ///
+    /// A zero `validUntil` means the proof never expires.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.validUntil != 0 && now > self.validUntil
+    }
+
+    /// The committed status, or `None` for a code this version does not know.
+    pub fn status(&self) -> Option<Status> {
+        Status::from_code(self.status)
+    }
+
+    /// The name of the committed status, or its code if this version does not know it.
+    pub fn status_name(&self) -> String {
+        Status::name_of(self.status)
+    }
+
+    /// Whether the program rejected the input instead of computing its values.
+    pub fn is_rejected(&self) -> bool {
+        self.status().is_none_or(Status::is_rejected)
+    }
+}
+
+/// One line with the result (or the rejection), the committed range and whatever the values are
+/// bound to, e.g. `n = 20: Fibonacci(19) = 4181, Fibonacci(20) = 6765 (nonce 7)`.
+impl fmt::Display for PublicValuesStruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n = {}: ", self.n)?;
+        if self.is_rejected() {
+            return write!(f, "rejected ({})", self.status_name());
+        }
+        if self.is_recurrence() {
+            write!(f, "x({}) = {} mod {}", self.n, self.result, self.modulus)?;
+            write!(f, " for coefficients {:?}", self.coefficients)?;
+            write!(f, " and initial values {:?}", self.initialValues)?;
+        } else {
+            let name = self.sequence().unwrap_or(Sequence::Fibonacci);
+            write!(f, "{}({}) = {}, ", name, self.n.saturating_sub(1), self.a)?;
+            write!(f, "{}({}) = {}", name, self.n, self.b)?;
+        }
+        if self.status != Status::Ok as u8 {
+            write!(f, " [{}]", self.status_name())?;
+        }
+        if let Some(start) = self.range_start() {
+            write!(f, ", Fibonacci({}..={}) = {:?}", start, self.n, self.fibValues)?;
+        }
+        if self.has_range_hash() {
+            write!(f, ", range hash {}", self.rangeHash)?;
+        }
+
+        let mut bindings = Vec::new();
+        if self.chainId != 0 {
+            bindings.push(format!("chain {}", self.chainId));
+        }
+        if !self.consumer.is_zero() {
+            bindings.push(format!("consumer {}", self.consumer));
+        }
+        if self.nonce != 0 {
+            bindings.push(format!("nonce {}", self.nonce));
+        }
+        if self.validUntil != 0 {
+            bindings.push(format!("valid until {}", self.validUntil));
+        }
+        if !bindings.is_empty() {
+            write!(f, " ({})", bindings.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The hash of a program's verifying key, as the verifier contracts are deployed with
+/// (`bytes32 programVKey`).
+///
+/// Parsing accepts it with or without the `0x` prefix and in either case; it is always displayed
+/// (and serialized) `0x`-prefixed in lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct VkHash(pub B256);
+
+impl FromStr for VkHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+        if digits.len() != 64 {
+            return Err(format!("expected a 32-byte hex vkey hash, got `{}`", s));
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(digits, &mut bytes)
+            .map_err(|err| format!("invalid vkey hash `{}`: {}", s, err))?;
+        Ok(Self(B256::from(bytes)))
+    }
+}
+
+impl fmt::Display for VkHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl From<B256> for VkHash {
+    fn from(bytes: B256) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<VkHash> for B256 {
+    fn from(vk_hash: VkHash) -> Self {
+        vk_hash.0
+    }
+}
+
+impl From<VkHash> for String {
+    fn from(vk_hash: VkHash) -> Self {
+        vk_hash.to_string()
+    }
+}
+
+impl TryFrom<String> for VkHash {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
     }
 }
 
@@ -20,3 +492,300 @@ pub fn fibonacci(n: u32) -> (u32, u32) {
     }
     (a, b)
 }
+
+/// Compute the same pair as [`fibonacci`] in `O(log n)` steps, using the fast doubling identities
+/// `F(2k) = F(k) * (2F(k+1) - F(k))` and `F(2k+1) = F(k)^2 + F(k+1)^2`, which also hold modulo 2^32.
+pub fn fibonacci_fast_doubling(n: u32) -> (u32, u32) {
+    let mut a = 0u32;
+    let mut b = 1u32;
+    for bit in (0..u32::BITS - n.leading_zeros()).rev() {
+        let c = a.wrapping_mul(b.wrapping_mul(2).wrapping_sub(a));
+        let d = a.wrapping_mul(a).wrapping_add(b.wrapping_mul(b));
+        (a, b) = if (n >> bit) & 1 == 0 { (c, d) } else { (d, c.wrapping_add(d)) };
+    }
+    (a, b)
+}
+
+/// Compute `fibonacci(k).1` for every `k` in `start..=end` (wrapping around on overflows).
+pub fn fibonacci_range(start: u32, end: u32) -> Vec<u32> {
+    if start > end {
+        return Vec::new();
+    }
+    let (mut a, mut b) = fibonacci(start);
+    let mut values = Vec::with_capacity((end - start + 1) as usize);
+    for _ in start..=end {
+        values.push(b);
+        let c = a.wrapping_add(b);
+        a = b;
+        b = c;
+    }
+    values
+}
+
+/// Compute the pair [`fibonacci`] computes for the sequence starting from `seeds` instead of
+/// `0, 1` (wrapping around on overflows).
+pub fn seeded(n: u32, seeds: [u32; 2]) -> (u32, u32) {
+    let [mut a, mut b] = seeds;
+    for _ in 0..n {
+        let c = a.wrapping_add(b);
+        a = b;
+        b = c;
+    }
+    (a, b)
+}
+
+/// Compute `seeded(k, seeds).1` for every `k` in `start..=end` (wrapping around on overflows).
+pub fn seeded_range(start: u32, end: u32, seeds: [u32; 2]) -> Vec<u32> {
+    if start > end {
+        return Vec::new();
+    }
+    let (mut a, mut b) = seeded(start, seeds);
+    let mut values = Vec::with_capacity((end - start + 1) as usize);
+    for _ in start..=end {
+        values.push(b);
+        let c = a.wrapping_add(b);
+        a = b;
+        b = c;
+    }
+    values
+}
+
+/// The smallest `n` whose `seeded(n, seeds)` wraps around ([`FIRST_OVERFLOWING_N`] for the
+/// Fibonacci seeds), or `MAX_N + 1` if no accepted `n` does.
+pub fn first_overflowing_n(seeds: [u32; 2]) -> u32 {
+    let [mut a, mut b] = seeds.map(u64::from);
+    let mut n = 0;
+    while b <= u32::MAX as u64 && n <= MAX_N {
+        let c = a + b;
+        a = b;
+        b = c;
+        n += 1;
+    }
+    n
+}
+
+/// The hash a range of Fibonacci values is committed as with [`ProgramInput::range_hash`]:
+/// `keccak256(abi.encodePacked(start, values))` in Solidity, where `start` is a `uint32` and
+/// `values` the `uint32[]` of `F(start..=n)` (each element padded to 32 bytes).
+pub fn range_hash(start: u32, values: &[u32]) -> B256 {
+    let mut packed = Vec::with_capacity(4 + 32 * values.len());
+    packed.extend_from_slice(&start.to_be_bytes());
+    for value in values {
+        packed.extend_from_slice(&[0u8; 28]);
+        packed.extend_from_slice(&value.to_be_bytes());
+    }
+    keccak256(packed)
+}
+
+/// Run the program's logic natively: compute the public values (or the rejection of the input)
+/// and check them. The program itself is a thin wrapper around this, so the host can replay a
+/// failed execution to find out why it failed.
+pub fn run_program(input: &ProgramInput) -> PublicValuesStruct {
+    let public_values = compute_public_values(input);
+    if public_values.is_rejected() {
+        return public_values;
+    }
+    if public_values.is_recurrence() {
+        if let Some(x) = public_values.initialValues.get(input.n as usize) {
+            assert_eq!(public_values.result, *x);
+        }
+        return public_values;
+    }
+    let (a, b) = (public_values.a, public_values.b);
+    let [x0, x1] = input.seeds.unwrap_or(Sequence::Fibonacci.seeds());
+    if input.n == 0 {
+        assert_eq!(a, x0);
+        assert_eq!(b, x1);
+    } else if input.n == 1 {
+        assert_eq!(a, x1);
+        assert_eq!(b, x0.wrapping_add(x1));
+    }
+    if let Some(last) = public_values.fibValues.last() {
+        assert_eq!(*last, b);
+    }
+    public_values
+}
+
+/// Compute the public values the program commits for the given input. A rejected input commits
+/// its status with zero values.
+pub fn compute_public_values(input: &ProgramInput) -> PublicValuesStruct {
+    let mut public_values = PublicValuesStruct {
+        n: input.n,
+        a: 0,
+        b: 0,
+        fibValues: Vec::new(),
+        chainId: input.chain_id.unwrap_or_default(),
+        consumer: input.consumer.unwrap_or_default(),
+        nonce: input.nonce.unwrap_or_default(),
+        validUntil: input.valid_until.unwrap_or_default(),
+        status: Status::Ok as u8,
+        rangeHash: B256::ZERO,
+        coefficients: Vec::new(),
+        initialValues: Vec::new(),
+        modulus: 0,
+        result: 0,
+    };
+    if let Some(rejection) = input.rejection() {
+        public_values.status = rejection as u8;
+        return public_values;
+    }
+
+    // A recurrence is computed modulo its modulus, so it never overflows.
+    if let Some(recurrence) = &input.recurrence {
+        public_values.result = recurrence.evaluate(input.n);
+        public_values.coefficients = recurrence.coefficients.clone();
+        public_values.initialValues = recurrence.initial.clone();
+        public_values.modulus = recurrence.modulus;
+        return public_values;
+    }
+
+    let seeds = input.seeds.unwrap_or(Sequence::Fibonacci.seeds());
+    (public_values.a, public_values.b) = seeded(input.n, seeds);
+    // The Fibonacci seeds are the default, so committing them would only make the values longer.
+    if let Some(seeds) = Sequence::from_seeds(seeds).input_seeds() {
+        public_values.initialValues = seeds.map(u64::from).into();
+    }
+    if let Some(start) = input.range_start {
+        let values = seeded_range(start, input.n, seeds);
+        if input.range_hash {
+            public_values.rangeHash = range_hash(start, &values);
+        } else {
+            public_values.fibValues = values;
+        }
+    }
+    if input.n >= first_overflowing_n(seeds) {
+        public_values.status = Status::Overflow as u8;
+    }
+    public_values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, U256};
+
+    const VK_HASH: &str = "0x00a1b2c3d4e5f60718293a4b5c6d7e8f900a1b2c3d4e5f60718293a4b5c6d7e8";
+
+    #[test]
+    fn vk_hash_parses_with_and_without_prefix() {
+        let prefixed: VkHash = VK_HASH.parse().unwrap();
+        let bare: VkHash = VK_HASH.trim_start_matches("0x").parse().unwrap();
+        assert_eq!(prefixed, bare);
+        assert_eq!(prefixed.0[0], 0x00);
+        assert_eq!(prefixed.0[31], 0xe8);
+    }
+
+    #[test]
+    fn vk_hash_normalises_uppercase() {
+        let upper: VkHash = VK_HASH.to_uppercase().replacen("0X", "0x", 1).parse().unwrap();
+        let upper_prefix: VkHash = VK_HASH.to_uppercase().parse().unwrap();
+        assert_eq!(upper, VK_HASH.parse::<VkHash>().unwrap());
+        assert_eq!(upper_prefix.to_string(), VK_HASH);
+    }
+
+    #[test]
+    fn vk_hash_rejects_wrong_length_and_non_hex() {
+        assert!(VK_HASH[..VK_HASH.len() - 2].parse::<VkHash>().is_err());
+        assert!(format!("{}00", VK_HASH).parse::<VkHash>().is_err());
+        assert!("".parse::<VkHash>().is_err());
+        assert!(VK_HASH.replacen('a', "g", 1).parse::<VkHash>().is_err());
+    }
+
+    #[test]
+    fn vk_hash_display_round_trips() {
+        let vk_hash: VkHash = VK_HASH.parse().unwrap();
+        assert_eq!(vk_hash.to_string(), VK_HASH);
+        assert_eq!(vk_hash.to_string().parse::<VkHash>().unwrap(), vk_hash);
+    }
+
+    #[test]
+    fn vk_hash_serde_round_trips() {
+        let vk_hash: VkHash = VK_HASH.parse().unwrap();
+        let json = serde_json::to_string(&vk_hash).unwrap();
+        assert_eq!(json, format!("\"{}\"", VK_HASH));
+        assert_eq!(serde_json::from_str::<VkHash>(&json).unwrap(), vk_hash);
+        assert!(serde_json::from_str::<VkHash>("\"0x1234\"").is_err());
+    }
+
+    /// `keccak256(abi.encodePacked(start, values))` for `uint32 start` and `uint32[] values`, as
+    /// Solidity computes it: `start` in 4 bytes, then each element padded to 32 bytes.
+    fn encode_packed(start: u32, values: &[u32]) -> Vec<u8> {
+        let mut packed = start.to_be_bytes().to_vec();
+        for value in values {
+            packed.extend_from_slice(&U256::from(*value).to_be_bytes::<32>());
+        }
+        packed
+    }
+
+    #[test]
+    fn range_hash_matches_solidity() {
+        // `keccak256(abi.encodePacked(start, values))`, computed independently of this crate. The
+        // first range is the one `contracts/test/Fibonacci.t.sol` settles.
+        assert_eq!(
+            range_hash(3, &[2, 3, 5]),
+            b256!("bae2e0cd3ce8affdd65d50690cad0c7323eb6f6b4512276451ff661a14a82331")
+        );
+        assert_eq!(
+            range_hash(3, &fibonacci_range(3, 10)),
+            b256!("25359e5686efbf5a761a7e38d86fe1975e00ec92625098901df1a6a0d4f6f5e9")
+        );
+        assert_eq!(
+            range_hash(0, &[]),
+            b256!("e8e77626586f73b955364c7b4bbf0bb7f7685ebd40e852b164633a4acbd3244c")
+        );
+    }
+
+    #[test]
+    fn range_hash_is_of_the_packed_encoding() {
+        for (start, end) in [(0, 0), (1, 5), (7, 10), (40, 60)] {
+            let values = fibonacci_range(start, end);
+            assert_eq!(range_hash(start, &values), keccak256(encode_packed(start, &values)));
+        }
+        // The start is part of the hash, so the same values from another index differ.
+        assert_ne!(range_hash(3, &[2, 3, 5]), range_hash(2, &[2, 3, 5]));
+    }
+
+    #[test]
+    fn committed_range_hash_is_of_the_range() {
+        let input =
+            ProgramInput { range_start: Some(3), range_hash: true, ..ProgramInput::new(10) };
+        let public_values = compute_public_values(&input);
+        assert!(public_values.fibValues.is_empty());
+        assert_eq!(public_values.rangeHash, range_hash(3, &fibonacci_range(3, 10)));
+    }
+
+    #[test]
+    fn fast_doubling_equals_iterative() {
+        for n in (0..=2_000).chain([4_095, 4_096, 65_535, 65_536, 1_000_003]) {
+            assert_eq!(fibonacci_fast_doubling(n), fibonacci(n), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn fast_doubling_follows_the_recurrence_for_large_n() {
+        // Too far to iterate to: check that consecutive pairs chain and wrap like iterating does.
+        for n in (1_000_000..u32::MAX - 2).step_by(99_999_989).chain([u32::MAX - 2]) {
+            let (a, b) = fibonacci_fast_doubling(n);
+            let (next_a, next_b) = fibonacci_fast_doubling(n + 1);
+            assert_eq!(next_a, b, "n = {}", n);
+            assert_eq!(next_b, a.wrapping_add(b), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn vk_hash_converts_to_and_from_b256() {
+        let bytes = B256::repeat_byte(0xab);
+        let vk_hash = VkHash::from(bytes);
+        assert_eq!(B256::from(vk_hash), bytes);
+        let fixed: alloy_primitives::FixedBytes<32> = vk_hash.into();
+        assert_eq!(VkHash::from(fixed), vk_hash);
+    }
+
+    #[test]
+    fn status_names_of_codes() {
+        assert_eq!(Status::name_of(0), "OK");
+        assert_eq!(Status::name_of(Status::InvalidRecurrence as u8), "INVALID_RECURRENCE");
+        assert_eq!(Status::name_of(9), "unknown status 9");
+        assert_eq!(compute_public_values(&ProgramInput::new(50)).status_name(), "OVERFLOW");
+    }
+}
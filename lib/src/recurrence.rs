@@ -0,0 +1,164 @@
+//! Linear recurrences `x_k = c1*x_{k-1} + ... + cm*x_{k-m} (mod p)`, which the program evaluates
+//! instead of the Fibonacci sequence when the input specifies one.
+//!
+//! Fibonacci itself is the recurrence with coefficients `[1, 1]` and initial values `[0, 1]`, so
+//! the same program proves any sequence of this kind (Lucas numbers, Pell numbers, tribonacci, ...)
+//! without being rebuilt.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256};
+use alloy_sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+
+use crate::limit;
+
+/// The largest order (number of coefficients) the program accepts, overridable at build time
+/// with `FIBONACCI_MAX_ORDER`.
+pub const MAX_ORDER: u32 = limit(option_env!("FIBONACCI_MAX_ORDER"), 16);
+
+/// A linear recurrence of order `m` modulo `modulus`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Recurrence {
+    /// `c1..=cm`: `coefficients[i]` multiplies `x_{k-1-i}`.
+    pub coefficients: Vec<u64>,
+    /// `x_0..x_{m-1}`.
+    pub initial: Vec<u64>,
+    pub modulus: u64,
+}
+
+impl Recurrence {
+    /// The Fibonacci sequence modulo `modulus`.
+    pub fn fibonacci(modulus: u64) -> Self {
+        Self { coefficients: [1, 1].into(), initial: [0, 1].into(), modulus }
+    }
+
+    /// The order of the recurrence: how many previous values each value depends on.
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// The id the `Fibonacci` contract records the result `x_n` under:
+    /// `keccak256(abi.encode(coefficients, initialValues, modulus, n))`.
+    pub fn id(&self, n: u32) -> B256 {
+        let params = (self.coefficients.clone(), self.initial.clone(), self.modulus, n);
+        keccak256(params.abi_encode_params())
+    }
+
+    /// Check that the recurrence is within the limits enforced by the program, with every
+    /// coefficient and initial value already reduced modulo `modulus`.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.coefficients.is_empty() || self.order() > MAX_ORDER as usize {
+            return Err(format!(
+                "A recurrence needs 1 to {} coefficients, got {}",
+                MAX_ORDER,
+                self.order()
+            ));
+        }
+        if self.initial.len() != self.order() {
+            return Err(format!(
+                "A recurrence of order {} needs {} initial values, got {}",
+                self.order(),
+                self.order(),
+                self.initial.len()
+            ));
+        }
+        if self.modulus < 2 {
+            return Err(format!("The modulus must be at least 2, got {}", self.modulus));
+        }
+        if let Some(value) =
+            self.coefficients.iter().chain(&self.initial).find(|value| **value >= self.modulus)
+        {
+            return Err(format!("{} is not reduced modulo {}", value, self.modulus));
+        }
+        Ok(())
+    }
+
+    /// Compute `x_n`. The recurrence must be valid.
+    pub fn evaluate(&self, n: u32) -> u64 {
+        let order = self.order();
+        if (n as usize) < order {
+            return self.initial[n as usize];
+        }
+        let modulus = self.modulus as u128;
+        // The last `order` values, oldest first.
+        let mut window = self.initial.clone();
+        for _ in order..=n as usize {
+            let next = self
+                .coefficients
+                .iter()
+                .zip(window.iter().rev())
+                .fold(0u128, |sum, (c, x)| (sum + *c as u128 * *x as u128) % modulus);
+            window.rotate_left(1);
+            window[order - 1] = next as u64;
+        }
+        window[order - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recurrence(coefficients: &[u64], initial: &[u64], modulus: u64) -> Recurrence {
+        Recurrence { coefficients: coefficients.into(), initial: initial.into(), modulus }
+    }
+
+    fn first(recurrence: &Recurrence, count: u32) -> Vec<u64> {
+        (0..count).map(|n| recurrence.evaluate(n)).collect()
+    }
+
+    #[test]
+    fn evaluates_known_sequences() {
+        let modulus = 1_000_000_007;
+        let lucas = recurrence(&[1, 1], &[2, 1], modulus);
+        assert_eq!(first(&lucas, 10), [2, 1, 3, 4, 7, 11, 18, 29, 47, 76]);
+        let pell = recurrence(&[2, 1], &[0, 1], modulus);
+        assert_eq!(first(&pell, 8), [0, 1, 2, 5, 12, 29, 70, 169]);
+        let tribonacci = recurrence(&[1, 1, 1], &[0, 0, 1], modulus);
+        assert_eq!(first(&tribonacci, 10), [0, 0, 1, 1, 2, 4, 7, 13, 24, 44]);
+        // x_k = 2 x_{k-2}: the coefficients apply to the values from the most recent back.
+        let doubling = recurrence(&[0, 2], &[1, 3], modulus);
+        assert_eq!(first(&doubling, 6), [1, 3, 2, 6, 4, 12]);
+    }
+
+    #[test]
+    fn fibonacci_modulo_2_32_equals_the_wrapping_sequence() {
+        let recurrence = Recurrence::fibonacci(1 << 32);
+        for n in 0..=1_000 {
+            assert_eq!(recurrence.evaluate(n), u64::from(crate::fibonacci(n).0), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn reduces_modulo_the_modulus() {
+        let powers = recurrence(&[3], &[1], 1_000_000_007);
+        let mut expected = 1u64;
+        for n in 0..200 {
+            assert_eq!(powers.evaluate(n), expected, "n = {}", n);
+            expected = expected * 3 % 1_000_000_007;
+        }
+        // Products of values near u64::MAX do not overflow.
+        let modulus = u64::MAX - 58;
+        let large = recurrence(&[modulus - 1, modulus - 2], &[modulus - 3, modulus - 4], modulus);
+        let (m, x0, x1) = (modulus as u128, (modulus - 3) as u128, (modulus - 4) as u128);
+        let x2 = ((m - 1) * x1 % m + (m - 2) * x0 % m) % m;
+        assert_eq!(large.evaluate(2), x2 as u64);
+        assert!(first(&large, 50).iter().all(|x| *x < modulus));
+    }
+
+    #[test]
+    fn validates_limits() {
+        assert!(Recurrence::fibonacci(7).validate().is_ok());
+        assert!(recurrence(&[], &[], 7).validate().is_err());
+        assert!(recurrence(&[1; MAX_ORDER as usize + 1], &[0; MAX_ORDER as usize + 1], 7)
+            .validate()
+            .is_err());
+        assert!(recurrence(&[1, 1], &[0], 7).validate().is_err());
+        assert!(recurrence(&[1, 1], &[0, 1], 1).validate().is_err());
+        assert!(recurrence(&[1, 7], &[0, 1], 7).validate().is_err());
+        assert!(recurrence(&[1, 1], &[0, 8], 7).validate().is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Verifying a single compressed Fibonacci proof inside another program, as the recursion
+//! program does to demonstrate two levels of recursion: the inner compressed proof is checked by
+//! the outer program, and only the outer proof is wrapped in a SNARK for the EVM.
+
+use alloc::vec::Vec;
+use alloy_primitives::Bytes;
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+use crate::batch::vkey_digest_bytes;
+
+sol! {
+    /// The public values of the recursion program: the digest of the vkey the inner proof was
+    /// verified against, and the public values it committed, unchanged.
+    #[derive(Serialize, Deserialize)]
+    struct RecursionPublicValuesStruct {
+        bytes32 programVKeyDigest;
+        bytes publicValues;
+    }
+}
+
+impl RecursionPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the recursion program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the recursion program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// The input passed from the host to the recursion program, followed by the compressed proof
+/// of the Fibonacci program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecursionInput {
+    /// The digest of the Fibonacci program's vkey (`SP1VerifyingKey::hash_u32`).
+    pub vkey_digest: [u32; 8],
+    /// The public values of the inner proof.
+    pub public_values: Vec<u8>,
+}
+
+impl RecursionInput {
+    /// The public values the recursion program commits for this input.
+    pub fn recursion_public_values(&self) -> RecursionPublicValuesStruct {
+        RecursionPublicValuesStruct {
+            programVKeyDigest: vkey_digest_bytes(&self.vkey_digest),
+            publicValues: Bytes::copy_from_slice(&self.public_values),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! The little RLP decoding the storage and header chain programs need.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::U256;
+use alloy_rlp::Header;
+
+/// An RLP item of a list: a string or a nested list.
+pub(crate) struct Item<'a> {
+    pub list: bool,
+    /// The contents of the item, without its header.
+    pub payload: &'a [u8],
+    /// The whole encoding of the item.
+    pub raw: &'a [u8],
+}
+
+impl Item<'_> {
+    /// Decode the item as an integer of at most 32 bytes.
+    pub fn integer(&self) -> Result<U256, String> {
+        if self.list || self.payload.len() > 32 {
+            return Err("expected an RLP integer of at most 32 bytes".into());
+        }
+        Ok(U256::from_be_slice(self.payload))
+    }
+}
+
+/// Split an RLP list into its items.
+pub(crate) fn list_items(encoded: &[u8]) -> Result<Vec<Item<'_>>, String> {
+    let mut buf = encoded;
+    let header = Header::decode(&mut buf).map_err(|err| format!("invalid RLP: {}", err))?;
+    if !header.list || buf.len() != header.payload_length {
+        return Err("not a well-formed RLP list".into());
+    }
+    let mut items = Vec::new();
+    while !buf.is_empty() {
+        let start = buf;
+        let header = Header::decode(&mut buf).map_err(|err| format!("invalid RLP: {}", err))?;
+        if buf.len() < header.payload_length {
+            return Err("an RLP item is longer than its list".into());
+        }
+        let (payload, rest) = buf.split_at(header.payload_length);
+        let raw = &start[..start.len() - rest.len()];
+        items.push(Item { list: header.list, payload, raw });
+        buf = rest;
+    }
+    Ok(items)
+}
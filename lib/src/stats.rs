@@ -0,0 +1,68 @@
+//! Statistics over a private dataset, as the statistics program computes them to publish the
+//! count, sum, mean, minimum and maximum of values it does not reveal.
+//!
+//! The program commits the hash of the dataset, `keccak256(abi.encode(int64[] values))`, so
+//! whoever holds the data can show which dataset the statistics are about.
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256, I256};
+use alloy_sol_types::{sol, SolType, SolValue};
+use serde::{Deserialize, Serialize};
+
+sol! {
+    /// The public values of the statistics program. `mean` is rounded down.
+    #[derive(Serialize, Deserialize)]
+    struct StatsPublicValuesStruct {
+        bytes32 datasetHash;
+        uint64 count;
+        int256 sum;
+        int64 min;
+        int64 max;
+        int64 mean;
+    }
+}
+
+/// The input passed from the host to the statistics program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsInput {
+    /// The values, as integers (scaled by the host if they have decimals).
+    pub values: Vec<i64>,
+}
+
+impl StatsInput {
+    /// Compute the public values the statistics program commits, or `None` for an empty
+    /// dataset, whose mean is undefined.
+    pub fn statistics(&self) -> Option<StatsPublicValuesStruct> {
+        let min = *self.values.iter().min()?;
+        let max = *self.values.iter().max()?;
+        let count = self.values.len() as u64;
+        // Fewer than 2^64 values of at most 2^63 each cannot overflow an i128.
+        let sum: i128 = self.values.iter().map(|value| *value as i128).sum();
+        let mean = sum.div_euclid(count as i128) as i64;
+        Some(StatsPublicValuesStruct {
+            datasetHash: dataset_hash(&self.values),
+            count,
+            sum: I256::try_from(sum).expect("an i128 fits in an int256"),
+            min,
+            max,
+            mean,
+        })
+    }
+}
+
+impl StatsPublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the statistics program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the statistics program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// The hash of a dataset: `keccak256(abi.encode(values))`.
+pub fn dataset_hash(values: &[i64]) -> B256 {
+    keccak256(values.to_vec().abi_encode())
+}
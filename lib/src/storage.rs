@@ -0,0 +1,184 @@
+//! Verifying an Ethereum storage proof (as returned by `eth_getProof`), as the storage program
+//! does to attest the value of a contract's storage slot at a given state root.
+//!
+//! The account proof is a Merkle-Patricia trie path from the state root to the account, whose
+//! storage root the storage proof leads from to the slot. Both may also prove absence: an account
+//! that does not exist has an empty storage, and a slot that is not in the trie holds zero.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{b256, keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::Header;
+use alloy_sol_types::{sol, SolType};
+use serde::{Deserialize, Serialize};
+
+use crate::rlp::{list_items, Item};
+
+/// The root of an empty trie: `keccak256(rlp(""))`.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+sol! {
+    /// The public values of the storage program.
+    #[derive(Serialize, Deserialize)]
+    struct StoragePublicValuesStruct {
+        bytes32 stateRoot;
+        address account;
+        bytes32 slot;
+        uint256 value;
+    }
+}
+
+/// The input passed from the host to the storage program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProofInput {
+    pub state_root: B256,
+    pub account: Address,
+    pub slot: B256,
+    /// The trie nodes from the state root to the account, root first.
+    pub account_proof: Vec<Bytes>,
+    /// The trie nodes from the account's storage root to the slot, root first.
+    pub storage_proof: Vec<Bytes>,
+}
+
+impl StorageProofInput {
+    /// Verify both proofs, and return the public values the storage program commits if they
+    /// hold.
+    pub fn verify(&self) -> Result<StoragePublicValuesStruct, String> {
+        let account = verify_proof(self.state_root, keccak256(self.account), &self.account_proof)
+            .map_err(|err| format!("invalid account proof: {}", err))?;
+        let storage_root = match account {
+            Some(account) => storage_root(&account)?,
+            None => EMPTY_ROOT_HASH,
+        };
+        let value = verify_proof(storage_root, keccak256(self.slot), &self.storage_proof)
+            .map_err(|err| format!("invalid storage proof: {}", err))?;
+        let value = match value {
+            Some(value) => decode_value(&value)?,
+            None => U256::ZERO,
+        };
+        Ok(StoragePublicValuesStruct {
+            stateRoot: self.state_root,
+            account: self.account,
+            slot: self.slot,
+            value,
+        })
+    }
+}
+
+impl StoragePublicValuesStruct {
+    /// Decode the ABI-encoded public values committed by the storage program.
+    pub fn from_abi_bytes(bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        <Self as SolType>::abi_decode(bytes, true)
+    }
+
+    /// ABI-encode the public values, as the storage program commits them.
+    pub fn to_abi_bytes(&self) -> Vec<u8> {
+        <Self as SolType>::abi_encode(self)
+    }
+}
+
+/// Where the next node of a proof is: in the proof, by hash, or embedded in its parent.
+enum Child<'a> {
+    Hash(B256),
+    Inline(&'a [u8]),
+}
+
+/// Walk `proof` from `root` along the path `key`, returning the value stored at it, or `None` if
+/// the proof shows there is none.
+fn verify_proof(root: B256, key: B256, proof: &[Bytes]) -> Result<Option<Vec<u8>>, String> {
+    if root == EMPTY_ROOT_HASH {
+        return Ok(None);
+    }
+    let path: Vec<u8> = key.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect();
+    let mut position = 0;
+    let mut nodes = proof.iter();
+    let mut child = Child::Hash(root);
+    loop {
+        let node: &[u8] = match child {
+            Child::Hash(hash) => {
+                let node = nodes.next().ok_or("the proof ends before the key is reached")?;
+                if keccak256(node) != hash {
+                    return Err(format!("a node does not match its hash {}", hash));
+                }
+                node
+            }
+            Child::Inline(node) => node,
+        };
+        let items = list_items(node).map_err(|err| format!("invalid trie node: {}", err))?;
+        child = match items.len() {
+            17 => {
+                // Keys are hashes of the same length, so no value is stored in a branch.
+                let Some(&nibble) = path.get(position) else {
+                    return Err("the key ends at a branch node".into());
+                };
+                position += 1;
+                match child_of(&items[nibble as usize])? {
+                    Some(child) => child,
+                    None => return Ok(None),
+                }
+            }
+            2 => {
+                let (leaf, segment) = decode_path(items[0].payload)?;
+                let rest = &path[position..];
+                if leaf {
+                    return Ok((rest == segment.as_slice()).then(|| items[1].payload.to_vec()));
+                }
+                if !rest.starts_with(&segment) {
+                    return Ok(None);
+                }
+                position += segment.len();
+                child_of(&items[1])?.ok_or("an extension node has no child")?
+            }
+            count => return Err(format!("a trie node has 2 or 17 items, got {}", count)),
+        };
+    }
+}
+
+/// The child a branch or extension node refers to, or `None` for an empty branch.
+fn child_of<'a>(item: &Item<'a>) -> Result<Option<Child<'a>>, String> {
+    match (item.list, item.payload.len()) {
+        (true, _) => Ok(Some(Child::Inline(item.raw))),
+        (false, 0) => Ok(None),
+        (false, 32) => Ok(Some(Child::Hash(B256::from_slice(item.payload)))),
+        (false, len) => Err(format!("a node reference is a hash or a list, got {} bytes", len)),
+    }
+}
+
+/// Decode the hex-prefix encoded path of a leaf or extension node into whether it is a leaf and
+/// its nibbles.
+fn decode_path(encoded: &[u8]) -> Result<(bool, Vec<u8>), String> {
+    let (&first, rest) = encoded.split_first().ok_or("a node has an empty path")?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(format!("invalid hex-prefix flag {}", flag));
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Ok((flag & 2 == 2, nibbles))
+}
+
+/// The storage root of an RLP-encoded account: `[nonce, balance, storageRoot, codeHash]`.
+fn storage_root(account: &[u8]) -> Result<B256, String> {
+    let items = list_items(account).map_err(|err| format!("invalid account: {}", err))?;
+    match items.get(2) {
+        Some(item) if items.len() == 4 && !item.list && item.payload.len() == 32 => {
+            Ok(B256::from_slice(item.payload))
+        }
+        _ => Err("an account is [nonce, balance, storageRoot, codeHash]".into()),
+    }
+}
+
+/// Decode a storage value, which the trie stores as an RLP-encoded integer.
+fn decode_value(encoded: &[u8]) -> Result<U256, String> {
+    let mut buf = encoded;
+    let header = Header::decode(&mut buf).map_err(|err| format!("invalid RLP: {}", err))?;
+    if header.list || buf.len() != header.payload_length || header.payload_length > 32 {
+        return Err("a storage value is an RLP integer of at most 32 bytes".into());
+    }
+    Ok(U256::from_be_slice(buf))
+}
@@ -0,0 +1,88 @@
+//! Streaming a large byte payload into a program in chunks, so the program never holds more than
+//! one chunk in memory.
+//!
+//! The host writes a [`StreamHeader`] with the total length, then each chunk as a separate item
+//! of the stdin (`fibonacci_script::stream::write_chunked`). The program reads the header, then
+//! the chunks one at a time with a [`ChunkReader`], which hashes them as it goes:
+//! ```ignore
+//! let header = sp1_zkvm::io::read::<StreamHeader>();
+//! let mut reader = ChunkReader::new(header, sp1_zkvm::io::read_vec);
+//! for chunk in &mut reader {
+//!     // Process the chunk, then drop it.
+//! }
+//! let hash = reader.finish().expect("the stream must be complete");
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{Keccak256, B256};
+use serde::{Deserialize, Serialize};
+
+/// The size of the chunks the host splits a payload into by default.
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// What the host writes before the chunks of a payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamHeader {
+    /// The length of the whole payload.
+    pub len: u64,
+    /// The number of chunks that follow.
+    pub chunks: u32,
+}
+
+impl StreamHeader {
+    /// The header of a payload of `len` bytes split into chunks of `chunk_size` bytes.
+    pub fn new(len: u64, chunk_size: usize) -> Self {
+        Self { len, chunks: len.div_ceil(chunk_size.max(1) as u64) as u32 }
+    }
+}
+
+/// Reads the chunks of a payload one at a time, and hashes them as it goes.
+pub struct ChunkReader<F> {
+    read: F,
+    header: StreamHeader,
+    read_chunks: u32,
+    read_len: u64,
+    hasher: Keccak256,
+}
+
+impl<F: FnMut() -> Vec<u8>> ChunkReader<F> {
+    /// A reader of the chunks announced by `header`, each read with `read` (in a program,
+    /// `sp1_zkvm::io::read_vec`).
+    pub fn new(header: StreamHeader, read: F) -> Self {
+        Self { read, header, read_chunks: 0, read_len: 0, hasher: Keccak256::new() }
+    }
+
+    /// The number of bytes read so far.
+    pub fn read_len(&self) -> u64 {
+        self.read_len
+    }
+
+    /// Check that every chunk was read and that they add up to the announced length, and return
+    /// the keccak256 hash of the whole payload.
+    pub fn finish(self) -> Result<B256, String> {
+        if self.read_chunks != self.header.chunks {
+            return Err(format!("read {} of {} chunks", self.read_chunks, self.header.chunks));
+        }
+        if self.read_len != self.header.len {
+            return Err(format!("read {} bytes, {} were announced", self.read_len, self.header.len));
+        }
+        Ok(self.hasher.finalize())
+    }
+}
+
+impl<F: FnMut() -> Vec<u8>> Iterator for ChunkReader<F> {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Vec<u8>> {
+        if self.read_chunks == self.header.chunks {
+            return None;
+        }
+        let chunk = (self.read)();
+        self.read_chunks += 1;
+        self.read_len += chunk.len() as u64;
+        self.hasher.update(&chunk);
+        Some(chunk)
+    }
+}
@@ -0,0 +1,6 @@
+use sp1_build::build_program_with_args;
+
+fn main() {
+    napi_build::setup();
+    build_program_with_args("../program", Default::default());
+}
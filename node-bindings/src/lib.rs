@@ -0,0 +1,192 @@
+//! Node.js bindings for proving the Fibonacci program and verifying proofs on-chain.
+//!
+//! ```js
+//! const { prove, verifyOnChain } = require("@sp1-fibonacci/node");
+//!
+//! const proof = await prove(20, { system: "groth16" });
+//! const result = await verifyOnChain(proof, { rpcUrl, contract });
+//! ```
+
+use alloy_primitives::{Address, Bytes};
+use alloy_provider::ProviderBuilder;
+use alloy_sol_types::sol;
+use fibonacci_lib::{ProgramInput, PublicValuesStruct, Sequence};
+use napi::{Error, Result};
+use napi_derive::napi;
+use sp1_sdk::{include_elf, EnvProver, HashableKey, ProverClient, SP1ProvingKey, SP1Stdin};
+use std::sync::OnceLock;
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+
+sol! {
+    /// The verification entry point shared by the Fibonacci verifier contracts.
+    #[sol(rpc)]
+    interface IFibonacciVerifier {
+        function verifyFibonacciProof(bytes publicValues, bytes proofBytes) external;
+    }
+}
+
+/// Options for `prove`. Unset fields leave the public values unbound.
+#[napi(object)]
+#[derive(Default)]
+pub struct ProveOptions {
+    /// `"groth16"` (the default) or `"plonk"`.
+    pub system: Option<String>,
+    /// `"fibonacci"` (the default), `"lucas"`, or two seeds such as `"3,7"`.
+    pub sequence: Option<String>,
+    pub range_start: Option<u32>,
+    /// Commit the hash of the range instead of its values.
+    pub range_hash: Option<bool>,
+    pub chain_id: Option<i64>,
+    pub consumer: Option<String>,
+    pub nonce: Option<i64>,
+    pub valid_until: Option<i64>,
+}
+
+/// An EVM-compatible proof, with its public values decoded.
+#[napi(object)]
+pub struct ProofArtifact {
+    pub system: String,
+    pub vkey_hash: String,
+    /// `0x`-prefixed proof bytes to pass to `verifyFibonacciProof`.
+    pub proof_bytes: String,
+    /// `0x`-prefixed ABI-encoded public values.
+    pub public_values: String,
+    pub n: u32,
+    pub a: u32,
+    pub b: u32,
+    pub fib_values: Vec<u32>,
+    /// The committed status (0 is OK; see `STATUS_*` in the generated Solidity).
+    pub status: u32,
+    /// The `0x`-prefixed hash of the range with `rangeHash: true`, else zero.
+    pub range_hash: String,
+}
+
+/// The proof to verify on-chain; a `ProofArtifact` or the `parameters` of a saved
+/// `contract_call_data_*.json` both fit.
+#[napi(object)]
+pub struct CallArtifact {
+    pub proof_bytes: String,
+    pub public_values: String,
+}
+
+/// The chain and contract to verify against.
+#[napi(object)]
+pub struct ChainOptions {
+    pub rpc_url: String,
+    /// The address of a `Fibonacci` or `FibonacciSimple` contract.
+    pub contract: String,
+}
+
+/// The outcome of simulating `verifyFibonacciProof`.
+#[napi(object)]
+pub struct VerifyResult {
+    pub verified: bool,
+    /// Why the call reverted, if it did.
+    pub error: Option<String>,
+}
+
+/// The prover and proving key, set up on first use and shared by every call.
+fn prover() -> &'static (EnvProver, SP1ProvingKey, String) {
+    static PROVER: OnceLock<(EnvProver, SP1ProvingKey, String)> = OnceLock::new();
+    PROVER.get_or_init(|| {
+        let client = ProverClient::from_env();
+        let (pk, vk) = client.setup(FIBONACCI_ELF);
+        (client, pk, vk.bytes32())
+    })
+}
+
+/// Generate an EVM-compatible proof of the n'th Fibonacci number, off the JS thread.
+#[napi]
+pub async fn prove(n: u32, options: Option<ProveOptions>) -> Result<ProofArtifact> {
+    let options = options.unwrap_or_default();
+    let system = options.system.unwrap_or_else(|| "groth16".to_string());
+    let input = ProgramInput {
+        n,
+        range_start: options.range_start,
+        range_hash: options.range_hash.unwrap_or_default(),
+        chain_id: options.chain_id.map(to_u64).transpose()?,
+        consumer: options.consumer.as_deref().map(parse_address).transpose()?,
+        nonce: options.nonce.map(to_u64).transpose()?,
+        valid_until: options.valid_until.map(to_u64).transpose()?,
+        recurrence: None,
+        seeds: parse_sequence(options.sequence.as_deref())?.input_seeds(),
+    };
+    input.validate().map_err(Error::from_reason)?;
+
+    tokio::task::spawn_blocking(move || {
+        let (client, pk, vkey_hash) = prover();
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&input);
+
+        let proof = match system.as_str() {
+            "groth16" => client.prove(pk, &stdin).groth16().run(),
+            "plonk" => client.prove(pk, &stdin).plonk().run(),
+            _ => return Err(Error::from_reason(format!("unknown proof system: {}", system))),
+        }
+        .map_err(|err| Error::from_reason(format!("failed to generate proof: {}", err)))?;
+
+        let decoded = PublicValuesStruct::from_abi_bytes(proof.public_values.as_slice())
+            .map_err(|err| Error::from_reason(format!("failed to decode public values: {}", err)))?;
+        Ok(ProofArtifact {
+            system,
+            vkey_hash: vkey_hash.clone(),
+            proof_bytes: format!("0x{}", hex::encode(proof.bytes())),
+            public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
+            n: decoded.n,
+            a: decoded.a,
+            b: decoded.b,
+            fib_values: decoded.fibValues,
+            status: decoded.status.into(),
+            range_hash: decoded.rangeHash.to_string(),
+        })
+    })
+    .await
+    .map_err(|err| Error::from_reason(format!("proving task failed: {}", err)))?
+}
+
+/// Simulate `verifyFibonacciProof` against a deployed contract.
+#[napi(js_name = "verifyOnChain")]
+pub async fn verify_on_chain(artifact: CallArtifact, chain: ChainOptions) -> Result<VerifyResult> {
+    let contract = parse_address(&chain.contract)?;
+    let public_values = parse_bytes(&artifact.public_values)?;
+    let proof_bytes = parse_bytes(&artifact.proof_bytes)?;
+
+    let provider = ProviderBuilder::new()
+        .on_builtin(&chain.rpc_url)
+        .await
+        .map_err(|err| Error::from_reason(format!("failed to connect to RPC: {}", err)))?;
+    let verifier = IFibonacciVerifier::new(contract, &provider);
+    match verifier.verifyFibonacciProof(public_values, proof_bytes).call().await {
+        Ok(_) => Ok(VerifyResult { verified: true, error: None }),
+        Err(err) => Ok(VerifyResult { verified: false, error: Some(err.to_string()) }),
+    }
+}
+
+/// Convert a JS number to an unsigned public value field.
+fn to_u64(value: i64) -> Result<u64> {
+    u64::try_from(value)
+        .map_err(|_| Error::from_reason(format!("expected a non-negative value: {}", value)))
+}
+
+/// Parse a sequence name or seeds.
+fn parse_sequence(sequence: Option<&str>) -> Result<Sequence> {
+    sequence.map_or(Ok(Sequence::Fibonacci), |sequence| {
+        sequence.parse().map_err(|err: String| Error::from_reason(err))
+    })
+}
+
+/// Parse a hex address.
+fn parse_address(address: &str) -> Result<Address> {
+    address
+        .parse()
+        .map_err(|err| Error::from_reason(format!("invalid address {}: {}", address, err)))
+}
+
+/// Parse `0x`-prefixed (or bare) hex bytes.
+fn parse_bytes(hex_str: &str) -> Result<Bytes> {
+    hex::decode(hex_str.trim_start_matches("0x"))
+        .map(Bytes::from)
+        .map_err(|err| Error::from_reason(format!("invalid hex: {}", err)))
+}
@@ -14,43 +14,60 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use alloy_sol_types::SolType;
-use fibonacci_lib::{fibonacci, PublicValuesStruct};
+use fibonacci_lib::{run_program, ProgramInput, Sequence, GUEST_LOG_FD};
 
-pub fn main() {
-    // Read input from the prover
-    let n = sp1_zkvm::io::read::<u32>();
+/// Write a line to the guest log, which the host collects separately from its own output.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        sp1_zkvm::io::write(GUEST_LOG_FD, format!("{}\n", format_args!($($arg)*)).as_bytes())
+    };
+}
 
-    // Input validation - prevent excessive computation
-    if n > 10000 {
-        panic!("Input too large: maximum allowed is 10000");
-    }
+pub fn main() {
+    // Read input from the prover. The `cycle-tracker-report-*` markers (which the executor
+    // consumes instead of printing) delimit the regions `cli profile` breaks the cycles down by.
+    println!("cycle-tracker-report-start: read_input");
+    let input = sp1_zkvm::io::read::<ProgramInput>();
+    println!("cycle-tracker-report-end: read_input");
+    let n = input.n;
 
-    // Log the input for debugging (will appear in proof generation logs)
-    println!("Computing Fibonacci for n = {}", n);
+    // Log the input for debugging (collected by the host as `guest_stdout`)
+    log!("Computing Fibonacci for n = {}", n);
 
-    // Compute the n'th fibonacci number using the optimized function
-    let (a, b) = fibonacci(n);
+    // Validate the input (preventing excessive computation), then compute and check the n'th
+    // fibonacci number (and the requested range, if any). A rejected input is committed with its
+    // status rather than panicking, so the rejection itself can be proven.
+    println!("cycle-tracker-report-start: fibonacci");
+    let public_values = run_program(&input);
+    println!("cycle-tracker-report-end: fibonacci");
+    let (a, b) = (public_values.a, public_values.b);
 
-    // Additional verification - ensure computation is correct
-    if n == 0 {
-        assert_eq!(a, 0);
-        assert_eq!(b, 1);
-    } else if n == 1 {
-        assert_eq!(a, 1);
-        assert_eq!(b, 1);
+    if public_values.is_rejected() {
+        log!("Rejected n = {}: {}", n, public_values.status_name());
+    } else if public_values.is_recurrence() {
+        log!("x({}) = {} (mod {})", n, public_values.result, public_values.modulus);
+    } else {
+        let name = public_values.sequence().unwrap_or(Sequence::Fibonacci);
+        log!("{}({}) = {}, {}({}) = {}", name, n.saturating_sub(1), a, name, n, b);
+    }
+    if let Some(start) = public_values.range_start() {
+        log!("Committing {} values for Fibonacci({}..={})", public_values.fibValues.len(), start, n);
+    }
+    if let (Some(start), true) = (input.range_start, public_values.has_range_hash()) {
+        log!("Committing the hash of Fibonacci({}..={}): {}", start, n, public_values.rangeHash);
     }
-
-    println!("Fibonacci({}) = {}, Fibonacci({}) = {}", n.saturating_sub(1), a, n, b);
 
     // Encode the public values using Solidity-compatible ABI encoding
-    let public_values = PublicValuesStruct { n, a, b };
-    let bytes = PublicValuesStruct::abi_encode(&public_values);
+    println!("cycle-tracker-report-start: abi_encode");
+    let bytes = public_values.to_abi_bytes();
+    println!("cycle-tracker-report-end: abi_encode");
 
     // Commit to the public values - this will be verified on-chain
+    println!("cycle-tracker-report-start: commit");
     sp1_zkvm::io::commit_slice(&bytes);
+    println!("cycle-tracker-report-end: commit");
 
-    println!("✅ Proof generation completed successfully!");
+    log!("✅ Proof generation completed successfully!");
 }
 
 // You can add custom functions here for your specific use case
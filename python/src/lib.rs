@@ -0,0 +1,280 @@
+//! Python bindings for proving and verifying the Fibonacci program.
+//!
+//! ```python
+//! import sp1_fib_py
+//!
+//! prover = sp1_fib_py.Prover()  # configured from SP1_PROVER / NETWORK_PRIVATE_KEY
+//! print(prover.execute(20).b)
+//! proof = prover.prove(20, system="groth16")
+//! prover.verify(proof)
+//! proof.save("artifacts")
+//! ```
+
+use alloy_primitives::Address;
+use fibonacci_lib::{ProgramInput, PublicValuesStruct, Sequence};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use sp1_sdk::{
+    include_elf, EnvProver, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey,
+    SP1Stdin, SP1VerifyingKey,
+};
+use std::fs;
+use std::path::Path;
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+
+/// A prover for the Fibonacci program, configured from the environment like the script binaries.
+#[pyclass(module = "sp1_fib_py")]
+struct Prover {
+    client: EnvProver,
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+}
+
+/// The outputs of executing the program without proving it.
+#[pyclass(module = "sp1_fib_py", get_all)]
+struct Execution {
+    n: u32,
+    a: u32,
+    b: u32,
+    fib_values: Vec<u32>,
+    /// The hash of the range, if it was committed with `range_hash=True` (else all zeros).
+    #[getter]
+    fn range_hash(&self) -> String {
+        self.decoded.rangeHash.to_string()
+    }
+
+    /// The committed status (0 is OK; see `STATUS_*` in the generated Solidity).
+    status: u8,
+    cycles: u64,
+}
+
+/// An EVM-compatible proof and the public values it commits to.
+#[pyclass(module = "sp1_fib_py")]
+struct Proof {
+    proof: SP1ProofWithPublicValues,
+    #[pyo3(get)]
+    system: String,
+    #[pyo3(get)]
+    vkey_hash: String,
+    decoded: PublicValuesStruct,
+}
+
+#[pymethods]
+impl Prover {
+    /// Set up the program. Proving keys are generated once and reused for every proof.
+    #[new]
+    fn new(py: Python<'_>) -> Self {
+        py.allow_threads(|| {
+            let client = ProverClient::from_env();
+            let (pk, vk) = client.setup(FIBONACCI_ELF);
+            Self { client, pk, vk }
+        })
+    }
+
+    /// The program vkey hash the verifier contracts are deployed with.
+    #[getter]
+    fn vkey_hash(&self) -> String {
+        self.vk.bytes32()
+    }
+
+    /// Execute the program without generating a proof.
+    #[pyo3(signature = (n, range_start=None))]
+    fn execute(&self, py: Python<'_>, n: u32, range_start: Option<u32>) -> PyResult<Execution> {
+        let input = ProgramInput { n, range_start, ..Default::default() };
+        let stdin = to_stdin(&input)?;
+
+        let (output, report) = py
+            .allow_threads(|| self.client.execute(FIBONACCI_ELF, &stdin).run())
+            .map_err(|err| PyRuntimeError::new_err(format!("execution failed: {}", err)))?;
+        let decoded = decode(output.as_slice())?;
+        Ok(Execution {
+            n: decoded.n,
+            a: decoded.a,
+            b: decoded.b,
+            fib_values: decoded.fibValues,
+            status: decoded.status,
+            cycles: report.total_instruction_count(),
+        })
+    }
+
+    /// Generate an EVM-compatible proof. The GIL is released while proving.
+    #[pyo3(signature = (
+        n,
+        system = "groth16",
+        sequence = "fibonacci",
+        range_start = None,
+        range_hash = false,
+        chain_id = None,
+        consumer = None,
+        nonce = None,
+        valid_until = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn prove(
+        &self,
+        py: Python<'_>,
+        n: u32,
+        system: &str,
+        sequence: &str,
+        range_start: Option<u32>,
+        range_hash: bool,
+        chain_id: Option<u64>,
+        consumer: Option<&str>,
+        nonce: Option<u64>,
+        valid_until: Option<u64>,
+    ) -> PyResult<Proof> {
+        let consumer = consumer
+            .map(|consumer| consumer.parse::<Address>())
+            .transpose()
+            .map_err(|err| PyValueError::new_err(format!("invalid consumer: {}", err)))?;
+        let sequence = sequence.parse::<Sequence>().map_err(PyValueError::new_err)?;
+        let input = ProgramInput {
+            n,
+            range_start,
+            range_hash,
+            chain_id,
+            consumer,
+            nonce,
+            valid_until,
+            recurrence: None,
+            seeds: sequence.input_seeds(),
+        };
+        let stdin = to_stdin(&input)?;
+
+        let proof = py
+            .allow_threads(|| match system {
+                "groth16" => Ok(self.client.prove(&self.pk, &stdin).groth16().run()),
+                "plonk" => Ok(self.client.prove(&self.pk, &stdin).plonk().run()),
+                _ => Err(PyValueError::new_err(format!("unknown proof system: {}", system))),
+            })?
+            .map_err(|err| PyRuntimeError::new_err(format!("failed to generate proof: {}", err)))?;
+        let decoded = decode(proof.public_values.as_slice())?;
+        Ok(Proof { proof, system: system.to_string(), vkey_hash: self.vk.bytes32(), decoded })
+    }
+
+    /// Verify a proof generated by this prover. Raises if the proof is invalid.
+    fn verify(&self, py: Python<'_>, proof: &Proof) -> PyResult<()> {
+        py.allow_threads(|| self.client.verify(&proof.proof, &self.vk))
+            .map_err(|err| PyRuntimeError::new_err(format!("failed to verify proof: {}", err)))
+    }
+}
+
+#[pymethods]
+impl Proof {
+    /// The proof bytes to pass to `verifyFibonacciProof`.
+    #[getter]
+    fn proof_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.proof.bytes())
+    }
+
+    /// The ABI-encoded public values.
+    #[getter]
+    fn public_values<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, self.proof.public_values.as_slice())
+    }
+
+    #[getter]
+    fn n(&self) -> u32 {
+        self.decoded.n
+    }
+
+    #[getter]
+    fn a(&self) -> u32 {
+        self.decoded.a
+    }
+
+    #[getter]
+    fn b(&self) -> u32 {
+        self.decoded.b
+    }
+
+    #[getter]
+    fn fib_values(&self) -> Vec<u32> {
+        self.decoded.fibValues.clone()
+    }
+
+    /// The committed status (0 is OK; see `STATUS_*` in the generated Solidity).
+    #[getter]
+    fn status(&self) -> u8 {
+        self.decoded.status
+    }
+
+    fn __str__(&self) -> String {
+        self.decoded.to_string()
+    }
+
+    /// Save the proof in the same layout as `network_evm`, so `verify_onchain` and
+    /// `cli verify-wrapped` can pick it up.
+    fn save(&self, dir: &str) -> PyResult<()> {
+        self.write_artifacts(Path::new(dir))
+            .map_err(|err| PyRuntimeError::new_err(format!("failed to save artifacts: {}", err)))
+    }
+}
+
+impl Proof {
+    fn write_artifacts(&self, dir: &Path) -> std::io::Result<()> {
+        let n = self.decoded.n;
+        let public_values = format!("0x{}", hex::encode(self.proof.public_values.as_slice()));
+        let proof_bytes = format!("0x{}", hex::encode(self.proof.bytes()));
+
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join(format!("proof_{}_n{}.bin", self.system, n)), self.proof.bytes())?;
+        let public_values_path = dir.join(format!("public_values_n{}.bin", n));
+        fs::write(public_values_path, self.proof.public_values.as_slice())?;
+        fs::write(dir.join("verification_key.txt"), &self.vkey_hash)?;
+
+        let call_data = serde_json::json!({
+            "function": "verifyFibonacciProof",
+            "parameters": {
+                "publicValues": public_values,
+                "proofBytes": proof_bytes
+            },
+            "expected_output": {
+                "n": self.decoded.n,
+                "a": self.decoded.a,
+                "b": self.decoded.b,
+                "fib_values": self.decoded.fibValues,
+                "chain_id": self.decoded.chainId,
+                "consumer": self.decoded.consumer.to_string(),
+                "nonce": self.decoded.nonce,
+                "valid_until": self.decoded.validUntil,
+                "status": self.decoded.status,
+                "range_hash": self.decoded.rangeHash.to_string(),
+                "coefficients": self.decoded.coefficients,
+                "initial_values": self.decoded.initialValues,
+                "modulus": self.decoded.modulus,
+                "result": self.decoded.result
+            }
+        });
+        fs::write(
+            dir.join(format!("contract_call_data_n{}.json", n)),
+            serde_json::to_string_pretty(&call_data).expect("JSON values always serialize"),
+        )
+    }
+}
+
+/// Validate the input and write it to the program's stdin.
+fn to_stdin(input: &ProgramInput) -> PyResult<SP1Stdin> {
+    input.validate().map_err(PyValueError::new_err)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(input);
+    Ok(stdin)
+}
+
+/// Decode the public values committed by the program.
+fn decode(public_values: &[u8]) -> PyResult<PublicValuesStruct> {
+    PublicValuesStruct::from_abi_bytes(public_values)
+        .map_err(|err| PyValueError::new_err(format!("failed to decode public values: {}", err)))
+}
+
+/// The `sp1_fib_py` Python module.
+#[pymodule]
+fn sp1_fib_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Prover>()?;
+    m.add_class::<Execution>()?;
+    m.add_class::<Proof>()?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+//! The recursion program: verifies one compressed proof of the Fibonacci program and commits its
+//! public values, so that the Fibonacci result is settled on-chain through a proof of a proof.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::recursion::RecursionInput;
+use sha2::{Digest, Sha256};
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<RecursionInput>();
+
+    // The proof is read by the verifier from the proof the host wrote to the stdin; it is
+    // checked against the digest of the public values it is claimed to commit.
+    let digest: [u8; 32] = Sha256::digest(&input.public_values).into();
+    sp1_zkvm::lib::verify::verify_sp1_proof(&input.vkey_digest, &digest);
+
+    sp1_zkvm::io::commit_slice(&input.recursion_public_values().to_abi_bytes());
+}
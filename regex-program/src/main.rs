@@ -0,0 +1,14 @@
+//! The regex program: runs a DFA compiled from a public regular expression over private text,
+//! and commits whether the text matches with the hashes of the pattern, the DFA and the text.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::dfa::RegexInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<RegexInput>();
+
+    let public_values = input.public_values().expect("the DFA must be valid");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
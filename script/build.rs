@@ -1,5 +1,145 @@
-use sp1_build::build_program_with_args;
+use sp1_build::{build_program_with_args, BuildArgs};
+use std::path::Path;
+use std::process::Command;
+
+#[path = "src/fingerprint.rs"]
+mod fingerprint;
 
 fn main() {
-    build_program_with_args("../program", Default::default())
+    build_program_with_args("../program", program_build_args());
+    build_program_with_args("../aggregator-program", BuildArgs::default());
+    build_program_with_args("../recursion-program", BuildArgs::default());
+    build_program_with_args("../json-program", BuildArgs::default());
+    build_program_with_args("../ed25519-program", BuildArgs::default());
+    build_program_with_args("../storage-program", BuildArgs::default());
+    build_program_with_args("../header-chain-program", BuildArgs::default());
+    build_program_with_args("../stats-program", BuildArgs::default());
+    build_program_with_args("../jwt-program", BuildArgs::default());
+    build_program_with_args("../regex-program", BuildArgs::default());
+    build_program_with_args("../bls-program", BuildArgs::default());
+    if std::env::var_os("CARGO_FEATURE_HASH_BENCH").is_some() {
+        build_program_with_args("../hashing-program", BuildArgs::default());
+        build_program_with_args("../hashing-program-patched", BuildArgs::default());
+    }
+    embed_source_fingerprint();
+    embed_provenance();
+    build_contracts();
+}
+
+/// Embed the fingerprint of the guest sources the ELF is built from as `GUEST_SOURCE_FINGERPRINT`,
+/// so the scripts can detect that they embed a stale ELF.
+fn embed_source_fingerprint() {
+    for dir in fingerprint::GUEST_SOURCE_DIRS {
+        println!("cargo:rerun-if-changed={}/src", dir);
+        println!("cargo:rerun-if-changed={}/Cargo.toml", dir);
+    }
+    println!("cargo:rerun-if-env-changed=SP1_SKIP_PROGRAM_BUILD");
+    // A skipped build keeps whatever ELF was built before, so its sources are unknown.
+    let fingerprint = match std::env::var("SP1_SKIP_PROGRAM_BUILD") {
+        Ok(_) => None,
+        Err(_) => fingerprint::source_fingerprint(Path::new("."), fingerprint::GUEST_SOURCE_DIRS),
+    };
+    println!("cargo:rustc-env=GUEST_SOURCE_FINGERPRINT={}", fingerprint.unwrap_or_default());
+}
+
+/// Embed the commit, compiler, package versions and cargo features the scripts are built from as
+/// `BUILD_GIT_COMMIT`, `BUILD_RUSTC_VERSION`, `BUILD_PACKAGES` and `BUILD_FEATURES`, for the
+/// provenance recorded with every saved proof (see `src/provenance.rs`).
+fn embed_provenance() {
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/index");
+    println!("cargo:rerun-if-changed=../Cargo.lock");
+
+    let output = |command: &mut Command| {
+        command
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let commit = output(Command::new("git").args(["rev-parse", "HEAD"]));
+    let status = output(Command::new("git").args(["status", "--porcelain"]));
+    let commit = match (commit, status) {
+        (Some(commit), Some(status)) if !status.is_empty() => format!("{}-dirty", commit),
+        (commit, _) => commit.unwrap_or_default(),
+    };
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc = output(Command::new(rustc).arg("--version")).unwrap_or_default();
+
+    println!("cargo:rustc-env=BUILD_GIT_COMMIT={}", commit);
+    println!("cargo:rustc-env=BUILD_RUSTC_VERSION={}", rustc);
+    println!("cargo:rustc-env=BUILD_PACKAGES={}", locked_versions(TRACKED_PACKAGES));
+
+    // Some features (e.g. `profiling`) turn on SP1 SDK features, which change how proofs are made.
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(name, _)| {
+            let feature = name.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_ascii_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=BUILD_FEATURES={}", features.join(","));
+}
+
+/// The packages whose versions are recorded in the provenance of proofs.
+const TRACKED_PACKAGES: &[&str] =
+    &["sp1-sdk", "alloy-primitives", "alloy-provider", "alloy-sol-types"];
+
+/// The versions `Cargo.lock` resolves each of `packages` to, as `name=version[,version...]`
+/// pairs separated by `;`.
+fn locked_versions(packages: &[&str]) -> String {
+    let lock = std::fs::read_to_string("../Cargo.lock").unwrap_or_default();
+    let mut name = "";
+    let mut versions: Vec<(&str, Vec<&str>)> =
+        packages.iter().map(|package| (*package, Vec::new())).collect();
+    for line in lock.lines() {
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = value.trim_matches('"');
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            if let Some((_, found)) = versions.iter_mut().find(|(package, _)| *package == name) {
+                found.push(value.trim_matches('"'));
+            }
+        }
+    }
+    versions
+        .iter()
+        .filter(|(_, found)| !found.is_empty())
+        .map(|(package, found)| format!("{}={}", package, found.join(",")))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Build the program inside the SP1 Docker image tagged `SP1_DOCKER_TAG` if it is set, so the
+/// embedded ELF matches the reproducible build pinned in `elf.lock`.
+///
+/// The build-time limits of `fibonacci-lib` are read from the environment by both the program and
+/// the scripts, so a change to them rebuilds both.
+fn program_build_args() -> BuildArgs {
+    println!("cargo:rerun-if-env-changed=SP1_DOCKER_TAG");
+    println!("cargo:rerun-if-env-changed=FIBONACCI_MAX_N");
+    println!("cargo:rerun-if-env-changed=FIBONACCI_MAX_RANGE_LEN");
+    println!("cargo:rerun-if-env-changed=FIBONACCI_MAX_ORDER");
+    match std::env::var("SP1_DOCKER_TAG") {
+        Ok(tag) => BuildArgs { docker: true, tag, ..Default::default() },
+        Err(_) => BuildArgs::default(),
+    }
+}
+
+/// Compile the contracts with Foundry so the `sol!` bindings can be generated from their ABIs.
+fn build_contracts() {
+    println!("cargo:rerun-if-changed=../contracts/src");
+    println!("cargo:rerun-if-changed=../contracts/foundry.toml");
+
+    let status = Command::new("forge").arg("build").current_dir("../contracts").status();
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => panic!("`forge build` failed with {}", status),
+        Err(err) => {
+            // Allow building without Foundry as long as the artifacts already exist.
+            if !Path::new("../contracts/out/FibonacciSimple.sol/FibonacciSimple.json").exists() {
+                panic!("failed to run `forge build` (is Foundry installed?): {}", err);
+            }
+            println!("cargo:warning=skipping `forge build`: {}", err);
+        }
+    }
 }
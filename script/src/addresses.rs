@@ -0,0 +1,162 @@
+//! Resolution of contract targets given on the command line.
+//!
+//! A target is either a raw hex address, an ENS name (e.g. `fib.myproject.eth`), or an alias from
+//! the per-chain address book (`addresses.toml` by default, overridable with `ADDRESS_BOOK`):
+//!
+//! ```toml
+//! [11155111]
+//! fibonacci = "0x44a4c90114d64A027DB4630639153DC54eaA6224"
+//! oracle = "oracle.myproject.eth"
+//! ```
+
+use alloy_primitives::{address, keccak256, Address, B256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_sol_types::sol;
+use alloy_transport::TransportError;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The ENS registry, deployed at the same address on mainnet and the public testnets.
+const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// The address book used when `ADDRESS_BOOK` is not set.
+const DEFAULT_ADDRESS_BOOK: &str = "addresses.toml";
+
+sol! {
+    #[sol(rpc)]
+    interface IENSRegistry {
+        function resolver(bytes32 node) external view returns (address resolver);
+    }
+
+    #[sol(rpc)]
+    interface IENSResolver {
+        function addr(bytes32 node) external view returns (address addr);
+    }
+}
+
+/// Errors from resolving a contract target.
+#[derive(Debug, Error)]
+pub enum AddressError {
+    #[error("RPC request failed: {0}")]
+    Rpc(#[from] TransportError),
+    #[error("ENS lookup failed: {0}")]
+    Ens(#[from] alloy_contract::Error),
+    #[error("ENS name {0} has no resolver")]
+    NoResolver(String),
+    #[error("ENS name {0} does not resolve to an address")]
+    Unresolved(String),
+    #[error("address book {path} not found (needed for alias {alias})")]
+    BookNotFound { path: String, alias: String },
+    #[error("failed to read address book {path}: {reason}")]
+    InvalidBook { path: String, reason: String },
+    #[error("alias {alias} is not defined for chain {chain_id} in {path}")]
+    UnknownAlias { alias: String, chain_id: u64, path: String },
+    #[error("alias {0} must map to an address or ENS name")]
+    InvalidAlias(String),
+}
+
+/// Aliases by chain id (as a string, since TOML keys are strings) and name.
+type AddressBook = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A contract given by address, ENS name, or address book alias.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContractTarget {
+    Address(Address),
+    Ens(String),
+    Alias(String),
+}
+
+impl FromStr for ContractTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") {
+            return Address::from_str(s)
+                .map(Self::Address)
+                .map_err(|err| format!("invalid address {}: {}", s, err));
+        }
+        if s.is_empty() {
+            return Err("empty contract target".to_string());
+        }
+        if s.contains('.') {
+            Ok(Self::Ens(s.to_lowercase()))
+        } else {
+            Ok(Self::Alias(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for ContractTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{}", address),
+            Self::Ens(name) | Self::Alias(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl ContractTarget {
+    /// Resolve the target to an address on the chain served by `rpc_url`.
+    pub async fn resolve(&self, rpc_url: &str) -> Result<Address, AddressError> {
+        let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+        match self {
+            Self::Address(address) => Ok(*address),
+            Self::Ens(name) => resolve_ens(&provider, name).await,
+            Self::Alias(alias) => {
+                let chain_id = provider.get_chain_id().await?;
+                let entry = lookup_alias(chain_id, alias)?;
+                // An alias may itself point at an ENS name, but not at another alias.
+                match entry.parse::<ContractTarget>() {
+                    Ok(Self::Address(address)) => Ok(address),
+                    Ok(Self::Ens(name)) => resolve_ens(&provider, &name).await,
+                    Ok(Self::Alias(_)) | Err(_) => Err(AddressError::InvalidAlias(alias.clone())),
+                }
+            }
+        }
+    }
+}
+
+/// Resolve an ENS name through the registry and the name's resolver.
+async fn resolve_ens<P: Provider>(provider: &P, name: &str) -> Result<Address, AddressError> {
+    let node = namehash(name);
+    let registry = IENSRegistry::new(ENS_REGISTRY, provider);
+    let resolver = registry.resolver(node).call().await?.resolver;
+    if resolver.is_zero() {
+        return Err(AddressError::NoResolver(name.to_string()));
+    }
+
+    let address = IENSResolver::new(resolver, provider).addr(node).call().await?.addr;
+    if address.is_zero() {
+        return Err(AddressError::Unresolved(name.to_string()));
+    }
+    Ok(address)
+}
+
+/// Look up an alias for the given chain in the address book.
+fn lookup_alias(chain_id: u64, alias: &str) -> Result<String, AddressError> {
+    let path = env::var("ADDRESS_BOOK").unwrap_or_else(|_| DEFAULT_ADDRESS_BOOK.to_string());
+    if !Path::new(&path).exists() {
+        return Err(AddressError::BookNotFound { path, alias: alias.to_string() });
+    }
+    let invalid = |reason: String| AddressError::InvalidBook { path: path.clone(), reason };
+    let contents = fs::read_to_string(&path).map_err(|err| invalid(err.to_string()))?;
+    let book: AddressBook = toml::from_str(&contents).map_err(|err| invalid(err.to_string()))?;
+    book.get(&chain_id.to_string()).and_then(|aliases| aliases.get(alias)).cloned().ok_or_else(
+        || AddressError::UnknownAlias { alias: alias.to_string(), chain_id, path: path.clone() },
+    )
+}
+
+/// The EIP-137 namehash of an (already normalized) ENS name.
+fn namehash(name: &str) -> B256 {
+    name.rsplit('.').filter(|label| !label.is_empty()).fold(B256::ZERO, |node, label| {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(node.as_slice());
+        buf[32..].copy_from_slice(keccak256(label.as_bytes()).as_slice());
+        keccak256(buf)
+    })
+}
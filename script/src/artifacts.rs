@@ -0,0 +1,905 @@
+//! Reading and writing proof artifacts: the `artifacts/` directory consumed by `verify_onchain`
+//! and `cli` (or any other [`ArtifactStore`]), and the Solidity test fixtures.
+
+use crate::artifact_store::{self, ArtifactStore, FsStore};
+use crate::jobs::DEFAULT_TENANT;
+use crate::provenance::{Provenance, ProverIdentity};
+use crate::prover::{ProofSystem, FIBONACCI_ELF};
+use alloy_primitives::{keccak256, Bytes, B256};
+use fibonacci_lib::recurrence::MAX_ORDER;
+use fibonacci_lib::{ProgramInput, PublicValuesStruct, VkHash, MAX_N, MAX_RANGE_LEN};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sp1_sdk::SP1ProofWithPublicValues;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// The default directory proof artifacts are written to and read from.
+pub const DEFAULT_ARTIFACTS_DIR: &str = "artifacts";
+
+/// The subdirectory of the artifacts directory proofs are cached in, keyed by [`input_hash`].
+pub const CACHE_DIR: &str = "cache";
+
+/// The subdirectory of the artifacts directory the artifacts of tenants other than the default one
+/// are kept in (see [`tenant_dir`]).
+pub const TENANTS_DIR: &str = "tenants";
+
+/// The version of the [`Manifest`] format written by [`save_proof_artifacts`].
+pub const MANIFEST_VERSION: u32 = 4;
+
+/// The first bytes of every zstd frame, by which compressed artifacts are recognized.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Errors from reading or writing artifacts.
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("{}: invalid JSON: {source}", path.display())]
+    Json { path: PathBuf, source: serde_json::Error },
+    #[error("{}: missing or invalid `{field}`", path.display())]
+    InvalidField { path: PathBuf, field: &'static str },
+    #[error("failed to decode public values: {0}")]
+    Decode(#[from] alloy_sol_types::Error),
+    #[error("{location}: {reason}")]
+    Store { location: String, reason: String },
+}
+
+/// How the large files of a proof bundle (the proof, call data, summary and guest output) are
+/// compressed when saved. Compressed files get a `.zst` suffix; every load path recognizes them
+/// by their contents, so readers do not need to know how a bundle was saved.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Compression {
+    #[default]
+    None,
+    /// zstd at the given level (1 to 22).
+    Zstd(i32),
+}
+
+impl Compression {
+    /// The zstd level used when none is given.
+    pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+    /// The path a file meant for `path` is saved at.
+    pub fn path(self, path: PathBuf) -> PathBuf {
+        match self {
+            Self::None => path,
+            Self::Zstd(_) => compressed_path(&path),
+        }
+    }
+
+    /// Compress `contents`.
+    pub fn compress(self, contents: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(contents.to_vec()),
+            Self::Zstd(level) => zstd::encode_all(contents, level),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Zstd(level) => write!(f, "zstd:{}", level),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    /// Parse `none`, `zstd` or `zstd:<level>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let level = match s {
+            "none" => return Ok(Self::None),
+            "zstd" => Self::DEFAULT_ZSTD_LEVEL,
+            _ => match s.strip_prefix("zstd:") {
+                Some(level) => level.parse().map_err(|_| format!("invalid level `{}`", level))?,
+                None => {
+                    return Err(format!("expected `none`, `zstd` or `zstd:<level>`, got `{}`", s))
+                }
+            },
+        };
+        if !(1..=22).contains(&level) {
+            return Err(format!("zstd levels go from 1 to 22, got {}", level));
+        }
+        Ok(Self::Zstd(level))
+    }
+}
+
+impl From<Compression> for String {
+    fn from(compression: Compression) -> Self {
+        compression.to_string()
+    }
+}
+
+impl TryFrom<String> for Compression {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// The path `path` is saved at when compressed.
+pub fn compressed_path(path: &Path) -> PathBuf {
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(".zst");
+    PathBuf::from(compressed)
+}
+
+/// Decompress `contents` if they are zstd-compressed, and return them as they are otherwise.
+pub fn decompress(contents: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    if contents.starts_with(&ZSTD_MAGIC) {
+        zstd::decode_all(contents.as_slice())
+    } else {
+        Ok(contents)
+    }
+}
+
+/// Read an artifact, whether it was saved compressed or not: from `path`, or from `path` with a
+/// `.zst` suffix if only that exists.
+pub fn read_artifact(path: &Path) -> Result<Vec<u8>, ArtifactError> {
+    let compressed = compressed_path(path);
+    let path = if !path.exists() && compressed.exists() { compressed.as_path() } else { path };
+    fs::read(path)
+        .and_then(decompress)
+        .map_err(|source| ArtifactError::Io { path: path.to_path_buf(), source })
+}
+
+/// Read the artifact at `key` in `store`, whether it was saved compressed or not (see
+/// [`read_artifact`]).
+pub fn read_stored(store: &dyn ArtifactStore, key: &str) -> Result<Vec<u8>, ArtifactError> {
+    let io_err = |key: &str, source: std::io::Error| ArtifactError::Io {
+        path: store.location(key).into(),
+        source,
+    };
+    let compressed = format!("{}.zst", key);
+    let (key, contents) = match store.get(key)? {
+        Some(contents) => (key, contents),
+        None => match store.get(&compressed)? {
+            Some(contents) => (compressed.as_str(), contents),
+            None => return Err(io_err(key, std::io::ErrorKind::NotFound.into())),
+        },
+    };
+    decompress(contents).map_err(|source| io_err(key, source))
+}
+
+/// Read and parse the JSON artifact at `key` in `store`, which may be compressed.
+pub fn read_stored_json<T: serde::de::DeserializeOwned>(
+    store: &dyn ArtifactStore,
+    key: &str,
+) -> Result<T, ArtifactError> {
+    let contents = read_stored(store, key)?;
+    serde_json::from_slice(&contents)
+        .map_err(|source| ArtifactError::Json { path: store.location(key).into(), source })
+}
+
+/// Write a value as canonical JSON (see [`to_canonical_json`]) at `key` in `store`.
+pub fn write_stored_json<T: Serialize>(
+    store: &dyn ArtifactStore,
+    key: &str,
+    value: &T,
+) -> Result<(), ArtifactError> {
+    store.put(key, to_json_string(value).as_bytes())
+}
+
+/// The arguments of a `verifyFibonacciProof` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallData {
+    pub public_values: Bytes,
+    pub proof_bytes: Bytes,
+}
+
+impl CallData {
+    /// The call data of a proof.
+    pub fn from_proof(proof: &SP1ProofWithPublicValues) -> Self {
+        Self {
+            public_values: proof.public_values.to_vec().into(),
+            proof_bytes: proof.bytes().into(),
+        }
+    }
+
+    /// Decode the public values.
+    pub fn decode_public_values(&self) -> Result<PublicValuesStruct, ArtifactError> {
+        Ok(PublicValuesStruct::from_abi_bytes(&self.public_values)?)
+    }
+
+    /// Render the call data in the `contract_call_data_n<n>.json` format, stamped with the
+    /// provenance of this build (so only for proofs it generated).
+    pub fn to_json(&self) -> Result<Value, ArtifactError> {
+        let decoded = self.decode_public_values()?;
+        Ok(json!({
+            "function": "verifyFibonacciProof",
+            "parameters": {
+                "publicValues": self.public_values.to_string(),
+                "proofBytes": self.proof_bytes.to_string()
+            },
+            "expected_output": {
+                "n": decoded.n,
+                "a": decoded.a,
+                "b": decoded.b,
+                "fib_values": decoded.fibValues,
+                "chain_id": decoded.chainId,
+                "consumer": decoded.consumer.to_string(),
+                "nonce": decoded.nonce,
+                "valid_until": decoded.validUntil,
+                "status": decoded.status,
+                "range_hash": decoded.rangeHash,
+                "coefficients": decoded.coefficients,
+                "initial_values": decoded.initialValues,
+                "modulus": decoded.modulus,
+                "result": decoded.result,
+                "decoded_from_public_values": "Use abi.decode(publicValues, (PublicValuesStruct))"
+            },
+            "contract_interface": {
+                "function_signature": "verifyFibonacciProof(bytes,bytes)",
+                "returns": "(uint32,uint32,uint32)"
+            },
+            "guest_config": guest_config(),
+            "provenance": Provenance::current(FIBONACCI_ELF)
+        }))
+    }
+
+    /// Load the call data from a `contract_call_data_n<n>.json` file.
+    pub fn load(path: &Path) -> Result<Self, ArtifactError> {
+        Self::from_json(&read_json(path)?, path)
+    }
+
+    /// Load the call data from the `contract_call_data_n<n>.json` artifact at `key` in `store`.
+    pub fn load_stored(store: &dyn ArtifactStore, key: &str) -> Result<Self, ArtifactError> {
+        Self::from_json(&read_stored_json(store, key)?, Path::new(&store.location(key)))
+    }
+
+    fn from_json(json: &Value, path: &Path) -> Result<Self, ArtifactError> {
+        let field = |field: &'static str| -> Result<Bytes, ArtifactError> {
+            json["parameters"][field]
+                .as_str()
+                .and_then(|hex| hex.parse().ok())
+                .ok_or_else(|| ArtifactError::InvalidField { path: path.to_path_buf(), field })
+        };
+        Ok(Self { public_values: field("publicValues")?, proof_bytes: field("proofBytes")? })
+    }
+}
+
+/// The build-time limits the program was built with (see `FIBONACCI_MAX_N`,
+/// `FIBONACCI_MAX_RANGE_LEN` and `FIBONACCI_MAX_ORDER`), which are part of what its vkey commits
+/// to.
+pub fn guest_config() -> Value {
+    json!({ "max_n": MAX_N, "max_range_len": MAX_RANGE_LEN, "max_order": MAX_ORDER })
+}
+
+/// The path of the call data for `n` in `dir`.
+pub fn call_data_path(dir: &Path, n: u32) -> PathBuf {
+    dir.join(format!("contract_call_data_n{}.json", n))
+}
+
+/// The path of what the program logged when executed on `n` in `dir`.
+pub fn guest_stdout_path(dir: &Path, n: u32) -> PathBuf {
+    dir.join(format!("guest_stdout_n{}.txt", n))
+}
+
+/// The path of the manifest of the proof bundle for `n` in `dir`.
+pub fn manifest_path(dir: &Path, n: u32) -> PathBuf {
+    dir.join(format!("manifest_n{}.json", n))
+}
+
+/// Load every `contract_call_data_*.json` file in `dir`, skipping the directory if it is missing.
+pub fn load_all_call_data(dir: &Path) -> Result<Vec<(PathBuf, CallData)>, ArtifactError> {
+    let call_data = load_all_stored_call_data(&FsStore::new(dir))?;
+    Ok(call_data.into_iter().map(|(key, call_data)| (dir.join(key), call_data)).collect())
+}
+
+/// Load every `contract_call_data_*.json` artifact at the top level of `store`, by key.
+pub fn load_all_stored_call_data(
+    store: &dyn ArtifactStore,
+) -> Result<Vec<(String, CallData)>, ArtifactError> {
+    let mut call_data = Vec::new();
+    for key in store.list("")? {
+        if key.starts_with("contract_call_data_")
+            && (key.ends_with(".json") || key.ends_with(".json.zst"))
+        {
+            let loaded = CallData::load_stored(store, &key)?;
+            call_data.push((key, loaded));
+        }
+    }
+    Ok(call_data)
+}
+
+/// Identify a proof request by the hash of its input and proof system, so that repeated requests
+/// can be served from the cache instead of being proved again.
+pub fn input_hash(input: &ProgramInput, system: ProofSystem) -> B256 {
+    keccak256(to_json_string(&(input, system.to_string())))
+}
+
+/// The directory the artifacts of `tenant` are kept in under `dir`: `dir` itself for the
+/// [`DEFAULT_TENANT`], so existing deployments keep their layout, and `dir/tenants/<tenant>`
+/// otherwise.
+pub fn tenant_dir(dir: &Path, tenant: &str) -> PathBuf {
+    match tenant {
+        DEFAULT_TENANT => dir.to_path_buf(),
+        tenant => dir.join(TENANTS_DIR).join(tenant),
+    }
+}
+
+/// The path the call data for the request with `hash` is cached at in `dir`.
+pub fn cached_call_data_path(dir: &Path, hash: B256) -> PathBuf {
+    dir.join(CACHE_DIR).join(format!("{}.json", hash))
+}
+
+/// The key the call data for the request of `tenant` with `hash` is cached at in an artifact
+/// store laid out like the artifacts directory (see [`tenant_dir`]).
+pub fn cached_call_data_key(tenant: &str, hash: B256) -> String {
+    artifact_store::key(&cached_call_data_path(&tenant_dir(Path::new(""), tenant), hash))
+}
+
+/// Where the files written by [`save_proof_artifacts`] are (see [`ArtifactStore::location`]).
+#[derive(Debug, Clone)]
+pub struct SavedArtifacts {
+    pub proof: String,
+    pub public_values: String,
+    pub verification_key: String,
+    pub call_data: String,
+    pub summary: String,
+    pub guest_stdout: Option<String>,
+    pub manifest: String,
+}
+
+/// The index of a proof bundle: what was proved, and the hash of every file saved for it. A bundle
+/// is referred to as a whole by the hash of its manifest file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub n: u32,
+    pub system: String,
+    pub vkey: VkHash,
+    /// The keccak256 hash of the proof bytes, as in the contracts' settlement events.
+    pub proof_hash: B256,
+    /// The keccak256 hash of each file of the bundle as saved (compressed or not), by file name.
+    pub files: BTreeMap<String, B256>,
+    /// How the large files were compressed (`none` in manifests from before version 2).
+    #[serde(default)]
+    pub compression: Compression,
+    /// The build and host the proof was generated by; unknown for manifests from before version 3
+    /// and for those written by `cli migrate-artifacts`.
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+    /// Who generated the proof: the prover mode, machine and network request; unknown for
+    /// manifests from before version 4.
+    #[serde(default)]
+    pub prover: Option<ProverIdentity>,
+}
+
+impl Manifest {
+    /// The hash of the manifest of the bundle for `n` in `dir`, if there is one and it describes
+    /// the proof with `proof_hash` (a bundle saved later for the same `n` replaces it).
+    pub fn hash_for(dir: &Path, n: u32, proof_hash: B256) -> Result<Option<B256>, ArtifactError> {
+        Self::hash_in(&FsStore::new(dir), n, proof_hash)
+    }
+
+    /// The hash of the manifest of the bundle for `n` at the top level of `store`, as for
+    /// [`Manifest::hash_for`].
+    pub fn hash_in(
+        store: &dyn ArtifactStore,
+        n: u32,
+        proof_hash: B256,
+    ) -> Result<Option<B256>, ArtifactError> {
+        let key = artifact_store::key(&manifest_path(Path::new(""), n));
+        let Some(contents) = store.get(&key)? else {
+            return Ok(None);
+        };
+        let manifest: Self = serde_json::from_slice(&contents)
+            .map_err(|source| ArtifactError::Json { path: store.location(&key).into(), source })?;
+        if manifest.proof_hash != proof_hash {
+            return Ok(None);
+        }
+        Ok(Some(keccak256(contents)))
+    }
+}
+
+/// Save a proof for on-chain verification at the top level of `store`: the raw proof and public
+/// values, the vkey hash, the `verifyFibonacciProof` call data, a human-readable summary, what
+/// the program logged when it was executed, if known, and a manifest of all of them, recording
+/// the `prover` that generated it.
+pub fn save_proof_artifacts(
+    store: &dyn ArtifactStore,
+    proof: &SP1ProofWithPublicValues,
+    system: ProofSystem,
+    vkey_hash: &VkHash,
+    prover: &ProverIdentity,
+    guest_stdout: Option<&str>,
+    compression: Compression,
+) -> Result<SavedArtifacts, ArtifactError> {
+    let call_data = CallData::from_proof(proof);
+    save_call_data_artifacts(
+        store,
+        &call_data,
+        system,
+        vkey_hash,
+        prover,
+        guest_stdout,
+        compression,
+    )
+}
+
+/// Save the artifacts of a proof from its call data, as [`save_proof_artifacts`] does.
+fn save_call_data_artifacts(
+    store: &dyn ArtifactStore,
+    call_data: &CallData,
+    system: ProofSystem,
+    vkey_hash: &VkHash,
+    prover: &ProverIdentity,
+    guest_stdout: Option<&str>,
+    compression: Compression,
+) -> Result<SavedArtifacts, ArtifactError> {
+    let n = call_data.decode_public_values()?.n;
+
+    let dir = Path::new("");
+    let key = |path: PathBuf| artifact_store::key(&path);
+    let proof_key = key(compression.path(dir.join(format!("proof_{}_n{}.bin", system, n))));
+    let public_values_key = key(dir.join(format!("public_values_n{}.bin", n)));
+    let verification_key_key = key(dir.join("verification_key.txt"));
+    let call_data_key = key(compression.path(call_data_path(dir, n)));
+    let summary_key = key(compression.path(dir.join(format!("summary_n{}.txt", n))));
+    let guest_stdout_key = guest_stdout.map(|_| key(compression.path(guest_stdout_path(dir, n))));
+    let manifest_key = key(manifest_path(dir, n));
+    let provenance = Provenance::current(FIBONACCI_ELF);
+    let mut manifest = Manifest {
+        version: MANIFEST_VERSION,
+        n,
+        system: system.to_string(),
+        vkey: *vkey_hash,
+        proof_hash: keccak256(&call_data.proof_bytes),
+        files: BTreeMap::new(),
+        compression,
+        provenance: Some(provenance.clone()),
+        prover: Some(prover.clone()),
+    };
+    // Large files are compressed; either way, a copy saved earlier the other way is removed, so
+    // readers never pick up a stale one.
+    let mut save = |key: &str, contents: &[u8], compress: bool| -> Result<(), ArtifactError> {
+        let io_err = |source| ArtifactError::Io { path: store.location(key).into(), source };
+        let contents = if compress {
+            if let Some(plain) = key.strip_suffix(".zst") {
+                store.delete(plain)?;
+            }
+            compression.compress(contents).map_err(io_err)?
+        } else {
+            store.delete(&format!("{}.zst", key))?;
+            contents.to_vec()
+        };
+        manifest.files.insert(key.to_string(), keccak256(&contents));
+        store.put(key, &contents)
+    };
+    let compress = compression != Compression::None;
+    save(&proof_key, call_data.proof_bytes.as_ref(), compress)?;
+    save(&public_values_key, call_data.public_values.as_ref(), false)?;
+    save(&verification_key_key, vkey_hash.to_string().as_bytes(), false)?;
+    save(&call_data_key, to_json_string(&call_data.to_json()?).as_bytes(), compress)?;
+
+    let system_name = system.to_string();
+    let summary = format!(
+        "SP1 {} Proof Summary\n\
+        ===================\n\
+        Input: {}\n\
+        System: {}\n\
+        Verification Key: {}\n\
+        Public Values: {}\n\
+        Proof: {}\n\
+        Proof Size: {} bytes\n\
+        Guest Config: MAX_N = {}, MAX_RANGE_LEN = {}, MAX_ORDER = {}\n\
+        ELF Hash: {}\n\
+        Built From: commit {} with {}\n\
+        Packages: {}\n\
+        Host: {}/{}\n\
+        Proved By: {}\n\
+        \n\
+        To verify on-chain:\n\
+        1. Deploy Fibonacci contract with VKey: {}\n\
+        2. Call verifyFibonacciProof(publicValues, proof)\n\
+        3. Public Values: {}\n\
+        4. Proof: {}\n",
+        system_name.to_uppercase(),
+        n,
+        system_name,
+        vkey_hash,
+        call_data.public_values,
+        call_data.proof_bytes,
+        call_data.proof_bytes.len(),
+        MAX_N,
+        MAX_RANGE_LEN,
+        MAX_ORDER,
+        provenance.elf_hash,
+        provenance.git_commit,
+        provenance.rustc,
+        provenance.package_list(),
+        provenance.os,
+        provenance.arch,
+        prover.describe(),
+        vkey_hash,
+        call_data.public_values,
+        call_data.proof_bytes
+    );
+    save(&summary_key, summary.as_bytes(), compress)?;
+    if let (Some(key), Some(guest_stdout)) = (&guest_stdout_key, guest_stdout) {
+        save(key, guest_stdout.as_bytes(), compress)?;
+    }
+    write_stored_json(store, &manifest_key, &manifest)?;
+    Ok(SavedArtifacts {
+        proof: store.location(&proof_key),
+        public_values: store.location(&public_values_key),
+        verification_key: store.location(&verification_key_key),
+        call_data: store.location(&call_data_key),
+        summary: store.location(&summary_key),
+        guest_stdout: guest_stdout_key.map(|key| store.location(&key)),
+        manifest: store.location(&manifest_key),
+    })
+}
+
+/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SP1FibonacciProofFixture {
+    pub a: u32,
+    pub b: u32,
+    pub n: u32,
+    pub vkey: VkHash,
+    pub public_values: String,
+    pub proof: String,
+}
+
+impl SP1FibonacciProofFixture {
+    /// Create a fixture for the given proof.
+    pub fn new(
+        proof: &SP1ProofWithPublicValues,
+        vkey_hash: &VkHash,
+    ) -> Result<Self, ArtifactError> {
+        let call_data = CallData::from_proof(proof);
+        let public_values = call_data.decode_public_values()?;
+        Ok(Self {
+            a: public_values.a,
+            b: public_values.b,
+            n: public_values.n,
+            vkey: *vkey_hash,
+            public_values: call_data.public_values.to_string(),
+            proof: call_data.proof_bytes.to_string(),
+        })
+    }
+
+    /// The directory the Solidity tests read fixtures from.
+    pub fn default_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../contracts/src/fixtures")
+    }
+
+    /// Save the fixture as `<system>-fixture.json` in `dir`.
+    pub fn save(&self, dir: &Path, system: ProofSystem) -> Result<PathBuf, ArtifactError> {
+        create_dir(dir)?;
+        let path = dir.join(format!("{}-fixture.json", system));
+        write(&path, to_json_string(self))?;
+        Ok(path)
+    }
+}
+
+/// A proof of one of the programs other than Fibonacci, as saved by its host binary.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProgramProof {
+    /// The name of the program (see [`crate::programs::PROGRAMS`]).
+    pub program: String,
+    #[schema(value_type = String)]
+    pub vkey: VkHash,
+    pub system: ProofSystem,
+    #[schema(value_type = String)]
+    pub public_values: Bytes,
+    #[schema(value_type = String)]
+    pub proof: Bytes,
+    /// The public values, decoded for reading.
+    #[schema(value_type = Object)]
+    pub decoded: Value,
+}
+
+impl ProgramProof {
+    /// The path the proof of `program` is saved at in `dir`.
+    pub fn path(dir: &Path, program: &str) -> PathBuf {
+        dir.join(format!("{}_proof.json", program))
+    }
+
+    /// Save the proof in `dir`, replacing any previous proof of the same program.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, ArtifactError> {
+        let path = Self::path(dir, &self.program);
+        write_json(&path, self)?;
+        Ok(path)
+    }
+
+    /// Load a saved proof.
+    pub fn load(path: &Path) -> Result<Self, ArtifactError> {
+        read_json(path)
+    }
+}
+
+/// Read and parse a JSON file, which may be compressed (see [`read_artifact`]).
+pub fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, ArtifactError> {
+    let contents = read_artifact(path)?;
+    serde_json::from_slice(&contents)
+        .map_err(|source| ArtifactError::Json { path: path.to_path_buf(), source })
+}
+
+/// Write a value as canonical JSON (see [`to_canonical_json`]), creating the parent directory if
+/// needed.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), ArtifactError> {
+    if let Some(parent) = path.parent() {
+        create_dir(parent)?;
+    }
+    write(path, to_json_string(value))
+}
+
+/// Pretty-print a value as JSON, in canonical form.
+fn to_json_string<T: Serialize + ?Sized>(value: &T) -> String {
+    to_canonical_json(value)
+}
+
+/// Render a value as canonical JSON, so that artifacts with the same content are byte-identical
+/// and can be hashed, signed and diffed: object keys sorted by their UTF-8 bytes (whatever the
+/// order of the struct fields), two-space indentation, integers in full, floats in their shortest
+/// round-tripping form, and a trailing newline.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> String {
+    let value = serde_json::to_value(value).expect("artifacts always serialize to JSON");
+    let mut json =
+        serde_json::to_string_pretty(&sort_keys(value)).expect("JSON values always serialize");
+    json.push('\n');
+    json
+}
+
+/// Sort the keys of every object in `value`. `serde_json::Map` keeps insertion order when its
+/// `preserve_order` feature is enabled by another crate, so the keys are inserted sorted.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(key, value)| (key, sort_keys(value))).collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+/// Create a directory and its parents.
+pub(crate) fn create_dir(dir: &Path) -> Result<(), ArtifactError> {
+    fs::create_dir_all(dir).map_err(|source| ArtifactError::Io { path: dir.to_path_buf(), source })
+}
+
+/// Write a file atomically (via a temporary file and a rename), so that an interrupted run never
+/// leaves it half-written.
+pub(crate) fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), ArtifactError> {
+    #[cfg(feature = "chaos")]
+    if crate::chaos::inject(crate::chaos::Fault::ArtifactWrite) {
+        let source = std::io::Error::other("write failed (injected by chaos mode)");
+        return Err(ArtifactError::Io { path: path.to_path_buf(), source });
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents)
+        .and_then(|()| fs::rename(&tmp, path))
+        .map_err(|source| ArtifactError::Io { path: path.to_path_buf(), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifact_store::MemoryStore;
+    use alloy_primitives::b256;
+    use fibonacci_lib::compute_public_values;
+
+    #[derive(Serialize)]
+    struct Forward {
+        alpha: u32,
+        beta: Vec<u32>,
+        gamma: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct Backward {
+        gamma: &'static str,
+        beta: Vec<u32>,
+        alpha: u32,
+    }
+
+    #[test]
+    fn canonical_json_is_independent_of_field_order() {
+        let forward = Forward { alpha: 1, beta: vec![3, 2], gamma: "x" };
+        let backward = Backward { gamma: "x", beta: vec![3, 2], alpha: 1 };
+        assert_eq!(to_canonical_json(&forward), to_canonical_json(&backward));
+    }
+
+    #[test]
+    fn canonical_json_bytes_are_stable() {
+        let value = json!({
+            "z": { "b": [2, { "y": 1, "x": 0 }], "a": null },
+            "a": true,
+            "B": "upper before lower",
+        });
+        let expected = concat!(
+            "{\n",
+            "  \"B\": \"upper before lower\",\n",
+            "  \"a\": true,\n",
+            "  \"z\": {\n",
+            "    \"a\": null,\n",
+            "    \"b\": [\n",
+            "      2,\n",
+            "      {\n",
+            "        \"x\": 0,\n",
+            "        \"y\": 1\n",
+            "      }\n",
+            "    ]\n",
+            "  }\n",
+            "}\n",
+        );
+        assert_eq!(to_canonical_json(&value), expected);
+    }
+
+    #[test]
+    fn canonical_json_writes_numbers_in_full() {
+        let value = json!({ "max": u64::MAX, "min": i64::MIN, "third": 0.1 + 0.2, "half": 0.5 });
+        let json = to_canonical_json(&value);
+        assert!(json.contains("\"max\": 18446744073709551615"));
+        assert!(json.contains("\"min\": -9223372036854775808"));
+        assert!(json.contains("\"third\": 0.30000000000000004"));
+        assert!(json.contains("\"half\": 0.5"));
+    }
+
+    #[test]
+    fn canonical_json_round_trips_to_the_same_bytes() {
+        let value = json!({ "list": [3, 1, 2], "nested": { "k": "v", "a": [{ "d": 1, "c": 2 }] } });
+        let json = to_canonical_json(&value);
+        let reparsed: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(to_canonical_json(&reparsed), json);
+        assert!(json.ends_with("}\n") && !json.ends_with("\n\n"));
+    }
+
+    const VKEY: VkHash =
+        VkHash(b256!("00aa000000000000000000000000000000000000000000000000000000000001"));
+
+    const PROOF: [u8; 260] = [7; 260];
+
+    fn call_data(n: u32) -> CallData {
+        let public_values = compute_public_values(&ProgramInput::new(n));
+        CallData {
+            public_values: public_values.to_abi_bytes().into(),
+            proof_bytes: PROOF.to_vec().into(),
+        }
+    }
+
+    fn prover() -> ProverIdentity {
+        ProverIdentity {
+            mode: "mock".to_string(),
+            hostname: "test".to_string(),
+            features: Vec::new(),
+            request_id: None,
+            fulfiller: None,
+        }
+    }
+
+    fn save(store: &dyn ArtifactStore, n: u32, compression: Compression) -> SavedArtifacts {
+        let call_data = call_data(n);
+        let stdout = Some("computed\n");
+        save_call_data_artifacts(
+            store,
+            &call_data,
+            ProofSystem::Groth16,
+            &VKEY,
+            &prover(),
+            stdout,
+            compression,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn saved_artifacts_load_back_from_the_store() {
+        for (compression, suffix) in [(Compression::None, ""), (Compression::Zstd(3), ".zst")] {
+            let store = artifact_store::open("memory://").unwrap();
+            let store = store.as_ref();
+            let saved = save(store, 10, compression);
+
+            let call_data_key = format!("contract_call_data_n10.json{}", suffix);
+            let mut expected = vec![
+                call_data_key.clone(),
+                format!("guest_stdout_n10.txt{}", suffix),
+                "manifest_n10.json".to_string(),
+                format!("proof_groth16_n10.bin{}", suffix),
+                "public_values_n10.bin".to_string(),
+                format!("summary_n10.txt{}", suffix),
+                "verification_key.txt".to_string(),
+            ];
+            expected.sort();
+            assert_eq!(store.list("").unwrap(), expected);
+            assert_eq!(saved.call_data, format!("memory://{}", call_data_key));
+            assert_eq!(store.key_of(&saved.manifest).as_deref(), Some("manifest_n10.json"));
+
+            // The loaders find each file whether or not it was compressed.
+            assert_eq!(
+                CallData::load_stored(store, "contract_call_data_n10.json").unwrap(),
+                call_data(10)
+            );
+            assert_eq!(load_all_stored_call_data(store).unwrap(), [(call_data_key, call_data(10))]);
+            assert_eq!(read_stored(store, "proof_groth16_n10.bin").unwrap(), PROOF);
+            assert_eq!(read_stored(store, "guest_stdout_n10.txt").unwrap(), b"computed\n");
+            assert_eq!(
+                read_stored(store, "verification_key.txt").unwrap(),
+                VKEY.to_string().as_bytes()
+            );
+            let summary =
+                String::from_utf8(read_stored(store, "summary_n10.txt").unwrap()).unwrap();
+            assert!(summary.contains("Proved By: "), "{}", summary);
+
+            let manifest: Manifest = read_stored_json(store, "manifest_n10.json").unwrap();
+            assert_eq!((manifest.n, manifest.vkey, manifest.compression), (10, VKEY, compression));
+            assert_eq!(manifest.proof_hash, keccak256(PROOF));
+            assert_eq!(manifest.prover, Some(prover()));
+            let files: Vec<&String> = manifest.files.keys().collect();
+            assert_eq!(
+                files,
+                expected.iter().filter(|key| *key != "manifest_n10.json").collect::<Vec<_>>()
+            );
+            for (key, hash) in &manifest.files {
+                assert_eq!(keccak256(store.get(key).unwrap().unwrap()), *hash, "{}", key);
+            }
+
+            let manifest_hash = keccak256(store.get("manifest_n10.json").unwrap().unwrap());
+            assert_eq!(
+                Manifest::hash_in(store, 10, manifest.proof_hash).unwrap(),
+                Some(manifest_hash)
+            );
+            assert_eq!(Manifest::hash_in(store, 10, B256::ZERO).unwrap(), None);
+            assert_eq!(Manifest::hash_in(store, 11, manifest.proof_hash).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn saving_again_replaces_copies_compressed_the_other_way() {
+        let store = MemoryStore::default();
+        save(&store, 10, Compression::Zstd(3));
+        save(&store, 10, Compression::None);
+        assert!(store.list("").unwrap().iter().all(|key| !key.ends_with(".zst")));
+        save(&store, 10, Compression::Zstd(3));
+        let keys = store.list("").unwrap();
+        for plain in ["contract_call_data_n10.json", "proof_groth16_n10.bin", "summary_n10.txt"] {
+            assert!(!keys.iter().any(|key| key == plain), "{} was left behind", plain);
+        }
+        assert_eq!(load_all_stored_call_data(&store).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn loaders_report_missing_and_invalid_artifacts() {
+        let store = MemoryStore::default();
+        assert!(load_all_stored_call_data(&store).unwrap().is_empty());
+        assert!(matches!(
+            CallData::load_stored(&store, "contract_call_data_n10.json"),
+            Err(ArtifactError::Io { source, .. }) if source.kind() == std::io::ErrorKind::NotFound
+        ));
+        assert_eq!(Manifest::hash_in(&store, 10, B256::ZERO).unwrap(), None);
+
+        // Only the call data at the top level is loaded, as the cache is laid out under it.
+        save(&store, 10, Compression::None);
+        let cached = cached_call_data_key(DEFAULT_TENANT, B256::ZERO);
+        store.put(&cached, &store.get("contract_call_data_n10.json").unwrap().unwrap()).unwrap();
+        assert_eq!(load_all_stored_call_data(&store).unwrap().len(), 1);
+        assert_eq!(CallData::load_stored(&store, &cached).unwrap(), call_data(10));
+
+        store.put("contract_call_data_n11.json", b"{\"parameters\": {}}").unwrap();
+        assert!(matches!(
+            load_all_stored_call_data(&store),
+            Err(ArtifactError::InvalidField { field: "publicValues", .. })
+        ));
+        store.put("contract_call_data_n11.json", b"not json").unwrap();
+        assert!(matches!(load_all_stored_call_data(&store), Err(ArtifactError::Json { .. })));
+    }
+}
@@ -0,0 +1,293 @@
+//! An append-only, hash-chained log of the prove, verify and submit operations the binaries run.
+//!
+//! Every entry records who ran the operation, when, on what, and how it ended, and commits to the
+//! previous entry by hash, so editing or dropping an entry breaks the chain from that point on.
+
+use crate::artifacts::ArtifactError;
+use alloy_primitives::{keccak256, B256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// The audit log used when `AUDIT_LOG` is not set.
+pub const DEFAULT_AUDIT_LOG: &str = "artifacts/audit.log";
+
+/// Errors from writing or checking the audit log.
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error(transparent)]
+    Io(#[from] ArtifactError),
+    #[error("{}: entry {line} is not valid JSON: {source}", path.display())]
+    Json { path: PathBuf, line: usize, source: serde_json::Error },
+    #[error("{}: chain broken at entry {line}: {reason}", path.display())]
+    Broken { path: PathBuf, line: usize, reason: String },
+}
+
+/// The hashed part of an entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// The position of the entry in the log, starting at 1.
+    pub seq: u64,
+    /// When the operation finished, as a unix timestamp.
+    pub timestamp: u64,
+    /// Who ran the operation (`AUDIT_ACTOR`, or the login user).
+    pub actor: String,
+    /// `prove`, `verify`, `submit`, ...
+    pub operation: String,
+    /// The inputs, key fingerprints and transaction hashes of the operation.
+    pub details: Value,
+    /// `ok`, or `failed: <reason>`.
+    pub outcome: String,
+    /// The hash of the previous entry, or zero for the first one.
+    pub prev_hash: B256,
+}
+
+impl AuditRecord {
+    /// The hash the next entry chains to.
+    pub fn hash(&self) -> B256 {
+        keccak256(serde_json::to_vec(self).expect("audit records always serialize to JSON"))
+    }
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub hash: B256,
+}
+
+/// The audit log file.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// The log at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The log at `AUDIT_LOG`, or [`DEFAULT_AUDIT_LOG`].
+    pub fn from_env() -> Self {
+        Self::new(env::var("AUDIT_LOG").unwrap_or_else(|_| DEFAULT_AUDIT_LOG.to_string()))
+    }
+
+    /// The path of the log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry for `operation`, with the outcome taken from `result`.
+    pub fn record<T, E: Display>(
+        &self,
+        operation: &str,
+        details: Value,
+        result: &Result<T, E>,
+    ) -> Result<AuditEntry, AuditError> {
+        let outcome = match result {
+            Ok(_) => "ok".to_string(),
+            Err(err) => format!("failed: {}", err),
+        };
+        self.append(operation, details, outcome)
+    }
+
+    /// Append an entry chained to the last one in the log.
+    pub fn append(
+        &self,
+        operation: &str,
+        details: Value,
+        outcome: String,
+    ) -> Result<AuditEntry, AuditError> {
+        self.append_as(&actor(), operation, details, outcome)
+    }
+
+    /// Append an entry for an operation run on behalf of `actor`, e.g. a client of `service`.
+    pub fn append_as(
+        &self,
+        actor: &str,
+        operation: &str,
+        details: Value,
+        outcome: String,
+    ) -> Result<AuditEntry, AuditError> {
+        let last = self.entries()?.pop();
+        let record = AuditRecord {
+            seq: last.as_ref().map_or(1, |entry| entry.record.seq + 1),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system clock is after the unix epoch")
+                .as_secs(),
+            actor: actor.to_string(),
+            operation: operation.to_string(),
+            details,
+            outcome,
+            prev_hash: last.map_or(B256::ZERO, |entry| entry.hash),
+        };
+        let entry = AuditEntry { hash: record.hash(), record };
+
+        let io_err = |source| ArtifactError::Io { path: self.path.clone(), source };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let mut line = serde_json::to_string(&entry).expect("audit entries always serialize");
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(io_err)?;
+        Ok(entry)
+    }
+
+    /// Read every entry, without checking the chain. A missing log has no entries.
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, AuditError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&self.path)
+            .map_err(|source| ArtifactError::Io { path: self.path.clone(), source })?;
+        contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|source| AuditError::Json {
+                    path: self.path.clone(),
+                    line: i + 1,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// Check that every entry is numbered in order, hashes to its recorded hash, and chains to
+    /// the one before it. Returns the number of entries.
+    pub fn verify(&self) -> Result<usize, AuditError> {
+        let entries = self.entries()?;
+        let mut prev_hash = B256::ZERO;
+        for (i, entry) in entries.iter().enumerate() {
+            let broken = |reason: String| AuditError::Broken {
+                path: self.path.clone(),
+                line: i + 1,
+                reason,
+            };
+            if entry.record.seq != i as u64 + 1 {
+                return Err(broken(format!("expected seq {}, found {}", i + 1, entry.record.seq)));
+            }
+            if entry.record.prev_hash != prev_hash {
+                return Err(broken(format!(
+                    "prev_hash {} does not match the previous entry's hash {}",
+                    entry.record.prev_hash, prev_hash
+                )));
+            }
+            let hash = entry.record.hash();
+            if entry.hash != hash {
+                return Err(broken(format!(
+                    "recorded hash {} does not match its contents ({})",
+                    entry.hash, hash
+                )));
+            }
+            prev_hash = entry.hash;
+        }
+        Ok(entries.len())
+    }
+}
+
+/// Who is running the binary: `AUDIT_ACTOR` if set (e.g. a service account), otherwise the login
+/// user.
+fn actor() -> String {
+    ["AUDIT_ACTOR", "USER", "USERNAME"]
+        .iter()
+        .find_map(|name| env::var(name).ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A fresh log of three entries in the temporary directory.
+    fn log_of_three(name: &str) -> AuditLog {
+        let path = env::temp_dir().join(format!("audit-{}-{}.log", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = AuditLog::new(path);
+        for n in [10, 20, 30] {
+            log.append_as("tester", "prove", json!({ "n": n }), "ok".to_string()).unwrap();
+        }
+        log
+    }
+
+    /// Rewrite the log's lines with `edit`.
+    fn edit_lines(log: &AuditLog, edit: impl FnOnce(&mut Vec<String>)) {
+        let contents = fs::read_to_string(log.path()).unwrap();
+        let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        edit(&mut lines);
+        fs::write(log.path(), lines.join("\n") + "\n").unwrap();
+    }
+
+    fn broken_line(result: Result<usize, AuditError>) -> usize {
+        match result {
+            Err(AuditError::Broken { line, .. }) => line,
+            other => panic!("expected a broken chain, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn appended_entries_chain_and_verify() {
+        let log = log_of_three("chain");
+        let entries = log.entries().unwrap();
+        assert_eq!(log.verify().unwrap(), 3);
+        assert_eq!(entries[0].record.prev_hash, B256::ZERO);
+        for pair in entries.windows(2) {
+            assert_eq!(pair[1].record.seq, pair[0].record.seq + 1);
+            assert_eq!(pair[1].record.prev_hash, pair[0].hash);
+        }
+        fs::remove_file(log.path()).unwrap();
+        assert_eq!(log.verify().unwrap(), 0);
+    }
+
+    #[test]
+    fn edited_entry_is_detected() {
+        let log = log_of_three("edited");
+        edit_lines(&log, |lines| lines[1] = lines[1].replace("\"n\":20", "\"n\":21"));
+        assert_eq!(broken_line(log.verify()), 2);
+        fs::remove_file(log.path()).unwrap();
+    }
+
+    #[test]
+    fn rehashed_entry_breaks_the_next_link() {
+        let log = log_of_three("rehashed");
+        let mut entry = log.entries().unwrap().remove(1);
+        entry.record.outcome = "failed: hidden".to_string();
+        entry.hash = entry.record.hash();
+        edit_lines(&log, |lines| lines[1] = serde_json::to_string(&entry).unwrap());
+        assert_eq!(broken_line(log.verify()), 3);
+        fs::remove_file(log.path()).unwrap();
+    }
+
+    #[test]
+    fn dropped_entry_is_detected() {
+        let log = log_of_three("dropped");
+        edit_lines(&log, |lines| {
+            lines.remove(1);
+        });
+        assert_eq!(broken_line(log.verify()), 2);
+        fs::remove_file(log.path()).unwrap();
+    }
+
+    #[test]
+    fn corrupt_line_is_reported() {
+        let log = log_of_three("corrupt");
+        edit_lines(&log, |lines| lines[2].truncate(10));
+        assert!(matches!(log.verify(), Err(AuditError::Json { line: 3, .. })));
+        fs::remove_file(log.path()).unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+//! API keys and per-key limits for `service`, so the proving service can be exposed beyond
+//! localhost.
+//!
+//! Keys are created with `cli keys create` and only stored hashed in the job store. Clients send
+//! them as `Authorization: Bearer <key>` or `X-Api-Key: <key>`. Each key can be limited to a
+//! number of requests per minute and a number of unfinished jobs at once, and the requests the
+//! service records in the audit log are attributed to the key that made them.
+
+use crate::jobs::ApiKey;
+use alloy_primitives::{keccak256, B256};
+use jsonrpsee::server::HttpRequest;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The prefix of every key, so leaked keys are easy to recognize.
+pub const KEY_PREFIX: &str = "sp1_";
+
+/// The key presented with a request, before it is checked.
+#[derive(Clone, Debug)]
+pub struct PresentedKey(pub String);
+
+/// A new random key. Show it to its owner once: only its [`key_hash`] is stored.
+pub fn generate_key() -> String {
+    format!("{}{}", KEY_PREFIX, hex::encode(rand::random::<[u8; 32]>()))
+}
+
+/// The hash a key is stored and looked up by. Keys are random, so a fast hash is enough.
+pub fn key_hash(key: &str) -> B256 {
+    keccak256(key.as_bytes())
+}
+
+/// Attach the key presented with an HTTP request to its extensions, where the RPC methods find
+/// it.
+pub fn extract_key(mut request: HttpRequest) -> HttpRequest {
+    let headers = request.headers();
+    let bearer = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let api_key = headers.get("x-api-key").and_then(|value| value.to_str().ok());
+    if let Some(key) = bearer.or(api_key).map(|key| PresentedKey(key.trim().to_string())) {
+        request.extensions_mut().insert(key);
+    }
+    request
+}
+
+/// Counts the requests of each key in fixed one-minute windows.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<String, (u64, u32)>>,
+}
+
+impl RateLimiter {
+    /// Count a request of `key`, and return whether it is within the key's `rate_limit`.
+    pub fn check(&self, key: &ApiKey) -> bool {
+        let Some(limit) = key.rate_limit else {
+            return true;
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        self.check_at(key, limit, now.as_secs() / 60)
+    }
+
+    /// Count a request of `key` made in `minute`, within `limit` requests per minute.
+    fn check_at(&self, key: &ApiKey, limit: u32, minute: u64) -> bool {
+        let mut windows = self.windows.lock().expect("the rate limiter is never poisoned");
+        // Only the current window counts, so earlier ones are dropped rather than kept for every
+        // key that ever made a request.
+        windows.retain(|_, (window, _)| *window == minute);
+        let (_, count) = windows.entry(key.name.clone()).or_insert((minute, 0));
+        if *count >= limit {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> ApiKey {
+        ApiKey {
+            name: name.to_string(),
+            tenant: "default".to_string(),
+            rate_limit: Some(2),
+            max_concurrent: None,
+            created_at: 0,
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn limits_requests_per_minute() {
+        let limiter = RateLimiter::default();
+        let (alice, bob) = (key("alice"), key("bob"));
+        assert!(limiter.check_at(&alice, 2, 10));
+        assert!(limiter.check_at(&alice, 2, 10));
+        assert!(!limiter.check_at(&alice, 2, 10));
+        assert!(limiter.check_at(&bob, 2, 10));
+        assert!(limiter.check_at(&alice, 2, 11));
+        assert!(limiter.check(&ApiKey { rate_limit: None, ..key("carol") }));
+    }
+
+    #[test]
+    fn drops_expired_windows() {
+        let limiter = RateLimiter::default();
+        for name in ["alice", "bob", "carol"] {
+            assert!(limiter.check_at(&key(name), 2, 10));
+        }
+        assert!(limiter.check_at(&key("alice"), 2, 11));
+        let windows = limiter.windows.lock().unwrap();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows.get("alice"), Some(&(11, 1)));
+    }
+}
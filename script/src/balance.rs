@@ -0,0 +1,149 @@
+//! Checks of the balances proving and settlement spend from: the prover network credits of the
+//! `NETWORK_PRIVATE_KEY` account, and the ETH of the account submitting proofs on each chain.
+//!
+//! A balance under its minimum is reported on every check, and notified as `balance_low` once,
+//! until it is topped up again (see [`crate::notify`]), so accounts can be funded before jobs
+//! start failing mid-batch.
+
+use crate::chain::{wei_to_eth, ChainError};
+use crate::notify::{Event, EventKind, Notifications};
+use alloy_primitives::{Address, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use serde::Serialize;
+use serde_json::json;
+use sp1_sdk::{NetworkProver, ProverClient};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// Errors from looking up a balance.
+#[derive(Debug, Error)]
+pub enum BalanceError {
+    #[error("prover network request failed: {0}")]
+    Network(String),
+    #[error(transparent)]
+    Chain(#[from] ChainError),
+}
+
+/// A balance, and the least it should be.
+#[derive(Debug, Clone, Serialize)]
+pub struct Balance {
+    /// The account, e.g. `prover network` or `0x... on chain 11155111`.
+    pub account: String,
+    /// The balance, in `unit`.
+    pub amount: f64,
+    pub minimum: f64,
+    pub unit: &'static str,
+}
+
+impl Balance {
+    pub fn is_low(&self) -> bool {
+        self.amount < self.minimum
+    }
+}
+
+/// The account proofs are submitted from, and the chains it settles on.
+struct Submitter {
+    address: Address,
+    rpc_urls: Vec<String>,
+    /// The least ETH to keep on each chain.
+    minimum: f64,
+}
+
+/// Watches balances against their minimums.
+#[derive(Default)]
+pub struct BalanceMonitor {
+    /// The prover network client, and the fewest credits (in PROVE) to keep.
+    network: Option<(NetworkProver, f64)>,
+    submitter: Option<Submitter>,
+    /// The accounts found low at the last check, so each drop is notified once.
+    low: BTreeSet<String>,
+}
+
+impl BalanceMonitor {
+    /// Watch the prover network credits of the `NETWORK_PRIVATE_KEY` account.
+    pub fn network(mut self, minimum: f64) -> Self {
+        self.network = Some((ProverClient::builder().network().build(), minimum));
+        self
+    }
+
+    /// Watch the ETH of `address` on the chains served by `rpc_urls`.
+    pub fn submitter(mut self, address: Address, rpc_urls: Vec<String>, minimum: f64) -> Self {
+        self.submitter = Some(Submitter { address, rpc_urls, minimum });
+        self
+    }
+
+    /// Whether there is no balance to watch.
+    pub fn is_empty(&self) -> bool {
+        self.network.is_none() && self.submitter.is_none()
+    }
+
+    /// Look up every watched balance.
+    pub async fn balances(&self) -> Vec<Result<Balance, BalanceError>> {
+        let mut balances = Vec::new();
+        if let Some((network, minimum)) = &self.network {
+            let balance = network.get_balance().await.map(|credits| Balance {
+                account: "prover network".to_string(),
+                amount: to_units(credits),
+                minimum: *minimum,
+                unit: "PROVE",
+            });
+            balances.push(balance.map_err(|err| BalanceError::Network(err.to_string())));
+        }
+        if let Some(submitter) = &self.submitter {
+            for rpc_url in &submitter.rpc_urls {
+                let balance = match eth_balance(rpc_url, submitter.address).await {
+                    Ok((chain_id, wei)) => Ok(Balance {
+                        account: format!("{} on chain {}", submitter.address, chain_id),
+                        amount: to_units(wei),
+                        minimum: submitter.minimum,
+                        unit: "ETH",
+                    }),
+                    Err(err) => Err(err.into()),
+                };
+                balances.push(balance);
+            }
+        }
+        balances
+    }
+
+    /// Check every watched balance, reporting the low ones and notifying the ones that were not
+    /// low at the last check.
+    pub async fn check(&mut self, notifications: &Notifications) {
+        let mut low = BTreeSet::new();
+        for balance in self.balances().await {
+            let balance = match balance {
+                Ok(balance) if balance.is_low() => balance,
+                Ok(_) => continue,
+                Err(err) => {
+                    println!("⚠️  Could not check a balance: {}", err);
+                    continue;
+                }
+            };
+            println!(
+                "⚠️  Balance low: {} has {:.4} {}, under the minimum of {}",
+                balance.account, balance.amount, balance.unit, balance.minimum
+            );
+            if !self.low.contains(&balance.account) {
+                let message = format!(
+                    "{} has {:.4} {} left, under the minimum of {}",
+                    balance.account, balance.amount, balance.unit, balance.minimum
+                );
+                notifications.notify(&Event::new(EventKind::BalanceLow, message, json!(balance)));
+            }
+            low.insert(balance.account);
+        }
+        self.low = low;
+    }
+}
+
+/// The balance of `address` on the chain served by `rpc_url`, with the chain's id.
+pub async fn eth_balance(rpc_url: &str, address: Address) -> Result<(u64, U256), ChainError> {
+    let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+    let chain_id = provider.get_chain_id().await?;
+    Ok((chain_id, provider.get_balance(address).await?))
+}
+
+/// Convert an amount of a token with 18 decimals, like ETH or PROVE, to whole units.
+fn to_units(amount: U256) -> f64 {
+    wei_to_eth(u128::try_from(amount).unwrap_or(u128::MAX))
+}
@@ -0,0 +1,132 @@
+//! The artifacts of a batch proof (see [`FibonacciProver::prove_batch`]): the aggregator proof and
+//! its `settleBatch` call data and, for a batch committed as a Merkle root, a proof of inclusion
+//! of each item, with which `FibonacciBatch.proveItem` settles the item on demand.
+//!
+//! [`FibonacciProver::prove_batch`]: crate::prover::FibonacciProver::prove_batch
+
+use crate::artifacts::{create_dir, write, write_json, ArtifactError};
+use crate::prover::{BatchProof, ProofSystem};
+use alloy_primitives::{keccak256, Bytes, B256};
+use fibonacci_lib::batch::{leaf_hash, merkle_proof};
+use fibonacci_lib::{seeded_range, ProgramInput, PublicValuesStruct, Sequence};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A proof that the public values of one item are in a batch committed as a Merkle root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchItem {
+    /// The position of the item in the batch.
+    pub index: usize,
+    pub n: u32,
+    /// The public values of the item's proof, as `proveItem` takes them.
+    pub public_values: Bytes,
+    pub leaf: B256,
+    /// The siblings of the leaf, from the bottom up.
+    pub proof: Vec<B256>,
+    pub root: B256,
+    /// The values of a range committed as its hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RangeOpening>,
+}
+
+/// The values behind a range committed as its hash, which the contract checks against
+/// `rangeHash` with `PublicValuesLib.rangeHash(start, values)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeOpening {
+    /// The position of the item in the batch.
+    pub index: usize,
+    pub start: u32,
+    /// `F(start..=n)`.
+    pub values: Vec<u32>,
+}
+
+impl RangeOpening {
+    /// The opening of the range of the input at `index`, if it is committed as its hash.
+    pub fn of(index: usize, input: &ProgramInput) -> Option<Self> {
+        let start = input.range_start.filter(|_| input.range_hash && input.rejection().is_none())?;
+        let seeds = input.seeds.unwrap_or(Sequence::Fibonacci.seeds());
+        Some(Self { index, start, values: seeded_range(start, input.n, seeds) })
+    }
+}
+
+/// The files written by [`save_batch_artifacts`].
+#[derive(Debug, Clone)]
+pub struct SavedBatch {
+    /// The directory of the batch, named after the hash of its public values.
+    pub dir: PathBuf,
+    pub proof: PathBuf,
+    pub public_values: PathBuf,
+    pub call_data: PathBuf,
+    /// The proof of inclusion of each item, for a batch committed as a Merkle root.
+    pub items: Vec<PathBuf>,
+}
+
+/// Save a batch proof under `dir`, in a `batch_<hash>` directory of its own.
+pub fn save_batch_artifacts(
+    dir: &Path,
+    batch: &BatchProof,
+    system: ProofSystem,
+) -> Result<SavedBatch, ArtifactError> {
+    let public_values = Bytes::from(batch.proof.public_values.to_vec());
+    let proof_bytes = Bytes::from(batch.proof.bytes());
+    let batch_dir = dir.join(format!("batch_{}", &keccak256(&public_values).to_string()[2..18]));
+    create_dir(&batch_dir)?;
+
+    let committed = batch.input.batch_public_values();
+    let ranges: Vec<_> = batch
+        .inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, input)| RangeOpening::of(index, input))
+        .collect();
+    let mut saved = SavedBatch {
+        proof: batch_dir.join(format!("proof_{}.bin", system)),
+        public_values: batch_dir.join("public_values.bin"),
+        call_data: batch_dir.join("call_data.json"),
+        items: Vec::new(),
+        dir: batch_dir,
+    };
+    write(&saved.proof, &proof_bytes)?;
+    write(&saved.public_values, &public_values)?;
+    write_json(
+        &saved.call_data,
+        &serde_json::json!({
+            "function": "settleBatch",
+            "parameters": {
+                "publicValues": public_values,
+                "proofBytes": proof_bytes
+            },
+            "expected_output": {
+                "count": committed.count,
+                "root": committed.root,
+                "program_vkey_digest": committed.programVKeyDigest,
+                "ranges": ranges
+            },
+            "aggregator_vkey": batch.aggregator_vkey,
+            "contract_interface": {
+                "function_signature": "settleBatch(bytes,bytes)",
+                "returns": "(uint32)"
+            }
+        }),
+    )?;
+
+    if !batch.input.merkle_root {
+        return Ok(saved);
+    }
+    let leaves: Vec<_> = batch.input.public_values.iter().map(|values| leaf_hash(values)).collect();
+    for (index, values) in batch.input.public_values.iter().enumerate() {
+        let item = BatchItem {
+            index,
+            n: PublicValuesStruct::from_abi_bytes(values)?.n,
+            public_values: values.clone().into(),
+            leaf: leaves[index],
+            proof: merkle_proof(&leaves, index).expect("every item has a leaf"),
+            root: committed.root,
+            range: ranges.iter().find(|range| range.index == index).cloned(),
+        };
+        let path = saved.dir.join(format!("item_{}_n{}.json", index, item.n));
+        write_json(&path, &item)?;
+        saved.items.push(path);
+    }
+    Ok(saved)
+}
@@ -0,0 +1,138 @@
+//! Prove a batch of Fibonacci inputs with a single EVM-compatible proof, which settles the whole
+//! batch on the `FibonacciBatch` contract in one transaction.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin aggregate -- --n 10,20,30 --merkle-root
+//! ```
+//! or, committing the hash of each of two ranges of 500 values:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin aggregate -- --range 0..=499,500..=999 --range-hash
+//! ```
+//! The outcome is notified to the channels of `notifications.toml`, if configured; see
+//! [`fibonacci_script::notify`].
+
+use clap::Parser;
+use fibonacci_lib::batch::vkey_digest_bytes;
+use fibonacci_lib::ProgramInput;
+use fibonacci_script::batch::save_batch_artifacts;
+use fibonacci_script::notify::{Event, EventKind, Notifications};
+use fibonacci_script::prover::{FibonacciProver, ProofSystem};
+use serde_json::json;
+use sp1_sdk::HashableKey;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The inputs to prove, comma-separated
+    #[arg(long, value_delimiter = ',', required_unless_present = "range")]
+    n: Vec<u32>,
+
+    /// Ranges to prove, each as `<start>..=<end>` and committing `F(start..=end)` in one proof,
+    /// comma-separated
+    #[arg(long, value_delimiter = ',', value_parser = parse_range)]
+    range: Vec<(u32, u32)>,
+
+    /// Commit the hash of each range instead of its values; the values are saved with the call
+    /// data
+    #[arg(long, requires = "range")]
+    range_hash: bool,
+
+    /// The proof system of the aggregated proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Commit only the Merkle root of the public values, and save a proof of inclusion of each
+    /// item, so the contract settles items one by one on demand
+    #[arg(long)]
+    merkle_root: bool,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+
+    /// Inject faults at random, e.g. `prover=0.2,write=0.05,seed=7`, to test how a failed batch
+    /// is re-run
+    #[cfg(feature = "chaos")]
+    #[arg(long, env = "SP1_CHAOS")]
+    chaos: Option<fibonacci_script::chaos::ChaosConfig>,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    #[cfg(feature = "chaos")]
+    if let Some(chaos) = args.chaos {
+        fibonacci_script::chaos::install(chaos);
+    }
+
+    let notifications = Notifications::from_env()?;
+    let prover = FibonacciProver::new();
+    let ranges = args.range.iter().map(|&(start, end)| ProgramInput {
+        range_start: Some(start),
+        range_hash: args.range_hash,
+        ..ProgramInput::new(end)
+    });
+    let inputs: Vec<_> = args.n.iter().map(|&n| ProgramInput::new(n)).chain(ranges).collect();
+    // Fail before proving any of them.
+    for input in &inputs {
+        input.validate().map_err(|err| eyre::eyre!("invalid input (n = {}): {}", input.n, err))?;
+    }
+    println!("🔄 Proving {} inputs, then aggregating them into one proof...", inputs.len());
+    let details = json!({
+        "inputs": inputs,
+        "system": args.system.to_string(),
+        "merkle_root": args.merkle_root,
+    });
+    let batch = match prover.prove_batch(&inputs, args.system, args.merkle_root) {
+        Ok(batch) => batch,
+        Err(err) => {
+            let message = format!("Aggregating a batch of {} inputs failed: {}", inputs.len(), err);
+            let details = json!({ "error": err.to_string(), "batch": details });
+            notifications.notify(&Event::new(EventKind::ProofFailed, message, details));
+            return Err(err.into());
+        }
+    };
+
+    let committed = batch.input.batch_public_values();
+    println!("✅ Batch of {} proofs aggregated", committed.count);
+    println!("   Aggregator VKey: {}", batch.aggregator_vkey);
+    println!("   Fibonacci VKey digest: {}", vkey_digest_bytes(&prover.vk().hash_u32()));
+    if args.merkle_root {
+        println!("   Merkle root: {}", committed.root);
+    }
+
+    let saved = save_batch_artifacts(&args.output_dir, &batch, args.system)?;
+    println!("💾 Proof saved to: {}", saved.proof.display());
+    println!("💾 Public values saved to: {}", saved.public_values.display());
+    println!("💾 Call data saved to: {}", saved.call_data.display());
+    if args.range_hash {
+        println!("   Range values saved with the call data for each hashed range");
+    }
+    for item in &saved.items {
+        println!("💾 Proof of inclusion saved to: {}", item.display());
+    }
+    let message =
+        format!("Batch of {} proofs aggregated: {}", committed.count, saved.call_data.display());
+    let details = json!({
+        "batch": details,
+        "aggregator_vkey": batch.aggregator_vkey,
+        "call_data": saved.call_data,
+    });
+    notifications.notify(&Event::new(EventKind::ProofCompleted, message, details));
+    Ok(())
+}
+
+/// Parse `<start>..=<end>`.
+fn parse_range(range: &str) -> Result<(u32, u32), String> {
+    let (start, end) = range
+        .split_once("..=")
+        .ok_or_else(|| format!("expected <start>..=<end>, got `{}`", range))?;
+    let index = |index: &str| {
+        index.trim().parse::<u32>().map_err(|_| format!("invalid index `{}` in `{}`", index, range))
+    };
+    Ok((index(start)?, index(end)?))
+}
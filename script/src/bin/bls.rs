@@ -0,0 +1,156 @@
+//! Prove that a set of BLS12-381 keys all signed a message with the BLS program, which verifies
+//! their aggregate signature and commits the aggregate key and the hashes of the keys and the
+//! message. For an Ethereum attestation, the keys are those of the attesting validators and the
+//! message is the signing root of the attestation data.
+//!
+//! Keys and signatures are lists of `0x`-prefixed hex, comma-separated or one per line in a file.
+//! Individual signatures given with `--signatures` are aggregated here. You can run this script
+//! using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin bls -- \
+//!     --public-keys validators.txt --signatures signatures.txt --message 0x...
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use alloy_primitives::Bytes;
+use clap::Parser;
+use fibonacci_lib::bls::{aggregate_signatures, BlsInput, BlsPublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The 48-byte public keys of the signers, as a list or a file
+    #[arg(long, value_parser = hex_list)]
+    public_keys: HexList,
+
+    /// The 96-byte aggregate signature, as hex
+    #[arg(long, conflicts_with = "signatures", required_unless_present = "signatures")]
+    signature: Option<Bytes>,
+
+    /// The 96-byte signatures of the signers, as a list or a file, to aggregate
+    #[arg(long, value_parser = hex_list)]
+    signatures: Option<HexList>,
+
+    /// The signed message, as hex or a file
+    #[arg(long, value_parser = hex_or_file)]
+    message: Bytes,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+/// A list of byte strings; a type of its own so clap parses it as one value.
+#[derive(Debug, Clone)]
+struct HexList(Vec<Vec<u8>>);
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let signature = match (&args.signatures, &args.signature) {
+        (Some(signatures), _) => {
+            println!("🔗 Aggregating {} signatures", signatures.0.len());
+            aggregate_signatures(&signatures.0).map_err(|err| eyre::eyre!(err))?
+        }
+        (None, signature) => signature.clone().unwrap_or_default().to_vec(),
+    };
+    let input = BlsInput {
+        public_keys: args.public_keys.0.clone(),
+        signature,
+        message: args.message.to_vec(),
+    };
+    // The program panics on an invalid signature, so fail before running it.
+    let expected = input.verify().map_err(|err| eyre::eyre!(err))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("bls").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Aggregate signature verified in the program");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "bls".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "aggregatePublicKey": expected.aggregatePublicKey,
+            "publicKeysHash": expected.publicKeysHash,
+            "signerCount": expected.signerCount,
+            "messageHash": expected.messageHash,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &BlsPublicValuesStruct) {
+    println!("   Signers: {}", values.signerCount);
+    println!("   Aggregate public key: {}", values.aggregatePublicKey);
+    println!("   Public keys hash: {}", values.publicKeysHash);
+    println!("   Message hash: {}", values.messageHash);
+}
+
+/// Parse comma-separated `0x`-prefixed hex, or read it one item per line from a file.
+fn hex_list(value: &str) -> Result<HexList, String> {
+    let contents = if value.starts_with("0x") {
+        value.replace(',', "\n")
+    } else {
+        std::fs::read_to_string(value).map_err(|err| format!("failed to read {}: {}", value, err))?
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse::<Bytes>()
+                .map(|bytes| bytes.to_vec())
+                .map_err(|err| format!("invalid hex `{}`: {}", line, err))
+        })
+        .collect::<Result<_, _>>()
+        .map(HexList)
+}
+
+/// Parse `0x`-prefixed hex, or read the raw bytes of a file.
+fn hex_or_file(value: &str) -> Result<Bytes, String> {
+    if value.starts_with("0x") {
+        return value.parse().map_err(|err| format!("invalid hex `{}`: {}", value, err));
+    }
+    std::fs::read(value)
+        .map(Bytes::from)
+        .map_err(|err| format!("failed to read {}: {}", value, err))
+}
@@ -0,0 +1,2416 @@
+//! Developer tooling for the Fibonacci project.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! cargo run --release --bin cli -- codegen
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- reconcile --contract 0x... --from-block 5000000
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- submit --contract 0x... --n 10
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- read-results --contract 0x... --from-block 5000000
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- verify-wrapped --proof artifacts/proof_groth16_n10.bin \
+//!     --public-values artifacts/public_values_n10.bin --vkey artifacts/verification_key.txt
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- verifier --system groth16 --update-env
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- jobs submit --n 20
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- audit verify
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- keys create --name acme --rate-limit 60 --max-concurrent 4
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- report costs --since 2024-01-01
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- report html --since 2024-01-01
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- stats --since 2024-01-01 --period 604800
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- when-to-submit --wait-for-gwei 5 --within 7200
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- sweep --from 1 --to 10000 --step 500
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- execute --program fibonacci --input 20
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- build --reproducible
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- build-all
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- init-program my-thing
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --features profiling --bin cli -- debug-execute --input 20
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- inspect --n 10
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- decode-pv 0x0000...
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- expected-pv --n 10
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- bundle export --n 10
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- export --format snarkjs \
+//!     --proof artifacts/proof_groth16_n10.bin --public-values artifacts/public_values_n10.bin \
+//!     --vkey artifacts/verification_key.txt
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --bin cli -- profile --input 20
+//! ```
+//! or
+//! ```shell
+//! cargo run --release --features hash-bench --bin cli -- hash-bench --bytes 4096
+//! ```
+
+use alloy_network::EthereumWallet;
+use alloy_primitives::{keccak256, Address, Bytes, B256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_sol_types::SolStruct;
+use clap::{Args, Parser, Subcommand};
+use eyre::Result;
+use fibonacci_lib::recurrence::{Recurrence, MAX_ORDER};
+use fibonacci_lib::{
+    compute_public_values, ProgramInput, PublicValuesStruct, Sequence, Status, VkHash,
+    GUEST_LOG_FD, MAX_N, MAX_RANGE_LEN,
+};
+use fibonacci_script::addresses::ContractTarget;
+use fibonacci_script::artifacts::{
+    call_data_path, guest_stdout_path, load_all_call_data, manifest_path, read_artifact,
+    read_json, write_json, CallData, Manifest, DEFAULT_ARTIFACTS_DIR, MANIFEST_VERSION,
+};
+use fibonacci_script::audit::{AuditLog, DEFAULT_AUDIT_LOG};
+use fibonacci_script::auth::{generate_key, key_hash};
+use fibonacci_script::bundle::{
+    bundle_path, export_bundle, fetch_bundle, import_bundle, load_bundle,
+};
+use fibonacci_script::chain::{wei_to_eth, Fibonacci, SettlementReceipt};
+use fibonacci_script::config::{self, DEFAULT_RPC_URL};
+use fibonacci_script::deadline::parse_secs;
+use fibonacci_script::diff::diff_bundles;
+use fibonacci_script::domain::InputDomain;
+use fibonacci_script::export::{export, ExportFormat};
+use fibonacci_script::gas::{sample_base_fees, wait_for_base_fee, Recommendation};
+use fibonacci_script::html_report::{find_run_reports, format_time, ActivityReport};
+use fibonacci_script::jobs::{
+    Job, JobOptions, JobStatus, JobStore, Phase, Priority, DEFAULT_JOB_STORE_URL, DEFAULT_TENANT,
+};
+use fibonacci_script::migrate::{migrate_artifacts, Migration};
+use fibonacci_script::profile::ProfileNode;
+use fibonacci_script::revert::{decode_revert, revert_data};
+use fibonacci_script::run_report::RunReport;
+use fibonacci_script::programs::{
+    elf_sha256, parse_program_input, program, read_builds, save_vkey, BuiltElf, ElfLock,
+    LockedElf, Program, BUILDS_PATH, ELF_LOCK_PATH, PROGRAMS, VKEYS_PATH,
+};
+use fibonacci_script::prover::{
+    check_same_execution, vk_hash, FibonacciProver, GuestLog, NetworkStrategy, ProofSystem,
+    FIBONACCI_ELF,
+};
+use fibonacci_script::public_values::{decode_any, DecodedPublicValues, CURRENT_LAYOUT};
+use fibonacci_script::scaffold::{self, ProgramNames};
+use fibonacci_script::symbols::{function_symbols, Symbol};
+use fibonacci_script::verifiers::resolve_gateway;
+use fibonacci_verifier_wasm::verify_proof;
+use serde::Serialize;
+use sp1_sdk::{Prover, ProverClient};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate the Solidity definitions of the public values from `fibonacci-lib`.
+    Codegen(CodegenArgs),
+    /// Cross-check locally generated proofs against settlements on-chain.
+    Reconcile(ReconcileArgs),
+    /// Settle a saved proof on the `Fibonacci` contract, then read back the result it stored.
+    Submit(SubmitArgs),
+    /// Export the results stored on the `Fibonacci` contract as JSON.
+    ReadResults(ReadResultsArgs),
+    /// Verify a saved Groth16/PLONK proof off-chain, without a prover, contract or RPC.
+    VerifyWrapped(VerifyWrappedArgs),
+    /// Find the SP1 verifier gateway for a chain and check on-chain that it routes this SP1
+    /// version's proofs to a matching verifier.
+    Verifier(VerifierArgs),
+    /// Manage the queue of proving jobs worked through by `worker`.
+    Jobs(JobsArgs),
+    /// Manage the API keys clients of `service` authenticate with.
+    Keys(KeysArgs),
+    /// Inspect the audit log of prove, verify and submit operations.
+    Audit(AuditArgs),
+    /// Summarize what proving and settling proofs has cost, from the job store.
+    Report(ReportArgs),
+    /// Report the median and 95th percentile duration of each phase of the jobs (setup, execute,
+    /// prove, verify, save, submit) per proof system and period, from the job store.
+    Stats(StatsArgs),
+    /// Sample the recent base fees of the chain and recommend submitting now or waiting, or wait
+    /// until the base fee drops under a threshold.
+    WhenToSubmit(WhenToSubmitArgs),
+    /// Execute (without proving) the program across a range of inputs and record the cycles and
+    /// estimated proving cost of each as CSV.
+    Sweep(SweepArgs),
+    /// Execute any registered guest program on an input given on the command line.
+    Execute(ExecuteArgs),
+    /// Build a guest program with `cargo prove build`.
+    Build(BuildArgs),
+    /// Build every registered guest program, record their ELF hashes and vkeys, and report which
+    /// changed since the last build.
+    BuildAll(BuildAllArgs),
+    /// Scaffold a new guest program: its crate, input types, host binary and registry entry.
+    InitProgram(InitProgramArgs),
+    /// Execute a program with SP1's profiler and list its functions, for debugging panics and
+    /// unexpected cycle counts.
+    DebugExecute(DebugExecuteArgs),
+    /// Show a saved proof bundle: its public values and what the program logged.
+    Inspect(InspectArgs),
+    /// Decode public values of any layout the program has committed, field by field, and flag
+    /// malformed encodings.
+    DecodePv(DecodePvArgs),
+    /// Compute the public values the program commits for an input natively and print them
+    /// ABI-encoded, to compare with what a proof or a contract event holds.
+    ExpectedPv(ExpectedPvArgs),
+    /// Pack a saved proof bundle into a single `.sp1bundle` file, or unpack and check one.
+    Bundle(BundleArgs),
+    /// Compare two proof bundles: public values, vkeys, proof systems and provenance.
+    Diff(DiffArgs),
+    /// Convert a saved Groth16 proof and the verifying key into the JSON layouts of snarkjs or
+    /// gnark, to verify the proof with their tooling.
+    Export(ExportArgs),
+    /// Upgrade saved proof bundles in place to the current manifest format.
+    MigrateArtifacts(MigrateArtifactsArgs),
+    /// Break the cycles of an execution down by the regions the program tracks, as a flame
+    /// graph.
+    Profile(ProfileArgs),
+    /// Execute the hashing program with and without the SP1 precompile patches and compare
+    /// their cycles.
+    #[cfg(feature = "hash-bench")]
+    HashBench(HashBenchArgs),
+}
+
+#[derive(Args, Debug)]
+struct CodegenArgs {
+    /// The file to write the generated Solidity to.
+    #[arg(long, default_value = "../contracts/src/generated/PublicValues.sol")]
+    out: PathBuf,
+
+    /// Only check that the generated file is up to date.
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args, Debug)]
+struct ReconcileArgs {
+    /// The Fibonacci contract proofs are settled on: an address, an ENS name, or an alias.
+    #[arg(long, env = "FIBONACCI_CONTRACT_ADDRESS")]
+    contract: ContractTarget,
+
+    /// The RPC endpoint of the chain the contract is deployed on.
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// The first block to scan for settlements (e.g. the deployment block).
+    #[arg(long, env = "DEPLOYMENT_BLOCK", default_value = "0")]
+    from_block: u64,
+
+    /// The directory containing the locally generated proof artifacts.
+    #[arg(long, default_value = "artifacts")]
+    artifacts_dir: PathBuf,
+
+    /// Also write the reconciliation report to this file as JSON.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct SubmitArgs {
+    /// The Fibonacci contract to settle the proof on: an address, an ENS name, or an alias.
+    #[arg(long, env = "FIBONACCI_CONTRACT_ADDRESS")]
+    contract: ContractTarget,
+
+    /// The RPC endpoint of the chain the contract is deployed on.
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// The input the proof was generated for (its `contract_call_data_n<n>.json` is submitted).
+    #[arg(long, default_value = "10")]
+    n: u32,
+
+    /// The directory the proof was saved to (the settlement receipt is saved there too).
+    #[arg(long, default_value = DEFAULT_ARTIFACTS_DIR)]
+    artifacts_dir: PathBuf,
+
+    /// The job store the settlement is recorded in, linked to the job that proved it.
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+}
+
+#[derive(Args, Debug)]
+struct ReadResultsArgs {
+    /// The Fibonacci contract to read: an address, an ENS name, or an alias.
+    #[arg(long, env = "FIBONACCI_CONTRACT_ADDRESS")]
+    contract: ContractTarget,
+
+    /// The RPC endpoint of the chain the contract is deployed on.
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// The first block to scan for settlements (e.g. the deployment block).
+    #[arg(long, env = "DEPLOYMENT_BLOCK", default_value = "0")]
+    from_block: u64,
+
+    /// Only read these inputs, comma separated (default: every settled input).
+    #[arg(long, value_delimiter = ',')]
+    n: Vec<u32>,
+
+    /// The file to write the results to.
+    #[arg(long, default_value = "artifacts/results.json")]
+    out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct VerifyWrappedArgs {
+    /// The proof bytes, as saved by `network_evm` (`proof_<system>_n<n>.bin`).
+    #[arg(long, required_unless_present = "bundle", requires = "public_values")]
+    proof: Option<PathBuf>,
+
+    /// The public values, as saved by `network_evm` (`public_values_n<n>.bin`).
+    #[arg(long, requires = "proof")]
+    public_values: Option<PathBuf>,
+
+    /// Verify the proof of this bundle instead: a `.sp1bundle` file or a manifest, at an
+    /// `https://` URL, in S3 (`s3://<bucket>/<path>`) or on disk. Its files are checked against
+    /// the manifest before the proof is verified.
+    #[arg(long, conflicts_with_all = ["proof", "public_values"])]
+    bundle: Option<String>,
+
+    /// Refuse the bundle unless its manifest has this hash, e.g. the one a settlement refers to.
+    #[arg(long, requires = "bundle")]
+    manifest_hash: Option<B256>,
+
+    /// The program vkey hash, or a file containing it (e.g. `verification_key.txt`).
+    #[arg(long, env = "FIBONACCI_PROGRAM_VKEY")]
+    vkey: String,
+}
+
+#[derive(Args, Debug)]
+struct VerifierArgs {
+    /// The RPC endpoint of the chain to find the gateway on.
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// The proof system the gateway verifies.
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Check this gateway instead of looking one up.
+    #[arg(long, env = "SP1_VERIFIER_GATEWAY")]
+    gateway: Option<Address>,
+
+    /// Also set SP1_VERIFIER_ADDRESS (read by the deploy scripts) in this env file
+    #[arg(long, num_args = 0..=1, default_missing_value = ".env")]
+    update_env: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct InspectArgs {
+    /// The input the proof was generated for.
+    #[arg(long)]
+    n: u32,
+
+    /// The directory the proof was saved to.
+    #[arg(long, default_value = DEFAULT_ARTIFACTS_DIR)]
+    artifacts_dir: PathBuf,
+
+    /// Also show what produced the proof: the commit, toolchain, packages, host and ELF hash.
+    #[arg(long)]
+    provenance: bool,
+}
+
+#[derive(Args, Debug)]
+struct DecodePvArgs {
+    /// The ABI-encoded public values, as hex (with or without `0x`).
+    public_values: Bytes,
+}
+
+#[derive(Args, Debug)]
+struct ExpectedPvArgs {
+    /// The input number for Fibonacci computation.
+    #[arg(long)]
+    n: u32,
+
+    /// Also commit every Fibonacci number from this index up to `n`.
+    #[arg(long)]
+    range_start: Option<u32>,
+
+    /// Commit the hash of the range instead of its values.
+    #[arg(long, requires = "range_start")]
+    range_hash: bool,
+
+    /// The sequence to compute: `fibonacci`, `lucas`, or any two seeds such as `3,7`.
+    #[arg(long, default_value = "fibonacci")]
+    sequence: Sequence,
+
+    /// Evaluate the recurrence with these coefficients `c1,...,cm` instead of Fibonacci.
+    #[arg(long, value_delimiter = ',', requires = "modulus")]
+    coefficients: Vec<u64>,
+
+    /// The initial values `x_0,...,x_{m-1}` of the recurrence.
+    #[arg(long, value_delimiter = ',', requires = "modulus")]
+    initial: Vec<u64>,
+
+    /// The modulus of the recurrence.
+    #[arg(long, requires_all = ["coefficients", "initial"])]
+    modulus: Option<u64>,
+
+    /// The chain id the values are bound to.
+    #[arg(long)]
+    chain_id: Option<u64>,
+
+    /// The consumer contract the values are bound to.
+    #[arg(long)]
+    consumer: Option<Address>,
+
+    /// The request id echoed in the values.
+    #[arg(long)]
+    nonce: Option<u64>,
+
+    /// Unix timestamp after which the proof must no longer be settled.
+    #[arg(long)]
+    valid_until: Option<u64>,
+
+    /// Compare with these ABI-encoded public values (hex) and list the fields that differ.
+    #[arg(long)]
+    against: Option<Bytes>,
+}
+
+#[derive(Args, Debug)]
+struct BundleArgs {
+    /// The directory proof bundles are saved in.
+    #[arg(long, default_value = DEFAULT_ARTIFACTS_DIR)]
+    artifacts_dir: PathBuf,
+
+    #[command(subcommand)]
+    command: BundleCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum BundleCommand {
+    /// Pack the manifest, proof, public values, vkey and run report of a proof into one file.
+    Export {
+        /// The input the proof was generated for.
+        #[arg(long)]
+        n: u32,
+
+        /// The file to write (defaults to `fibonacci_n<n>.sp1bundle` in the artifacts directory).
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Check a bundle against its manifest and unpack it into the artifacts directory.
+    Import {
+        /// The `.sp1bundle` file.
+        bundle: PathBuf,
+    },
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// The first bundle: a `.sp1bundle` file, or the `manifest_n<n>.json` of a saved proof.
+    left: PathBuf,
+
+    /// The second bundle.
+    right: PathBuf,
+
+    /// Only list the fields that differ.
+    #[arg(long)]
+    changed_only: bool,
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    /// The layout to convert to.
+    #[arg(long, value_enum)]
+    format: ExportFormat,
+
+    /// The Groth16 proof bytes, as saved by `network_evm` (`proof_groth16_n<n>.bin`).
+    #[arg(long)]
+    proof: PathBuf,
+
+    /// The public values, as saved by `network_evm` (`public_values_n<n>.bin`).
+    #[arg(long)]
+    public_values: PathBuf,
+
+    /// The program vkey hash, or a file containing it (e.g. `verification_key.txt`).
+    #[arg(long, env = "FIBONACCI_PROGRAM_VKEY")]
+    vkey: String,
+
+    /// The directory to write the files to [default: a `<format>` directory next to the proof].
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct MigrateArtifactsArgs {
+    /// The directory proof bundles are saved in.
+    #[arg(long, default_value = DEFAULT_ARTIFACTS_DIR)]
+    artifacts_dir: PathBuf,
+
+    /// Only print what would be migrated.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+struct JobsArgs {
+    /// The job store: `sqlite://...`, or `postgres://...` with the `postgres` feature.
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    #[command(subcommand)]
+    command: JobsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum JobsCommand {
+    /// Queue a proof of the n'th Fibonacci number.
+    Submit {
+        /// The input number for Fibonacci computation.
+        #[arg(long)]
+        n: u32,
+
+        /// Also commit every Fibonacci number from this index up to `n`.
+        #[arg(long)]
+        range_start: Option<u32>,
+
+        /// Unix timestamp after which the proof must no longer be settled.
+        #[arg(long)]
+        valid_until: Option<u64>,
+
+        /// The proof system to use.
+        #[arg(long, value_enum, default_value = "groth16")]
+        system: ProofSystem,
+
+        /// Higher priorities are proved first.
+        #[arg(long, value_enum, default_value = "normal")]
+        priority: Priority,
+
+        /// Do not start the job before this Unix timestamp.
+        #[arg(long)]
+        at: Option<u64>,
+
+        /// How the prover network should fulfill the job, overriding the worker's default for
+        /// its priority: `reserved` capacity for latency-sensitive proofs, or the cheaper
+        /// `auction`.
+        #[arg(long, value_enum)]
+        network_strategy: Option<NetworkStrategy>,
+
+        /// The job id (defaults to one derived from the current time).
+        #[arg(long)]
+        id: Option<String>,
+
+        /// The tenant the job belongs to.
+        #[arg(long, default_value = DEFAULT_TENANT)]
+        tenant: String,
+    },
+    /// List jobs, oldest first.
+    List {
+        /// Only list jobs with this status.
+        #[arg(long, value_enum)]
+        status: Option<JobStatus>,
+
+        /// Only list jobs of this tenant.
+        #[arg(long)]
+        tenant: Option<String>,
+
+        /// Show a live dashboard of the jobs instead, with keys to cancel or retry them.
+        #[cfg(feature = "tui")]
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Show a job.
+    Show { id: String },
+    /// Put a failed or interrupted job back in the queue.
+    Retry { id: String },
+}
+
+#[derive(Args, Debug)]
+struct KeysArgs {
+    /// The job store: `sqlite://...`, or `postgres://...` with the `postgres` feature.
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    #[command(subcommand)]
+    command: KeysCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum KeysCommand {
+    /// Create a key and print it. It is only stored hashed, so it cannot be shown again.
+    Create {
+        /// The name requests made with the key are attributed to.
+        #[arg(long)]
+        name: String,
+
+        /// The tenant whose jobs the key submits and sees.
+        #[arg(long, default_value = DEFAULT_TENANT)]
+        tenant: String,
+
+        /// The most requests the key may make per minute.
+        #[arg(long)]
+        rate_limit: Option<u32>,
+
+        /// The most unfinished jobs the key may have submitted at once.
+        #[arg(long)]
+        max_concurrent: Option<u32>,
+    },
+    /// List keys, including revoked ones.
+    List,
+    /// Revoke a key. Jobs submitted with it are kept.
+    Revoke { name: String },
+}
+
+#[derive(Args, Debug)]
+struct AuditArgs {
+    /// The audit log file.
+    #[arg(long, env = "AUDIT_LOG", default_value = DEFAULT_AUDIT_LOG)]
+    log: PathBuf,
+
+    #[command(subcommand)]
+    command: AuditCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum AuditCommand {
+    /// Check that no entry of the log has been altered, removed or reordered.
+    Verify,
+}
+
+#[derive(Args, Debug)]
+struct ReportArgs {
+    /// The job store: `sqlite://...`, or `postgres://...` with the `postgres` feature.
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    #[command(subcommand)]
+    command: ReportCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum ReportCommand {
+    /// Sum the prover network spend and settlement gas costs by program and chain.
+    Costs {
+        /// Only count jobs completed and transactions settled since this date (`YYYY-MM-DD`,
+        /// UTC) or Unix timestamp.
+        #[arg(long, value_parser = parse_since, default_value = "0")]
+        since: u64,
+
+        /// The proving price per million cycles, used to estimate the network spend (in USD or
+        /// PROVE).
+        #[arg(long, env = "PROVING_PRICE_PER_MCYCLE", default_value = "0.1")]
+        price_per_mcycle: f64,
+
+        /// Also write the summary to this CSV file.
+        #[arg(long)]
+        csv: Option<PathBuf>,
+    },
+    /// Render the jobs, their costs and settlements, and the run reports as a static HTML page.
+    Html {
+        /// Only include jobs updated and transactions settled since this date (`YYYY-MM-DD`,
+        /// UTC) or Unix timestamp.
+        #[arg(long, value_parser = parse_since, default_value = "0")]
+        since: u64,
+
+        /// The proving price per million cycles, used to estimate the network spend (in USD or
+        /// PROVE).
+        #[arg(long, env = "PROVING_PRICE_PER_MCYCLE", default_value = "0.1")]
+        price_per_mcycle: f64,
+
+        /// The directory to find run reports in.
+        #[arg(long, default_value = "artifacts")]
+        artifacts_dir: PathBuf,
+
+        /// Where to write the page.
+        #[arg(long, default_value = "artifacts/report.html")]
+        out: PathBuf,
+    },
+}
+
+#[derive(Args, Debug)]
+struct StatsArgs {
+    /// The job store: `sqlite://...`, or `postgres://...` with the `postgres` feature.
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    /// Only count phases recorded since this date (`YYYY-MM-DD`, UTC) or Unix timestamp.
+    #[arg(long, value_parser = parse_since, default_value = "0")]
+    since: u64,
+
+    /// The length of each period, in seconds (a day by default).
+    #[arg(long, value_parser = parse_secs, default_value = "86400")]
+    period: Duration,
+
+    /// Only report the jobs of this proof system.
+    #[arg(long, value_enum)]
+    system: Option<ProofSystem>,
+
+    /// Also write the timings to this CSV file.
+    #[arg(long)]
+    csv: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct WhenToSubmitArgs {
+    /// The RPC endpoint of the chain proofs are submitted on.
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// How many recent blocks to sample (at most 1024).
+    #[arg(long, default_value = "300")]
+    blocks: u64,
+
+    /// Wait until the base fee of the next block is at most this many gwei, then exit
+    /// successfully; fail if it is still higher after `--within`.
+    #[arg(long)]
+    wait_for_gwei: Option<f64>,
+
+    /// How many seconds to wait at most.
+    #[arg(long, value_parser = parse_secs, default_value = "3600", requires = "wait_for_gwei")]
+    within: Duration,
+
+    /// Seconds between checks of the base fee while waiting.
+    #[arg(long, value_parser = parse_secs, default_value = "12", requires = "wait_for_gwei")]
+    poll_interval: Duration,
+
+    /// Print the sample and recommendation as JSON.
+    #[arg(long, conflicts_with = "wait_for_gwei")]
+    json: bool,
+}
+
+#[derive(Args, Debug)]
+struct SweepArgs {
+    /// The first `n` to execute.
+    #[arg(long, default_value = "1")]
+    from: u32,
+
+    /// The last `n` to execute (inclusive).
+    #[arg(long, default_value_t = MAX_N)]
+    to: u32,
+
+    /// The distance between consecutive inputs.
+    #[arg(long, default_value = "500", value_parser = clap::value_parser!(u32).range(1..))]
+    step: u32,
+
+    /// The proving price per million cycles, used for the cost estimate (in USD or PROVE; the
+    /// default is only a ballpark, use a current quote from the network).
+    #[arg(long, env = "PROVING_PRICE_PER_MCYCLE", default_value = "0.1")]
+    price_per_mcycle: f64,
+
+    /// The CSV file to write.
+    #[arg(long, default_value = "artifacts/sweep.csv")]
+    out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ExecuteArgs {
+    /// The program to execute.
+    #[arg(long, value_parser = program, default_value = "fibonacci")]
+    program: &'static Program,
+
+    /// The input, in the program's format (for `fibonacci`: `n`, or a JSON `ProgramInput`).
+    #[arg(long)]
+    input: String,
+
+    /// Execute the program twice and check that both executions commit the same public values
+    /// in the same number of cycles.
+    #[arg(long)]
+    check_determinism: bool,
+}
+
+#[derive(Args, Debug)]
+struct BuildArgs {
+    /// The program to build.
+    #[arg(long, value_parser = program, default_value = "fibonacci")]
+    program: &'static Program,
+
+    /// Build inside the pinned SP1 Docker image and pin the hash of the ELF in `elf.lock`.
+    #[arg(long)]
+    reproducible: bool,
+
+    /// The SP1 Docker image tag to build with.
+    #[arg(long, env = "SP1_DOCKER_TAG", default_value = "v5.0.0", requires = "reproducible")]
+    tag: String,
+}
+
+#[derive(Args, Debug)]
+struct BuildAllArgs {
+    /// Build inside the pinned SP1 Docker image and pin the hash of every ELF in `elf.lock`.
+    #[arg(long)]
+    reproducible: bool,
+
+    /// The SP1 Docker image tag to build with.
+    #[arg(long, env = "SP1_DOCKER_TAG", default_value = "v5.0.0", requires = "reproducible")]
+    tag: String,
+}
+
+#[derive(Args, Debug)]
+struct InitProgramArgs {
+    /// The name of the program, e.g. `my-thing`, selected with `--program my-thing`.
+    name: String,
+
+    /// The root of the repository.
+    #[arg(long, default_value = "..")]
+    root: PathBuf,
+
+    /// Only list the files that would be created or changed.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+struct DebugExecuteArgs {
+    /// The program to execute.
+    #[arg(long, value_parser = program, default_value = "fibonacci")]
+    program: &'static Program,
+
+    /// The input, in the program's format (for `fibonacci`: `n`, or a JSON `ProgramInput`).
+    #[arg(long)]
+    input: String,
+
+    /// Where SP1 writes the execution profile (requires the `profiling` feature); open it in the
+    /// Firefox Profiler.
+    #[arg(long, default_value = "artifacts/trace.json")]
+    trace_out: PathBuf,
+
+    /// Sample the program counter every this many cycles.
+    #[arg(long, default_value = "100")]
+    sample_rate: u64,
+
+    /// Only list the functions overlapping this `<start>..<end>` range of program counters.
+    #[arg(long, value_parser = parse_pc_range)]
+    pc_range: Option<(u32, u32)>,
+
+    /// Only list the functions whose name contains this.
+    #[arg(long)]
+    symbol: Option<String>,
+
+    /// Where to write the execution report.
+    #[arg(long, default_value = "artifacts/debug_execute.json")]
+    out: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct ProfileArgs {
+    /// The program to execute.
+    #[arg(long, value_parser = program, default_value = "fibonacci")]
+    program: &'static Program,
+
+    /// The input, in the program's format (for `fibonacci`: `n`, or a JSON `ProgramInput`).
+    #[arg(long)]
+    input: String,
+
+    /// Where to write the profile as flame graph JSON.
+    #[arg(long, default_value = "artifacts/profile.json")]
+    out: PathBuf,
+
+    /// Where to write the profile as an HTML page.
+    #[arg(long, default_value = "artifacts/profile.html")]
+    html: PathBuf,
+}
+
+#[cfg(feature = "hash-bench")]
+#[derive(Args, Debug)]
+struct HashBenchArgs {
+    /// How many bytes to hash.
+    #[arg(long, default_value = "1024")]
+    bytes: usize,
+
+    /// Where to write the comparison as JSON.
+    #[arg(long, default_value = "artifacts/hash_bench.json")]
+    out: PathBuf,
+}
+
+/// The largest block range requested from the RPC in a single `eth_getLogs` call.
+const LOG_CHUNK_SIZE: u64 = 10_000;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Codegen(args) => codegen(args),
+        Command::Reconcile(args) => reconcile(args).await?,
+        Command::Submit(args) => submit(args).await?,
+        Command::ReadResults(args) => read_results(args).await?,
+        Command::VerifyWrapped(args) => verify_wrapped(args)?,
+        Command::Verifier(args) => verifier(args).await?,
+        Command::Jobs(args) => jobs(args).await?,
+        Command::Keys(args) => keys(args).await?,
+        Command::Audit(args) => audit(args)?,
+        Command::Report(args) => report(args).await?,
+        Command::Stats(args) => stats(args).await?,
+        Command::WhenToSubmit(args) => when_to_submit(args).await?,
+        Command::Sweep(args) => sweep(args)?,
+        Command::Execute(args) => execute(args)?,
+        Command::Build(args) => build(args)?,
+        Command::BuildAll(args) => build_all(args)?,
+        Command::InitProgram(args) => init_program(args)?,
+        Command::DebugExecute(args) => debug_execute(args)?,
+        Command::Inspect(args) => inspect(args)?,
+        Command::DecodePv(args) => decode_pv(args)?,
+        Command::ExpectedPv(args) => expected_pv(args)?,
+        Command::Bundle(args) => bundle(args)?,
+        Command::Diff(args) => diff(args)?,
+        Command::Export(args) => export_proof(args)?,
+        Command::MigrateArtifacts(args) => migrate(args)?,
+        Command::Profile(args) => profile(args)?,
+        #[cfg(feature = "hash-bench")]
+        Command::HashBench(args) => hash_bench(args)?,
+    }
+    Ok(())
+}
+
+/// Write (or check) the generated Solidity definitions.
+fn codegen(args: CodegenArgs) {
+    let source = generate_solidity();
+
+    if args.check {
+        let existing = std::fs::read_to_string(&args.out).unwrap_or_default();
+        if existing != source {
+            eprintln!(
+                "Error: {} is out of date, run `cargo run --bin cli -- codegen`",
+                args.out.display()
+            );
+            std::process::exit(1);
+        }
+        println!("{} is up to date", args.out.display());
+        return;
+    }
+
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent).expect("failed to create output directory");
+    }
+    std::fs::write(&args.out, source).expect("failed to write generated Solidity");
+    println!("Wrote {}", args.out.display());
+}
+
+/// Render the Solidity counterpart of the public values types and limits in `fibonacci-lib`.
+fn generate_solidity() -> String {
+    // The EIP-712 type is `Name(type1 field1,type2 field2,...)`, which is everything we need to
+    // reproduce the struct in Solidity.
+    let root_type = PublicValuesStruct::eip712_root_type();
+    let fields = root_type
+        .strip_prefix(PublicValuesStruct::NAME)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .expect("unexpected EIP-712 type");
+    let name = PublicValuesStruct::NAME;
+
+    let mut out = String::new();
+    out.push_str("// SPDX-License-Identifier: MIT\n");
+    out.push_str("// Generated by `cargo run --bin cli -- codegen` from `fibonacci-lib`. Do not edit.\n");
+    out.push_str("pragma solidity ^0.8.20;\n\n");
+    out.push_str("/// @notice The largest `n` the SP1 program accepts\n");
+    out.push_str(&format!("uint32 constant MAX_N = {};\n\n", MAX_N));
+    out.push_str("/// @notice The largest number of values the SP1 program commits in a single range\n");
+    out.push_str(&format!("uint32 constant MAX_RANGE_LEN = {};\n\n", MAX_RANGE_LEN));
+    out.push_str("/// @notice The largest order of a recurrence the SP1 program evaluates\n");
+    out.push_str(&format!("uint32 constant MAX_ORDER = {};\n\n", MAX_ORDER));
+    out.push_str("/// @notice The outcomes the SP1 program commits in `status`\n");
+    for status in Status::ALL {
+        out.push_str(&format!("uint8 constant STATUS_{} = {};\n", status.name(), status as u8));
+    }
+    out.push('\n');
+    out.push_str("/// @notice Struct representing the public values from the SP1 program\n");
+    out.push_str(&format!("struct {} {{\n", name));
+    for field in fields.split(',') {
+        out.push_str(&format!("    {};\n", field));
+    }
+    out.push_str("}\n\n");
+    out.push_str("/// @notice Helpers for the public values committed by the SP1 program\n");
+    out.push_str("library PublicValuesLib {\n");
+    out.push_str("    /// @notice Decode the ABI-encoded public values\n");
+    out.push_str(&format!(
+        "    function decode(bytes memory publicValues) internal pure returns ({} memory) {{\n",
+        name
+    ));
+    out.push_str(&format!("        return abi.decode(publicValues, ({}));\n", name));
+    out.push_str("    }\n");
+    out.push('\n');
+    out.push_str("    /// @notice The hash the SP1 program commits in `rangeHash` for `values` = F(start..=n)\n");
+    out.push_str(
+        "    function rangeHash(uint32 start, uint32[] memory values) internal pure returns (bytes32) {\n",
+    );
+    out.push_str("        return keccak256(abi.encodePacked(start, values));\n");
+    out.push_str("    }\n");
+    out.push('\n');
+    out.push_str("    /// @notice The id of the recurrence and index whose result the public values commit\n");
+    out.push_str(&format!(
+        "    function recurrenceId({} memory values) internal pure returns (bytes32) {{\n",
+        name
+    ));
+    out.push_str(
+        "        return keccak256(abi.encode(values.coefficients, values.initialValues, values.modulus, values.n));\n",
+    );
+    out.push_str("    }\n");
+    out.push_str("}\n");
+    out
+}
+
+/// A proof found in the local artifacts.
+#[derive(Debug, Serialize)]
+struct LocalProof {
+    file: String,
+    n: u32,
+}
+
+/// A `FibonacciVerified` event found on-chain.
+#[derive(Debug, Serialize)]
+struct Settlement {
+    n: u32,
+    tx_hash: Option<B256>,
+    block_number: Option<u64>,
+}
+
+/// The outcome of reconciling local proofs against on-chain settlements.
+#[derive(Debug, Default, Serialize)]
+struct ReconcileReport {
+    /// Proofs that were generated locally and settled on-chain.
+    settled: Vec<B256>,
+    /// Proofs that were generated locally but never settled.
+    unsettled: BTreeMap<B256, LocalProof>,
+    /// Settlements of proofs that are not in the local artifacts.
+    unknown: BTreeMap<B256, Settlement>,
+}
+
+/// Cross-reference local proof artifacts with `FibonacciVerified` events, keyed by proof hash.
+async fn reconcile(args: ReconcileArgs) -> Result<()> {
+    println!("🔍 Reconciling {} against {}", args.artifacts_dir.display(), args.contract);
+
+    let local = load_local_proofs(&args.artifacts_dir)?;
+    println!("📁 Local proofs: {}", local.len());
+
+    let provider = ProviderBuilder::new().on_http(args.rpc_url.parse()?);
+    let contract = Fibonacci::new(args.contract.resolve(&args.rpc_url).await?, &provider);
+    let latest = provider.get_block_number().await?;
+
+    let mut settlements = BTreeMap::new();
+    let mut from = args.from_block;
+    while from <= latest {
+        let to = (from + LOG_CHUNK_SIZE - 1).min(latest);
+        let events =
+            contract.FibonacciVerified_filter().from_block(from).to_block(to).query().await?;
+        for (event, log) in events {
+            let settlement = Settlement {
+                n: event.n,
+                tx_hash: log.transaction_hash,
+                block_number: log.block_number,
+            };
+            settlements.insert(event.proofHash, settlement);
+        }
+        from = to + 1;
+    }
+    println!("⛓️  On-chain settlements: {}", settlements.len());
+
+    let mut report = ReconcileReport::default();
+    for (hash, proof) in local {
+        if settlements.remove(&hash).is_some() {
+            report.settled.push(hash);
+        } else {
+            report.unsettled.insert(hash, proof);
+        }
+    }
+    report.unknown = settlements;
+
+    println!();
+    println!("✅ Settled: {}", report.settled.len());
+    println!("⚠️  Generated but never settled: {}", report.unsettled.len());
+    for (hash, proof) in &report.unsettled {
+        println!("   {} n = {} ({})", hash, proof.n, proof.file);
+    }
+    println!("❓ Settled but not in local artifacts: {}", report.unknown.len());
+    for (hash, settlement) in &report.unknown {
+        let tx = settlement.tx_hash.map(|tx| tx.to_string()).unwrap_or_default();
+        println!("   {} n = {} (tx {})", hash, settlement.n, tx);
+    }
+
+    if let Some(out) = &args.out {
+        write_json(out, &report)?;
+        println!("💾 Report saved to: {}", out.display());
+    }
+    Ok(())
+}
+
+/// Load the proofs recorded in `contract_call_data_*.json` artifacts, keyed by proof hash.
+fn load_local_proofs(dir: &std::path::Path) -> Result<BTreeMap<B256, LocalProof>> {
+    let mut proofs = BTreeMap::new();
+    for (path, call_data) in load_all_call_data(dir)? {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let n = call_data.decode_public_values().map(|values| values.n).unwrap_or_default();
+        proofs.insert(
+            keccak256(&call_data.proof_bytes),
+            LocalProof { file: name.to_string(), n },
+        );
+    }
+    Ok(proofs)
+}
+
+/// Send a saved proof to the `Fibonacci` contract and read back the result it stored.
+async fn submit(args: SubmitArgs) -> Result<()> {
+    let call_data = CallData::load(&call_data_path(&args.artifacts_dir, args.n))?;
+    let public_values = call_data.decode_public_values()?;
+    let contract_address = args.contract.resolve(&args.rpc_url).await?;
+    println!("📤 Settling {} on {}", public_values, contract_address);
+
+    let signer = config::signer()?;
+    let signer_address = signer.address();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(args.rpc_url.parse()?);
+    let contract = Fibonacci::new(contract_address, &provider);
+    let call = contract
+        .verifyFibonacciProof(call_data.public_values.clone(), call_data.proof_bytes.clone());
+
+    // Simulate first, so a rejection is reported with its decoded reason instead of as a
+    // reverted transaction.
+    if let Err(err) = call.call().await {
+        let Some(data) = revert_data(&err) else {
+            return Err(err.into());
+        };
+        match decode_revert(&data) {
+            Some(decoded) => eyre::bail!("the contract rejects the proof: {}", decoded.error),
+            None => eyre::bail!("the contract rejects the proof with unknown data: {}", data),
+        }
+    }
+
+    let chain_id = provider.get_chain_id().await?;
+    let mut details = serde_json::json!({
+        "chain_id": chain_id,
+        "contract": contract_address.to_string(),
+        "signer": signer_address.to_string(),
+        "n": public_values.n,
+        "nonce": public_values.nonce,
+        "public_values_hash": keccak256(&call_data.public_values),
+        "proof_hash": keccak256(&call_data.proof_bytes),
+    });
+    let started = Instant::now();
+    let receipt = match call.send().await {
+        Ok(pending) => pending.get_receipt().await.map_err(eyre::Report::from),
+        Err(err) => Err(err.into()),
+    };
+    let submit_time = started.elapsed();
+    let outcome = match &receipt {
+        Ok(receipt) => {
+            details["tx_hash"] = serde_json::json!(receipt.transaction_hash.to_string());
+            if receipt.status() { "ok" } else { "reverted" }.to_string()
+        }
+        Err(err) => format!("failed: {}", err),
+    };
+    AuditLog::from_env().append("submit", details, outcome)?;
+    let receipt = receipt?;
+
+    let proof_hash = keccak256(&call_data.proof_bytes);
+    let manifest_hash = Manifest::hash_for(&args.artifacts_dir, public_values.n, proof_hash)?;
+    let settlement =
+        SettlementReceipt::new(&receipt, chain_id, contract_address, &call_data, manifest_hash)?;
+    let settlement_path = settlement.save(&args.artifacts_dir)?;
+    println!("💾 Settlement receipt saved to: {}", settlement_path.display());
+    let store = JobStore::connect(&args.job_store).await?;
+    let settlement_artifact = settlement_path.display().to_string();
+    if let Some(job_id) = store.record_settlement(&settlement, &settlement_artifact).await? {
+        store.record_phase(&job_id, Phase::Submit, submit_time).await?;
+        println!("🔗 Settlement linked to job {}", job_id);
+    }
+    if !receipt.status() {
+        eyre::bail!("transaction {} reverted", receipt.transaction_hash);
+    }
+    println!(
+        "✅ Transaction mined: {} (block {}, gas used {})",
+        receipt.transaction_hash,
+        receipt.block_number.unwrap_or_default(),
+        receipt.gas_used
+    );
+
+    // Read the settlement back from the contract's storage.
+    if public_values.is_rejected() {
+        let status = contract.rejectedInputs(public_values.n).call().await?._0;
+        println!("📖 Stored rejection of n = {}: {}", public_values.n, Status::name_of(status));
+    } else {
+        let stored = contract.getFibonacciResult(public_values.n).call().await?;
+        if !stored.verified {
+            eyre::bail!("the contract has no stored result for n = {}", public_values.n);
+        }
+        println!(
+            "📖 Stored result: Fibonacci({}) = {}, Fibonacci({}) = {}",
+            stored.result.n.saturating_sub(1),
+            stored.result.a,
+            stored.result.n,
+            stored.result.b
+        );
+    }
+    Ok(())
+}
+
+/// A result stored on the `Fibonacci` contract, with the settlement that last wrote it.
+#[derive(Debug, Serialize)]
+struct StoredResult {
+    n: u32,
+    a: u32,
+    b: u32,
+    status: String,
+    nonce: u64,
+    /// How many `FibonacciVerified` events were emitted for `n` in the scanned blocks.
+    settlements: usize,
+    tx_hash: Option<B256>,
+    block_number: Option<u64>,
+    proof_hash: Option<B256>,
+}
+
+/// A proven rejection stored on the `Fibonacci` contract.
+#[derive(Debug, Serialize)]
+struct StoredRejection {
+    n: u32,
+    status: String,
+    tx_hash: Option<B256>,
+    block_number: Option<u64>,
+}
+
+/// The export written by `read-results`.
+#[derive(Debug, Serialize)]
+struct ResultsExport {
+    contract: String,
+    chain_id: u64,
+    /// The block the results were read at.
+    block_number: u64,
+    results: Vec<StoredResult>,
+    rejected: Vec<StoredRejection>,
+}
+
+/// Read the results stored on the `Fibonacci` contract, found through its settlement events, and
+/// export them as JSON.
+async fn read_results(args: ReadResultsArgs) -> Result<()> {
+    let provider = ProviderBuilder::new().on_http(args.rpc_url.parse()?);
+    let contract_address = args.contract.resolve(&args.rpc_url).await?;
+    let contract = Fibonacci::new(contract_address, &provider);
+    let latest = provider.get_block_number().await?;
+    println!("🔍 Reading results from {} (blocks {}..={})", args.contract, args.from_block, latest);
+
+    let mut settlements: BTreeMap<u32, Vec<Settlement>> = BTreeMap::new();
+    let mut proof_hashes = BTreeMap::new();
+    let mut rejections = BTreeMap::new();
+    let mut from = args.from_block;
+    while from <= latest {
+        let to = (from + LOG_CHUNK_SIZE - 1).min(latest);
+        for (event, log) in
+            contract.FibonacciVerified_filter().from_block(from).to_block(to).query().await?
+        {
+            proof_hashes.insert(event.n, event.proofHash);
+            settlements.entry(event.n).or_default().push(Settlement {
+                n: event.n,
+                tx_hash: log.transaction_hash,
+                block_number: log.block_number,
+            });
+        }
+        for (event, log) in
+            contract.FibonacciRejected_filter().from_block(from).to_block(to).query().await?
+        {
+            let rejection = StoredRejection {
+                n: event.n,
+                status: Status::name_of(event.status),
+                tx_hash: log.transaction_hash,
+                block_number: log.block_number,
+            };
+            rejections.insert(event.n, rejection);
+        }
+        from = to + 1;
+    }
+
+    let inputs: Vec<u32> =
+        if args.n.is_empty() { settlements.keys().copied().collect() } else { args.n.clone() };
+    let mut results = Vec::new();
+    for n in inputs {
+        let stored = contract.getFibonacciResult(n).block(latest.into()).call().await?;
+        if !stored.verified {
+            println!("   n = {}: no stored result", n);
+            continue;
+        }
+        let history = settlements.get(&n).map(Vec::as_slice).unwrap_or_default();
+        let last = history.last();
+        let result = StoredResult {
+            n,
+            a: stored.result.a,
+            b: stored.result.b,
+            status: Status::name_of(stored.result.status),
+            nonce: stored.result.nonce,
+            settlements: history.len(),
+            tx_hash: last.and_then(|settlement| settlement.tx_hash),
+            block_number: last.and_then(|settlement| settlement.block_number),
+            proof_hash: proof_hashes.get(&n).copied(),
+        };
+        println!(
+            "   n = {}: Fibonacci({}) = {}, Fibonacci({}) = {} [{}]",
+            n,
+            n.saturating_sub(1),
+            result.a,
+            n,
+            result.b,
+            result.status
+        );
+        results.push(result);
+    }
+    let rejected: Vec<StoredRejection> = rejections
+        .into_values()
+        .filter(|rejection| args.n.is_empty() || args.n.contains(&rejection.n))
+        .collect();
+    for rejection in &rejected {
+        println!("   n = {}: rejected ({})", rejection.n, rejection.status);
+    }
+
+    let export = ResultsExport {
+        contract: contract_address.to_string(),
+        chain_id: provider.get_chain_id().await?,
+        block_number: latest,
+        results,
+        rejected,
+    };
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_json(&args.out, &export)?;
+    println!(
+        "💾 {} results and {} rejections saved to: {}",
+        export.results.len(),
+        export.rejected.len(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+/// Verify a wrapped proof with `sp1-verifier` and print the public values it commits to.
+fn verify_wrapped(args: VerifyWrappedArgs) -> Result<()> {
+    let vkey: VkHash = match std::fs::read_to_string(&args.vkey) {
+        Ok(contents) => contents.parse(),
+        Err(_) => args.vkey.parse(),
+    }
+    .map_err(|err| eyre::eyre!(err))?;
+    let (proof, public_values) = match (&args.bundle, &args.proof, &args.public_values) {
+        (Some(uri), _, _) => {
+            println!("🔍 Fetching proof bundle {}", uri);
+            let bundle = fetch_bundle(uri, args.manifest_hash)?;
+            println!("✅ Bundle matches its manifest {}", bundle.manifest_hash);
+            // The vkey is the verifier's own, not the bundle's claim about itself.
+            if bundle.manifest.vkey != vkey {
+                let proved_for = bundle.manifest.vkey;
+                eyre::bail!("the bundle was proved for vkey {}, not {}", proved_for, vkey);
+            }
+            let call_data = bundle.call_data()?;
+            (call_data.proof_bytes.to_vec(), call_data.public_values.to_vec())
+        }
+        (None, Some(proof), Some(public_values)) => {
+            println!("🔍 Verifying proof {}", proof.display());
+            (read_artifact(proof)?, read_artifact(public_values)?)
+        }
+        _ => eyre::bail!("pass --bundle, or --proof and --public-values"),
+    };
+    println!("🔑 Program VKey: {}", vkey);
+
+    let system = match verify_proof(&proof, &public_values, &vkey.to_string()) {
+        Ok(system) => system,
+        Err(err) => {
+            eprintln!("❌ Proof verification failed: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let decoded = PublicValuesStruct::from_abi_bytes(&public_values)?;
+    println!("✅ {:?} proof verified", system);
+    println!("📊 Public values: {}", decoded);
+    Ok(())
+}
+
+/// Resolve and check the verifier gateway for the chain served by the RPC endpoint.
+async fn verifier(args: VerifierArgs) -> Result<()> {
+    let provider = ProviderBuilder::new().on_http(args.rpc_url.parse()?);
+    let resolved = resolve_gateway(&provider, args.system, args.gateway).await?;
+    println!("✅ {} verifier gateway on chain {}", args.system, resolved.chain_id);
+    println!("   Gateway: {} ({})", resolved.gateway, resolved.source);
+    println!("   Selector: {}", resolved.selector);
+    println!("   Verifier: {} ({})", resolved.verifier, resolved.version);
+    if let Some(env_file) = &args.update_env {
+        config::set_in_env_file(env_file, "SP1_VERIFIER_ADDRESS", &resolved.gateway.to_string())?;
+        println!("📝 Set SP1_VERIFIER_ADDRESS in {}", env_file.display());
+    }
+    Ok(())
+}
+
+/// Run a `jobs` subcommand against the job store.
+async fn jobs(args: JobsArgs) -> Result<()> {
+    let store = JobStore::connect(&args.job_store).await?;
+    match args.command {
+        JobsCommand::Submit {
+            n,
+            range_start,
+            valid_until,
+            system,
+            priority,
+            at,
+            network_strategy,
+            id,
+            tenant,
+        } => {
+            let id = id.unwrap_or_else(|| {
+                let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                format!("job-{}", millis.as_millis())
+            });
+            let input = ProgramInput { n, range_start, valid_until, ..Default::default() };
+            InputDomain::from_env()?.check(&input)?;
+            let options = JobOptions {
+                priority,
+                run_after: at.unwrap_or_default(),
+                network_strategy,
+                api_key: None,
+                max_concurrent: None,
+                tenant: Some(tenant),
+            };
+            let (job, coalesced) = store.submit(&id, &input, system, options).await?;
+            if coalesced {
+                println!("🔗 Identical to job {} ({}), not queued again", job.id, job.status);
+                if let Some(artifact) = &job.artifact {
+                    println!("   Artifact: {}", artifact);
+                }
+                return Ok(());
+            }
+            println!("📥 Queued job {} (n = {}, {}, {} priority)", job.id, n, system, priority);
+            if job.run_after != 0 {
+                println!("   Scheduled for {}", job.run_after);
+            }
+        }
+        #[cfg(feature = "tui")]
+        JobsCommand::List { status, tenant, tui: true } => {
+            fibonacci_script::dashboard::run(&store, status, tenant.as_deref()).await?;
+        }
+        JobsCommand::List { status, tenant, .. } => {
+            let jobs = store.list(status, tenant.as_deref()).await?;
+            if jobs.is_empty() {
+                println!("No jobs");
+            }
+            for job in jobs {
+                print_job_line(&job);
+            }
+        }
+        JobsCommand::Show { id } => {
+            let job = store.get(&id).await?;
+            print_job_line(&job);
+            println!("   Input: {}", serde_json::to_string(&job.input)?);
+            println!("   Input hash: {}", job.input_hash);
+            println!("   Tenant: {}", job.tenant);
+            println!("   Priority: {}", job.priority);
+            if let Some(strategy) = job.network_strategy {
+                println!("   Network strategy: {}", strategy);
+            }
+            if job.run_after != 0 {
+                println!("   Scheduled for: {}", job.run_after);
+            }
+            println!("   Attempts: {}", job.attempts);
+            if let Some(worker) = &job.worker {
+                println!("   Worker: {}", worker);
+            }
+            if let Some(artifact) = &job.artifact {
+                println!("   Artifact: {}", artifact);
+            }
+            if let Some(error) = &job.error {
+                println!("   Error: {}", error);
+            }
+            if let Some(renewed_by) = &job.renewed_by {
+                println!("   Renewed by: {}", renewed_by);
+            }
+            if let Some(proof_hash) = &job.proof_hash {
+                println!("   Proof hash: {}", proof_hash);
+            }
+            if let (Some(prover), Some(cycles)) = (&job.prover, job.cycles) {
+                println!("   Proved: {} cycles ({})", cycles, prover);
+            }
+            for tx_hash in store.settlement_txs(&job.id).await? {
+                println!("   Settled in: {}", tx_hash);
+            }
+        }
+        JobsCommand::Retry { id } => {
+            store.set_status(&id, JobStatus::Queued, None).await?;
+            println!("🔁 Requeued job {}", id);
+        }
+    }
+    Ok(())
+}
+
+/// Print a one-line summary of a job.
+fn print_job_line(job: &Job) {
+    println!(
+        "{:<24} {:<12} {:<11} n = {:<6} {}",
+        job.id, job.status, job.priority, job.input.n, job.system
+    );
+}
+
+/// Run a `keys` subcommand against the job store.
+async fn keys(args: KeysArgs) -> Result<()> {
+    let store = JobStore::connect(&args.job_store).await?;
+    match args.command {
+        KeysCommand::Create { name, tenant, rate_limit, max_concurrent } => {
+            let key = generate_key();
+            let hash = key_hash(&key);
+            store.create_api_key(&name, &tenant, hash, rate_limit, max_concurrent).await?;
+            println!("🔑 Created API key {} for tenant {}", name, tenant);
+            println!("   {}", key);
+            println!("   Store it now: it cannot be shown again.");
+        }
+        KeysCommand::List => {
+            let keys = store.list_api_keys().await?;
+            if keys.is_empty() {
+                println!("No API keys");
+            }
+            let limit = |limit: Option<u32>| limit.map_or("-".into(), |limit| limit.to_string());
+            for key in keys {
+                let status =
+                    key.revoked_at.map_or("active".into(), |at| format!("revoked at {}", at));
+                println!(
+                    "{:<24} {:<16} {:>6}/min {:>4} jobs  {}",
+                    key.name,
+                    key.tenant,
+                    limit(key.rate_limit),
+                    limit(key.max_concurrent),
+                    status
+                );
+            }
+        }
+        KeysCommand::Revoke { name } => {
+            store.revoke_api_key(&name).await?;
+            println!("🚫 Revoked API key {}", name);
+        }
+    }
+    Ok(())
+}
+
+/// Run an `audit` subcommand against the audit log.
+fn audit(args: AuditArgs) -> Result<()> {
+    let log = AuditLog::new(args.log);
+    match args.command {
+        AuditCommand::Verify => {
+            let count = log.verify()?;
+            match log.entries()?.last() {
+                Some(last) => {
+                    println!("✅ {}: {} entries, chain intact", log.path().display(), count);
+                    println!("   Head: {} (entry {})", last.hash, last.record.seq);
+                }
+                None => println!("No entries in {}", log.path().display()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run a `report` subcommand against the job store.
+async fn report(args: ReportArgs) -> Result<()> {
+    let store = JobStore::connect(&args.job_store).await?;
+    match args.command {
+        ReportCommand::Costs { since, price_per_mcycle, csv: csv_path } => {
+            let summaries = store.costs(since).await?;
+            if summaries.is_empty() {
+                println!("No completed jobs or settlements since {}", since);
+                return Ok(());
+            }
+
+            let mut csv = String::from(
+                "program,chain_id,proofs,network_cycles,local_cycles,prover_spend,settlements,\
+                 reverted_settlements,gas_used,gas_cost_eth\n",
+            );
+            println!(
+                "{:<12} {:>10} {:>7} {:>16} {:>12} {:>12} {:>14} {:>14}",
+                "program", "chain", "proofs", "network cycles", "prover spend", "settlements",
+                "gas used", "gas cost (ETH)"
+            );
+            let (mut total_spend, mut total_gas_cost) = (0.0, 0);
+            for summary in &summaries {
+                let chain = summary.chain_id.map(|id| id.to_string()).unwrap_or_default();
+                let spend = summary.network_cycles as f64 / 1e6 * price_per_mcycle;
+                let gas_cost = wei_to_eth(summary.gas_cost_wei);
+                total_spend += spend;
+                total_gas_cost += summary.gas_cost_wei;
+                println!(
+                    "{:<12} {:>10} {:>7} {:>16} {:>12.6} {:>12} {:>14} {:>14.6}",
+                    summary.program,
+                    if chain.is_empty() { "-" } else { chain.as_str() },
+                    summary.proofs,
+                    summary.network_cycles,
+                    spend,
+                    summary.settlements,
+                    summary.gas_used,
+                    gas_cost
+                );
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{:.6},{},{},{},{:.18}\n",
+                    summary.program,
+                    chain,
+                    summary.proofs,
+                    summary.network_cycles,
+                    summary.local_cycles,
+                    spend,
+                    summary.settlements,
+                    summary.reverted_settlements,
+                    summary.gas_used,
+                    gas_cost
+                ));
+            }
+            println!();
+            println!(
+                "💸 Prover network spend: {:.6} (at {} per Mcycle)",
+                total_spend, price_per_mcycle
+            );
+            let total_gas_cost = wei_to_eth(total_gas_cost);
+            println!("⛽ Settlement gas: {:.6} ETH (excluding L1 data fees)", total_gas_cost);
+
+            if let Some(path) = &csv_path {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(path, csv)?;
+                println!("💾 Report saved to: {}", path.display());
+            }
+        }
+        ReportCommand::Html { since, price_per_mcycle, artifacts_dir, out } => {
+            let mut jobs = store.list(None, None).await?;
+            jobs.retain(|job| job.updated_at >= since);
+            let mut runs = Vec::new();
+            for path in find_run_reports(&artifacts_dir)? {
+                match RunReport::load(&path) {
+                    Ok(run) if run.started_at >= since => runs.push((path, run)),
+                    Ok(_) => {}
+                    Err(err) => println!("⚠️  Skipping {}: {}", path.display(), err),
+                }
+            }
+            let report = ActivityReport {
+                generated_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                since,
+                price_per_mcycle,
+                costs: store.costs(since).await?,
+                jobs,
+                settlements: store.settlements(since).await?,
+                runs,
+            };
+            if let Some(parent) = out.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&out, report.to_html())?;
+            println!(
+                "📊 {} jobs, {} settlements and {} runs",
+                report.jobs.len(),
+                report.settlements.len(),
+                report.runs.len()
+            );
+            println!("💾 Report saved to: {}", out.display());
+        }
+    }
+    Ok(())
+}
+
+/// Report the phase timings of the jobs, per proof system and period.
+/// Recommend when to submit from the recent base fees, or wait for a cheap enough one. Waiting
+/// fails at its deadline, so a script can chain the submission after it with `&&`.
+async fn when_to_submit(args: WhenToSubmitArgs) -> Result<()> {
+    let provider = ProviderBuilder::new().on_http(args.rpc_url.parse()?);
+    if let Some(max_gwei) = args.wait_for_gwei {
+        println!("⏳ Waiting up to {}s for a base fee of {} gwei", args.within.as_secs(), max_gwei);
+        let waited = wait_for_base_fee(&provider, max_gwei, args.within, args.poll_interval);
+        return match waited.await? {
+            Some(gwei) => {
+                println!("✅ Base fee is {:.3} gwei, submit now", gwei);
+                Ok(())
+            }
+            None => eyre::bail!(
+                "the base fee stayed above {} gwei for {}s",
+                max_gwei,
+                args.within.as_secs()
+            ),
+        };
+    }
+
+    let sample = sample_base_fees(&provider, args.blocks).await?;
+    let recommendation = sample.recommendation();
+    if args.json {
+        let report = serde_json::json!({ "sample": sample, "recommendation": recommendation });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!("⛽ Base fees of the {} blocks up to {}:", sample.blocks, sample.latest_block);
+    println!("   Min: {:.3} gwei", sample.min_gwei);
+    println!("   25th percentile: {:.3} gwei", sample.p25_gwei);
+    println!("   Median: {:.3} gwei", sample.median_gwei);
+    println!("   Max: {:.3} gwei", sample.max_gwei);
+    println!("   Next block: {:.3} gwei", sample.next_gwei);
+    let icon = match recommendation {
+        Recommendation::SubmitNow | Recommendation::Acceptable => "✅",
+        Recommendation::Wait { .. } => "⏳",
+    };
+    println!("{} {}", icon, recommendation);
+    Ok(())
+}
+
+async fn stats(args: StatsArgs) -> Result<()> {
+    let store = JobStore::connect(&args.job_store).await?;
+    let mut stats = store.phase_stats(args.since, args.period.as_secs()).await?;
+    if let Some(system) = args.system {
+        stats.retain(|stats| stats.system == system.to_string());
+    }
+    if stats.is_empty() {
+        println!("No phase timings recorded since {}", args.since);
+        return Ok(());
+    }
+
+    let mut csv = String::from("period,system,phase,count,p50_ms,p95_ms,max_ms\n");
+    println!(
+        "{:<20} {:<8} {:<8} {:>6} {:>10} {:>10} {:>10}",
+        "period", "system", "phase", "jobs", "p50", "p95", "max"
+    );
+    let secs = |ms: u64| format!("{:.1}s", ms as f64 / 1000.0);
+    for stats in &stats {
+        println!(
+            "{:<20} {:<8} {:<8} {:>6} {:>10} {:>10} {:>10}",
+            format_time(stats.period),
+            stats.system,
+            stats.phase,
+            stats.count,
+            secs(stats.p50_ms),
+            secs(stats.p95_ms),
+            secs(stats.max_ms)
+        );
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            stats.period,
+            stats.system,
+            stats.phase,
+            stats.count,
+            stats.p50_ms,
+            stats.p95_ms,
+            stats.max_ms
+        ));
+    }
+    if let Some(path) = &args.csv {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, csv)?;
+        println!("💾 Timings saved to: {}", path.display());
+    }
+    Ok(())
+}
+
+/// Parse a `--since` date: `YYYY-MM-DD` (midnight UTC) or a Unix timestamp.
+fn parse_since(since: &str) -> Result<u64, String> {
+    if let Ok(timestamp) = since.parse() {
+        return Ok(timestamp);
+    }
+    let invalid = || format!("expected YYYY-MM-DD or a Unix timestamp, got `{}`", since);
+    let mut parts = since.splitn(3, '-').map(|part| part.parse::<u32>().map_err(|_| invalid()));
+    let (year, month, day) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(year), Some(month), Some(day)) => (year?, month?, day?),
+        _ => return Err(invalid()),
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=days_in_month).contains(&day) {
+        return Err(invalid());
+    }
+    // Days since the epoch of a proleptic Gregorian date (Howard Hinnant's `days_from_civil`).
+    let (year, month, day) = (u64::from(year), u64::from(month), u64::from(day));
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Ok(days * 86_400)
+}
+
+/// Execute the program for every `n` in the sweep and write the cycles and cost to CSV.
+fn sweep(args: SweepArgs) -> Result<()> {
+    if args.from > args.to {
+        eyre::bail!("--from {} is after --to {}", args.from, args.to);
+    }
+    let prover = FibonacciProver::new();
+    let mut csv = String::from("n,cycles,estimated_cost\n");
+    println!("{:>8} {:>14} {:>14}", "n", "cycles", "est. cost");
+    for n in (args.from..=args.to).step_by(args.step as usize) {
+        let execution = prover.execute(&ProgramInput::new(n))?;
+        let cost = execution.cycles as f64 / 1e6 * args.price_per_mcycle;
+        println!("{:>8} {:>14} {:>14.6}", n, execution.cycles, cost);
+        csv.push_str(&format!("{},{},{:.6}\n", n, execution.cycles, cost));
+    }
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&args.out, csv)?;
+    println!("💾 Sweep saved to: {}", args.out.display());
+    Ok(())
+}
+
+/// Execute a registered program and print its cycles and raw public values.
+fn execute(args: ExecuteArgs) -> Result<()> {
+    let domain = InputDomain::from_env()?;
+    if args.program.name == "fibonacci" {
+        domain.check(&parse_program_input(&args.input)?)?;
+    } else {
+        domain.check_payload(args.input.len())?;
+    }
+    let stdin = (args.program.parse_input)(&args.input)?;
+    let guest_log = GuestLog::default();
+    let client = ProverClient::from_env();
+    let (output, report) = client
+        .execute(args.program.elf, &stdin)
+        .with_hook(GUEST_LOG_FD, guest_log.hook())
+        .run()
+        .map_err(|err| eyre::eyre!("execution of {} failed: {}", args.program, err))?;
+    println!("✅ Executed {}", args.program);
+    println!("   Cycles: {}", report.total_instruction_count());
+    println!("   Public values: 0x{}", hex::encode(output.as_slice()));
+    print_guest_stdout(&guest_log.contents());
+    if args.check_determinism {
+        let (again, again_report) = client
+            .execute(args.program.elf, &stdin)
+            .run()
+            .map_err(|err| eyre::eyre!("execution of {} failed: {}", args.program, err))?;
+        check_same_execution(
+            (output.as_slice(), report.total_instruction_count()),
+            (again.as_slice(), again_report.total_instruction_count()),
+        )?;
+        println!("✅ Executed again with the same public values and cycles");
+    }
+    Ok(())
+}
+
+/// Print what the program logged, set apart from the host's own output.
+fn print_guest_stdout(guest_stdout: &str) {
+    if guest_stdout.is_empty() {
+        return;
+    }
+    println!("📜 Guest output:");
+    for line in guest_stdout.lines() {
+        println!("   | {}", line);
+    }
+}
+
+/// Show the public values and guest output of a proof saved by `network_evm`.
+fn inspect(args: InspectArgs) -> Result<()> {
+    let call_data = CallData::load(&call_data_path(&args.artifacts_dir, args.n))?;
+    let decoded = call_data.decode_public_values()?;
+    println!("📦 Proof bundle for n = {} in {}", args.n, args.artifacts_dir.display());
+    println!("   Public values: {}", decoded);
+    println!("   Proof size: {} bytes", call_data.proof_bytes.len());
+
+    let guest_stdout_path = guest_stdout_path(&args.artifacts_dir, args.n);
+    match read_artifact(&guest_stdout_path) {
+        Ok(guest_stdout) => print_guest_stdout(&String::from_utf8_lossy(&guest_stdout)),
+        Err(_) => println!("   No guest output saved ({})", guest_stdout_path.display()),
+    }
+    if args.provenance {
+        print_provenance(&args.artifacts_dir, args.n, keccak256(&call_data.proof_bytes))?;
+    }
+    Ok(())
+}
+
+/// Print the prover and provenance recorded in the manifest of the proof with `proof_hash`.
+fn print_provenance(dir: &Path, n: u32, proof_hash: B256) -> Result<()> {
+    let path = manifest_path(dir, n);
+    let manifest: Option<Manifest> = if path.exists() { Some(read_json(&path)?) } else { None };
+    let Some(manifest) = manifest.filter(|manifest| manifest.proof_hash == proof_hash) else {
+        println!("   No manifest for this proof ({})", path.display());
+        return Ok(());
+    };
+    if let Some(prover) = &manifest.prover {
+        println!("🖥️  Prover:");
+        println!("   Mode: {}", prover.mode);
+        println!("   Hostname: {}", prover.hostname);
+        println!("   Features: {}", prover.features.join(", "));
+        if let Some(request_id) = prover.request_id {
+            println!("   Network request: {}", request_id);
+        }
+        if let Some(fulfiller) = prover.fulfiller {
+            println!("   Fulfilled by: {}", fulfiller);
+        }
+    }
+    let Some(provenance) = manifest.provenance else {
+        println!("   No provenance recorded (manifest version {})", manifest.version);
+        return Ok(());
+    };
+    println!("🔎 Provenance:");
+    println!("   Commit: {}", provenance.git_commit);
+    println!("   Compiler: {}", provenance.rustc);
+    for (name, version) in &provenance.packages {
+        println!("   {}: {}", name, version);
+    }
+    println!("   Host: {}/{}", provenance.os, provenance.arch);
+    let elf = if provenance.elf_hash == keccak256(FIBONACCI_ELF) {
+        "the ELF embedded in this build"
+    } else {
+        "not the ELF embedded in this build"
+    };
+    println!("   ELF hash: {} ({})", provenance.elf_hash, elf);
+    Ok(())
+}
+
+/// Decode public values with the layout they were encoded with and report what is wrong with them.
+fn decode_pv(args: DecodePvArgs) -> Result<()> {
+    let decoded = decode_any(&args.public_values)?;
+    let age = if decoded.layout == CURRENT_LAYOUT { "current" } else { "older" };
+    println!(
+        "🔎 Public values ({} bytes, layout {}, {})",
+        args.public_values.len(),
+        decoded.layout,
+        age
+    );
+    for (name, value) in decoded.fields() {
+        println!("   {}: {}", name, value);
+    }
+    if decoded.issues.is_empty() {
+        println!("✅ Well-formed: {}", decoded.values);
+        return Ok(());
+    }
+    for issue in &decoded.issues {
+        println!("⚠️  {}", issue);
+    }
+    eyre::bail!("the public values are malformed ({} issues)", decoded.issues.len())
+}
+
+/// Print the ABI-encoded public values the program commits for an input, optionally comparing
+/// them with given ones. Only the hex goes to stdout, so it can be piped.
+fn expected_pv(args: ExpectedPvArgs) -> Result<()> {
+    let input = ProgramInput {
+        n: args.n,
+        range_start: args.range_start,
+        range_hash: args.range_hash,
+        chain_id: args.chain_id,
+        consumer: args.consumer,
+        nonce: args.nonce,
+        valid_until: args.valid_until,
+        recurrence: args.modulus.map(|modulus| Recurrence {
+            coefficients: args.coefficients,
+            initial: args.initial,
+            modulus,
+        }),
+        seeds: args.sequence.input_seeds(),
+    };
+    let values = compute_public_values(&input);
+    let encoded = Bytes::from(values.to_abi_bytes());
+    eprintln!("🧮 Expected public values: {}", values);
+    println!("{}", encoded);
+
+    let Some(against) = args.against else {
+        return Ok(());
+    };
+    if against == encoded {
+        eprintln!("✅ Byte-identical to the given public values");
+        return Ok(());
+    }
+    let expected = DecodedPublicValues { layout: CURRENT_LAYOUT, values, issues: Vec::new() };
+    match decode_any(&against) {
+        Ok(given) => {
+            if given.layout != CURRENT_LAYOUT {
+                eprintln!("≠ layout: {} (expected {})", given.layout, CURRENT_LAYOUT);
+            }
+            for ((name, given), (_, expected)) in given.fields().into_iter().zip(expected.fields())
+            {
+                if given != expected {
+                    eprintln!("≠ {}: {} (expected {})", name, given, expected);
+                }
+            }
+            for issue in &given.issues {
+                eprintln!("⚠️  {}", issue);
+            }
+        }
+        Err(err) => eprintln!("⚠️  The given public values do not decode: {}", err),
+    }
+    eyre::bail!("the given public values differ from the expected ones")
+}
+
+/// Run a `bundle` subcommand.
+fn bundle(args: BundleArgs) -> Result<()> {
+    match args.command {
+        BundleCommand::Export { n, out } => {
+            let out = out.unwrap_or_else(|| bundle_path(&args.artifacts_dir, n));
+            let manifest = export_bundle(&args.artifacts_dir, n, &out)?;
+            println!("📦 Bundled the {} proof for n = {}", manifest.system, manifest.n);
+            println!("   Files: {}", manifest.files.len());
+            println!("💾 Bundle saved to: {}", out.display());
+        }
+        BundleCommand::Import { bundle } => {
+            let imported = import_bundle(&bundle, &args.artifacts_dir)?;
+            let manifest = &imported.manifest;
+            println!("✅ {} matches its manifest", bundle.display());
+            println!("   Proof: {} for n = {}", manifest.system, manifest.n);
+            println!("   Program VKey: {}", manifest.vkey);
+            println!("   Manifest hash: {}", imported.manifest_hash);
+            for path in &imported.files {
+                println!("💾 {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compare two proof bundles, exiting with status 1 if they differ.
+fn diff(args: DiffArgs) -> Result<()> {
+    let fields = diff_bundles(&load_bundle(&args.left)?, &load_bundle(&args.right)?);
+    println!("--- {}", args.left.display());
+    println!("+++ {}", args.right.display());
+    let changed = fields.iter().filter(|field| field.changed()).count();
+    for field in &fields {
+        if field.changed() {
+            println!("≠ {}", field.field);
+            println!("  - {}", field.left);
+            println!("  + {}", field.right);
+        } else if !args.changed_only {
+            println!("  {}: {}", field.field, field.left);
+        }
+    }
+    if changed == 0 {
+        println!("✅ The bundles match ({} fields)", fields.len());
+        return Ok(());
+    }
+    println!("❌ {} of {} fields differ", changed, fields.len());
+    std::process::exit(1);
+}
+
+/// Convert a saved Groth16 proof for snarkjs or gnark tooling.
+fn export_proof(args: ExportArgs) -> Result<()> {
+    let proof = read_artifact(&args.proof)?;
+    let public_values = read_artifact(&args.public_values)?;
+    let vkey: VkHash = match std::fs::read_to_string(&args.vkey) {
+        Ok(contents) => contents.parse(),
+        Err(_) => args.vkey.parse(),
+    }
+    .map_err(|err| eyre::eyre!(err))?;
+    let output_dir = args.output_dir.unwrap_or_else(|| {
+        args.proof.parent().unwrap_or(Path::new(".")).join(args.format.to_string())
+    });
+
+    let exported = export(args.format, &proof, &public_values, &vkey)?;
+    println!("📦 Exported {} for {}", args.proof.display(), args.format);
+    println!("🔑 Program VKey: {}", vkey);
+    for path in exported.save(&output_dir)? {
+        println!("💾 {}", path.display());
+    }
+    Ok(())
+}
+
+/// Upgrade the proof bundles in the artifacts directory to the current manifest format.
+fn migrate(args: MigrateArtifactsArgs) -> Result<()> {
+    let migrations = migrate_artifacts(&args.artifacts_dir, args.dry_run)?;
+    let verb = if args.dry_run { "Would write" } else { "Wrote" };
+    let mut migrated = 0;
+    for migration in &migrations {
+        match migration {
+            Migration::UpToDate { n } => println!("✅ n = {}: up to date", n),
+            Migration::Created { n, manifest_hash } => {
+                println!("📝 n = {}: {} a manifest for the loose files", n, verb);
+                println!("   Manifest hash: {}", manifest_hash);
+                migrated += 1;
+            }
+            Migration::Upgraded { n, from, old_hash, new_hash } => {
+                println!("📝 n = {}: {} the manifest upgraded from version {}", n, verb, from);
+                println!("   Manifest hash: {} -> {}", old_hash, new_hash);
+                migrated += 1;
+            }
+        }
+    }
+    println!(
+        "{} of {} bundles {} to manifest version {}",
+        migrated,
+        migrations.len(),
+        if args.dry_run { "to migrate" } else { "migrated" },
+        MANIFEST_VERSION
+    );
+    Ok(())
+}
+
+/// Build a guest program, pinning the hash of reproducible builds.
+fn init_program(args: InitProgramArgs) -> Result<()> {
+    let names = ProgramNames::new(&args.name)?;
+    let plan = scaffold::plan(&args.root, &names)?;
+    if !args.dry_run {
+        plan.write(&args.root)?;
+    }
+    for (path, created) in plan.files() {
+        println!("   {} {}", if created { "created" } else { "updated" }, path.display());
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+    println!("✅ Scaffolded the {} program", names.name);
+    println!("   Try it: cli execute --program {} --input '{{\"data\": [1, 2, 3]}}'", names.name);
+    Ok(())
+}
+
+fn build(args: BuildArgs) -> Result<()> {
+    let elf = build_elf(args.program, args.reproducible.then_some(args.tag.as_str()))?;
+    if args.reproducible {
+        pin_elf(args.program, &args.tag, &elf_sha256(&elf))?;
+    }
+    Ok(())
+}
+
+/// Build every registered program, record their ELF hashes and vkeys, and report which changed
+/// since the last `build-all`.
+fn build_all(args: BuildAllArgs) -> Result<()> {
+    let tag = args.reproducible.then_some(args.tag.as_str());
+    let builds_path = Path::new(BUILDS_PATH);
+    let previous = read_builds(builds_path)?;
+    let client = ProverClient::builder().cpu().build();
+    let mut builds = previous.clone();
+    let mut changed = Vec::new();
+    for program in PROGRAMS {
+        let elf = build_elf(program, tag)?;
+        let sha256 = elf_sha256(&elf);
+        let (_, vk) = client.setup(&elf);
+        let built = BuiltElf { sha256, vkey: vk_hash(&vk), toolchain: tag.map(str::to_string) };
+        save_vkey(Path::new(VKEYS_PATH), program, &built.vkey)?;
+        if let Some(tag) = tag {
+            pin_elf(program, tag, &built.sha256)?;
+        }
+        match previous.get(program.name) {
+            None => changed.push(format!("{} (new)", program.name)),
+            Some(last) if last.sha256 != built.sha256 => changed.push(format!(
+                "{} (ELF {} -> {}, vkey {} -> {})",
+                program.name, last.sha256, built.sha256, last.vkey, built.vkey
+            )),
+            Some(_) => {}
+        }
+        builds.insert(program.name.to_string(), built);
+    }
+    write_json(builds_path, &builds)?;
+
+    println!("📝 Recorded the builds in {} and their vkeys in {}", BUILDS_PATH, VKEYS_PATH);
+    if changed.is_empty() {
+        println!("✅ No program changed since the last build");
+    } else {
+        println!("🔀 {} changed since the last build:", changed.len());
+        for program in &changed {
+            println!("   {}", program);
+        }
+    }
+    Ok(())
+}
+
+/// Build a program with `cargo prove build`, inside the SP1 Docker image `tag` if given, and
+/// return its ELF.
+fn build_elf(program: &Program, tag: Option<&str>) -> Result<Vec<u8>> {
+    let out_dir = std::env::current_dir()?.join("../target/elf-build");
+    let mut command = std::process::Command::new("cargo");
+    command
+        .args(["prove", "build", "--elf-name", program.package, "--output-directory"])
+        .arg(&out_dir)
+        .current_dir(program.dir);
+    if let Some(tag) = tag {
+        command.args(["--docker", "--tag", tag]);
+    }
+    println!("🔨 Building {}...", program);
+    let status = command.status()?;
+    if !status.success() {
+        eyre::bail!("`cargo prove build` failed with {}", status);
+    }
+
+    let elf_path = out_dir.join(program.package);
+    let elf = std::fs::read(&elf_path)?;
+    println!("✅ Built {} (SHA-256 {})", elf_path.display(), elf_sha256(&elf));
+    Ok(elf)
+}
+
+/// Pin the hash of a reproducibly built ELF of `program` in [`ELF_LOCK_PATH`].
+fn pin_elf(program: &Program, tag: &str, sha256: &str) -> Result<()> {
+    let lock_path = Path::new(ELF_LOCK_PATH);
+    let mut lock = ElfLock::load(lock_path)?.unwrap_or_default();
+    lock.pin(LockedElf {
+        name: program.name.to_string(),
+        toolchain: tag.to_string(),
+        sha256: sha256.to_string(),
+    });
+    lock.save(lock_path)?;
+    println!("📌 Pinned in {}", ELF_LOCK_PATH);
+    if elf_sha256(program.elf) != sha256 {
+        println!(
+            "⚠️  The ELF embedded in the scripts differs; rebuild them with SP1_DOCKER_TAG={} \
+             before proving",
+            tag
+        );
+    }
+    Ok(())
+}
+
+/// Execute a program under SP1's profiler, and save a report of its cycles and functions.
+fn debug_execute(args: DebugExecuteArgs) -> Result<()> {
+    let trace_file = if cfg!(feature = "profiling") {
+        if let Some(parent) = args.trace_out.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Read by the SP1 executor when it is built with profiling.
+        std::env::set_var("TRACE_FILE", &args.trace_out);
+        std::env::set_var("TRACE_SAMPLE_RATE", args.sample_rate.to_string());
+        Some(args.trace_out.display().to_string())
+    } else {
+        println!("⚠️  Built without the `profiling` feature, so no execution profile is written");
+        None
+    };
+
+    let symbols = function_symbols(args.program.elf).map_err(|err| eyre::eyre!(err))?;
+    let functions: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|symbol| match args.pc_range {
+            Some((start, end)) => {
+                symbol.address < end && start < symbol.address.saturating_add(symbol.size)
+            }
+            None => true,
+        })
+        .filter(|symbol| args.symbol.as_ref().map_or(true, |name| symbol.name.contains(name)))
+        .collect();
+
+    let stdin = (args.program.parse_input)(&args.input)?;
+    let guest_log = GuestLog::default();
+    let executed = ProverClient::from_env()
+        .execute(args.program.elf, &stdin)
+        .with_hook(GUEST_LOG_FD, guest_log.hook())
+        .run();
+    let mut report = serde_json::json!({
+        "program": args.program.name,
+        "input": args.input,
+        "trace_file": trace_file,
+        "functions": functions
+            .iter()
+            .map(|symbol| {
+                serde_json::json!({
+                    "name": symbol.name,
+                    "address": format!("{:#010x}", symbol.address),
+                    "size": symbol.size,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "guest_stdout": guest_log.contents(),
+    });
+    print_guest_stdout(&guest_log.contents());
+    match &executed {
+        Ok((_, execution)) => {
+            println!("{}", execution);
+            report["cycles"] = execution.total_instruction_count().into();
+            report["cycle_tracker"] = serde_json::to_value(&execution.cycle_tracker)?;
+        }
+        Err(err) => {
+            println!("❌ Execution failed: {}", err);
+            report["error"] = err.to_string().into();
+        }
+    }
+
+    println!("🔎 Functions ({}):", functions.len());
+    for symbol in &functions {
+        println!("   {:#010x} {:>6} {}", symbol.address, symbol.size, symbol.name);
+    }
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_json(&args.out, &report)?;
+    println!("💾 Report saved to: {}", args.out.display());
+    if let Some(trace_file) = &trace_file {
+        println!("💾 Execution profile saved to: {}", trace_file);
+    }
+    executed.map_err(|err| eyre::eyre!("execution of {} failed: {}", args.program, err))?;
+    Ok(())
+}
+
+/// Execute a program and save where its cycles went.
+fn profile(args: ProfileArgs) -> Result<()> {
+    let stdin = (args.program.parse_input)(&args.input)?;
+    let guest_log = GuestLog::default();
+    let (_, report) = ProverClient::from_env()
+        .execute(args.program.elf, &stdin)
+        .with_hook(GUEST_LOG_FD, guest_log.hook())
+        .run()
+        .map_err(|err| eyre::eyre!("execution of {} failed: {}", args.program, err))?;
+    let profile = ProfileNode::from_regions(
+        args.program.name,
+        report.total_instruction_count(),
+        &report.cycle_tracker,
+    );
+
+    println!("📊 {}: {} cycles", profile.name, profile.value);
+    for region in &profile.children {
+        let share = region.value as f64 * 100.0 / profile.value.max(1) as f64;
+        println!("   {:>12} {:>5.1}%  {}", region.value, share, region.name);
+    }
+    for path in [&args.out, &args.html] {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    write_json(&args.out, &profile)?;
+    std::fs::write(&args.html, profile.to_html())?;
+    println!("💾 Profile saved to: {} and {}", args.out.display(), args.html.display());
+    Ok(())
+}
+
+/// Execute the hashing program with the vanilla and the precompile-patched hash crates on the
+/// same data, and report how many cycles the patches save.
+#[cfg(feature = "hash-bench")]
+fn hash_bench(args: HashBenchArgs) -> Result<()> {
+    use fibonacci_script::programs::{hashing_data, HASHING_ELF, HASHING_PATCHED_ELF};
+    use sp1_sdk::SP1Stdin;
+
+    const REGIONS: [&str; 2] = ["sha256", "keccak256"];
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write_vec(hashing_data(args.bytes));
+    let client = ProverClient::from_env();
+    let run = |variant: &str, elf: &[u8]| -> Result<(Vec<u8>, BTreeMap<&'static str, u64>)> {
+        let (output, report) = client.execute(elf, &stdin).run().map_err(|err| {
+            eyre::eyre!("execution of the {} hashing program failed: {}", variant, err)
+        })?;
+        let mut cycles = BTreeMap::new();
+        cycles.insert("total", report.total_instruction_count());
+        for region in REGIONS {
+            cycles.insert(region, report.cycle_tracker.get(region).copied().unwrap_or_default());
+        }
+        Ok((output.to_vec(), cycles))
+    };
+    let (vanilla_output, vanilla) = run("vanilla", HASHING_ELF)?;
+    let (patched_output, patched) = run("patched", HASHING_PATCHED_ELF)?;
+    if vanilla_output != patched_output {
+        eyre::bail!("the patched hash crates computed different digests");
+    }
+
+    println!("🔬 Hashing {} bytes", args.bytes);
+    println!("   {:<10} {:>12} {:>12} {:>8}", "region", "vanilla", "patched", "saved");
+    let mut comparison = BTreeMap::new();
+    for region in ["total"].into_iter().chain(REGIONS) {
+        let (before, after) = (vanilla[region], patched[region]);
+        let saved = 100.0 * before.saturating_sub(after) as f64 / before.max(1) as f64;
+        println!("   {:<10} {:>12} {:>12} {:>7.1}%", region, before, after, saved);
+        comparison.insert(
+            region,
+            serde_json::json!({ "vanilla": before, "patched": after, "saved_percent": saved }),
+        );
+    }
+
+    if let Some(parent) = args.out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let report = serde_json::json!({ "bytes": args.bytes, "cycles": comparison });
+    write_json(&args.out, &report)?;
+    println!("💾 Comparison saved to: {}", args.out.display());
+    Ok(())
+}
+
+/// Parse a `<start>..<end>` range of program counters, in hex (`0x...`) or decimal.
+fn parse_pc_range(range: &str) -> Result<(u32, u32), String> {
+    let parse = |pc: &str| match pc.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => pc.parse(),
+    };
+    let (start, end) = range
+        .split_once("..")
+        .ok_or_else(|| format!("expected <start>..<end>, got `{}`", range))?;
+    match (parse(start), parse(end)) {
+        (Ok(start), Ok(end)) if start < end => Ok((start, end)),
+        _ => Err(format!("invalid program counter range `{}`", range)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_since_accepts_dates_and_timestamps() {
+        assert_eq!(parse_since("1970-01-01"), Ok(0));
+        assert_eq!(parse_since("2024-02-29"), Ok(1_709_164_800));
+        assert_eq!(parse_since("2000-02-29"), Ok(951_782_400));
+        assert_eq!(parse_since("2024-12-31"), Ok(1_735_603_200));
+        assert_eq!(parse_since("1709164800"), Ok(1_709_164_800));
+    }
+
+    #[test]
+    fn parse_since_rejects_impossible_dates() {
+        for since in [
+            "2024-02-30",
+            "2024-02-31",
+            "2023-02-29",
+            "2100-02-29",
+            "2024-04-31",
+            "2024-11-31",
+            "2024-00-10",
+            "2024-13-01",
+            "2024-01-00",
+            "1969-12-31",
+            "2024-01",
+            "2024-01-01-01",
+            "yesterday",
+        ] {
+            assert!(parse_since(since).is_err(), "{}", since);
+        }
+    }
+}
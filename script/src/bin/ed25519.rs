@@ -0,0 +1,119 @@
+//! Prove that a message was signed by an Ed25519 key with the Ed25519 program, which commits the
+//! public key and the hash of the message but not the signature.
+//!
+//! Each of the key, signature and message is given as `0x`-prefixed hex or as the path of a file
+//! holding the raw bytes. You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin ed25519 -- \
+//!     --public-key key.bin --signature sig.bin --message message.txt
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use alloy_primitives::Bytes;
+use clap::Parser;
+use fibonacci_lib::ed25519::{Ed25519Input, Ed25519PublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The 32-byte public key, as hex or a file
+    #[arg(long, value_parser = hex_or_file)]
+    public_key: Bytes,
+
+    /// The 64-byte signature, as hex or a file
+    #[arg(long, value_parser = hex_or_file)]
+    signature: Bytes,
+
+    /// The signed message, as hex or a file
+    #[arg(long, value_parser = hex_or_file)]
+    message: Bytes,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let public_key: [u8; 32] = args
+        .public_key
+        .as_ref()
+        .try_into()
+        .map_err(|_| eyre::eyre!("a public key is 32 bytes, got {}", args.public_key.len()))?;
+    let input = Ed25519Input {
+        public_key,
+        signature: args.signature.to_vec(),
+        message: args.message.to_vec(),
+    };
+    // The program panics on an invalid signature, so fail before running it.
+    let expected = input.verify().map_err(|err| eyre::eyre!(err))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("ed25519").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Signature verified in the program");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "ed25519".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "publicKey": expected.publicKey,
+            "messageHash": expected.messageHash,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &Ed25519PublicValuesStruct) {
+    println!("   Public key: {}", values.publicKey);
+    println!("   Message hash: {}", values.messageHash);
+}
+
+/// Parse `0x`-prefixed hex, or read the raw bytes of a file.
+fn hex_or_file(value: &str) -> Result<Bytes, String> {
+    if value.starts_with("0x") {
+        return value.parse().map_err(|err| format!("invalid hex `{}`: {}", value, err));
+    }
+    std::fs::read(value)
+        .map(Bytes::from)
+        .map_err(|err| format!("failed to read {}: {}", value, err))
+}
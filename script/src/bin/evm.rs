@@ -10,17 +10,12 @@
 //! RUST_LOG=info cargo run --release --bin evm -- --system plonk
 //! ```
 
-use alloy_sol_types::SolType;
-use clap::{Parser, ValueEnum};
-use fibonacci_lib::PublicValuesStruct;
-use serde::{Deserialize, Serialize};
-use sp1_sdk::{
-    include_elf, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
-};
-use std::path::PathBuf;
-
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+use alloy_primitives::Address;
+use clap::Parser;
+use fibonacci_lib::{ProgramInput, Status, VkHash};
+use fibonacci_script::artifacts::SP1FibonacciProofFixture;
+use fibonacci_script::prover::{decode_public_values, FibonacciProver, ProofSystem};
+use sp1_sdk::SP1ProofWithPublicValues;
 
 /// The arguments for the EVM command.
 #[derive(Parser, Debug)]
@@ -28,29 +23,22 @@ pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
 struct EVMArgs {
     #[arg(long, default_value = "20")]
     n: u32,
+    #[arg(long)]
+    range_start: Option<u32>,
+    #[arg(long, requires = "range_start")]
+    range_hash: bool,
+    #[arg(long)]
+    chain_id: Option<u64>,
+    #[arg(long)]
+    consumer: Option<Address>,
+    #[arg(long)]
+    nonce: Option<u64>,
+    #[arg(long)]
+    valid_until: Option<u64>,
     #[arg(long, value_enum, default_value = "groth16")]
     system: ProofSystem,
 }
 
-/// Enum representing the available proof systems
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
-enum ProofSystem {
-    Plonk,
-    Groth16,
-}
-
-/// A fixture that can be used to test the verification of SP1 zkVM proofs inside Solidity.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct SP1FibonacciProofFixture {
-    a: u32,
-    b: u32,
-    n: u32,
-    vkey: String,
-    public_values: String,
-    proof: String,
-}
-
 fn main() {
     // Setup the logger.
     sp1_sdk::utils::setup_logger();
@@ -59,47 +47,38 @@ fn main() {
     let args = EVMArgs::parse();
 
     // Setup the prover client.
-    let client = ProverClient::from_env();
-
-    // Setup the program.
-    let (pk, vk) = client.setup(FIBONACCI_ELF);
+    let prover = FibonacciProver::new();
 
     // Setup the inputs.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&args.n);
+    let input = ProgramInput {
+        n: args.n,
+        range_start: args.range_start,
+        range_hash: args.range_hash,
+        chain_id: args.chain_id,
+        consumer: args.consumer,
+        nonce: args.nonce,
+        valid_until: args.valid_until,
+        recurrence: None,
+        seeds: None,
+    };
 
     println!("n: {}", args.n);
     println!("Proof System: {:?}", args.system);
 
     // Generate the proof based on the selected proof system.
-    let proof = match args.system {
-        ProofSystem::Plonk => client.prove(&pk, &stdin).plonk().run(),
-        ProofSystem::Groth16 => client.prove(&pk, &stdin).groth16().run(),
-    }
-    .expect("failed to generate proof");
+    let proof = prover.prove(&input, args.system).expect("failed to generate proof");
 
-    create_proof_fixture(&proof, &vk, args.system);
+    create_proof_fixture(&proof, &prover.vkey_hash(), args.system);
 }
 
 /// Create a fixture for the given proof.
-fn create_proof_fixture(
-    proof: &SP1ProofWithPublicValues,
-    vk: &SP1VerifyingKey,
-    system: ProofSystem,
-) {
+fn create_proof_fixture(proof: &SP1ProofWithPublicValues, vkey_hash: &VkHash, system: ProofSystem) {
     // Deserialize the public values.
-    let bytes = proof.public_values.as_slice();
-    let PublicValuesStruct { n, a, b } = PublicValuesStruct::abi_decode(bytes, true).unwrap();
+    let public_values = decode_public_values(proof).unwrap();
+    let n = public_values.n;
 
     // Create the testing fixture so we can test things end-to-end.
-    let fixture = SP1FibonacciProofFixture {
-        a,
-        b,
-        n,
-        vkey: vk.bytes32().to_string(),
-        public_values: format!("0x{}", hex::encode(bytes)),
-        proof: format!("0x{}", hex::encode(proof.bytes())),
-    };
+    let fixture = SP1FibonacciProofFixture::new(proof, vkey_hash).unwrap();
 
     // The verification key is used to verify that the proof corresponds to the execution of the
     // program on the given input.
@@ -112,17 +91,34 @@ fn create_proof_fixture(
     // If you need to expose the inputs or outputs of your program, you should commit them in
     // the public values.
     println!("Public Values: {}", fixture.public_values);
+    if public_values.chainId != 0 || !public_values.consumer.is_zero() {
+        println!(
+            "Bound To: chain {} / consumer {}",
+            public_values.chainId, public_values.consumer
+        );
+    }
+    if public_values.nonce != 0 {
+        println!("Nonce: {}", public_values.nonce);
+    }
+    if public_values.validUntil != 0 {
+        println!("Valid Until: {}", public_values.validUntil);
+    }
+    if public_values.status != Status::Ok as u8 {
+        println!("Status: {}", public_values.status_name());
+    }
+    if let Some(start) = public_values.range_start() {
+        println!("Committed Range: Fibonacci({}..={}) = {:?}", start, n, public_values.fibValues);
+    }
+    if public_values.has_range_hash() {
+        println!("Committed Range Hash: {}", public_values.rangeHash);
+    }
 
     // The proof proves to the verifier that the program was executed with some inputs that led to
     // the give public values.
     println!("Proof Bytes: {}", fixture.proof);
 
     // Save the fixture to a file.
-    let fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../contracts/src/fixtures");
-    std::fs::create_dir_all(&fixture_path).expect("failed to create fixture path");
-    std::fs::write(
-        fixture_path.join(format!("{:?}-fixture.json", system).to_lowercase()),
-        serde_json::to_string_pretty(&fixture).unwrap(),
-    )
-    .expect("failed to write fixture");
+    fixture
+        .save(&SP1FibonacciProofFixture::default_dir(), system)
+        .expect("failed to write fixture");
 }
@@ -0,0 +1,348 @@
+//! A minimal proving oracle: watches a `FibonacciOracle` contract for `ProofRequested` events,
+//! generates the requested proofs and settles them on-chain with `fulfillProof`.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! SP1_PROVER=network RUST_LOG=info cargo run --release --bin fulfill -- --contract 0x...
+//! ```
+//! Confirmed settlements, and requests given up on, are notified to the channels of
+//! `notifications.toml`, if configured; see [`fibonacci_script::notify`].
+
+use alloy_network::EthereumWallet;
+use alloy_provider::{Provider, ProviderBuilder};
+use clap::Parser;
+use eyre::Result;
+use fibonacci_lib::ProgramInput;
+use fibonacci_script::addresses::ContractTarget;
+use fibonacci_script::artifacts::{
+    input_hash, read_json, write_json, CallData, DEFAULT_ARTIFACTS_DIR,
+};
+use fibonacci_script::audit::AuditLog;
+use fibonacci_script::chain::{FibonacciOracle, SettlementReceipt};
+use fibonacci_script::config;
+use fibonacci_script::jobs::{JobStore, DEFAULT_JOB_STORE_URL};
+use fibonacci_script::notify::{Event, EventKind, Notifications};
+use fibonacci_script::prover::{FibonacciProver, ProofSystem};
+use fibonacci_script::shutdown::Shutdown;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sp1_sdk::SP1ProofWithPublicValues;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The FibonacciOracle contract to serve: an address, an ENS name, or an address book alias
+    #[arg(long, env = "FIBONACCI_ORACLE_ADDRESS")]
+    contract: ContractTarget,
+
+    /// The block to start watching from (defaults to the saved state, or the latest block)
+    #[arg(long)]
+    from_block: Option<u64>,
+
+    /// Seconds between polls for new requests
+    #[arg(long, default_value = "12")]
+    poll_interval: u64,
+
+    /// How many times to attempt a request before giving up on it
+    #[arg(long, default_value = "3")]
+    max_attempts: u32,
+
+    /// The proof system to use
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Where the fulfiller persists its progress
+    #[arg(long, default_value = "artifacts/fulfill_state.json")]
+    state_file: String,
+
+    /// The job store settlements are recorded in
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+}
+
+/// Progress persisted between runs, so that restarts neither miss nor repeat requests.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FulfillState {
+    /// The next block to scan for requests.
+    next_block: u64,
+    /// Requests that still need to be settled.
+    pending: BTreeMap<u64, PendingRequest>,
+    /// Requests that have been settled.
+    fulfilled: BTreeSet<u64>,
+    /// Requests given up on after `max_attempts` failures.
+    failed: BTreeSet<u64>,
+    /// The request being worked on when the fulfiller was last stopped, if it did not finish.
+    #[serde(default)]
+    interrupted: Option<u64>,
+}
+
+/// A request that has been seen on-chain but not settled yet.
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingRequest {
+    n: u32,
+    attempts: u32,
+}
+
+impl FulfillState {
+    /// Load the saved state, if any.
+    fn load(path: &str) -> Result<Option<Self>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_json(Path::new(path))?))
+    }
+
+    /// Persist the state.
+    fn save(&self, path: &str) -> Result<()> {
+        Ok(write_json(Path::new(path), self)?)
+    }
+
+    /// Where the proof for a request is checkpointed until it has been settled, next to the
+    /// state file.
+    fn checkpoint_path(state_file: &str, request_id: u64) -> PathBuf {
+        Path::new(state_file).with_file_name(format!("fulfill_request_{}.json", request_id))
+    }
+
+    /// Whether the request has already been seen.
+    fn knows(&self, request_id: u64) -> bool {
+        self.pending.contains_key(&request_id)
+            || self.fulfilled.contains(&request_id)
+            || self.failed.contains(&request_id)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+
+    // Parse the command line arguments.
+    let args = Args::parse();
+    let notifications = Notifications::from_env()?;
+
+    // Setup the provider used to watch and settle requests.
+    let rpc_url = config::rpc_url();
+    let signer = config::signer()?;
+    let signer_address = signer.address();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(rpc_url.parse()?);
+    let chain_id = provider.get_chain_id().await?;
+    let contract = args.contract.resolve(&rpc_url).await?;
+    let oracle = FibonacciOracle::new(contract, &provider);
+
+    let mut state = match FulfillState::load(&args.state_file)? {
+        Some(state) => state,
+        None => {
+            FulfillState { next_block: provider.get_block_number().await?, ..Default::default() }
+        }
+    };
+    if let Some(from_block) = args.from_block {
+        state.next_block = from_block;
+    }
+
+    // Setup the program once up front; every request proves the same program.
+    let prover = FibonacciProver::new();
+    prover.vk();
+    let audit = AuditLog::from_env();
+    let store = JobStore::connect(&args.job_store).await?;
+
+    println!("🔮 SP1 Fibonacci Proving Oracle");
+    println!("===============================");
+    println!("📝 Oracle: {} (chain {})", contract, chain_id);
+    println!("🔧 System: {:?}", args.system);
+    println!("👀 Watching from block {}", state.next_block);
+    if let Some(request_id) = state.interrupted {
+        println!("↩️  Resuming request {}, which was interrupted", request_id);
+    }
+
+    let shutdown = Shutdown::listen();
+    while !shutdown.requested() {
+        // Pick up new requests.
+        let scanned: Result<()> = async {
+            let latest = provider.get_block_number().await?;
+            if latest < state.next_block {
+                return Ok(());
+            }
+            let events = oracle
+                .ProofRequested_filter()
+                .from_block(state.next_block)
+                .to_block(latest)
+                .query()
+                .await?;
+            for (event, _) in events {
+                if state.knows(event.requestId) {
+                    continue;
+                }
+                println!("📥 Request {}: n = {}", event.requestId, event.n);
+                state.pending.insert(event.requestId, PendingRequest { n: event.n, attempts: 0 });
+            }
+            state.next_block = latest + 1;
+            state.save(&args.state_file)
+        }
+        .await;
+        if let Err(err) = scanned {
+            println!("⚠️  Failed to scan for requests: {}", err);
+        }
+
+        // Work through pending requests; failed ones are retried on the next poll.
+        let request_ids: Vec<u64> = state.pending.keys().copied().collect();
+        for request_id in request_ids {
+            if shutdown.requested() {
+                break;
+            }
+            let n = state.pending[&request_id].n;
+
+            // Mark the request, so that a forced exit is reported as an interruption on resume.
+            state.interrupted = Some(request_id);
+            state.save(&args.state_file)?;
+
+            let checkpoint = FulfillState::checkpoint_path(&args.state_file, request_id);
+            let fulfilled: Result<()> = async {
+                // Someone else may have settled the request in the meantime.
+                if oracle.requests(request_id).call().await?.fulfilled {
+                    println!("⏭️  Request {} was already fulfilled", request_id);
+                    return Ok(());
+                }
+
+                // Reuse a proof generated before an interruption, or checkpoint a new one before
+                // settling it.
+                let call_data = if checkpoint.exists() {
+                    println!("♻️  Reusing the checkpointed proof for request {}", request_id);
+                    CallData::load(&checkpoint)?
+                } else {
+                    println!("⚙️  Proving request {} (n = {})...", request_id, n);
+                    let input = ProgramInput {
+                        n,
+                        chain_id: Some(chain_id),
+                        consumer: Some(contract),
+                        nonce: Some(request_id),
+                        ..Default::default()
+                    };
+                    let proof = prove(&prover, &input, args.system);
+                    let details = json!({
+                        "request_id": request_id,
+                        "input": input,
+                        "input_hash": input_hash(&input, args.system),
+                        "system": args.system.to_string(),
+                        "vkey": prover.vkey_hash(),
+                    });
+                    audit.record("prove", details, &proof)?;
+                    let proof = proof?;
+                    let call_data = CallData::from_proof(&proof);
+                    write_json(&checkpoint, &call_data.to_json()?)?;
+                    call_data
+                };
+
+                println!("📤 Settling request {}...", request_id);
+                let mut details = json!({
+                    "request_id": request_id,
+                    "chain_id": chain_id,
+                    "contract": contract.to_string(),
+                    "signer": signer_address.to_string(),
+                    "vkey": prover.vkey_hash(),
+                });
+                let submitted: Result<_> = async {
+                    Ok(oracle
+                        .fulfillProof(
+                            request_id,
+                            call_data.public_values.clone(),
+                            call_data.proof_bytes.clone(),
+                        )
+                        .send()
+                        .await?
+                        .get_receipt()
+                        .await?)
+                }
+                .await;
+                let outcome = match &submitted {
+                    Ok(receipt) => {
+                        details["tx_hash"] = json!(receipt.transaction_hash.to_string());
+                        if receipt.status() { "ok" } else { "reverted" }.to_string()
+                    }
+                    Err(err) => format!("failed: {}", err),
+                };
+                audit.append("submit", details.clone(), outcome)?;
+                let receipt = submitted?;
+                let settlement =
+                    SettlementReceipt::new(&receipt, chain_id, contract, &call_data, None)?;
+                let settlement_path = settlement.save(Path::new(DEFAULT_ARTIFACTS_DIR))?;
+                store.record_settlement(&settlement, &settlement_path.display().to_string()).await?;
+                if !receipt.status() {
+                    eyre::bail!("transaction {} reverted", receipt.transaction_hash);
+                }
+                println!("✅ Request {} fulfilled in {}", request_id, receipt.transaction_hash);
+                let message = format!(
+                    "Request {} (n = {}) settled in {}",
+                    request_id, n, receipt.transaction_hash
+                );
+                details["block_number"] = json!(receipt.block_number);
+                notifications.notify(&Event::new(EventKind::SettlementConfirmed, message, details));
+                Ok(())
+            }
+            .await;
+
+            state.interrupted = None;
+            match fulfilled {
+                Ok(()) => {
+                    state.pending.remove(&request_id);
+                    state.fulfilled.insert(request_id);
+                    let _ = std::fs::remove_file(&checkpoint);
+                }
+                Err(err) => {
+                    let pending = state.pending.get_mut(&request_id).expect("request is pending");
+                    pending.attempts += 1;
+                    println!(
+                        "❌ Request {} failed (attempt {}/{}): {}",
+                        request_id, pending.attempts, args.max_attempts, err
+                    );
+                    if pending.attempts >= args.max_attempts {
+                        let message = format!(
+                            "Request {} (n = {}) failed after {} attempts: {}",
+                            request_id, n, pending.attempts, err
+                        );
+                        let details = json!({
+                            "request_id": request_id,
+                            "n": n,
+                            "chain_id": chain_id,
+                            "contract": contract.to_string(),
+                            "attempts": pending.attempts,
+                            "error": err.to_string(),
+                        });
+                        notifications.notify(&Event::new(EventKind::ProofFailed, message, details));
+                        state.pending.remove(&request_id);
+                        state.failed.insert(request_id);
+                        let _ = std::fs::remove_file(&checkpoint);
+                    }
+                }
+            }
+            state.save(&args.state_file)?;
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(args.poll_interval)) => {}
+            _ = shutdown.wait() => {}
+        }
+    }
+
+    state.save(&args.state_file)?;
+    println!("👋 Stopped; progress saved to {}", args.state_file);
+    Ok(())
+}
+
+/// Generate an EVM-compatible proof for the given input.
+fn prove(
+    prover: &FibonacciProver,
+    input: &ProgramInput,
+    system: ProofSystem,
+) -> Result<SP1ProofWithPublicValues> {
+    // Proving blocks (and may drive its own runtime for network requests), so keep it off the
+    // async worker threads.
+    Ok(tokio::task::block_in_place(|| prover.prove(input, system))?)
+}
@@ -0,0 +1,113 @@
+//! Prove that a range of Ethereum blocks form a chain with the header chain program, which checks
+//! that each header is the parent of the next and commits the first and last hashes.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin header_chain -- --from 100 --to 131
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use clap::Parser;
+use fibonacci_lib::headers::{HeaderChainInput, HeaderChainPublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::config::DEFAULT_RPC_URL;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use fibonacci_script::state::fetch_raw_headers;
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The first block of the chain
+    #[arg(long)]
+    from: u64,
+
+    /// The last block of the chain
+    #[arg(long)]
+    to: u64,
+
+    /// The RPC endpoint to fetch the headers from
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    if args.to < args.from {
+        eyre::bail!("--to {} is before --from {}", args.to, args.from);
+    }
+
+    println!("📦 Fetching the headers of blocks {} to {}...", args.from, args.to);
+    let headers = tokio::runtime::Runtime::new()?.block_on(fetch_raw_headers(
+        &args.rpc_url,
+        args.from,
+        args.to,
+    ))?;
+    let input = HeaderChainInput { headers };
+
+    // The program panics on headers that do not form a chain, so fail before running it.
+    let expected = input.verify().map_err(|err| eyre::eyre!(err))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("header-chain").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Header chain verified in the program");
+    println!("   Cycles: {} ({} per header)", cycles, cycles / input.headers.len() as u64);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "header-chain".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "firstHash": expected.firstHash,
+            "lastHash": expected.lastHash,
+            "firstNumber": expected.firstNumber,
+            "lastNumber": expected.lastNumber,
+            "totalDifficulty": expected.totalDifficulty,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &HeaderChainPublicValuesStruct) {
+    println!("   First block: {} ({})", values.firstNumber, values.firstHash);
+    println!("   Last block: {} ({})", values.lastNumber, values.lastHash);
+    println!("   Total difficulty: {}", values.totalDifficulty);
+}
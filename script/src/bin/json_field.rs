@@ -0,0 +1,111 @@
+//! Prove the value of a field of a private JSON document, e.g. a saved API response, with the JSON
+//! program. Only the hash of the document, the path of the field and the hash of its value are
+//! public.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin json_field -- --file response.json --path /data/price
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use clap::Parser;
+use fibonacci_lib::json::{JsonInput, JsonPublicValuesStruct, JsonStatus};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use serde_json::{json, Value};
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The JSON document; its bytes are hashed as they are in the file
+    #[arg(long)]
+    file: PathBuf,
+
+    /// The JSON Pointer of the field, e.g. `/data/0/price`
+    #[arg(long)]
+    path: String,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let document = std::fs::read(&args.file)
+        .map_err(|err| eyre::eyre!("failed to read {}: {}", args.file.display(), err))?;
+    let parsed: Value = serde_json::from_slice(&document)
+        .map_err(|err| eyre::eyre!("{} is not valid JSON: {}", args.file.display(), err))?;
+    match parsed.pointer(&args.path) {
+        Some(value) => println!("🔎 {} = {}", args.path, value),
+        None => println!(
+            "⚠️  {} has no field at {}; proving its absence",
+            args.file.display(),
+            args.path
+        ),
+    }
+
+    let input = JsonInput { document, path: args.path.clone() };
+    let expected = input.public_values();
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("json").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Program executed successfully");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "json".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "documentHash": expected.documentHash,
+            "path": expected.path,
+            "status": expected.status,
+            "valueHash": expected.valueHash,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &JsonPublicValuesStruct) {
+    let status = values.status().map_or("UNKNOWN", JsonStatus::name);
+    println!("   Document hash: {}", values.documentHash);
+    println!("   Path: {}", values.path);
+    println!("   Status: {} ({})", values.status, status);
+    println!("   Value hash: {}", values.valueHash);
+}
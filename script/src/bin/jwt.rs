@@ -0,0 +1,153 @@
+//! Prove that a JWT signed by a known key was valid at a given time, and what some of its claims
+//! are, with the JWT program. The token itself stays private.
+//!
+//! The token is read from `--token-file`, or else from `--token` or the `JWT` environment variable.
+//! You can run this script using the following command:
+//! ```shell
+//! JWT=eyJ... RUST_LOG=info cargo run --release --bin jwt -- --public-key issuer.pem --claims sub
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::Parser;
+use fibonacci_lib::jwt::{JwtAlgorithm, JwtInput, JwtPublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The token
+    #[arg(long, env = "JWT", hide_env_values = true)]
+    token: Option<String>,
+
+    /// A file holding the token, which takes precedence over `--token`
+    #[arg(long)]
+    token_file: Option<PathBuf>,
+
+    /// The issuer's public key, as a PEM or DER `SubjectPublicKeyInfo`
+    #[arg(long)]
+    public_key: PathBuf,
+
+    /// The claims to commit the hash of, comma-separated
+    #[arg(long, value_delimiter = ',')]
+    claims: Vec<String>,
+
+    /// The Unix timestamp to check the token's expiry against (now by default)
+    #[arg(long)]
+    timestamp: Option<u64>,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let token = match (&args.token_file, &args.token) {
+        (Some(path), _) => std::fs::read_to_string(path)
+            .map_err(|err| eyre::eyre!("failed to read {}: {}", path.display(), err))?,
+        (None, Some(token)) => token.clone(),
+        (None, None) => eyre::bail!("pass the token with --token-file, --token or JWT"),
+    };
+    let timestamp = args.timestamp.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+    });
+    let input = JwtInput {
+        token: token.trim().to_string(),
+        public_key: read_public_key(&args.public_key)?,
+        timestamp,
+        claims: args.claims.clone(),
+    };
+
+    // The program panics on an invalid token, so fail before running it.
+    let expected = input.verify().map_err(|err| eyre::eyre!(err))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("jwt").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Token verified in the program");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "jwt".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "publicKeyHash": expected.publicKeyHash,
+            "algorithm": expected.algorithm,
+            "timestamp": expected.timestamp,
+            "expiresAt": expected.expiresAt,
+            "claimNames": expected.claimNames,
+            "claimHashes": expected.claimHashes,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &JwtPublicValuesStruct) {
+    let algorithm = JwtAlgorithm::from_code(values.algorithm).map_or("UNKNOWN", JwtAlgorithm::name);
+    println!("   Public key hash: {}", values.publicKeyHash);
+    println!("   Algorithm: {}", algorithm);
+    println!("   Valid at {} (expires at {})", values.timestamp, values.expiresAt);
+    for (name, hash) in values.claimNames.iter().zip(&values.claimHashes) {
+        println!("   Claim `{}`: {}", name, hash);
+    }
+}
+
+/// Read a DER `SubjectPublicKeyInfo`, PEM-encoded (`-----BEGIN PUBLIC KEY-----`) or not.
+fn read_public_key(path: &Path) -> eyre::Result<Vec<u8>> {
+    let contents = std::fs::read(path)
+        .map_err(|err| eyre::eyre!("failed to read {}: {}", path.display(), err))?;
+    let Ok(pem) = std::str::from_utf8(&contents) else {
+        return Ok(contents);
+    };
+    if pem.contains("-----BEGIN RSA PUBLIC KEY-----") {
+        eyre::bail!("{} is a PKCS#1 key; convert it to a SubjectPublicKeyInfo", path.display());
+    }
+    if !pem.contains("-----BEGIN PUBLIC KEY-----") {
+        return Ok(contents);
+    }
+    let body: String =
+        pem.lines().filter(|line| !line.starts_with("-----")).map(str::trim).collect();
+    STANDARD
+        .decode(body)
+        .map_err(|err| eyre::eyre!("invalid PEM in {}: {}", path.display(), err))
+}
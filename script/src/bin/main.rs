@@ -5,18 +5,20 @@
 //! ```shell
 //! RUST_LOG=info cargo run --release -- --execute
 //! ```
+//! or, to also check that the program is deterministic,
+//! ```shell
+//! RUST_LOG=info cargo run --release -- --execute --check-determinism
+//! ```
 //! or
 //! ```shell
 //! RUST_LOG=info cargo run --release -- --prove
 //! ```
 
-use alloy_sol_types::SolType;
+use alloy_primitives::Address;
 use clap::Parser;
-use fibonacci_lib::PublicValuesStruct;
-use sp1_sdk::{include_elf, ProverClient, SP1Stdin};
-
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+use fibonacci_lib::recurrence::Recurrence;
+use fibonacci_lib::{ProgramInput, Sequence};
+use fibonacci_script::prover::{FibonacciProver, ProverError};
 
 /// The arguments for the command.
 #[derive(Parser, Debug)]
@@ -28,8 +30,54 @@ struct Args {
     #[arg(long)]
     prove: bool,
 
+    /// Execute the program twice and check that both executions commit the same public values
+    /// in the same number of cycles.
+    #[arg(long, requires = "execute")]
+    check_determinism: bool,
+
     #[arg(long, default_value = "20")]
     n: u32,
+
+    /// The sequence to compute: `fibonacci`, `lucas`, or any two seeds such as `3,7`.
+    #[arg(long, default_value = "fibonacci")]
+    sequence: Sequence,
+
+    /// Also commit every Fibonacci number from this index up to `n`.
+    #[arg(long)]
+    range_start: Option<u32>,
+
+    /// Commit the hash of the range instead of its values.
+    #[arg(long, requires = "range_start")]
+    range_hash: bool,
+
+    /// Evaluate the recurrence `x_k = c1*x_{k-1} + ... + cm*x_{k-m} (mod p)` with these
+    /// coefficients `c1,...,cm` instead of the Fibonacci sequence.
+    #[arg(long, value_delimiter = ',', requires = "modulus")]
+    coefficients: Vec<u64>,
+
+    /// The initial values `x_0,...,x_{m-1}` of the recurrence.
+    #[arg(long, value_delimiter = ',', requires = "modulus")]
+    initial: Vec<u64>,
+
+    /// The modulus `p` of the recurrence.
+    #[arg(long, requires_all = ["coefficients", "initial"])]
+    modulus: Option<u64>,
+
+    /// Bind the proof to the chain with this id.
+    #[arg(long)]
+    chain_id: Option<u64>,
+
+    /// Bind the proof to the consumer contract at this address.
+    #[arg(long)]
+    consumer: Option<Address>,
+
+    /// Echo this request id in the public values to prevent double settlement.
+    #[arg(long)]
+    nonce: Option<u64>,
+
+    /// Unix timestamp after which the proof must no longer be settled.
+    #[arg(long)]
+    valid_until: Option<u64>,
 }
 
 fn main() {
@@ -46,47 +94,103 @@ fn main() {
     }
 
     // Setup the prover client.
-    let client = ProverClient::from_env();
+    let prover = FibonacciProver::new();
 
     // Setup the inputs.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&args.n);
+    let input = ProgramInput {
+        n: args.n,
+        range_start: args.range_start,
+        range_hash: args.range_hash,
+        chain_id: args.chain_id,
+        consumer: args.consumer,
+        nonce: args.nonce,
+        valid_until: args.valid_until,
+        recurrence: args.modulus.map(|modulus| Recurrence {
+            coefficients: args.coefficients,
+            initial: args.initial,
+            modulus,
+        }),
+        seeds: args.sequence.input_seeds(),
+    };
 
     println!("n: {}", args.n);
+    if args.sequence != Sequence::Fibonacci {
+        println!("sequence: {} (seeds {:?})", args.sequence, args.sequence.seeds());
+    }
 
     if args.execute {
         // Execute the program
-        let (output, report) = client.execute(FIBONACCI_ELF, &stdin).run().unwrap();
+        let executed = if args.check_determinism {
+            prover.check_determinism(&input)
+        } else {
+            prover.execute(&input)
+        };
+        let execution = match executed {
+            Ok(execution) => execution,
+            Err(ProverError::GuestPanic(guest_panic)) => {
+                eprintln!("Error: {}", guest_panic);
+                eprintln!("{}", serde_json::json!({ "guest_panic": guest_panic }));
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                std::process::exit(1);
+            }
+        };
         println!("Program executed successfully.");
 
         // Read the output.
-        let decoded = PublicValuesStruct::abi_decode(output.as_slice(), true).unwrap();
-        let PublicValuesStruct { n, a, b } = decoded;
-        println!("n: {}", n);
-        println!("a: {}", a);
-        println!("b: {}", b);
-
-        let (expected_a, expected_b) = fibonacci_lib::fibonacci(n);
-        assert_eq!(a, expected_a);
-        assert_eq!(b, expected_b);
+        let decoded = execution.public_values;
+        println!("n: {}", decoded.n);
+        println!("a: {}", decoded.a);
+        println!("b: {}", decoded.b);
+        println!("chain_id: {}", decoded.chainId);
+        println!("consumer: {}", decoded.consumer);
+        println!("nonce: {}", decoded.nonce);
+        println!("valid_until: {}", decoded.validUntil);
+        println!("status: {}", decoded.status_name());
+        if decoded.is_recurrence() {
+            println!("coefficients: {:?}", decoded.coefficients);
+            println!("initial_values: {:?}", decoded.initialValues);
+            println!("modulus: {}", decoded.modulus);
+            println!("result: {}", decoded.result);
+        }
+        if let Some(start) = decoded.range_start() {
+            println!("fib_values:");
+            for (k, value) in (start..).zip(&decoded.fibValues) {
+                println!("  Fibonacci({}) = {}", k, value);
+            }
+        }
+        if decoded.has_range_hash() {
+            println!("range_hash: {}", decoded.rangeHash);
+        }
+
+        let expected = fibonacci_lib::compute_public_values(&input);
+        assert_eq!(decoded.a, expected.a);
+        assert_eq!(decoded.b, expected.b);
+        assert_eq!(decoded.fibValues, expected.fibValues);
+        assert_eq!(decoded.chainId, expected.chainId);
+        assert_eq!(decoded.consumer, expected.consumer);
+        assert_eq!(decoded.nonce, expected.nonce);
+        assert_eq!(decoded.validUntil, expected.validUntil);
+        assert_eq!(decoded.status, expected.status);
+        assert_eq!(decoded.rangeHash, expected.rangeHash);
+        assert_eq!(decoded.result, expected.result);
         println!("Values are correct!");
 
         // Record the number of cycles executed.
-        println!("Number of cycles: {}", report.total_instruction_count());
+        println!("Number of cycles: {}", execution.cycles);
+        if args.check_determinism {
+            println!("Executed twice with the same public values and cycles.");
+        }
     } else {
-        // Setup the program for proving.
-        let (pk, vk) = client.setup(FIBONACCI_ELF);
-
         // Generate the proof
-        let proof = client
-            .prove(&pk, &stdin)
-            .run()
-            .expect("failed to generate proof");
+        let proof = prover.prove_core(&input).expect("failed to generate proof");
 
         println!("Successfully generated proof!");
 
         // Verify the proof.
-        client.verify(&proof, &vk).expect("failed to verify proof");
+        prover.verify(&proof).expect("failed to verify proof");
         println!("Successfully verified proof!");
     }
 }
@@ -3,29 +3,100 @@
 //! This script generates EVM-compatible proofs using the Succinct Prover Network
 //! and prepares them for on-chain verification.
 
-use alloy_sol_types::SolType;
+use alloy_primitives::{keccak256, Address};
 use clap::Parser;
-use fibonacci_lib::PublicValuesStruct;
-use sp1_sdk::{
-    include_elf, ProverClient, SP1ProofWithPublicValues, SP1Stdin, HashableKey
+use fibonacci_lib::recurrence::Recurrence;
+use fibonacci_lib::{ProgramInput, Sequence, Status};
+use fibonacci_script::artifact_store;
+use fibonacci_script::artifacts::{input_hash, save_proof_artifacts, Compression};
+use fibonacci_script::audit::AuditLog;
+use fibonacci_script::config::ConfigError;
+use fibonacci_script::deadline::{parse_secs, Watchdog};
+use fibonacci_script::notify::{Event, EventKind, Notifications};
+use fibonacci_script::provenance::{Provenance, ProverIdentity};
+use fibonacci_script::prover::{
+    Execution, FibonacciProver, NetworkStrategy, ProofSystem, ProverError, FIBONACCI_ELF,
 };
-
-
-
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+use fibonacci_script::run_report::{RunOutcome, RunReport};
+use fibonacci_script::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sp1_sdk::SP1ProofWithPublicValues;
+use std::path::{Path, PathBuf};
+use std::thread::{self, ScopedJoinHandle};
+use std::time::{Duration, Instant};
+
+/// The name runs are recorded under in their run report.
+const COMMAND: &str = "network_evm";
 
 /// The arguments for the command.
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Serialize, Deserialize)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// The input number for Fibonacci computation
     #[arg(long, default_value = "10")]
     n: u32,
 
+    /// The sequence to compute: `fibonacci`, `lucas`, or any two seeds such as `3,7`
+    #[arg(long, default_value = "fibonacci")]
+    #[serde(default)]
+    sequence: Sequence,
+
+    /// Also commit every Fibonacci number from this index up to `n`
+    #[arg(long)]
+    range_start: Option<u32>,
+
+    /// Commit the hash of the range instead of its values, keeping the call data small for
+    /// long ranges
+    #[arg(long, requires = "range_start")]
+    #[serde(default)]
+    range_hash: bool,
+
+    /// Evaluate the recurrence with these coefficients `c1,...,cm` (mod `--modulus`) instead of
+    /// the Fibonacci sequence
+    #[arg(long, value_delimiter = ',', requires = "modulus")]
+    #[serde(default)]
+    coefficients: Vec<u64>,
+
+    /// The initial values `x_0,...,x_{m-1}` of the recurrence
+    #[arg(long, value_delimiter = ',', requires = "modulus")]
+    #[serde(default)]
+    initial: Vec<u64>,
+
+    /// The modulus of the recurrence
+    #[arg(long, requires_all = ["coefficients", "initial"])]
+    #[serde(default)]
+    modulus: Option<u64>,
+
+    /// Bind the proof to the chain with this id (e.g. 11155111 for Sepolia)
+    #[arg(long)]
+    chain_id: Option<u64>,
+
+    /// Bind the proof to the consumer contract at this address
+    #[arg(long)]
+    consumer: Option<Address>,
+
+    /// Echo this request id in the public values to prevent double settlement
+    #[arg(long)]
+    nonce: Option<u64>,
+
+    /// Unix timestamp after which the proof must no longer be settled
+    #[arg(long)]
+    valid_until: Option<u64>,
+
+    /// Prove each of these inputs instead of `--n`, comma-separated, executing them and
+    /// submitting their proof requests concurrently
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default)]
+    batch: Vec<u32>,
+
+    /// Prove an input the program rejects (e.g. n > MAX_N), committing its rejection status
+    #[arg(long)]
+    allow_rejected: bool,
+
     /// The proof system to use (groth16 or plonk)
-    #[arg(long, default_value = "groth16")]
-    system: String,
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
 
     /// Whether to save proof artifacts
     #[arg(long, default_value = "true")]
@@ -34,6 +105,68 @@ struct Args {
     /// Output directory for artifacts
     #[arg(long, default_value = "artifacts")]
     output_dir: String,
+
+    /// Save the proof artifacts to this store instead of the output directory: another
+    /// directory, or `s3://<bucket>/<prefix>`
+    #[arg(long, env = "ARTIFACT_STORE")]
+    #[serde(default)]
+    artifact_store: Option<String>,
+
+    /// Compress the proof, call data, summary and guest output: `none`, `zstd` or `zstd:<level>`
+    #[arg(long, env = "ARTIFACT_COMPRESSION", default_value = "none")]
+    compression: Compression,
+
+    /// How the prover network fulfills the request (with SP1_PROVER=network): `hosted`,
+    /// `reserved` capacity for latency-sensitive proofs, or the cheaper `auction`
+    #[arg(long, value_enum, env = "SP1_NETWORK_STRATEGY")]
+    network_strategy: Option<NetworkStrategy>,
+
+    /// Abort if generating the proof takes longer than this many seconds
+    #[arg(long, value_parser = parse_secs)]
+    prove_timeout: Option<Duration>,
+
+    /// Abort if the whole run takes longer than this many seconds
+    #[arg(long, value_parser = parse_secs)]
+    deadline: Option<Duration>,
+
+    /// Re-run the run recorded in this run report, with its arguments, input and configuration
+    /// (the other arguments are ignored), and compare the outcome with the recorded one
+    #[arg(long)]
+    #[serde(skip)]
+    replay: Option<PathBuf>,
+}
+
+impl Args {
+    /// The program input the arguments describe.
+    fn input(&self) -> ProgramInput {
+        ProgramInput {
+            n: self.n,
+            range_start: self.range_start,
+            range_hash: self.range_hash,
+            chain_id: self.chain_id,
+            consumer: self.consumer,
+            nonce: self.nonce,
+            valid_until: self.valid_until,
+            recurrence: self.modulus.map(|modulus| Recurrence {
+                coefficients: self.coefficients.clone(),
+                initial: self.initial.clone(),
+                modulus,
+            }),
+            seeds: self.sequence.input_seeds(),
+        }
+    }
+
+    /// The program inputs to prove: the one of `--n`, or one per `--batch` entry.
+    fn inputs(&self) -> Vec<ProgramInput> {
+        match &self.batch[..] {
+            [] => vec![self.input()],
+            batch => batch.iter().map(|&n| ProgramInput { n, ..self.input() }).collect(),
+        }
+    }
+    /// Where proof artifacts are saved: `--artifact-store`, or the output directory.
+    fn artifact_location(&self) -> &str {
+        self.artifact_store.as_deref().unwrap_or(&self.output_dir)
+    }
 }
 
 fn main() {
@@ -43,212 +176,354 @@ fn main() {
 
     // Parse the command line arguments.
     let args = Args::parse();
+    let (args, original) = match &args.replay {
+        Some(path) => match replay(path) {
+            Ok((args, original)) => (args, Some(original)),
+            Err(err) => {
+                eprintln!("❌ {}", err);
+                std::process::exit(err.exit_code());
+            }
+        },
+        None => (args, None),
+    };
+
+    let _deadline = Watchdog::start("run", args.deadline);
+    let mut report = RunReport::start(COMMAND, &args, args.input(), args.system);
+    let started = Instant::now();
+    let mut result = run(&args, &mut report.outcome);
+    report.finish(started.elapsed(), result.as_ref().err().map(|err| err.to_string()));
+    match report.save(Path::new(&args.output_dir), original.is_some()) {
+        Ok(path) => println!("💾 Run report saved to: {}", path.display()),
+        Err(err) => println!("⚠️  Could not save the run report: {}", err),
+    }
+    if let Some(original) = &original {
+        // A failed replay still reports whether it failed the same way.
+        result = result.and(compare_replay(original, &report.outcome));
+    }
+    if let Err(err) = result {
+        eprintln!("❌ {}", err);
+        std::process::exit(err.exit_code());
+    }
+}
+
+/// Rebuild the arguments of the run recorded in the report at `path` and restore its
+/// configuration.
+fn replay(path: &Path) -> Result<(Args, RunReport), Error> {
+    let original = RunReport::load(path)?;
+    let invalid = |reason: String| ConfigError::Invalid { name: "--replay", reason };
+    if original.command != COMMAND {
+        return Err(invalid(format!(
+            "{} reports a `{}` run, not `{}`",
+            path.display(),
+            original.command,
+            COMMAND
+        ))
+        .into());
+    }
+    let args: Args = serde_json::from_value(original.args.clone())
+        .map_err(|err| invalid(format!("{}: invalid arguments: {}", path.display(), err)))?;
+    if args.input() != original.input || args.system != original.system {
+        return Err(invalid(format!(
+            "{}: the recorded input does not match the recorded arguments",
+            path.display()
+        ))
+        .into());
+    }
+
+    println!("🔁 Replaying the run started at {} ({})", original.started_at, path.display());
+    let current = Provenance::current(FIBONACCI_ELF);
+    if current.git_commit != original.provenance.git_commit {
+        println!(
+            "⚠️  The run was built from commit {}, this build is {}",
+            original.provenance.git_commit, current.git_commit
+        );
+    }
+    if current.elf_hash != original.provenance.elf_hash {
+        println!("⚠️  The program ELF differs from the one the run proved");
+    }
+    for (name, value) in original.restore_env() {
+        println!("   {}={}", name, value.unwrap_or("(unset)"));
+    }
+    println!();
+    Ok((args, original))
+}
 
+/// Report how a replay's outcome differs from the original run's, failing if it does.
+fn compare_replay(original: &RunReport, replayed: &RunOutcome) -> Result<(), Error> {
+    let differences = original.differences(replayed);
+    if differences.is_empty() {
+        println!("🔁 The replay reproduced the original run");
+        return Ok(());
+    }
+    println!("🔁 The replay differs from the original run:");
+    for difference in &differences {
+        println!("   {}", difference);
+    }
+    Err(Error::Rejected("the replay did not reproduce the original run".to_string()))
+}
+
+fn run(args: &Args, outcome: &mut RunOutcome) -> Result<(), Error> {
     let prover_mode = std::env::var("SP1_PROVER").unwrap_or_else(|_| "local".to_string());
+    let inputs = args.inputs();
+    let notifications = Notifications::from_env()?;
     
     println!("🚀 SP1 Network EVM Proof Generation");
     println!("===================================");
-    println!("📊 Input: n = {}", args.n);
+    match &args.batch[..] {
+        [] => println!("📊 Input: n = {}", args.n),
+        batch => println!("📊 Inputs: n = {:?}", batch),
+    }
     println!("🔧 System: {}", args.system);
     println!("🌐 Prover Mode: {}", prover_mode);
-    println!();
-
-    // Validate system argument
-    if args.system != "groth16" && args.system != "plonk" {
-        eprintln!("❌ Error: system must be either 'groth16' or 'plonk'");
-        std::process::exit(1);
+    if let Some(strategy) = args.network_strategy {
+        println!("🎟️  Network Strategy: {}", strategy);
     }
+    println!();
 
     // Setup the prover client.
-    let client = ProverClient::from_env();
-
-    // Setup the inputs.
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&args.n);
-
-    // Setup the program for proving.
-    println!("🔧 Setting up program...");
-    let (pk, vk) = client.setup(FIBONACCI_ELF);
-    
-    // Get the verification key hash for contracts
-    let vk_bytes = vk.bytes32();
-    
-    // vk.bytes32() actually returns a String already!
-    println!("🔍 Debug vk_bytes: {:?}", vk_bytes);
-    println!("🔍 vk_bytes type: String");
-    
-    // Since vk_bytes is already a hex string, use it directly
-    let vk_hash = if vk_bytes.starts_with("0x") {
-        vk_bytes[2..].to_string()  // Remove "0x" prefix if present
+    let prover = if args.allow_rejected {
+        FibonacciProver::new().allow_rejected()
     } else {
-        vk_bytes.clone()
+        FibonacciProver::new()
     };
-    
-    println!("🔑 Program VKey: 0x{}", vk_hash);
 
-    // First, test execution locally to ensure everything works
-    println!("⚡ Testing local execution...");
-    let (output, report) = client.execute(FIBONACCI_ELF, &stdin).run().unwrap();
-    
-    // Read and validate the output
-    let decoded = PublicValuesStruct::abi_decode(output.as_slice(), true).unwrap();
-    println!("✅ Local execution successful:");
-    println!("   Input n: {}", decoded.n);
-    println!("   Fibonacci({}): {}", decoded.n.saturating_sub(1), decoded.a);
-    println!("   Fibonacci({}): {}", decoded.n, decoded.b);
-    println!("   Cycles: {}", report.total_instruction_count());
-    println!();
+    // Set the program up while executing every input locally, since neither needs the other.
+    // The SDK blocks in both, so they run on threads of their own.
+    println!("🔧 Setting up program and testing local execution...");
+    let (vk_hash, executions) = thread::scope(|scope| {
+        let prover = &prover;
+        let setup = scope.spawn(|| prover.vkey_hash());
+        let executions: Vec<_> = inputs
+            .iter()
+            .map(|input| scope.spawn(move || prover.execute(input)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(join)
+            .collect();
+        (join(setup), executions)
+    });
+    println!("🔑 Program VKey: {}", vk_hash);
+    outcome.vkey = Some(vk_hash);
+    let executions = executions.into_iter().collect::<Result<Vec<_>, _>>()?;
+    if let [execution] = &executions[..] {
+        outcome.cycles = Some(execution.cycles);
+        outcome.public_values = Some(execution.public_values.to_abi_bytes().into());
+    }
+    for (input, execution) in inputs.iter().zip(&executions) {
+        print_execution(input, execution);
+    }
 
     // Generate the EVM-compatible proof
+    let system_name = args.system.to_string().to_uppercase();
     match prover_mode.as_str() {
         "network" => {
-            println!("🌐 Generating {} proof using Succinct Prover Network...", args.system.to_uppercase());
+            println!("🌐 Generating {} proof using Succinct Prover Network...", system_name);
             println!("⏳ This may take several minutes depending on network load...");
         },
         "cpu" => {
-            println!("💻 Generating {} proof using CPU...", args.system.to_uppercase());
+            println!("💻 Generating {} proof using CPU...", system_name);
             println!("⚠️  WARNING: CPU proving can take HOURS for Groth16! Consider using 'mock' for testing.");
         },
         "mock" => {
-            println!("🎭 Generating {} mock proof...", args.system.to_uppercase());
+            println!("🎭 Generating {} mock proof...", system_name);
             println!("⚡ Mock proving is fast but proofs are not secure!");
         },
         _ => {
-            println!("🔧 Generating {} proof using {}...", args.system.to_uppercase(), prover_mode);
+            println!("🔧 Generating {} proof using {}...", system_name, prover_mode);
         }
     }
+    if inputs.len() > 1 {
+        println!("📨 Submitting {} requests at once...", inputs.len());
+    }
     
-    let proof = if args.system == "groth16" {
-        client.prove(&pk, &stdin).groth16().run()
+    // Submit every request at once, so a batch waits for the slowest proof rather than for the
+    // sum of them.
+    let watchdog = Watchdog::start("proving", args.prove_timeout);
+    let proofs: Vec<_> = thread::scope(|scope| {
+        inputs
+            .iter()
+            .map(|input| {
+                let prover = &prover;
+                scope.spawn(move || prover.prove_traced(input, args.system, args.network_strategy))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(join)
+            .collect()
+    });
+    drop(watchdog);
+
+    let audit = AuditLog::from_env();
+    let mut failed = None;
+    for ((input, execution), proof) in inputs.iter().zip(&executions).zip(proofs) {
+        let details = json!({
+            "input": input,
+            "input_hash": input_hash(input, args.system),
+            "system": args.system.to_string(),
+            "vkey": vk_hash,
+            "prover": prover_mode,
+            "network_strategy": args.network_strategy,
+        });
+        let result =
+            finish_proof(args, &prover, &audit, details.clone(), execution, proof, outcome);
+        let event = match &result {
+            Ok(()) => {
+                let message = format!("n = {} proved", input.n);
+                Event::new(EventKind::ProofCompleted, message, details)
+            }
+            Err(err) => {
+                let message = format!("n = {} failed: {}", input.n, err);
+                let details = json!({ "error": err.to_string(), "proof": details });
+                Event::new(EventKind::ProofFailed, message, details)
+            }
+        };
+        notifications.notify(&event);
+        if let Err(err) = result {
+            // Keep the other proofs of a batch, and report the first failure.
+            println!("❌ n = {}: {}", input.n, err);
+            failed.get_or_insert(err);
+        }
+    }
+    if let Some(err) = failed {
+        return Err(err);
+    }
+
+    if args.save_artifacts {
+        println!("   Artifacts saved to: {}/", args.artifact_location());
+    }
+
+    println!();
+    println!("🔗 Next steps for on-chain verification:");
+    println!("1. Set FIBONACCI_PROGRAM_VKEY={} in your .env", vk_hash);
+    println!("2. Deploy contract: cd ../contracts && forge script script/Deploy.s.sol --rpc-url $RPC_URL --private-key $PRIVATE_KEY --broadcast");
+    println!("3. Call verifyFibonacciProof with the generated proof and public values");
+
+    Ok(())
+}
+
+/// Wait for a thread, passing on its panic.
+fn join<T>(handle: ScopedJoinHandle<'_, T>) -> T {
+    handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Print the public values a local execution committed.
+fn print_execution(input: &ProgramInput, execution: &Execution) {
+    let decoded = &execution.public_values;
+    println!("✅ Local execution successful:");
+    println!("   Input n: {}", decoded.n);
+    if decoded.is_rejected() {
+        println!("   Rejected: {}", decoded.status_name());
     } else {
-        client.prove(&pk, &stdin).plonk().run()
-    }.expect("failed to generate proof");
+        println!("   Fibonacci({}): {}", decoded.n.saturating_sub(1), decoded.a);
+        println!("   Fibonacci({}): {}", decoded.n, decoded.b);
+        if decoded.status != Status::Ok as u8 {
+            println!("   Status: {} (the values wrapped around)", decoded.status_name());
+        }
+    }
+    if decoded.chainId != 0 {
+        println!("   Chain ID: {}", decoded.chainId);
+    }
+    if !decoded.consumer.is_zero() {
+        println!("   Consumer: {}", decoded.consumer);
+    }
+    if decoded.nonce != 0 {
+        println!("   Nonce: {}", decoded.nonce);
+    }
+    if decoded.validUntil != 0 {
+        println!("   Valid Until: {}", decoded.validUntil);
+    }
+    if let Some(start) = decoded.range_start() {
+        println!("   Range Fibonacci({}..={}):", start, decoded.n);
+        for (k, value) in (start..).zip(&decoded.fibValues) {
+            println!("     Fibonacci({}) = {}", k, value);
+        }
+    }
+    if decoded.has_range_hash() {
+        println!("   Range Hash: {}", decoded.rangeHash);
+    }
+    if decoded.is_recurrence() {
+        println!(
+            "   Recurrence: coefficients {:?}, initial values {:?}, modulus {}",
+            decoded.coefficients, decoded.initialValues, decoded.modulus
+        );
+        println!("   Result: x({}) = {}", decoded.n, decoded.result);
+        if let Some(recurrence) = &input.recurrence {
+            println!("   Recurrence ID: {}", recurrence.id(decoded.n));
+        }
+    }
+    println!("   Cycles: {}", execution.cycles);
+    if !execution.guest_stdout.is_empty() {
+        println!("   Guest output: {} lines", execution.guest_stdout.lines().count());
+    }
+    println!();
+}
 
-    match prover_mode.as_str() {
-        "mock" => println!("✅ {} mock proof generated successfully!", args.system.to_uppercase()),
-        _ => println!("✅ {} proof generated successfully!", args.system.to_uppercase()),
+/// Audit, verify and save the proof of one input.
+fn finish_proof(
+    args: &Args,
+    prover: &FibonacciProver,
+    audit: &AuditLog,
+    details: Value,
+    execution: &Execution,
+    proof: Result<(SP1ProofWithPublicValues, ProverIdentity), ProverError>,
+    outcome: &mut RunOutcome,
+) -> Result<(), Error> {
+    let vk_hash = prover.vkey_hash();
+    let system_name = args.system.to_string().to_uppercase();
+    audit.record("prove", details.clone(), &proof)?;
+    let (proof, identity) = proof?;
+    if args.batch.is_empty() {
+        outcome.public_values = Some(proof.public_values.to_vec().into());
+        outcome.proof_hash = Some(keccak256(proof.bytes()));
+    }
+
+    let n = execution.public_values.n;
+    match std::env::var("SP1_PROVER").as_deref() {
+        Ok("mock") => println!("✅ {} mock proof of n = {} generated!", system_name, n),
+        _ => println!("✅ {} proof of n = {} generated successfully!", system_name, n),
     }
+    println!("   Proved by: {}", identity.describe());
 
     // Verify the proof locally
     println!("🔍 Verifying proof...");
-    client.verify(&proof, &vk).expect("failed to verify proof");
+    let verified = prover.verify(&proof);
+    audit.record("verify", details, &verified)?;
+    verified?;
     println!("✅ Proof verification successful!");
 
     // Process and save artifacts if requested
     if args.save_artifacts {
-        save_proof_artifacts(&proof, &args, &vk_hash).expect("failed to save artifacts");
+        let store = artifact_store::open(args.artifact_location())?;
+        let guest_stdout = Some(execution.guest_stdout.as_str());
+        let saved = save_proof_artifacts(
+            store.as_ref(),
+            &proof,
+            args.system,
+            &vk_hash,
+            &identity,
+            guest_stdout,
+            args.compression,
+        )?;
+        println!("💾 Proof saved to: {}", saved.proof);
+        println!("💾 Public values saved to: {}", saved.public_values);
+        println!("💾 Verification key saved to: {}", saved.verification_key);
+        println!("💾 Contract call data saved to: {}", saved.call_data);
+        println!("💾 Summary saved to: {}", saved.summary);
+        println!("💾 Manifest saved to: {}", saved.manifest);
+        if let Some(guest_stdout) = &saved.guest_stdout {
+            println!("💾 Guest output saved to: {}", guest_stdout);
+        }
     }
 
     // Display final information
     println!();
     println!("🎉 Proof generation completed successfully!");
     println!("📋 Summary:");
-    println!("   Input: {}", args.n);
+    println!("   Input: {}", n);
     println!("   System: {}", args.system);
-    println!("   VKey: 0x{}", vk_hash);
+    println!("   VKey: {}", vk_hash);
     println!("   Public Values: 0x{}", hex::encode(&proof.public_values.to_vec()));
     println!("   Proof Size: {} bytes", proof.bytes().len());
-    
-    if args.save_artifacts {
-        println!("   Artifacts saved to: {}/", args.output_dir);
-    }
-
     println!();
-    println!("🔗 Next steps for on-chain verification:");
-    println!("1. Set FIBONACCI_PROGRAM_VKEY=0x{} in your .env", vk_hash);
-    println!("2. Deploy contract: cd ../contracts && forge script script/Deploy.s.sol --rpc-url $RPC_URL --private-key $PRIVATE_KEY --broadcast");
-    println!("3. Call verifyFibonacciProof with the generated proof and public values");
-}
-
-/// Save proof artifacts for on-chain verification
-fn save_proof_artifacts(
-    proof: &SP1ProofWithPublicValues,
-    args: &Args,
-    vk_hash: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use std::fs;
-
-    // Create output directory
-    fs::create_dir_all(&args.output_dir)?;
-
-    // Save proof bytes
-    let proof_path = format!("{}/proof_{}_n{}.bin", args.output_dir, args.system, args.n);
-    fs::write(&proof_path, proof.bytes())?;
-    println!("💾 Proof saved to: {}", proof_path);
-
-    // Save public values
-    let public_values_path = format!("{}/public_values_n{}.bin", args.output_dir, args.n);
-    fs::write(&public_values_path, &proof.public_values.to_vec())?;
-    println!("💾 Public values saved to: {}", public_values_path);
-
-    // Save verification key
-    let vkey_path = format!("{}/verification_key.txt", args.output_dir);
-    fs::write(&vkey_path, format!("0x{}", vk_hash))?;
-    println!("💾 Verification key saved to: {}", vkey_path);
-
-    // Save contract call data
-    let call_data = generate_contract_call_data(proof, args.n)?;
-    let call_data_path = format!("{}/contract_call_data_n{}.json", args.output_dir, args.n);
-    fs::write(&call_data_path, call_data)?;
-    println!("💾 Contract call data saved to: {}", call_data_path);
-
-    // Save human-readable summary
-    let summary = format!(
-        "SP1 {} Proof Summary\n\
-        ===================\n\
-        Input: {}\n\
-        System: {}\n\
-        Verification Key: 0x{}\n\
-        Public Values: 0x{}\n\
-        Proof: 0x{}\n\
-        Proof Size: {} bytes\n\
-        \n\
-        To verify on-chain:\n\
-        1. Deploy Fibonacci contract with VKey: 0x{}\n\
-        2. Call verifyFibonacciProof(publicValues, proof)\n\
-        3. Public Values: 0x{}\n\
-        4. Proof: 0x{}\n",
-        args.system.to_uppercase(),
-        args.n,
-        args.system,
-        vk_hash,
-        hex::encode(&proof.public_values.to_vec()),
-        hex::encode(&proof.bytes()),
-        proof.bytes().len(),
-        vk_hash,
-        hex::encode(&proof.public_values.to_vec()),
-        hex::encode(&proof.bytes())
-    );
-
-    let summary_path = format!("{}/summary_n{}.txt", args.output_dir, args.n);
-    fs::write(&summary_path, summary)?;
-    println!("💾 Summary saved to: {}", summary_path);
-
     Ok(())
 }
-
-/// Generate contract call data in JSON format
-fn generate_contract_call_data(
-    proof: &SP1ProofWithPublicValues,
-    n: u32,
-) -> Result<String, Box<dyn std::error::Error>> {
-    use serde_json::json;
-
-    let call_data = json!({
-        "function": "verifyFibonacciProof",
-        "parameters": {
-            "publicValues": format!("0x{}", hex::encode(&proof.public_values.to_vec())),
-            "proofBytes": format!("0x{}", hex::encode(&proof.bytes()))
-        },
-        "expected_output": {
-            "n": n,
-            "decoded_from_public_values": "Use abi.decode(publicValues, (PublicValuesStruct))"
-        },
-        "contract_interface": {
-            "function_signature": "verifyFibonacciProof(bytes,bytes)",
-            "returns": "(uint32,uint32,uint32)"
-        }
-    });
-
-    Ok(serde_json::to_string_pretty(&call_data)?)
-} 
\ No newline at end of file
@@ -0,0 +1,77 @@
+//! Prove a Fibonacci input with two levels of recursion: a compressed proof of the Fibonacci
+//! program, then an EVM-compatible proof of the recursion program, which verifies the first
+//! proof inside the zkVM and commits its public values.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin recursion -- --n 20
+//! ```
+
+use clap::Parser;
+use fibonacci_lib::recursion::RecursionPublicValuesStruct;
+use fibonacci_lib::{ProgramInput, PublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::prover::{FibonacciProver, ProofSystem};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(long, default_value = "20")]
+    n: u32,
+
+    /// The proof system of the outer proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let input = ProgramInput::new(args.n);
+    input.validate().map_err(|err| eyre::eyre!("invalid input: {}", err))?;
+    let prover = FibonacciProver::new();
+
+    println!("🔄 Generating the compressed proof, then the {} proof of it...", args.system);
+    let start = Instant::now();
+    let recursive = prover.prove_recursive(&input, args.system)?;
+    println!("✅ Proofs generated and verified in {:.1?}", start.elapsed());
+
+    let outer = recursive.proof.public_values.as_slice();
+    let outer = RecursionPublicValuesStruct::from_abi_bytes(outer)?;
+    if outer.publicValues.as_ref() != recursive.inner.public_values.as_slice() {
+        eyre::bail!("the recursion program committed different public values than the inner proof");
+    }
+    let inner = PublicValuesStruct::from_abi_bytes(&outer.publicValues)?;
+    println!("   Fibonacci VKey: {}", prover.vkey_hash());
+    println!("   Recursion VKey: {}", recursive.recursion_vkey);
+    println!("   Inner VKey digest: {}", outer.programVKeyDigest);
+    println!("   n: {}, a: {}, b: {}", inner.n, inner.a, inner.b);
+
+    let saved = ProgramProof {
+        program: "recursion".to_string(),
+        vkey: recursive.recursion_vkey,
+        system: args.system,
+        public_values: recursive.proof.public_values.to_vec().into(),
+        proof: recursive.proof.bytes().into(),
+        decoded: json!({
+            "programVKeyDigest": outer.programVKeyDigest,
+            "publicValues": outer.publicValues,
+            "n": inner.n,
+            "a": inner.a,
+            "b": inner.b,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Prove that private text matches (or does not match) a public regular expression with the
+//! regex program. The pattern is compiled into a DFA here, and only the DFA runs in the program.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin regex -- --pattern '^[a-z0-9.]+@example\.com$' \
+//!     --text alice@example.com
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use clap::Parser;
+use fibonacci_lib::dfa::{RegexInput, RegexPublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use fibonacci_script::regex::compile_dfa;
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The regular expression, in the syntax of the `regex` crate
+    #[arg(long)]
+    pattern: String,
+
+    /// The text to match
+    #[arg(long, conflicts_with = "text_file", required_unless_present = "text_file")]
+    text: Option<String>,
+
+    /// A file holding the text to match, as raw bytes
+    #[arg(long)]
+    text_file: Option<PathBuf>,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let text = match (&args.text_file, &args.text) {
+        (Some(path), _) => std::fs::read(path)
+            .map_err(|err| eyre::eyre!("failed to read {}: {}", path.display(), err))?,
+        (None, text) => text.clone().unwrap_or_default().into_bytes(),
+    };
+    let dfa = compile_dfa(&args.pattern)?;
+    println!("🔧 Compiled the pattern into a DFA with {} states", dfa.states());
+    let input = RegexInput { pattern: args.pattern.clone(), dfa, text };
+
+    let expected = input.public_values().map_err(|err| eyre::eyre!(err))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("regex").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Program executed successfully");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "regex".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "pattern": args.pattern,
+            "patternHash": expected.patternHash,
+            "dfaHash": expected.dfaHash,
+            "textHash": expected.textHash,
+            "matched": expected.matched,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &RegexPublicValuesStruct) {
+    println!("   Matched: {}", values.matched);
+    println!("   Pattern hash: {}", values.patternHash);
+    println!("   DFA hash: {}", values.dfaHash);
+    println!("   Text hash: {}", values.textHash);
+}
@@ -0,0 +1,119 @@
+//! Serve the prover over JSON-RPC 2.0 (`sp1_prove`, `sp1_getProof`, `sp1_verifyOnchain`), for
+//! clients built on Ethereum tooling. Requests are queued in the job store and proved by `worker`.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin service
+//! ```
+//! and call it like any Ethereum node:
+//! ```shell
+//! curl -s localhost:8645 -H 'content-type: application/json' \
+//!     -d '{"jsonrpc":"2.0","id":1,"method":"sp1_prove","params":[{"input":{"n":20}}]}'
+//! ```
+//! Its OpenAPI document is served at `/openapi.json`, or printed with `--openapi`.
+//!
+//! The service holds no proving keys and never runs a setup, so it starts at once; workers set the
+//! program up when they start, before claiming jobs, so a request does not wait for a setup.
+//!
+//! `sp1_prove` only queues inputs within the input domain of `input_domain.toml` (or the file
+//! `INPUT_DOMAIN` names), if there is one; see [`fibonacci_script::domain`].
+//!
+//! Requests need an API key, created with `cli keys create`, unless the service only listens on
+//! a loopback address and `--allow-anonymous` is passed.
+
+use clap::Parser;
+use eyre::Result;
+use fibonacci_script::addresses::ContractTarget;
+use fibonacci_script::artifact_store::{self, DEFAULT_ARTIFACT_STORE};
+use fibonacci_script::audit::{AuditLog, DEFAULT_AUDIT_LOG};
+use fibonacci_script::auth::{extract_key, RateLimiter};
+use fibonacci_script::config::{self, DEFAULT_CONTRACT_ADDRESS};
+use fibonacci_script::domain::InputDomain;
+use fibonacci_script::jobs::{JobStore, DEFAULT_JOB_STORE_URL};
+use fibonacci_script::openapi::openapi;
+use fibonacci_script::rpc::{rpc_module, RpcContext, DEFAULT_SERVICE_ADDR};
+use fibonacci_script::shutdown::Shutdown;
+use jsonrpsee::server::middleware::http::ProxyGetRequestLayer;
+use jsonrpsee::server::Server;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The address to listen on
+    #[arg(long, env = "SERVICE_ADDR", default_value = DEFAULT_SERVICE_ADDR)]
+    addr: SocketAddr,
+
+    /// The job store requests are queued in
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    /// Where workers cache proofs (see `worker --artifact-store`): a directory, or
+    /// `s3://<bucket>/<prefix>`
+    #[arg(long, env = "ARTIFACT_STORE", default_value = DEFAULT_ARTIFACT_STORE)]
+    artifact_store: String,
+
+    /// The FibonacciSimple contract `sp1_verifyOnchain` verifies against by default: an address,
+    /// an ENS name, or an address book alias
+    #[arg(long, env = "FIBONACCI_CONTRACT_ADDRESS", default_value = DEFAULT_CONTRACT_ADDRESS)]
+    contract: ContractTarget,
+
+    /// Serve requests without an API key too; only allowed on a loopback address
+    #[arg(long)]
+    allow_anonymous: bool,
+
+    /// The audit log requests are recorded in
+    #[arg(long, env = "AUDIT_LOG", default_value = DEFAULT_AUDIT_LOG)]
+    audit_log: String,
+
+    /// Print the OpenAPI document of the interface and exit, e.g. to generate a client SDK
+    #[arg(long)]
+    openapi: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    if args.openapi {
+        println!("{}", openapi().to_pretty_json()?);
+        return Ok(());
+    }
+    if args.allow_anonymous && !args.addr.ip().is_loopback() {
+        eyre::bail!("--allow-anonymous is only allowed on a loopback address, not {}", args.addr);
+    }
+
+    let context = RpcContext {
+        store: JobStore::connect(&args.job_store).await?,
+        artifacts: artifact_store::open(&args.artifact_store)?,
+        rpc_endpoint: config::rpc_endpoint()?,
+        contract: args.contract.resolve(&config::rpc_url()).await?,
+        allow_anonymous: args.allow_anonymous,
+        rate_limiter: RateLimiter::default(),
+        domain: InputDomain::from_env()?,
+        audit: Mutex::new(AuditLog::new(&args.audit_log)),
+    };
+    // Serve the OpenAPI document to plain GET requests too, for Swagger UI and SDK generators.
+    let middleware = tower::ServiceBuilder::new()
+        .map_request(extract_key)
+        .layer(ProxyGetRequestLayer::new("/openapi.json", "sp1_openApi")?);
+    let server = Server::builder().set_http_middleware(middleware).build(args.addr).await?;
+    let addr = server.local_addr()?;
+    let handle = server.start(rpc_module(context));
+    println!("🌐 Serving JSON-RPC on http://{}", addr);
+    println!("   Methods: sp1_prove, sp1_getProof, sp1_verifyOnchain");
+    println!("   OpenAPI document: http://{}/openapi.json", addr);
+    println!("   Proofs are generated by `worker` from {}", args.job_store);
+    println!("   Proofs are read from {}", args.artifact_store);
+    if args.allow_anonymous {
+        println!("⚠️  Serving requests without an API key");
+    }
+
+    Shutdown::listen().wait().await;
+    handle.stop()?;
+    handle.stopped().await;
+    Ok(())
+}
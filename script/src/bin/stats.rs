@@ -0,0 +1,170 @@
+//! Prove statistics of a private dataset with the statistics program, which commits the count,
+//! sum, mean, minimum and maximum of the values and the hash of the dataset, but not the values.
+//!
+//! The dataset is one column of a CSV file. You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin stats -- --file data.csv --header --column price \
+//!     --decimals 2
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use clap::Parser;
+use fibonacci_lib::stats::{StatsInput, StatsPublicValuesStruct};
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The CSV file of the dataset
+    #[arg(long)]
+    file: PathBuf,
+
+    /// The column of the values: its index from 0, or its name with `--header`
+    #[arg(long, default_value = "0")]
+    column: String,
+
+    /// The first line of the file holds the column names
+    #[arg(long)]
+    header: bool,
+
+    /// Scale the values by 10^decimals, so values with up to this many decimals are proven as
+    /// integers
+    #[arg(long, default_value = "0")]
+    decimals: u32,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let csv = std::fs::read_to_string(&args.file)
+        .map_err(|err| eyre::eyre!("failed to read {}: {}", args.file.display(), err))?;
+    let values = read_column(&csv, &args.column, args.header, args.decimals)?;
+    println!("📊 Read {} values from {}", values.len(), args.file.display());
+    let input = StatsInput { values };
+
+    // The program panics on an empty dataset, so fail before running it.
+    let expected = input.statistics().ok_or_else(|| eyre::eyre!("the dataset is empty"))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+
+    let prover = ProgramProver::new(program("stats").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Program executed successfully");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected, args.decimals);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "stats".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "datasetHash": expected.datasetHash,
+            "count": expected.count,
+            "sum": expected.sum.to_string(),
+            "min": expected.min,
+            "max": expected.max,
+            "mean": expected.mean,
+            "decimals": args.decimals,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed, scaled back by `decimals`.
+fn print_public_values(values: &StatsPublicValuesStruct, decimals: u32) {
+    let scaled = |value: String| match decimals {
+        0 => value,
+        decimals => format!("{} (x 10^-{})", value, decimals),
+    };
+    println!("   Dataset hash: {}", values.datasetHash);
+    println!("   Count: {}", values.count);
+    println!("   Sum: {}", scaled(values.sum.to_string()));
+    println!("   Mean: {}", scaled(values.mean.to_string()));
+    println!("   Min: {}", scaled(values.min.to_string()));
+    println!("   Max: {}", scaled(values.max.to_string()));
+}
+
+/// Read the values of `column` (an index, or a name if the file has a `header`) of a CSV file,
+/// skipping empty lines.
+fn read_column(csv: &str, column: &str, header: bool, decimals: u32) -> eyre::Result<Vec<i64>> {
+    let split = |line: &str| -> Vec<String> {
+        line.split(',').map(|field| field.trim().trim_matches('"').to_string()).collect()
+    };
+    let mut lines = csv.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let names = if header { lines.next().map(|(_, line)| split(line)) } else { None };
+    let index = match (column.parse::<usize>(), &names) {
+        (Ok(index), _) => index,
+        (Err(_), Some(names)) => names
+            .iter()
+            .position(|name| name == column)
+            .ok_or_else(|| eyre::eyre!("no column named `{}`", column))?,
+        (Err(_), None) => eyre::bail!("--column `{}` is a name, which needs --header", column),
+    };
+    lines
+        .map(|(number, line)| {
+            let fields = split(line);
+            let field = fields
+                .get(index)
+                .ok_or_else(|| eyre::eyre!("line {} has no column {}", number + 1, index))?;
+            parse_scaled(field, decimals)
+                .map_err(|err| eyre::eyre!("line {}: {}", number + 1, err))
+        })
+        .collect()
+}
+
+/// Parse a decimal number with at most `decimals` decimals as an integer scaled by
+/// `10^decimals`.
+fn parse_scaled(value: &str, decimals: u32) -> Result<i64, String> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_number = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_number(whole) || !is_number(fraction) {
+        return Err(format!("`{}` is not a number", value));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(format!("`{}` has more than {} decimals (see --decimals)", value, decimals));
+    }
+    let scaled = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    let magnitude: i128 = scaled.parse().map_err(|_| format!("`{}` is too large", value))?;
+    let value_scaled = if negative { -magnitude } else { magnitude };
+    i64::try_from(value_scaled).map_err(|_| format!("`{}` does not fit in an int64", value))
+}
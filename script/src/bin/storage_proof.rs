@@ -0,0 +1,131 @@
+//! Attest the value of a contract's storage slot at a block with the storage program, which
+//! verifies the `eth_getProof` proof of the slot against the block's state root.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! RUST_LOG=info cargo run --release --bin storage_proof -- --account 0x... --slot 0 --block 123
+//! ```
+//! and add `--prove` to generate an EVM-compatible proof.
+
+use alloy_primitives::{Address, B256, U256};
+use clap::Parser;
+use fibonacci_lib::storage::StoragePublicValuesStruct;
+use fibonacci_script::artifacts::ProgramProof;
+use fibonacci_script::config::DEFAULT_RPC_URL;
+use fibonacci_script::programs::program;
+use fibonacci_script::prover::{ProgramProver, ProofSystem};
+use fibonacci_script::state::fetch_storage_proof;
+use serde_json::json;
+use sp1_sdk::SP1Stdin;
+use std::path::PathBuf;
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The contract whose storage to attest
+    #[arg(long)]
+    account: Address,
+
+    /// The storage slot, in decimal or `0x` hex
+    #[arg(long)]
+    slot: U256,
+
+    /// The block to attest the slot at (the latest by default)
+    #[arg(long)]
+    block: Option<u64>,
+
+    /// The RPC endpoint to fetch the proof from
+    #[arg(long, env = "RPC_URL", default_value = DEFAULT_RPC_URL)]
+    rpc_url: String,
+
+    /// Generate an EVM-compatible proof instead of only executing the program
+    #[arg(long)]
+    prove: bool,
+
+    /// The proof system of the proof
+    #[arg(long, value_enum, default_value = "groth16")]
+    system: ProofSystem,
+
+    /// Output directory for artifacts
+    #[arg(long, default_value = "artifacts")]
+    output_dir: PathBuf,
+}
+
+fn main() -> eyre::Result<()> {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+
+    let slot = B256::from(args.slot);
+    let fetched = tokio::runtime::Runtime::new()?.block_on(fetch_storage_proof(
+        &args.rpc_url,
+        args.account,
+        slot,
+        args.block,
+    ))?;
+    println!("📦 Block {} ({})", fetched.block.number, fetched.block.hash);
+    println!("   State root: {}", fetched.block.state_root);
+    println!(
+        "   {} account and {} storage proof nodes",
+        fetched.input.account_proof.len(),
+        fetched.input.storage_proof.len()
+    );
+
+    // The program panics on an invalid proof, so fail before running it.
+    let expected = fetched.input.verify().map_err(|err| eyre::eyre!(err))?;
+    if expected.value != fetched.value {
+        eyre::bail!(
+            "the proof shows the slot holds {}, but the node claims {}",
+            expected.value,
+            fetched.value
+        );
+    }
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&fetched.input);
+
+    let prover = ProgramProver::new(program("storage").map_err(|err| eyre::eyre!(err))?);
+    let (output, cycles) = prover.execute(&stdin)?;
+    if output != expected.to_abi_bytes() {
+        eyre::bail!("the program committed different public values than expected");
+    }
+    println!("✅ Storage proof verified in the program");
+    println!("   Cycles: {}", cycles);
+    print_public_values(&expected);
+    if !args.prove {
+        return Ok(());
+    }
+
+    println!("🔄 Generating {} proof...", args.system);
+    let proof = prover.prove(&stdin, args.system)?;
+    prover.verify(&proof)?;
+    let vkey = prover.vkey_hash();
+    println!("✅ Proof verified");
+    println!("   VKey: {}", vkey);
+    let saved = ProgramProof {
+        program: "storage".to_string(),
+        vkey,
+        system: args.system,
+        public_values: proof.public_values.to_vec().into(),
+        proof: proof.bytes().into(),
+        decoded: json!({
+            "stateRoot": expected.stateRoot,
+            "account": expected.account,
+            "slot": expected.slot,
+            "value": expected.value,
+            "blockNumber": fetched.block.number,
+            "blockHash": fetched.block.hash,
+        }),
+    }
+    .save(&args.output_dir)?;
+    println!("💾 Proof saved to: {}", saved.display());
+    Ok(())
+}
+
+/// Print the public values the program committed.
+fn print_public_values(values: &StoragePublicValuesStruct) {
+    println!("   State root: {}", values.stateRoot);
+    println!("   Account: {}", values.account);
+    println!("   Slot: {}", values.slot);
+    println!("   Value: {}", values.value);
+}
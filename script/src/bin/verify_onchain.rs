@@ -1,122 +1,774 @@
-use alloy_primitives::Address;
+use alloy_network::{EthereumWallet, TransactionBuilder};
+use alloy_primitives::{keccak256, Address, Bytes, B256};
 use alloy_provider::{Provider, ProviderBuilder};
-use alloy_sol_types::sol;
-use eyre::Result;
-use serde_json::Value;
-use std::env;
-use std::fs;
+use alloy_rpc_types::{AccessList, TransactionReceipt, TransactionRequest};
+use alloy_signer_local::PrivateKeySigner;
+use alloy_transport::Transport;
+use clap::{Parser, ValueEnum};
+use fibonacci_lib::{Sequence, VkHash};
+use fibonacci_script::addresses::ContractTarget;
+use fibonacci_script::artifacts::{
+    call_data_path, write_json, CallData, Manifest, DEFAULT_ARTIFACTS_DIR,
+};
+use fibonacci_script::audit::AuditLog;
+use fibonacci_script::bundle::{fetch_bundle, LoadedBundle};
+use fibonacci_script::chain::{
+    access_list_savings, l2_fees, safe_transaction_bundle, submit_user_operation, trace_call,
+    wei_to_eth, FeeModel, FibonacciSimple, ISP1Verifier, OnchainRunReport, SettlementReceipt,
+    RUN_REPORT_PATH,
+};
+use fibonacci_script::config::{self, ConfigError, UserOpConfig, DEFAULT_CONTRACT_ADDRESS};
+use fibonacci_script::deadline::{parse_secs, with_timeout};
+use fibonacci_script::gas::wait_for_base_fee;
+use fibonacci_script::jobs::{JobError, JobStore, Phase, DEFAULT_JOB_STORE_URL};
+use fibonacci_script::revert::{decode_revert, decode_sp1_revert, revert_data};
+use fibonacci_script::verifiers::{proof_system, resolve_gateway};
+use fibonacci_script::Error;
+use serde_json::{json, Value};
+use std::path::Path;
 use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-sol! {
-    #[sol(rpc)]
-    interface IFibonacciSimple {
-        struct PublicValuesStruct {
-            uint32 n;
-            uint32 a;
-            uint32 b;
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The contract to verify against: an address, an ENS name, or an alias from the address book
+    #[arg(long, env = "FIBONACCI_CONTRACT_ADDRESS", default_value = DEFAULT_CONTRACT_ADDRESS)]
+    contract: ContractTarget,
+
+    /// Submit the proof even if its `validUntil` timestamp has already passed
+    #[arg(long)]
+    allow_expired: bool,
+
+    /// Send the verification transaction (signed with PRIVATE_KEY) instead of only simulating it
+    #[arg(long)]
+    submit: bool,
+
+    /// Where to get the ETH/USD price for cost estimates: `none`, `coingecko`, or a fixed price
+    #[arg(long, env = "ETH_PRICE_SOURCE", default_value = "coingecko")]
+    price_source: PriceSource,
+
+    /// How to submit the transaction: from the PRIVATE_KEY account, or as an ERC-4337 user
+    /// operation from SMART_ACCOUNT_ADDRESS through BUNDLER_URL
+    #[arg(long, value_enum, default_value = "eoa")]
+    submit_mode: SubmitMode,
+
+    /// Refuse to submit if the projected total cost (including any L1 data fee) exceeds this
+    #[arg(long)]
+    max_cost_eth: Option<f64>,
+
+    /// Before submitting, wait until the base fee of the next block is at most this many gwei
+    #[arg(long, requires = "submit")]
+    wait_for_gwei: Option<f64>,
+
+    /// Give up on `--wait-for-gwei` after this many seconds, without submitting
+    #[arg(long, value_parser = parse_secs, default_value = "3600")]
+    wait_within: Duration,
+
+    /// Do not generate an EIP-2930 access list for the transaction. By default one is generated
+    /// with `eth_createAccessList` and attached when it lowers the gas estimate
+    #[arg(long)]
+    no_access_list: bool,
+
+    /// Instead of broadcasting, write a Safe Transaction Builder bundle with the verification call
+    /// to the deployed contract. Deploying the contract is not exported: use the Foundry scripts
+    #[arg(long, conflicts_with = "submit")]
+    export_safe_tx: Option<String>,
+
+    /// The Safe that will execute the exported bundle (recorded in the bundle metadata)
+    #[arg(long, requires = "export_safe_tx")]
+    safe_address: Option<Address>,
+
+    /// Skip the consumer contract and call `verifyProof` on the SP1 verifier gateway directly, to
+    /// check a proof before any consumer contract is deployed
+    #[arg(long, conflicts_with_all = ["submit", "export_safe_tx"])]
+    via_gateway: bool,
+
+    /// The verifier gateway `--via-gateway` calls [default: the official gateway for the chain
+    /// and proof system, or the one in the verifier book]
+    #[arg(long, env = "SP1_VERIFIER_GATEWAY")]
+    gateway: Option<Address>,
+
+    /// The program vkey hash `--via-gateway` verifies against, or a file containing it
+    #[arg(long, env = "FIBONACCI_PROGRAM_VKEY", default_value = "artifacts/verification_key.txt")]
+    vkey: String,
+
+    /// Verify the proof of this bundle instead of the saved call data: a `.sp1bundle` file or a
+    /// manifest, at an `https://` URL, in S3 (`s3://<bucket>/<path>`) or on disk. Its files are
+    /// checked against the manifest before anything is verified
+    #[arg(long)]
+    bundle: Option<String>,
+
+    /// Refuse the bundle unless its manifest has this hash, e.g. the one a settlement refers to
+    #[arg(long, requires = "bundle")]
+    manifest_hash: Option<B256>,
+
+    /// The job store submitted transactions are recorded in, linked to the jobs that proved them
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    /// Give up on any single RPC request after this many seconds
+    #[arg(long, value_parser = parse_secs, default_value = "30")]
+    rpc_timeout: Duration,
+
+    /// Abort (cancelling in-flight requests) if the whole run takes longer than this many seconds
+    #[arg(long, value_parser = parse_secs)]
+    deadline: Option<Duration>,
+}
+
+/// How the verification transaction is submitted.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum SubmitMode {
+    Eoa,
+    UserOp,
+}
+
+/// Where to get the ETH/USD price used for cost estimates.
+#[derive(Clone, Debug)]
+enum PriceSource {
+    None,
+    Coingecko,
+    Fixed(f64),
+}
+
+impl FromStr for PriceSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "coingecko" => Ok(Self::Coingecko),
+            price => price
+                .parse()
+                .map(Self::Fixed)
+                .map_err(|_| format!("expected `none`, `coingecko` or a price, got `{}`", price)),
         }
+    }
+}
 
-        function verifyFibonacciProof(bytes calldata proofBytes, bytes calldata publicValues) 
-            external view returns (uint32 n, uint32 a, uint32 b);
-            
-        function getProgramVKey() external view returns (bytes32);
+impl PriceSource {
+    /// Fetch the current ETH/USD price, if a price source is configured and reachable.
+    async fn eth_usd(&self) -> Option<f64> {
+        match self {
+            Self::None => None,
+            Self::Fixed(price) => Some(*price),
+            Self::Coingecko => {
+                let url =
+                    "https://api.coingecko.com/api/v3/simple/price?ids=ethereum&vs_currencies=usd";
+                let response = match reqwest::get(url).await {
+                    Ok(response) => response.json::<Value>().await.ok(),
+                    Err(_) => None,
+                };
+                let price = response.and_then(|body| body["ethereum"]["usd"].as_f64());
+                if price.is_none() {
+                    println!("⚠️  Could not fetch the ETH/USD price from CoinGecko");
+                }
+                price
+            }
+        }
     }
 }
 
+/// Warn when a proof expires within this many seconds, as it may be stale by the time it lands.
+const EXPIRY_WARNING_SECS: u64 = 600;
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     dotenv::dotenv().ok();
     env_logger::init();
 
+    let args = Args::parse();
+
+    let deadline = args.deadline;
+    if let Err(err) = with_timeout("run", deadline, run(args)).await {
+        eprintln!("❌ {}", err);
+        std::process::exit(err.exit_code());
+    }
+}
+
+async fn run(args: Args) -> Result<(), Error> {
     println!("🔍 SP1 On-Chain Proof Verification");
     println!("==================================");
 
-    // Load contract address
-    let contract_address = env::var("FIBONACCI_CONTRACT_ADDRESS")
-        .unwrap_or_else(|_| "0x44a4c90114d64A027DB4630639153DC54eaA6224".to_string());
-    
-    println!("📝 Contract Address: {}", contract_address);
-    
     // Setup provider
-    let rpc_url = env::var("RPC_URL").unwrap_or_else(|_| "https://rpc.sepolia.succinct.xyz".to_string());
-    let provider = ProviderBuilder::new().on_http(rpc_url.parse()?);
-    
-    // Parse contract address
-    let contract_address = Address::from_str(&contract_address)?;
-    
-    println!("🔍 Reading contract call data...");
-    
-    // Read contract call data
-    let call_data_path = "artifacts/contract_call_data_n10.json";
-    if !std::path::Path::new(call_data_path).exists() {
-        println!("❌ Contract call data not found at {}", call_data_path);
-        println!("   Please run: cargo run --release --bin network_evm -- --system plonk");
-        return Ok(());
+    let rpc_timeout = Some(args.rpc_timeout);
+    let rpc_url = config::rpc_url();
+    let provider = ProviderBuilder::new().on_http(config::rpc_endpoint()?);
+
+    if args.via_gateway {
+        return verify_via_gateway(&args, &provider).await;
+    }
+
+    // Resolve the contract address
+    let contract_address =
+        with_timeout("resolving the contract", rpc_timeout, args.contract.resolve(&rpc_url))
+            .await?;
+    if args.contract == ContractTarget::Address(contract_address) {
+        println!("📝 Contract Address: {}", contract_address);
+    } else {
+        println!("📝 Contract Address: {} ({})", contract_address, args.contract);
     }
     
-    let call_data_content = fs::read_to_string(call_data_path)?;
-    let call_data: Value = serde_json::from_str(&call_data_content)?;
-    
-    // Extract proof bytes and public values
-    let proof_bytes = call_data["parameters"]["proofBytes"]
-        .as_str()
-        .ok_or_else(|| eyre::eyre!("Missing proofBytes"))?;
-    let public_values = call_data["parameters"]["publicValues"]
-        .as_str()
-        .ok_or_else(|| eyre::eyre!("Missing publicValues"))?;
-    
-    println!("🔍 Proof bytes length: {}", proof_bytes.len());
-    println!("🔍 Public values: {}", public_values);
-    
-    // Convert hex strings to bytes
-    let proof_bytes = hex::decode(proof_bytes.strip_prefix("0x").unwrap_or(proof_bytes))?;
-    let public_values_bytes = hex::decode(public_values.strip_prefix("0x").unwrap_or(public_values))?;
-    
-    println!("📊 Proof size: {} bytes", proof_bytes.len());
-    println!("📊 Public values size: {} bytes", public_values_bytes.len());
+    let (call_data, proof_bundle) = load_call_data(&args)?;
+
+    // Refuse to submit proofs that are bound to a different chain or consumer contract
+    let decoded = call_data.decode_public_values()?;
+    let chain_id = with_timeout("eth_chainId", rpc_timeout, provider.get_chain_id()).await?;
+    if !decoded.is_valid_for(chain_id, contract_address) {
+        return Err(Error::Rejected(format!(
+            "proof is bound to chain {} / consumer {}, but the target is chain {} / contract {}",
+            decoded.chainId, decoded.consumer, chain_id, contract_address
+        )));
+    }
+
+    if decoded.is_rejected() {
+        println!(
+            "ℹ️  The proof settles the program's rejection of n = {} ({})",
+            decoded.n,
+            decoded.status_name()
+        );
+    }
+
+    // Refuse to submit stale proofs
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if decoded.is_expired(now) {
+        if !args.allow_expired {
+            return Err(Error::Rejected(format!(
+                "proof expired at {} (now {}); pass --allow-expired to submit it anyway",
+                decoded.validUntil, now
+            )));
+        }
+        println!("⚠️  Proof expired at {} (now {})", decoded.validUntil, now);
+        println!("⚠️  Submitting expired proof because --allow-expired was set");
+    } else if decoded.validUntil != 0 && decoded.validUntil - now < EXPIRY_WARNING_SECS {
+        println!("⚠️  Proof expires in {} seconds", decoded.validUntil - now);
+    }
+
+    // Refuse to settle a request that is being settled or has been, by anyone sharing the job
+    // store
+    let nonce_store = match decoded.nonce {
+        0 => None,
+        _ => Some(JobStore::connect(&args.job_store).await?),
+    };
+    if let Some(store) = &nonce_store {
+        if store.nonce_used(contract_address, chain_id, decoded.nonce).await? {
+            return Err(Error::Rejected(format!(
+                "nonce {} has already been settled against this contract",
+                decoded.nonce
+            )));
+        }
+    }
     
     // Create contract instance
-    let contract = IFibonacciSimple::new(contract_address, provider);
+    let contract = FibonacciSimple::new(contract_address, &provider);
     
     println!("🔍 Checking contract VKey...");
-    let vkey = contract.getProgramVKey().call().await?;
-    println!("🔑 Contract VKey: 0x{}", hex::encode(vkey._0));
+    let vkey =
+        with_timeout("getProgramVKey", rpc_timeout, contract.getProgramVKey().call()).await?;
+    let vkey = VkHash::from(vkey._0);
+    println!("🔑 Contract VKey: {}", vkey);
     
     println!("🔍 Calling verifyFibonacciProof...");
     
     // Call verifyFibonacciProof
-    let result = contract.verifyFibonacciProof(proof_bytes.into(), public_values_bytes.into()).call().await;
+    let call = contract
+        .verifyFibonacciProof(call_data.public_values.clone(), call_data.proof_bytes.clone());
+    // Kept as a `Result` rather than propagated: a revert is reported below, not returned.
+    let limit = args.rpc_timeout;
+    let result = tokio::time::timeout(limit, call.call())
+        .await
+        .map_err(|_| Error::Timeout { operation: "verifyFibonacciProof", limit })?;
+
+    let audit = AuditLog::from_env();
+    let mut details = json!({
+        "chain_id": chain_id,
+        "contract": contract_address.to_string(),
+        "vkey": vkey,
+        "n": decoded.n,
+        "nonce": decoded.nonce,
+        "public_values_hash": keccak256(&call_data.public_values),
+        "proof_hash": keccak256(&call_data.proof_bytes),
+    });
+    audit.record("verify", details.clone(), &result)?;
     
     match result {
-        Ok(response) => {
+        Ok(_) => {
             println!("✅ Proof verification successful!");
             println!("📊 Results:");
-            println!("   n: {}", response.n);
-            println!("   Fibonacci({}) = {}", response.n - 1, response.a);
-            println!("   Fibonacci({}) = {}", response.n, response.b);
+            println!("   n: {}", decoded.n);
+            let sequence = decoded.sequence().unwrap_or(Sequence::Fibonacci);
+            println!("   {}({}) = {}", sequence, decoded.n.saturating_sub(1), decoded.a);
+            println!("   {}({}) = {}", sequence, decoded.n, decoded.b);
             
             // Verify the math
-            if response.n == 10 && response.a == 55 && response.b == 89 {
+            if fibonacci_lib::seeded(decoded.n, sequence.seeds()) == (decoded.a, decoded.b) {
                 println!("🎉 Mathematics verified correctly!");
-                println!("   Fibonacci(9) = 55 ✓");
-                println!("   Fibonacci(10) = 89 ✓");
+                println!("   {}({}) = {} ✓", sequence, decoded.n.saturating_sub(1), decoded.a);
+                println!("   {}({}) = {} ✓", sequence, decoded.n, decoded.b);
             } else {
                 println!("⚠️  Unexpected values returned");
             }
+
+            // Estimate what settling the proof costs before sending anything
+            let fee_model = FeeModel::for_chain(chain_id);
+            let fees = with_timeout(
+                "estimating L2 fees",
+                rpc_timeout,
+                l2_fees(&provider, fee_model, contract_address, call.calldata()),
+            )
+            .await?;
+            let mut gas_estimate = match fees.gas_estimate {
+                Some(gas_estimate) => gas_estimate,
+                None => u128::from(
+                    with_timeout("eth_estimateGas", rpc_timeout, call.estimate_gas()).await?,
+                ),
+            };
+            // User operations are executed by the entry point, so only a transaction sent
+            // directly can carry an access list.
+            let access_list = if args.no_access_list || args.submit_mode != SubmitMode::Eoa {
+                None
+            } else {
+                let from = config::signer().ok().map(|signer| signer.address());
+                let savings = with_timeout(
+                    "eth_createAccessList",
+                    rpc_timeout,
+                    access_list_savings(&provider, from, contract_address, call.calldata()),
+                )
+                .await;
+                match savings {
+                    Ok(savings) => savings,
+                    Err(err) => {
+                        println!("⚠️  Not using an access list: {}", err);
+                        None
+                    }
+                }
+            };
+            let access_list_gas_saved = access_list.as_ref().map(|savings| savings.gas_saved);
+            gas_estimate = gas_estimate.saturating_sub(access_list_gas_saved.unwrap_or_default());
+            let gas_price =
+                with_timeout("eth_gasPrice", rpc_timeout, provider.get_gas_price()).await?;
+            let estimated_cost_eth =
+                wei_to_eth(gas_estimate * gas_price + fees.l1_fee_wei.unwrap_or_default());
+            let eth_usd_price = args.price_source.eth_usd().await;
+            let mut report = OnchainRunReport {
+                chain_id,
+                contract: contract_address.to_string(),
+                fee_model,
+                gas_estimate,
+                access_list_gas_saved,
+                gas_price_wei: gas_price,
+                l1_gas_estimate: fees.l1_gas_estimate,
+                l1_fee_wei: fees.l1_fee_wei,
+                estimated_cost_eth,
+                eth_usd_price,
+                estimated_cost_usd: eth_usd_price.map(|price| price * estimated_cost_eth),
+                ..Default::default()
+            };
+            report.print_estimate();
+
+            if let Some(path) = &args.export_safe_tx {
+                let bundle = safe_transaction_bundle(
+                    chain_id,
+                    args.safe_address,
+                    contract_address,
+                    call.calldata(),
+                    decoded.n,
+                );
+                write_json(Path::new(path), &bundle)?;
+                println!("💾 Safe transaction bundle saved to: {}", path);
+                println!("   Import it in the Safe{{Wallet}} Transaction Builder to settle the proof");
+            }
+
+            if let Some(max_cost_eth) = args.max_cost_eth.filter(|_| args.submit) {
+                if estimated_cost_eth > max_cost_eth {
+                    return Err(Error::Rejected(format!(
+                        "projected cost {:.6} ETH exceeds --max-cost-eth {}, not submitting",
+                        estimated_cost_eth, max_cost_eth
+                    )));
+                }
+            }
+
+            if let Some(max_gwei) = args.wait_for_gwei {
+                let limit = args.wait_within;
+                println!("⏳ Waiting up to {:?} for a base fee of {} gwei", limit, max_gwei);
+                let poll = Duration::from_secs(12);
+                match wait_for_base_fee(&provider, max_gwei, limit, poll).await? {
+                    Some(gwei) => println!("⛽ Base fee is down to {:.3} gwei", gwei),
+                    None => {
+                        let operation = "waiting for the base fee";
+                        return Err(Error::Timeout { operation, limit });
+                    }
+                }
+            }
+
+            if args.submit {
+                let signer = config::signer()?;
+                let signer_address = signer.address();
+                if let Some(store) = &nonce_store {
+                    if !store.claim_nonce(contract_address, chain_id, decoded.nonce).await? {
+                        return Err(Error::Rejected(format!(
+                            "nonce {} is already being settled against this contract",
+                            decoded.nonce
+                        )));
+                    }
+                }
+                let started = Instant::now();
+                let submitted = send_verification(
+                    args.submit_mode,
+                    signer,
+                    contract_address,
+                    call.calldata().clone(),
+                    access_list.map(|savings| savings.access_list),
+                    chain_id,
+                    rpc_timeout,
+                )
+                .await;
+                let submit_time = started.elapsed();
+                if let Some(store) = &nonce_store {
+                    match &submitted {
+                        Ok((receipt, _)) if receipt.status() => {
+                            let tx_hash = receipt.transaction_hash;
+                            let nonce = decoded.nonce;
+                            store.settle_nonce(contract_address, chain_id, nonce, tx_hash).await?;
+                            println!("📝 Nonce {} recorded in the job store", decoded.nonce);
+                        }
+                        // The request was not settled, so it may be submitted again.
+                        _ => store.release_nonce(contract_address, chain_id, decoded.nonce).await?,
+                    }
+                }
+
+                details["signer"] = json!(signer_address.to_string());
+                details["submit_mode"] = json!(format!("{:?}", args.submit_mode).to_lowercase());
+                let outcome = match &submitted {
+                    Ok((receipt, user_op_hash)) => {
+                        details["tx_hash"] = json!(receipt.transaction_hash.to_string());
+                        if let Some(user_op_hash) = user_op_hash {
+                            details["user_op_hash"] = json!(user_op_hash.to_string());
+                        }
+                        if receipt.status() { "ok" } else { "reverted" }.to_string()
+                    }
+                    Err(err) => format!("failed: {}", err),
+                };
+                audit.append("submit", details, outcome)?;
+
+                let (receipt, user_op_hash) = submitted?;
+                report.user_op_hash = user_op_hash.map(|hash| hash.to_string());
+                report.record_receipt(&receipt);
+                println!("✅ Transaction mined: {}", receipt.transaction_hash);
+                println!(
+                    "   Gas used: {} ({:.6} ETH)",
+                    receipt.gas_used,
+                    report.actual_cost_eth.unwrap_or_default()
+                );
+
+                let artifacts_dir = Path::new(DEFAULT_ARTIFACTS_DIR);
+                let proof_hash = keccak256(&call_data.proof_bytes);
+                let manifest_hash = match &proof_bundle {
+                    Some(proof_bundle) => Some(proof_bundle.manifest_hash),
+                    None => Manifest::hash_for(artifacts_dir, decoded.n, proof_hash)?,
+                };
+                let settlement = SettlementReceipt::new(
+                    &receipt,
+                    chain_id,
+                    contract_address,
+                    &call_data,
+                    manifest_hash,
+                )?;
+                let settlement_path = settlement.save(artifacts_dir)?;
+                println!("💾 Settlement receipt saved to: {}", settlement_path.display());
+                let recorded =
+                    record_settlement(&args.job_store, &settlement, &settlement_path, submit_time);
+                match recorded.await {
+                    Ok(Some(job_id)) => println!("🔗 Settlement linked to job {}", job_id),
+                    Ok(None) => {}
+                    Err(err) => println!("⚠️  Could not record the settlement: {}", err),
+                }
+            }
+
+            report.save()?;
+            println!("💾 Run report saved to: {}", RUN_REPORT_PATH);
         }
         Err(e) => {
             println!("❌ Proof verification failed!");
-            println!("   Error: {}", e);
-            
-            // Try to provide more specific error information
-            if e.to_string().contains("revert") {
-                println!("   This might be due to:");
-                println!("   - Invalid proof data");
-                println!("   - Wrong program VKey");
-                println!("   - Contract not properly deployed");
-            }
+
+            let reason = match revert_data(&e) {
+                Some(data) => {
+                    match decode_revert(&data) {
+                        Some(decoded) => {
+                            println!("   Reverted with: {}", decoded.error);
+                            println!("   Likely cause: {}", decoded.hint);
+                            decoded.error
+                        }
+                        None => {
+                            println!("   Reverted with unknown data: {}", data);
+                            data.to_string()
+                        }
+                    }
+                }
+                None => {
+                    // The call never reached the contract, so this is not a rejection.
+                    return Err(e.into());
+                }
+            };
+
+            // Pinpoint the failing frame, if the RPC supports tracing.
+            trace_failure(&rpc_url, contract_address, call.calldata(), args.rpc_timeout).await;
+
+            return Err(Error::Rejected(format!("the contract rejected the proof: {}", reason)));
         }
     }
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Read the contract call data saved by `network_evm`, or that of `--bundle`, with the bundle.
+fn load_call_data(args: &Args) -> Result<(CallData, Option<LoadedBundle>), Error> {
+    let (call_data, bundle) = match &args.bundle {
+        Some(uri) => {
+            println!("🔍 Fetching proof bundle {}...", uri);
+            let bundle = fetch_bundle(uri, args.manifest_hash)?;
+            println!("✅ Bundle matches its manifest {}", bundle.manifest_hash);
+            (bundle.call_data()?, Some(bundle))
+        }
+        None => {
+            println!("🔍 Reading contract call data...");
+            let call_data_path = call_data_path(Path::new(DEFAULT_ARTIFACTS_DIR), 10);
+            let call_data = CallData::load(&call_data_path).map_err(|err| {
+                println!("❌ Contract call data could not be read");
+                println!("   Please run: cargo run --release --bin network_evm -- --system plonk");
+                err
+            })?;
+            (call_data, None)
+        }
+    };
+
+    println!("🔍 Public values: {}", call_data.public_values);
+    println!("📊 Proof size: {} bytes", call_data.proof_bytes.len());
+    println!("📊 Public values size: {} bytes", call_data.public_values.len());
+    Ok((call_data, bundle))
+}
+
+/// Verify the proof with the SP1 verifier gateway alone. This checks the proof against the vkey
+/// only: binding the public values to a chain and consumer is the consumer contract's job, so the
+/// binding is reported but not enforced.
+async fn verify_via_gateway<T, P>(args: &Args, provider: &P) -> Result<(), Error>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let rpc_timeout = Some(args.rpc_timeout);
+    let vkey: VkHash = match std::fs::read_to_string(&args.vkey) {
+        Ok(contents) => contents.parse(),
+        Err(_) => args.vkey.parse(),
+    }
+    .map_err(|reason| ConfigError::Invalid { name: "vkey", reason })?;
+    println!("🔑 Program VKey: {}", vkey);
+
+    let (call_data, bundle) = load_call_data(args)?;
+    if let Some(bundle) = bundle.filter(|bundle| bundle.manifest.vkey != vkey) {
+        return Err(Error::Rejected(format!(
+            "the bundle was proved for vkey {}, not {}",
+            bundle.manifest.vkey, vkey
+        )));
+    }
+    let decoded = call_data.decode_public_values()?;
+    let system = proof_system(&call_data.proof_bytes).ok_or_else(|| {
+        Error::Rejected("the proof is not a Groth16 or PLONK proof for this SP1 version".into())
+    })?;
+    let resolved = resolve_gateway(provider, system, args.gateway);
+    let resolved = with_timeout("resolving the verifier", rpc_timeout, resolved).await?;
+    let chain_id = resolved.chain_id;
+    println!("📝 Verifier gateway: {} ({})", resolved.gateway, resolved.source);
+    println!(
+        "   Routes {} proofs ({}) to verifier {} ({})",
+        system, resolved.selector, resolved.verifier, resolved.version
+    );
+    println!(
+        "ℹ️  The public values are bound to chain {} / consumer {} (not checked by the gateway)",
+        decoded.chainId, decoded.consumer
+    );
+
+    println!("🔍 Calling verifyProof on the gateway...");
+    let gateway = ISP1Verifier::new(resolved.gateway, provider);
+    let call = gateway.verifyProof(
+        vkey.0,
+        call_data.public_values.clone(),
+        call_data.proof_bytes.clone(),
+    );
+    let limit = args.rpc_timeout;
+    let result = tokio::time::timeout(limit, call.call())
+        .await
+        .map_err(|_| Error::Timeout { operation: "verifyProof", limit })?;
+
+    let details = json!({
+        "chain_id": chain_id,
+        "gateway": resolved.gateway.to_string(),
+        "vkey": vkey,
+        "n": decoded.n,
+        "nonce": decoded.nonce,
+        "public_values_hash": keccak256(&call_data.public_values),
+        "proof_hash": keccak256(&call_data.proof_bytes),
+    });
+    AuditLog::from_env().record("verify", details, &result)?;
+
+    match result {
+        Ok(_) => {
+            println!("✅ The verifier gateway accepted the proof");
+            println!("   {}", decoded);
+            Ok(())
+        }
+        Err(e) => {
+            println!("❌ Proof verification failed!");
+            let Some(data) = revert_data(&e) else {
+                // The call never reached the gateway, so this is not a rejection.
+                return Err(e.into());
+            };
+            let reason = match decode_sp1_revert(&data) {
+                Some(decoded) => {
+                    println!("   Reverted with: {}", decoded.error);
+                    println!("   Likely cause: {}", decoded.hint);
+                    decoded.error
+                }
+                None => {
+                    println!("   Reverted with unknown data: {}", data);
+                    data.to_string()
+                }
+            };
+            Err(Error::Rejected(format!("the verifier gateway rejected the proof: {}", reason)))
+        }
+    }
+}
+
+/// Record a settlement in the job store, and how long it took to submit (`submit_time`), returning
+/// the job it is linked to.
+async fn record_settlement(
+    job_store: &str,
+    settlement: &SettlementReceipt,
+    path: &Path,
+    submit_time: Duration,
+) -> Result<Option<String>, JobError> {
+    let store = JobStore::connect(job_store).await?;
+    let job_id = store.record_settlement(settlement, &path.display().to_string()).await?;
+    if let Some(job_id) = &job_id {
+        store.record_phase(job_id, Phase::Submit, submit_time).await?;
+    }
+    Ok(job_id)
+}
+
+/// Send the verification transaction the way `--submit-mode` asks for, returning its receipt and,
+/// for a user operation, the user operation hash.
+async fn send_verification(
+    mode: SubmitMode,
+    signer: PrivateKeySigner,
+    contract_address: Address,
+    calldata: Bytes,
+    access_list: Option<AccessList>,
+    chain_id: u64,
+    rpc_timeout: Option<Duration>,
+) -> Result<(TransactionReceipt, Option<B256>), Error> {
+    match mode {
+        SubmitMode::Eoa => {
+            let wallet_provider = ProviderBuilder::new()
+                .with_recommended_fillers()
+                .wallet(EthereumWallet::from(signer))
+                .on_http(config::rpc_endpoint()?);
+
+            println!("📤 Submitting verification transaction...");
+            let mut tx =
+                TransactionRequest::default().with_to(contract_address).with_input(calldata);
+            if let Some(access_list) = access_list {
+                println!("   With an access list of {} addresses", access_list.len());
+                tx = tx.with_access_list(access_list);
+            }
+            let sent = wallet_provider.send_transaction(tx);
+            let receipt = with_timeout("sending the transaction", rpc_timeout, sent)
+                .await?
+                .get_receipt()
+                .await?;
+            Ok((receipt, None))
+        }
+        SubmitMode::UserOp => {
+            println!("📤 Submitting verification as an ERC-4337 user operation...");
+            let (user_op_hash, receipt) = submit_user_operation(
+                &config::rpc_url(),
+                &UserOpConfig::from_env()?,
+                &signer,
+                contract_address,
+                calldata,
+                chain_id,
+            )
+            .await?;
+            Ok((receipt, Some(user_op_hash)))
+        }
+    }
+}
+
+/// Re-run the failed call with `debug_traceCall` and report which frame failed and who is at fault.
+async fn trace_failure(
+    rpc_url: &str,
+    contract_address: Address,
+    calldata: &Bytes,
+    rpc_timeout: Duration,
+) {
+    println!();
+    println!("🔬 Tracing the failed call with debug_traceCall...");
+
+    let traced = trace_call(rpc_url, contract_address, calldata);
+    let (trace, verifier) = match with_timeout("debug_traceCall", Some(rpc_timeout), traced).await {
+        Ok(traced) => traced,
+        Err(err) => {
+            println!("   Tracing is not available on this RPC: {}", err);
+            return;
+        }
+    };
+
+    let Some(failing) = trace.failing_frame() else {
+        println!("   The traced call succeeded; the failure may depend on the pending state");
+        return;
+    };
+    println!(
+        "   Failing frame: {} {} -> {} (selector 0x{})",
+        failing.kind,
+        failing.from,
+        failing.to.map(|to| to.to_string()).unwrap_or_default(),
+        hex::encode(failing.selector().unwrap_or_default())
+    );
+    println!("   Frame error: {}", failing.error.as_deref().unwrap_or_default());
+    let output = failing.output.clone().unwrap_or_default();
+    let sp1_error = decode_sp1_revert(&output);
+    if let Some(decoded) = decode_revert(&output) {
+        println!("   Revert: {}", decoded.error);
+    } else if let Some(reason) = &failing.revert_reason {
+        println!("   Revert: {}", reason);
+    }
+
+    let gateway = trace.find_call_to(verifier);
+    match gateway {
+        Some(gateway) => println!(
+            "   Verifier gateway call ({}): {}",
+            verifier,
+            if gateway.failed() { "failed" } else { "succeeded" }
+        ),
+        None => println!("   Verifier gateway ({}) was never called", verifier),
+    }
+
+    let fault = match (gateway, &sp1_error) {
+        (Some(gateway), Some(err)) if gateway.contains(failing) => {
+            if err.error.starts_with("InvalidProof") {
+                "the proof or the program vkey: the proof does not verify against the \
+                 contract's vkey"
+            } else {
+                "the proof: it targets a verifier version the gateway does not route to"
+            }
+        }
+        (Some(gateway), None) if gateway.contains(failing) => {
+            "the SP1 verifier, which failed without a known error"
+        }
+        (Some(_), _) => {
+            "the consumer contract: the proof verified, but the contract rejected the call"
+        }
+        (None, _) => "the consumer contract: it failed before calling the verifier",
+    };
+    println!("   ➡️  At fault: {}", fault);
+}
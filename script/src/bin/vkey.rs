@@ -1,10 +1,53 @@
-use sp1_sdk::{include_elf, HashableKey, Prover, ProverClient};
+//! Print the vkey of a program without proving anything: only the program setup is run.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! cargo run --release --bin vkey -- --update-env
+//! ```
 
-/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
-pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+use clap::Parser;
+use fibonacci_script::config::set_in_env_file;
+use fibonacci_script::programs::{program, save_vkey, Program, PROGRAMS, VKEYS_PATH};
+use fibonacci_script::prover::vk_hash;
+use sp1_sdk::{Prover, ProverClient};
+use std::path::{Path, PathBuf};
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// The program to print the vkey of
+    #[arg(long, value_parser = program, default_value = "fibonacci", conflicts_with = "all")]
+    program: &'static Program,
+
+    /// Print the vkeys of every registered program
+    #[arg(long)]
+    all: bool,
+
+    /// Also set `<PROGRAM>_PROGRAM_VKEY` (e.g. FIBONACCI_PROGRAM_VKEY) in this env file
+    #[arg(long, num_args = 0..=1, default_missing_value = ".env")]
+    update_env: Option<PathBuf>,
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let programs = if args.all { PROGRAMS } else { std::slice::from_ref(args.program) };
 
-fn main() {
     let prover = ProverClient::builder().cpu().build();
-    let (_, vk) = prover.setup(FIBONACCI_ELF);
-    println!("{}", vk.bytes32());
-}
\ No newline at end of file
+    for program in programs {
+        let (_, vk) = prover.setup(program.elf);
+        let vkey_hash = vk_hash(&vk);
+        save_vkey(Path::new(VKEYS_PATH), program, &vkey_hash)?;
+        if args.all {
+            println!("{} {}", program, vkey_hash);
+        } else {
+            println!("{}", vkey_hash);
+        }
+        if let Some(env_file) = &args.update_env {
+            let name = format!("{}_PROGRAM_VKEY", program.name.to_uppercase());
+            set_in_env_file(env_file, &name, &vkey_hash.to_string())?;
+            eprintln!("📝 Set {} in {}", name, env_file.display());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,642 @@
+//! A proving worker. By default it works through the job store (fed with `cli jobs submit`); with
+//! the `nats` feature it can instead pull proof requests from a NATS JetStream queue. Either way
+//! proofs are cached by input hash, so repeated requests are not proved twice.
+//!
+//! You can run this script using the following command:
+//! ```shell
+//! SP1_PROVER=network cargo run --release --bin worker
+//! ```
+//! or
+//! ```shell
+//! SP1_PROVER=network cargo run --release --features nats --bin worker -- --source nats
+//! ```
+//! Completed proofs, and jobs given up on, are notified to the channels of `notifications.toml`
+//! (or the file `NOTIFICATIONS` names); see [`fibonacci_script::notify`]. With
+//! `--min-network-balance` or `--min-submitter-balance`, the worker also checks the balances
+//! proving and settlement spend from, and notifies them running low.
+
+use alloy_primitives::{keccak256, Address};
+use clap::{Parser, ValueEnum};
+use eyre::Result;
+use fibonacci_lib::ProgramInput;
+use fibonacci_script::artifact_store::{self, ArtifactStore, DEFAULT_ARTIFACT_STORE};
+use fibonacci_script::artifacts::{cached_call_data_key, input_hash, write_stored_json, CallData};
+use fibonacci_script::audit::AuditLog;
+use fibonacci_script::balance::BalanceMonitor;
+use fibonacci_script::config::{self, ConfigError};
+use fibonacci_script::deadline::parse_secs;
+use fibonacci_script::jobs::{
+    JobOptions, JobStore, Phase, Priority, ProvingRun, DEFAULT_JOB_STORE_URL,
+};
+use fibonacci_script::notify::{Event, EventKind, Notifications};
+use fibonacci_script::prover::{FibonacciProver, NetworkStrategy, ProofSystem, ProverError};
+use fibonacci_script::shutdown::Shutdown;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The arguments for the command.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Where proof requests come from
+    #[arg(long, value_enum, default_value = "store")]
+    source: Source,
+
+    /// The job store to work through: `sqlite://...`, or `postgres://...` with the `postgres`
+    /// feature
+    #[arg(long, env = "JOB_STORE_URL", default_value = DEFAULT_JOB_STORE_URL)]
+    job_store: String,
+
+    /// Seconds between polls of the job store when it is empty
+    #[arg(long, default_value = "5")]
+    poll_interval: u64,
+
+    /// How many times a job is attempted before it is given up on
+    #[arg(long, default_value = "3")]
+    max_attempts: u32,
+
+    /// Limit how many jobs of a priority run at once across all workers, e.g. `background=1`
+    /// (repeatable)
+    #[arg(long, value_parser = parse_limit)]
+    max_running: Vec<(Priority, u32)>,
+
+    /// How the prover network fulfills jobs of a priority that do not set their own strategy,
+    /// e.g. `urgent=reserved` or `background=auction` (repeatable)
+    #[arg(long, value_parser = parse_strategy)]
+    network_strategy: Vec<(Priority, NetworkStrategy)>,
+
+    /// Re-prove completed proofs this many seconds before their committed `validUntil`, so a
+    /// fresh proof is always available
+    #[arg(long, value_parser = parse_secs)]
+    reprove_before: Option<Duration>,
+
+    /// Where proofs are cached, under `cache/<input hash>.json` (or
+    /// `tenants/<tenant>/cache/<input hash>.json` for jobs of tenants other than `default`): a
+    /// directory, or `s3://<bucket>/<prefix>`
+    #[arg(long, alias = "output-dir", env = "ARTIFACT_STORE")]
+    #[arg(default_value = DEFAULT_ARTIFACT_STORE)]
+    artifact_store: String,
+
+    /// Only work on the jobs of this tenant (repeatable); all tenants by default
+    #[arg(long)]
+    tenant: Vec<String>,
+
+    /// Warn when the prover network credits of `NETWORK_PRIVATE_KEY` drop under this many PROVE
+    #[arg(long, env = "MIN_NETWORK_BALANCE")]
+    min_network_balance: Option<f64>,
+
+    /// Warn when the account submitting proofs has less ETH than this on any of its chains
+    #[arg(long, env = "MIN_SUBMITTER_BALANCE")]
+    min_submitter_balance: Option<f64>,
+
+    /// The account submitting proofs (defaults to the account of `PRIVATE_KEY`)
+    #[arg(long, env = "SUBMITTER_ADDRESS")]
+    submitter: Option<Address>,
+
+    /// The RPC endpoints of the chains proofs are submitted on, comma-separated (defaults to
+    /// `RPC_URL`)
+    #[arg(long, env = "BALANCE_RPC_URLS", value_delimiter = ',')]
+    balance_rpc_url: Vec<String>,
+
+    /// Seconds between balance checks
+    #[arg(long, value_parser = parse_secs, default_value = "600")]
+    balance_check_interval: Duration,
+
+    /// Inject faults at random, e.g. `prover=0.2,rpc=0.1,write=0.05,seed=7`, to test how jobs are
+    /// retried and resumed
+    #[cfg(feature = "chaos")]
+    #[arg(long, env = "SP1_CHAOS")]
+    chaos: Option<fibonacci_script::chaos::ChaosConfig>,
+
+    #[cfg(feature = "nats")]
+    #[command(flatten)]
+    nats: nats::NatsArgs,
+}
+
+/// Where proof requests come from.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum Source {
+    /// The job store.
+    Store,
+    /// A NATS JetStream queue.
+    #[cfg(feature = "nats")]
+    Nats,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Setup the logger.
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+
+    // Parse the command line arguments.
+    let args = Args::parse();
+    #[cfg(feature = "chaos")]
+    if let Some(chaos) = args.chaos {
+        fibonacci_script::chaos::install(chaos);
+    }
+
+    println!("🛠️  SP1 Fibonacci Proving Worker");
+    println!("================================");
+
+    // Set up the program once up front; every job proves the same program.
+    let prover = FibonacciProver::new();
+    println!("🔑 Setting up proving keys...");
+    let setup = prover.warm_up();
+    println!("🔑 Proving keys set up in {:.1}s", setup.as_secs_f64());
+
+    let artifacts = artifact_store::open(&args.artifact_store)?;
+    println!("📦 Caching proofs in {}", artifacts.location(""));
+    let notifications = Arc::new(Notifications::from_env()?);
+
+    let shutdown = Shutdown::listen();
+    let monitor = balance_monitor(&args)?;
+    if !monitor.is_empty() {
+        println!("💰 Checking balances every {}s", args.balance_check_interval.as_secs());
+        let interval = args.balance_check_interval;
+        tokio::spawn(watch_balances(monitor, notifications.clone(), interval, shutdown.clone()));
+    }
+    let artifacts = artifacts.as_ref();
+    match args.source {
+        Source::Store => {
+            work_store(&args, &prover, artifacts, &notifications, &shutdown, setup).await?
+        }
+        #[cfg(feature = "nats")]
+        Source::Nats => nats::work(&args, &prover, artifacts, &notifications, &shutdown).await?,
+    }
+
+    println!("👋 Stopped consuming requests");
+    Ok(())
+}
+
+/// Work through the job store until shut down, caching proofs in `artifacts` and recording how
+/// long each phase of a job took. The `setup` of the proving keys is recorded with the first job
+/// the worker proves. Completed jobs and jobs given up on are sent to `notifications`.
+async fn work_store(
+    args: &Args,
+    prover: &FibonacciProver,
+    artifacts: &dyn ArtifactStore,
+    notifications: &Notifications,
+    shutdown: &Shutdown,
+    setup: Duration,
+) -> Result<()> {
+    let store = JobStore::connect(&args.job_store).await?;
+    let hostname = std::env::var("HOSTNAME").unwrap_or_else(|_| "worker".to_string());
+    let worker = format!("{}:{}", hostname, std::process::id());
+    println!("🗄️  Working through {} as {}", args.job_store, worker);
+    if !args.tenant.is_empty() {
+        println!("   Tenants: {}", args.tenant.join(", "));
+    }
+    let max_running: BTreeMap<Priority, u32> = args.max_running.iter().copied().collect();
+    let strategies: BTreeMap<Priority, NetworkStrategy> =
+        args.network_strategy.iter().copied().collect();
+
+    let mut setup = Some(setup);
+    let mut next_renewal = Instant::now();
+    while !shutdown.requested() {
+        if let Some(window) = args.reprove_before.filter(|_| Instant::now() >= next_renewal) {
+            renew_expiring(&store, window).await?;
+            next_renewal = Instant::now() + Duration::from_secs(args.poll_interval);
+        }
+
+        let Some(job) = store.claim(&worker, &max_running, &args.tenant).await? else {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(args.poll_interval)) => {}
+                _ = shutdown.wait() => {}
+            }
+            continue;
+        };
+
+        println!("📥 Job {}: n = {} ({}, {})", job.id, job.input.n, job.system, job.priority);
+        let strategy = job.network_strategy(&strategies);
+        let key = cached_call_data_key(&job.tenant, job.input_hash);
+        let location = artifacts.location(&key);
+        match prove_cached(prover, &job.id, &job.input, job.system, strategy, artifacts, &key) {
+            Ok(timings) => {
+                let cached = timings.is_none();
+                let proof_hash = keccak256(&CallData::load_stored(artifacts, &key)?.proof_bytes);
+                let mut timings = timings.unwrap_or_default();
+                let run = if cached {
+                    None
+                } else {
+                    let started = Instant::now();
+                    let run = proving_run(prover, &job.input);
+                    timings.push((Phase::Execute, started.elapsed()));
+                    timings.extend(setup.take().map(|setup| (Phase::Setup, setup)));
+                    Some(run)
+                };
+                store.complete(&job.id, &location, proof_hash, run.as_ref()).await?;
+                for (phase, duration) in timings {
+                    store.record_phase(&job.id, phase, duration).await?;
+                }
+                let source = if cached { " (from the cache)" } else { "" };
+                println!("✅ Job {} completed{}: {}", job.id, source, location);
+                let message =
+                    format!("Job {} (n = {}) completed: {}", job.id, job.input.n, location);
+                let details = json!({
+                    "job_id": job.id,
+                    "tenant": job.tenant,
+                    "input": job.input,
+                    "input_hash": job.input_hash,
+                    "system": job.system.to_string(),
+                    "artifact": location,
+                    "cached": cached,
+                });
+                notifications.notify(&Event::new(EventKind::ProofCompleted, message, details));
+            }
+            Err(err) => {
+                let retry = !is_invalid_input(&err) && job.attempts < args.max_attempts;
+                println!(
+                    "❌ Job {} failed (attempt {}/{}): {}",
+                    job.id, job.attempts, args.max_attempts, err
+                );
+                store.fail(&job.id, &err.to_string(), retry).await?;
+                if !retry {
+                    let message = format!(
+                        "Job {} (n = {}) failed after {} attempts: {}",
+                        job.id, job.input.n, job.attempts, err
+                    );
+                    let details = json!({
+                        "job_id": job.id,
+                        "tenant": job.tenant,
+                        "input": job.input,
+                        "input_hash": job.input_hash,
+                        "system": job.system.to_string(),
+                        "attempts": job.attempts,
+                        "error": err.to_string(),
+                    });
+                    notifications.notify(&Event::new(EventKind::ProofFailed, message, details));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The balances to watch, from the `--min-*-balance` arguments.
+fn balance_monitor(args: &Args) -> Result<BalanceMonitor> {
+    let mut monitor = BalanceMonitor::default();
+    if let Some(minimum) = args.min_network_balance {
+        // The network client cannot be built without an account.
+        if std::env::var("NETWORK_PRIVATE_KEY").is_err() {
+            return Err(ConfigError::Missing("NETWORK_PRIVATE_KEY").into());
+        }
+        monitor = monitor.network(minimum);
+    }
+    if let Some(minimum) = args.min_submitter_balance {
+        let address = match args.submitter {
+            Some(address) => address,
+            None => config::signer()?.address(),
+        };
+        let rpc_urls = match &args.balance_rpc_url[..] {
+            [] => vec![config::rpc_url()],
+            rpc_urls => rpc_urls.to_vec(),
+        };
+        monitor = monitor.submitter(address, rpc_urls, minimum);
+    }
+    Ok(monitor)
+}
+
+/// Check balances every `interval` until shut down, starting right away so an account that is
+/// already low is reported before the first job.
+async fn watch_balances(
+    mut monitor: BalanceMonitor,
+    notifications: Arc<Notifications>,
+    interval: Duration,
+    shutdown: Shutdown,
+) {
+    while !shutdown.requested() {
+        monitor.check(&notifications).await;
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = shutdown.wait() => {}
+        }
+    }
+}
+
+/// Queue urgent re-proofs of the completed proofs that expire within `window`. Each new proof stays
+/// valid for as long as the one it replaces.
+async fn renew_expiring(store: &JobStore, window: Duration) -> Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    for job in store.expiring(now + window.as_secs()).await? {
+        let valid_until = job.input.valid_until.unwrap_or_default();
+        let validity = valid_until.saturating_sub(job.created_at);
+        let input = ProgramInput { valid_until: Some(now + validity), ..job.input.clone() };
+        let base = job.id.split("-renewal-").next().unwrap_or(&job.id);
+        let id = format!("{}-renewal-{}", base, now);
+        let options = JobOptions {
+            priority: Priority::Urgent,
+            run_after: 0,
+            network_strategy: job.network_strategy,
+            api_key: job.api_key.clone(),
+            max_concurrent: None,
+            tenant: Some(job.tenant.clone()),
+        };
+        let (renewal, _) = store.submit(&id, &input, job.system, options).await?;
+        store.mark_renewed(&job.id, &renewal.id).await?;
+        println!(
+            "🔄 Proof of job {} expires at {}, re-proving it as job {}",
+            job.id, valid_until, renewal.id
+        );
+    }
+    Ok(())
+}
+
+/// Generate a proof for job `job_id` (with `strategy` on the prover network, if given), verify
+/// it and cache its call data at `key` in `artifacts`, unless it is cached already. Returns how
+/// long proving, verifying and saving took, or `None` if the proof was cached.
+fn prove_cached(
+    prover: &FibonacciProver,
+    job_id: &str,
+    input: &ProgramInput,
+    system: ProofSystem,
+    strategy: Option<NetworkStrategy>,
+    artifacts: &dyn ArtifactStore,
+    key: &str,
+) -> Result<Option<Vec<(Phase, Duration)>>> {
+    if artifacts.exists(key)? {
+        return Ok(None);
+    }
+    // Proving blocks (and may drive its own runtime for network requests), so keep it off the
+    // async worker threads.
+    let started = Instant::now();
+    let proof = tokio::task::block_in_place(|| match strategy {
+        Some(strategy) => prover.prove_with_strategy(input, system, strategy),
+        None => prover.prove(input, system),
+    });
+    let mut timings = vec![(Phase::Prove, started.elapsed())];
+    let details = json!({
+        "job_id": job_id,
+        "input": input,
+        "input_hash": input_hash(input, system),
+        "system": system.to_string(),
+        "vkey": prover.vkey_hash(),
+        "network_strategy": strategy,
+    });
+    AuditLog::from_env().record("prove", details, &proof)?;
+    let proof = proof?;
+
+    let started = Instant::now();
+    prover.verify(&proof)?;
+    timings.push((Phase::Verify, started.elapsed()));
+    let started = Instant::now();
+    write_stored_json(artifacts, key, &CallData::from_proof(&proof).to_json()?)?;
+    timings.push((Phase::Save, started.elapsed()));
+    Ok(Some(timings))
+}
+
+/// How a proof of `input` was just generated. The prover network bills by cycles, so they are
+/// counted by executing the program again, which is cheap next to proving it.
+fn proving_run(prover: &FibonacciProver, input: &ProgramInput) -> ProvingRun {
+    ProvingRun {
+        prover: std::env::var("SP1_PROVER").unwrap_or_else(|_| "cpu".to_string()),
+        cycles: prover.execute(input).map(|execution| execution.cycles).unwrap_or_default(),
+    }
+}
+
+/// Parse a `<priority>=<limit>` concurrency limit.
+fn parse_limit(limit: &str) -> Result<(Priority, u32), String> {
+    let (priority, limit) = limit
+        .split_once('=')
+        .ok_or_else(|| format!("expected <priority>=<limit>, got `{}`", limit))?;
+    let priority = Priority::from_str(priority, true)?;
+    let limit = limit.parse().map_err(|_| format!("invalid limit `{}`", limit))?;
+    Ok((priority, limit))
+}
+
+/// Parse a `<priority>=<strategy>` prover network strategy.
+fn parse_strategy(strategy: &str) -> Result<(Priority, NetworkStrategy), String> {
+    let (priority, strategy) = strategy
+        .split_once('=')
+        .ok_or_else(|| format!("expected <priority>=<strategy>, got `{}`", strategy))?;
+    let priority = Priority::from_str(priority, true)?;
+    Ok((priority, NetworkStrategy::from_str(strategy, true)?))
+}
+
+/// Whether a request failed because of its input, and will never succeed.
+fn is_invalid_input(err: &eyre::Report) -> bool {
+    matches!(err.downcast_ref(), Some(ProverError::InvalidInput(_)))
+}
+
+/// Consuming proof requests from NATS JetStream.
+#[cfg(feature = "nats")]
+mod nats {
+    use super::{is_invalid_input, prove_cached, Args};
+    use alloy_primitives::B256;
+    use async_nats::jetstream::{self, AckKind};
+    use eyre::Result;
+    use fibonacci_lib::ProgramInput;
+    use fibonacci_script::artifact_store::ArtifactStore;
+    use fibonacci_script::artifacts::{cached_call_data_key, input_hash};
+    use fibonacci_script::deadline::parse_secs;
+    use fibonacci_script::jobs::Priority;
+    use fibonacci_script::jobs::DEFAULT_TENANT;
+    use fibonacci_script::notify::{Event, EventKind, Notifications};
+    use fibonacci_script::prover::{FibonacciProver, NetworkStrategy, ProofSystem};
+    use fibonacci_script::shutdown::Shutdown;
+    use futures::StreamExt;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    /// The arguments for `--source nats`.
+    #[derive(clap::Args, Debug)]
+    pub struct NatsArgs {
+        /// The NATS server to connect to
+        #[arg(long, env = "NATS_URL", default_value = "nats://localhost:4222")]
+        nats_url: String,
+
+        /// The JetStream stream holding proof requests (created if missing)
+        #[arg(long, default_value = "PROOFS")]
+        stream: String,
+
+        /// The subject proof requests are published to
+        #[arg(long, default_value = "proofs.requests")]
+        subject: String,
+
+        /// The durable consumer shared by all workers, so each request goes to one of them
+        #[arg(long, default_value = "fibonacci-worker")]
+        consumer: String,
+
+        /// Events are published to `<events-subject>.<status>`
+        #[arg(long, default_value = "proofs.events")]
+        events_subject: String,
+
+        /// Seconds a request may stay unacknowledged before it is redelivered to another worker
+        #[arg(long, value_parser = parse_secs, default_value = "3600")]
+        ack_wait: Duration,
+
+        /// How many times a request is delivered before it is given up on
+        #[arg(long, default_value = "3")]
+        max_deliver: i64,
+
+        /// The proof system to use for requests from the queue
+        #[arg(long, value_enum, default_value = "groth16")]
+        system: ProofSystem,
+    }
+
+    /// A proof request pulled from the queue, e.g. `{"id": "job-1", "input": {"n": 20}}`.
+    #[derive(Debug, Deserialize)]
+    struct ProofJob {
+        id: String,
+        input: ProgramInput,
+        /// How the prover network should fulfill the request, e.g. `"reserved"` (defaults to the
+        /// worker's `--network-strategy` for normal priority).
+        #[serde(default)]
+        network_strategy: Option<NetworkStrategy>,
+    }
+
+    /// A progress event published for a request.
+    #[derive(Debug, Serialize)]
+    struct JobEvent<'a> {
+        id: &'a str,
+        status: &'static str,
+        input_hash: B256,
+        /// The cached call data, once the proof is available.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        artifact: Option<String>,
+        /// Whether the proof was served from the cache.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cached: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    }
+
+    impl<'a> JobEvent<'a> {
+        fn new(id: &'a str, status: &'static str, input_hash: B256) -> Self {
+            Self { id, status, input_hash, artifact: None, cached: None, error: None }
+        }
+    }
+
+    /// Consume requests from the queue until shut down, caching proofs in `artifacts`. Completed
+    /// requests and requests given up on are sent to `notifications`.
+    pub async fn work(
+        args: &Args,
+        prover: &FibonacciProver,
+        artifacts: &dyn ArtifactStore,
+        notifications: &Notifications,
+        shutdown: &Shutdown,
+    ) -> Result<()> {
+        let nats = &args.nats;
+        let client = async_nats::connect(&nats.nats_url).await?;
+        let jetstream = jetstream::new(client.clone());
+        let stream = jetstream
+            .get_or_create_stream(jetstream::stream::Config {
+                name: nats.stream.clone(),
+                subjects: vec![nats.subject.clone()],
+                ..Default::default()
+            })
+            .await?;
+        let consumer = stream
+            .get_or_create_consumer(
+                &nats.consumer,
+                jetstream::consumer::pull::Config {
+                    durable_name: Some(nats.consumer.clone()),
+                    ack_wait: nats.ack_wait,
+                    max_deliver: nats.max_deliver,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let mut messages = consumer.messages().await?;
+        println!("📡 Consuming {} from {} as {}", nats.subject, nats.nats_url, nats.consumer);
+        println!("🔧 System: {}", nats.system);
+
+        let events = nats.events_subject.as_str();
+        loop {
+            let message = tokio::select! {
+                message = messages.next() => message,
+                _ = shutdown.wait() => break,
+            };
+            let Some(message) = message else { break };
+            let message = message?;
+
+            let job: ProofJob = match serde_json::from_slice(&message.payload) {
+                Ok(job) => job,
+                Err(err) => {
+                    println!("⚠️  Dropping malformed request: {}", err);
+                    message.ack_with(AckKind::Term).await.map_err(|err| eyre::eyre!(err))?;
+                    continue;
+                }
+            };
+            let hash = input_hash(&job.input, nats.system);
+            let key = cached_call_data_key(DEFAULT_TENANT, hash);
+            let location = artifacts.location(&key);
+            println!("📥 Request {}: n = {} ({})", job.id, job.input.n, hash);
+            publish(&client, events, JobEvent::new(&job.id, "started", hash)).await?;
+
+            // Requests are acknowledged only once their completion has been published, so a
+            // crash leads to redelivery; the cache makes the retry cheap.
+            let strategy = job.network_strategy.or_else(|| {
+                args.network_strategy
+                    .iter()
+                    .find(|(priority, _)| *priority == Priority::Normal)
+                    .map(|(_, strategy)| *strategy)
+            });
+            let proved =
+                prove_cached(prover, &job.id, &job.input, nats.system, strategy, artifacts, &key);
+            match proved {
+                Ok(timings) => {
+                    println!("✅ Request {} completed: {}", job.id, location);
+                    let details = json!({
+                        "job_id": job.id,
+                        "input": job.input,
+                        "input_hash": hash,
+                        "system": nats.system.to_string(),
+                        "artifact": location,
+                        "cached": timings.is_none(),
+                    });
+                    let summary =
+                        format!("Request {} (n = {}) completed: {}", job.id, job.input.n, location);
+                    let mut event = JobEvent::new(&job.id, "completed", hash);
+                    event.artifact = Some(location);
+                    event.cached = Some(timings.is_none());
+                    publish(&client, events, event).await?;
+                    notifications.notify(&Event::new(EventKind::ProofCompleted, summary, details));
+                    message.ack().await.map_err(|err| eyre::eyre!(err))?;
+                }
+                Err(err) => {
+                    println!("❌ Request {} failed: {}", job.id, err);
+                    let mut event = JobEvent::new(&job.id, "failed", hash);
+                    event.error = Some(err.to_string());
+                    publish(&client, events, event).await?;
+                    // Invalid inputs will never succeed; anything else is worth another delivery.
+                    let ack =
+                        if is_invalid_input(&err) { AckKind::Term } else { AckKind::Nak(None) };
+                    let delivered = message.info().map_or(1, |info| info.delivered);
+                    let given_up = is_invalid_input(&err) || delivered >= nats.max_deliver;
+                    message.ack_with(ack).await.map_err(|err| eyre::eyre!(err))?;
+                    if given_up {
+                        let summary = format!(
+                            "Request {} (n = {}) failed after {} deliveries: {}",
+                            job.id, job.input.n, delivered, err
+                        );
+                        let details = json!({
+                            "job_id": job.id,
+                            "input": job.input,
+                            "input_hash": hash,
+                            "system": nats.system.to_string(),
+                            "deliveries": delivered,
+                            "error": err.to_string(),
+                        });
+                        notifications.notify(&Event::new(EventKind::ProofFailed, summary, details));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Publish an event to `<subject>.<status>`.
+    async fn publish(
+        client: &async_nats::Client,
+        subject: &str,
+        event: JobEvent<'_>,
+    ) -> Result<()> {
+        let subject = format!("{}.{}", subject, event.status);
+        client.publish(subject, serde_json::to_vec(&event)?.into()).await?;
+        Ok(())
+    }
+}
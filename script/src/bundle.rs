@@ -0,0 +1,331 @@
+//! Portable proof bundles: the manifest of a saved proof, the files it lists and the on-chain run
+//! report, packed into a single gzip-compressed tarball (`.sp1bundle`) that can be handed to
+//! another party and is checked against its manifest on import.
+//!
+//! Bundles can also be read straight from where they were published, with [`fetch_bundle`]: a
+//! `.sp1bundle` file or a manifest (whose files sit next to it) at an `https://` URL or in an S3
+//! bucket (`s3://<bucket>/<path>`, with the credentials of [`crate::artifact_store`]).
+
+use crate::artifact_store::{ArtifactStore, S3Store};
+use crate::artifacts::{
+    create_dir, decompress, manifest_path, read_json, write, ArtifactError, CallData, Manifest,
+};
+use crate::chain::RUN_REPORT_PATH;
+use crate::prover::block_on;
+use alloy_primitives::{keccak256, B256};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The file extension of proof bundles.
+pub const BUNDLE_EXTENSION: &str = "sp1bundle";
+
+/// Errors from exporting or importing a bundle.
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+    #[error("the bundle has no manifest")]
+    MissingManifest,
+    #[error("the bundle has more than one manifest")]
+    MultipleManifests,
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(serde_json::Error),
+    #[error("`{0}` is listed in the manifest but missing")]
+    MissingFile(String),
+    #[error("`{0}` does not match the hash in the manifest")]
+    Corrupt(String),
+    #[error("the proof does not match the proof hash in the manifest")]
+    WrongProof,
+    #[error("unexpected entry `{0}` in the bundle")]
+    UnexpectedEntry(String),
+    #[error("failed to fetch {uri}: {reason}")]
+    Fetch { uri: String, reason: String },
+    #[error("the manifest hash is {actual}, not the expected {expected}")]
+    WrongManifest { expected: B256, actual: B256 },
+}
+
+/// A bundle read back from disk, after checking it against its manifest.
+#[derive(Debug, Clone)]
+pub struct ImportedBundle {
+    pub manifest: Manifest,
+    /// The hash of the manifest file, which settlements of the proof refer to.
+    pub manifest_hash: B256,
+    /// The files written, including the manifest.
+    pub files: Vec<PathBuf>,
+}
+
+/// A bundle read for inspection, without unpacking it.
+#[derive(Debug, Clone)]
+pub struct LoadedBundle {
+    pub manifest: Manifest,
+    /// The hash of the manifest file, which settlements of the proof refer to.
+    pub manifest_hash: B256,
+    /// The contents of the files listed in the manifest, by file name.
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl LoadedBundle {
+    /// The raw public values of the proof.
+    pub fn public_values(&self) -> Option<&[u8]> {
+        self.files.get(&format!("public_values_n{}.bin", self.manifest.n)).map(Vec::as_slice)
+    }
+
+    /// The raw proof bytes, decompressed if the bundle was saved compressed.
+    pub fn proof(&self) -> Result<Vec<u8>, BundleError> {
+        let proof = format!("proof_{}_n{}.bin", self.manifest.system, self.manifest.n);
+        let contents = self.files.get(&proof).or_else(|| self.files.get(&format!("{}.zst", proof)));
+        match contents {
+            Some(contents) => decompress(contents.clone()).map_err(|_| BundleError::Corrupt(proof)),
+            None => Err(BundleError::MissingFile(proof)),
+        }
+    }
+
+    /// The `verifyFibonacciProof` arguments of the proof.
+    pub fn call_data(&self) -> Result<CallData, BundleError> {
+        let public_values = self.public_values().ok_or_else(|| {
+            BundleError::MissingFile(format!("public_values_n{}.bin", self.manifest.n))
+        })?;
+        Ok(CallData {
+            public_values: public_values.to_vec().into(),
+            proof_bytes: self.proof()?.into(),
+        })
+    }
+}
+
+/// The path `export_bundle` writes the bundle for `n` to by default.
+pub fn bundle_path(dir: &Path, n: u32) -> PathBuf {
+    dir.join(format!("fibonacci_n{}.{}", n, BUNDLE_EXTENSION))
+}
+
+/// Pack the proof bundle for `n` saved in `dir` into `out`, after checking that its files still
+/// match its manifest. The on-chain run report is included if there is one.
+pub fn export_bundle(dir: &Path, n: u32, out: &Path) -> Result<Manifest, BundleError> {
+    let manifest_file = manifest_path(dir, n);
+    let manifest: Manifest = read_json(&manifest_file)?;
+    let mut files = BTreeMap::new();
+    files.insert(file_name(&manifest_file), read(&manifest_file)?);
+    for name in manifest.files.keys() {
+        let path = dir.join(name);
+        if !path.exists() {
+            return Err(BundleError::MissingFile(name.clone()));
+        }
+        files.insert(name.clone(), read(&path)?);
+    }
+    let run_report = Path::new(RUN_REPORT_PATH);
+    if run_report.exists() {
+        files.insert(file_name(run_report), read(run_report)?);
+    }
+    check(&manifest, &files)?;
+
+    if let Some(parent) = out.parent() {
+        create_dir(parent)?;
+    }
+    let io_err = |source| ArtifactError::Io { path: out.to_path_buf(), source };
+    let file = File::create(out).map_err(io_err)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (name, contents) in &files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, name, contents.as_slice()).map_err(io_err)?;
+    }
+    tar.into_inner().and_then(|gz| gz.finish()).map_err(io_err)?;
+    Ok(manifest)
+}
+
+/// Unpack a bundle into `dir`, refusing it unless every file listed in its manifest is present
+/// and unmodified. Nothing is written if the check fails.
+pub fn import_bundle(bundle: &Path, dir: &Path) -> Result<ImportedBundle, BundleError> {
+    let files = read_archive(bundle)?;
+    let (manifest_name, manifest) = find_manifest(&files)?;
+    check(&manifest, &files)?;
+
+    let run_report = file_name(Path::new(RUN_REPORT_PATH));
+    if let Some(name) = files.keys().find(|name| {
+        **name != manifest_name && **name != run_report && !manifest.files.contains_key(*name)
+    }) {
+        return Err(BundleError::UnexpectedEntry(name.clone()));
+    }
+
+    create_dir(dir)?;
+    let mut written = Vec::new();
+    for (name, contents) in &files {
+        let path = dir.join(name);
+        write(&path, contents)?;
+        written.push(path);
+    }
+    Ok(ImportedBundle {
+        manifest_hash: keccak256(&files[&manifest_name]),
+        manifest,
+        files: written,
+    })
+}
+
+/// Read a bundle, either a `.sp1bundle` file or the manifest of a bundle saved in a directory,
+/// after checking it against its manifest.
+pub fn load_bundle(path: &Path) -> Result<LoadedBundle, BundleError> {
+    if path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION) {
+        return from_archive(read_archive(path)?);
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    from_manifest(&read(path)?, |name| {
+        let file = dir.join(name);
+        if !file.exists() {
+            return Err(BundleError::MissingFile(name.to_string()));
+        }
+        Ok(read(&file)?)
+    })
+}
+
+/// Read a bundle from `uri`: an `https://` URL or `s3://<bucket>/<path>` of a `.sp1bundle` file
+/// or of a manifest with its files next to it, or a local path as for [`load_bundle`]. The
+/// manifest is checked against `manifest_hash`, if given, and the files against the manifest,
+/// before anything is returned.
+pub fn fetch_bundle(uri: &str, manifest_hash: Option<B256>) -> Result<LoadedBundle, BundleError> {
+    let bundle = if !uri.starts_with("https://") && !uri.starts_with("s3://") {
+        load_bundle(Path::new(uri))?
+    } else if uri.ends_with(&format!(".{}", BUNDLE_EXTENSION)) {
+        from_archive(read_entries(fetch(uri)?.as_slice(), Path::new(uri))?)?
+    } else {
+        let contents = fetch(uri)?;
+        // Check the manifest first, so nothing it lists is fetched for the wrong one.
+        check_manifest_hash(keccak256(&contents), manifest_hash)?;
+        let base = &uri[..uri.rfind('/').map_or(0, |slash| slash + 1)];
+        from_manifest(&contents, |name| fetch(&format!("{}{}", base, name)))?
+    };
+    check_manifest_hash(bundle.manifest_hash, manifest_hash)?;
+    Ok(bundle)
+}
+
+/// The bundle packed in an archive, after checking it against its manifest.
+fn from_archive(mut files: BTreeMap<String, Vec<u8>>) -> Result<LoadedBundle, BundleError> {
+    let (manifest_name, manifest) = find_manifest(&files)?;
+    let manifest_hash = keccak256(&files[&manifest_name]);
+    check(&manifest, &files)?;
+    files.retain(|name, _| manifest.files.contains_key(name));
+    Ok(LoadedBundle { manifest, manifest_hash, files })
+}
+
+/// The bundle of the manifest file `contents`, with the files it lists read with `read_file`,
+/// after checking them against it.
+fn from_manifest(
+    contents: &[u8],
+    mut read_file: impl FnMut(&str) -> Result<Vec<u8>, BundleError>,
+) -> Result<LoadedBundle, BundleError> {
+    let manifest: Manifest =
+        serde_json::from_slice(contents).map_err(BundleError::InvalidManifest)?;
+    let mut files = BTreeMap::new();
+    for name in manifest.files.keys() {
+        // Only plain file names, so a manifest can never point outside its directory.
+        if name.contains('/') || name.starts_with('.') {
+            return Err(BundleError::UnexpectedEntry(name.clone()));
+        }
+        files.insert(name.clone(), read_file(name)?);
+    }
+    check(&manifest, &files)?;
+    Ok(LoadedBundle { manifest, manifest_hash: keccak256(contents), files })
+}
+
+/// Fetch a file over HTTPS or from S3.
+fn fetch(uri: &str) -> Result<Vec<u8>, BundleError> {
+    let fetch_err = |reason: String| BundleError::Fetch { uri: uri.to_string(), reason };
+    if let Some(path) = uri.strip_prefix("s3://") {
+        let (dir, name) = path.rsplit_once('/').ok_or_else(|| fetch_err("no object key".into()))?;
+        let store = S3Store::from_env(dir)?;
+        return store.get(name)?.ok_or_else(|| fetch_err("not found".into()));
+    }
+    block_on(async {
+        let response = reqwest::get(uri).await?.error_for_status()?;
+        Ok::<_, reqwest::Error>(response.bytes().await?.to_vec())
+    })
+    .map_err(|err| fetch_err(err.to_string()))
+}
+
+/// Check the hash of a manifest file against the one expected, if any.
+fn check_manifest_hash(actual: B256, expected: Option<B256>) -> Result<(), BundleError> {
+    match expected {
+        Some(expected) if expected != actual => {
+            Err(BundleError::WrongManifest { expected, actual })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Read every entry of a `.sp1bundle` file, by file name.
+fn read_archive(bundle: &Path) -> Result<BTreeMap<String, Vec<u8>>, BundleError> {
+    let io_err = |source| ArtifactError::Io { path: bundle.to_path_buf(), source };
+    read_entries(File::open(bundle).map_err(io_err)?, bundle)
+}
+
+/// Read every entry of the `.sp1bundle` archive read from `reader` (`bundle` in errors), by file
+/// name.
+fn read_entries(
+    reader: impl Read,
+    bundle: &Path,
+) -> Result<BTreeMap<String, Vec<u8>>, BundleError> {
+    let io_err = |source| ArtifactError::Io { path: bundle.to_path_buf(), source };
+    let mut archive = tar::Archive::new(GzDecoder::new(reader));
+    let mut files = BTreeMap::new();
+    for entry in archive.entries().map_err(io_err)? {
+        let mut entry = entry.map_err(io_err)?;
+        let path = entry.path().map_err(io_err)?.into_owned();
+        // Only plain file names, so a bundle can never write outside `dir`.
+        let name = match (path.to_str(), path.components().count()) {
+            (Some(name), 1) if !name.starts_with('.') => name.to_string(),
+            _ => return Err(BundleError::UnexpectedEntry(path.display().to_string())),
+        };
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents).map_err(io_err)?;
+        files.insert(name, contents);
+    }
+    Ok(files)
+}
+
+/// The name and contents of the one manifest among the files of a bundle.
+fn find_manifest(files: &BTreeMap<String, Vec<u8>>) -> Result<(String, Manifest), BundleError> {
+    let mut manifests =
+        files.keys().filter(|name| name.starts_with("manifest_n") && name.ends_with(".json"));
+    let manifest_name = manifests.next().ok_or(BundleError::MissingManifest)?.clone();
+    if manifests.next().is_some() {
+        return Err(BundleError::MultipleManifests);
+    }
+    let manifest: Manifest =
+        serde_json::from_slice(&files[&manifest_name]).map_err(BundleError::InvalidManifest)?;
+    Ok((manifest_name, manifest))
+}
+
+/// Check the files of a bundle against its manifest.
+fn check(manifest: &Manifest, files: &BTreeMap<String, Vec<u8>>) -> Result<(), BundleError> {
+    for (name, hash) in &manifest.files {
+        let contents = files.get(name).ok_or_else(|| BundleError::MissingFile(name.clone()))?;
+        if keccak256(contents) != *hash {
+            return Err(BundleError::Corrupt(name.clone()));
+        }
+    }
+    let proof = format!("proof_{}_n{}.bin", manifest.system, manifest.n);
+    let contents = match files.get(&proof).or_else(|| files.get(&format!("{}.zst", proof))) {
+        Some(contents) => {
+            decompress(contents.clone()).map_err(|_| BundleError::Corrupt(proof.clone()))?
+        }
+        None => return Err(BundleError::MissingFile(proof)),
+    };
+    if keccak256(contents) != manifest.proof_hash {
+        return Err(BundleError::WrongProof);
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, ArtifactError> {
+    std::fs::read(path).map_err(|source| ArtifactError::Io { path: path.to_path_buf(), source })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string()
+}
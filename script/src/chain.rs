@@ -0,0 +1,553 @@
+//! Interacting with the verifier contracts: bindings, cost estimates, settlement bookkeeping and
+//! alternative submission paths (ERC-4337, Safe).
+
+use crate::artifacts::{write_json, ArtifactError, CallData};
+use crate::config::UserOpConfig;
+use crate::trace::{call_tracer_options, CallFrame};
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{address, aliases::U192, keccak256, Address, Bytes, B256, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types::{AccessList, TransactionReceipt, TransactionRequest};
+use alloy_signer::Signer;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::{sol, SolCall, SolValue};
+use alloy_transport::{Transport, TransportError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+// Bindings generated from the Foundry artifacts, so they always match the deployed interfaces.
+// `build.rs` runs `forge build` to produce them.
+sol!(
+    #[sol(rpc)]
+    FibonacciSimple,
+    "../contracts/out/FibonacciSimple.sol/FibonacciSimple.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    Fibonacci,
+    "../contracts/out/Fibonacci.sol/Fibonacci.json"
+);
+
+sol!(
+    #[sol(rpc)]
+    FibonacciOracle,
+    "../contracts/out/FibonacciOracle.sol/FibonacciOracle.json"
+);
+
+sol! {
+    #[sol(rpc)]
+    interface IEntryPoint {
+        function getNonce(address sender, uint192 key) external view returns (uint256 nonce);
+    }
+
+    /// The OP-stack predeploy that prices the L1 data fee of L2 transactions.
+    #[sol(rpc)]
+    interface IGasPriceOracle {
+        function getL1Fee(bytes memory data) external view returns (uint256 fee);
+    }
+
+    /// Arbitrum's virtual contract for splitting gas estimates into their L1 and L2 parts.
+    #[sol(rpc)]
+    interface INodeInterface {
+        function gasEstimateComponents(address to, bool contractCreation, bytes calldata data)
+            external
+            payable
+            returns (
+                uint64 gasEstimate,
+                uint64 gasEstimateForL1,
+                uint256 baseFee,
+                uint256 l1BaseFeeEstimate
+            );
+    }
+
+    /// The execution entrypoint of the reference ERC-4337 `SimpleAccount`.
+    interface ISmartAccount {
+        function execute(address dest, uint256 value, bytes calldata func) external;
+    }
+
+    /// The SP1 verifier gateway, which routes a proof to the verifier for its SP1 version and
+    /// reverts unless the proof verifies.
+    #[sol(rpc)]
+    interface ISP1Verifier {
+        function verifyProof(
+            bytes32 programVKey,
+            bytes calldata publicValues,
+            bytes calldata proofBytes
+        ) external view;
+    }
+}
+
+/// The canonical SP1 verifier gateway the consumer contracts verify through
+/// (`FibonacciSimple.SP1_VERIFIER`).
+pub const SP1_VERIFIER_GATEWAY: Address = address!("3B6041173B80E77f038f3F2C0f9744f04837185e");
+
+/// The canonical ERC-4337 EntryPoint v0.6 deployment.
+pub const ENTRY_POINT_V06: Address = address!("5FF137D4b0FDCD49DcA30c7CF57E578a026d2789");
+
+/// A placeholder signature with the right shape, used while estimating user operation gas.
+const DUMMY_SIGNATURE: &str = concat!(
+    "0xfffffffffffffffffffffffffffffff000000000000000000000000000000000",
+    "7aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c"
+);
+
+/// The OP-stack `GasPriceOracle` predeploy.
+const OP_GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000F");
+
+/// Arbitrum's `NodeInterface` virtual contract.
+const ARB_NODE_INTERFACE: Address = address!("00000000000000000000000000000000000000C8");
+
+/// How long to wait for the bundler to include a user operation.
+pub const USER_OP_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Where the gas and cost report of the last on-chain verification is written.
+pub const RUN_REPORT_PATH: &str = "artifacts/onchain_run_report.json";
+
+/// Errors from talking to the chain, a bundler or a paymaster.
+#[derive(Debug, Error)]
+pub enum ChainError {
+    #[error("RPC request failed: {0}")]
+    Rpc(#[from] TransportError),
+    #[error("contract call failed: {0}")]
+    Contract(#[from] alloy_contract::Error),
+    #[error("HTTP request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("unexpected response: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("failed to sign: {0}")]
+    Signer(#[from] alloy_signer::Error),
+    #[error("{method} failed: {error}")]
+    Bundler { method: String, error: String },
+    #[error("user operation {0} was included but reverted")]
+    UserOpReverted(B256),
+    #[error("user operation {0} was not included within {1:?}")]
+    UserOpTimeout(B256, Duration),
+    #[error("block {0} not found")]
+    BlockNotFound(String),
+    #[error("the header of block {0} does not encode to its hash")]
+    HeaderEncoding(u64),
+    #[error("eth_createAccessList failed: {0}")]
+    AccessList(String),
+}
+
+/// How the chain the proof is settled on charges for transactions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FeeModel {
+    /// Execution gas only.
+    #[default]
+    Ethereum,
+    /// Execution gas plus an L1 data fee (Optimism, Base, ...).
+    OpStack,
+    /// Execution gas that already includes the L1 component (Arbitrum One, Nova, ...).
+    Arbitrum,
+}
+
+impl FeeModel {
+    /// The fee model of the chain with the given id.
+    pub fn for_chain(chain_id: u64) -> Self {
+        match chain_id {
+            10 | 8453 | 11155420 | 84532 => Self::OpStack,
+            42161 | 42170 | 421614 => Self::Arbitrum,
+            _ => Self::Ethereum,
+        }
+    }
+}
+
+/// The L2-specific parts of a cost estimate.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct L2Fees {
+    /// A gas estimate that replaces the plain `eth_estimateGas` one (Arbitrum).
+    pub gas_estimate: Option<u128>,
+    /// How much of the gas estimate pays for L1 data (Arbitrum).
+    pub l1_gas_estimate: Option<u128>,
+    /// The L1 data fee charged on top of execution gas (OP stack).
+    pub l1_fee_wei: Option<u128>,
+}
+
+/// Query the L2-specific fee components of a call to `to` with `calldata`.
+pub async fn l2_fees<T, P>(
+    provider: &P,
+    fee_model: FeeModel,
+    to: Address,
+    calldata: &Bytes,
+) -> Result<L2Fees, ChainError>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut fees = L2Fees::default();
+    match fee_model {
+        FeeModel::Ethereum => {}
+        FeeModel::OpStack => {
+            // Pricing the calldata alone slightly underestimates the fee of the full signed
+            // transaction, but is within a few percent for proof-sized payloads.
+            let oracle = IGasPriceOracle::new(OP_GAS_PRICE_ORACLE, provider);
+            let fee = oracle.getL1Fee(calldata.clone()).call().await?.fee;
+            fees.l1_fee_wei = Some(fee.saturating_to::<u128>());
+        }
+        FeeModel::Arbitrum => {
+            let node_interface = INodeInterface::new(ARB_NODE_INTERFACE, provider);
+            let components =
+                node_interface.gasEstimateComponents(to, false, calldata.clone()).call().await?;
+            fees.gas_estimate = Some(u128::from(components.gasEstimate));
+            fees.l1_gas_estimate = Some(u128::from(components.gasEstimateForL1));
+        }
+    }
+    Ok(fees)
+}
+
+/// An access list that makes a call cheaper.
+#[derive(Clone, Debug)]
+pub struct AccessListSavings {
+    pub access_list: AccessList,
+    /// The gas estimate of the call without the list, less the estimate with it.
+    pub gas_saved: u128,
+}
+
+/// Generate the EIP-2930 access list of a call to `to` with `eth_createAccessList`, and estimate
+/// how much gas attaching it saves. Returns `None` if it saves nothing, e.g. because the list's
+/// own cost outweighs the cold accesses it prepays.
+pub async fn access_list_savings<T, P>(
+    provider: &P,
+    from: Option<Address>,
+    to: Address,
+    calldata: &Bytes,
+) -> Result<Option<AccessListSavings>, ChainError>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let mut tx = TransactionRequest::default().with_to(to).with_input(calldata.clone());
+    if let Some(from) = from {
+        tx = tx.with_from(from);
+    }
+    let created = provider.create_access_list(&tx).await?;
+    if let Some(error) = created.error {
+        return Err(ChainError::AccessList(error));
+    }
+    if created.access_list.is_empty() {
+        return Ok(None);
+    }
+    let without = provider.estimate_gas(&tx).await?;
+    let with = provider.estimate_gas(&tx.with_access_list(created.access_list.clone())).await?;
+    Ok((with < without).then(|| AccessListSavings {
+        access_list: created.access_list,
+        gas_saved: u128::from(without - with),
+    }))
+}
+
+/// Gas and cost details of an on-chain verification, saved next to the proof artifacts.
+#[derive(Debug, Default, Serialize)]
+pub struct OnchainRunReport {
+    pub chain_id: u64,
+    pub contract: String,
+    pub fee_model: FeeModel,
+    /// The gas estimate, with the access list if one is attached.
+    pub gas_estimate: u128,
+    /// The gas the attached access list saves, if one is.
+    pub access_list_gas_saved: Option<u128>,
+    pub gas_price_wei: u128,
+    pub l1_gas_estimate: Option<u128>,
+    pub l1_fee_wei: Option<u128>,
+    pub estimated_cost_eth: f64,
+    pub eth_usd_price: Option<f64>,
+    pub estimated_cost_usd: Option<f64>,
+    pub user_op_hash: Option<String>,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<u64>,
+    pub gas_used: Option<u128>,
+    pub effective_gas_price_wei: Option<u128>,
+    pub actual_cost_eth: Option<f64>,
+}
+
+impl OnchainRunReport {
+    /// Print the projected cost of the verification transaction.
+    pub fn print_estimate(&self) {
+        println!("⛽ Gas estimate: {}", self.gas_estimate);
+        if let Some(saved) = self.access_list_gas_saved {
+            println!("   Saved by the access list: {}", saved);
+        }
+        println!("   Gas price: {:.3} gwei", self.gas_price_wei as f64 / 1e9);
+        if let Some(l1_gas) = self.l1_gas_estimate {
+            println!("   Of which L1 gas: {}", l1_gas);
+        }
+        if let Some(l1_fee) = self.l1_fee_wei {
+            println!("   L1 data fee: {:.6} ETH", wei_to_eth(l1_fee));
+        }
+        match self.estimated_cost_usd {
+            Some(usd) => {
+                println!("   Projected cost: {:.6} ETH (${:.2})", self.estimated_cost_eth, usd)
+            }
+            None => println!("   Projected cost: {:.6} ETH", self.estimated_cost_eth),
+        }
+    }
+
+    /// Record the actual gas usage from the transaction receipt.
+    pub fn record_receipt(&mut self, receipt: &TransactionReceipt) {
+        let gas_used = u128::from(receipt.gas_used);
+        let gas_price = u128::from(receipt.effective_gas_price);
+        self.tx_hash = Some(receipt.transaction_hash.to_string());
+        self.block_number = receipt.block_number;
+        self.gas_used = Some(gas_used);
+        self.effective_gas_price_wei = Some(gas_price);
+        // OP-stack receipts charge the L1 data fee on top; use the estimate for it.
+        let l1_fee = self.l1_fee_wei.unwrap_or_default();
+        self.actual_cost_eth = Some(wei_to_eth(gas_used * gas_price + l1_fee));
+    }
+
+    /// Save the report to [`RUN_REPORT_PATH`].
+    pub fn save(&self) -> Result<(), ArtifactError> {
+        write_json(Path::new(RUN_REPORT_PATH), self)
+    }
+}
+
+/// The receipt of a transaction that settled a proof, saved as `settlement_<tx hash>.json` and
+/// linked to the proof bundle it settled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementReceipt {
+    pub tx_hash: B256,
+    pub chain_id: u64,
+    pub contract: Address,
+    pub block_number: u64,
+    pub gas_used: u128,
+    pub effective_gas_price_wei: u128,
+    /// Whether the transaction succeeded (a reverted one settles nothing but still costs gas).
+    pub success: bool,
+    pub n: u32,
+    /// The keccak256 hash of the settled proof bytes.
+    pub proof_hash: B256,
+    /// The hash of the manifest of the proof bundle that was settled, if it came from one.
+    pub manifest_hash: Option<B256>,
+}
+
+impl SettlementReceipt {
+    /// The settlement of `call_data` on `contract` by the transaction with `receipt`.
+    pub fn new(
+        receipt: &TransactionReceipt,
+        chain_id: u64,
+        contract: Address,
+        call_data: &CallData,
+        manifest_hash: Option<B256>,
+    ) -> Result<Self, ArtifactError> {
+        Ok(Self {
+            tx_hash: receipt.transaction_hash,
+            chain_id,
+            contract,
+            block_number: receipt.block_number.unwrap_or_default(),
+            gas_used: u128::from(receipt.gas_used),
+            effective_gas_price_wei: u128::from(receipt.effective_gas_price),
+            success: receipt.status(),
+            n: call_data.decode_public_values()?.n,
+            proof_hash: keccak256(&call_data.proof_bytes),
+            manifest_hash,
+        })
+    }
+
+    /// Save the receipt as `settlement_<tx hash>.json` in `dir`.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, ArtifactError> {
+        let path = dir.join(format!("settlement_{}.json", self.tx_hash));
+        write_json(&path, self)?;
+        Ok(path)
+    }
+}
+
+/// Convert an amount of wei to ETH.
+pub fn wei_to_eth(wei: u128) -> f64 {
+    wei as f64 / 1e18
+}
+
+/// Build a Safe Transaction Builder bundle with the single `verifyFibonacciProof` call to
+/// `contract`. The contract must already be deployed: deployments are not exported, as a proof is
+/// bound to the address of the contract that settles it.
+pub fn safe_transaction_bundle(
+    chain_id: u64,
+    safe_address: Option<Address>,
+    contract: Address,
+    calldata: &Bytes,
+    n: u32,
+) -> Value {
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_millis() as u64)
+        .unwrap_or_default();
+    json!({
+        "version": "1.0",
+        "chainId": chain_id.to_string(),
+        "createdAt": created_at,
+        "meta": {
+            "name": format!("Verify Fibonacci proof (n = {})", n),
+            "description": "Settle an SP1 Fibonacci proof by calling verifyFibonacciProof",
+            "txBuilderVersion": "1.16.5",
+            "createdFromSafeAddress": safe_address.map(|a| a.to_string()).unwrap_or_default(),
+            "createdFromOwnerAddress": ""
+        },
+        "transactions": [{
+            "to": contract.to_string(),
+            "value": "0",
+            "data": calldata.to_string(),
+            "contractMethod": null,
+            "contractInputsValues": null
+        }]
+    })
+}
+
+/// Re-run a call to `contract` with `debug_traceCall`, returning the call trace and the address of
+/// the SP1 verifier gateway the contract calls into.
+pub async fn trace_call(
+    rpc_url: &str,
+    contract: Address,
+    calldata: &Bytes,
+) -> Result<(CallFrame, Address), ChainError> {
+    let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+    let tx = json!({ "to": contract, "data": calldata });
+    let trace: CallFrame = provider
+        .raw_request("debug_traceCall".into(), (tx, "latest", call_tracer_options()))
+        .await?;
+    let verifier = FibonacciSimple::new(contract, &provider).getVerifier().call().await?;
+    Ok((trace, verifier._0))
+}
+
+/// An ERC-4337 user operation, in the EntryPoint v0.6 format.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// The hash the smart account owner signs, as computed by `EntryPoint.getUserOpHash`.
+    pub fn hash(&self, entry_point: Address, chain_id: u64) -> B256 {
+        let packed = (
+            self.sender,
+            self.nonce,
+            keccak256(&self.init_code),
+            keccak256(&self.call_data),
+            self.call_gas_limit,
+            self.verification_gas_limit,
+            self.pre_verification_gas,
+            self.max_fee_per_gas,
+            self.max_priority_fee_per_gas,
+            keccak256(&self.paymaster_and_data),
+        )
+            .abi_encode_params();
+        keccak256((keccak256(packed), entry_point, U256::from(chain_id)).abi_encode_params())
+    }
+
+    /// Take over the gas limits (and paymaster data, if any) returned by a bundler or paymaster.
+    pub fn apply_gas_fields(&mut self, fields: &Value) -> Result<(), ChainError> {
+        let parse = |key: &str| -> Result<Option<U256>, ChainError> {
+            match fields.get(key) {
+                Some(value) if !value.is_null() => Ok(Some(serde_json::from_value(value.clone())?)),
+                _ => Ok(None),
+            }
+        };
+        if let Some(gas) = parse("callGasLimit")? {
+            self.call_gas_limit = gas;
+        }
+        if let Some(gas) = parse("verificationGasLimit")? {
+            self.verification_gas_limit = gas;
+        }
+        if let Some(gas) = parse("preVerificationGas")? {
+            self.pre_verification_gas = gas;
+        }
+        if let Some(data) = fields.get("paymasterAndData").filter(|data| !data.is_null()) {
+            self.paymaster_and_data = serde_json::from_value(data.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// Settle a call through an ERC-4337 smart account instead of an EOA.
+///
+/// The smart account must be owned by `signer` and expose the `execute(address,uint256,bytes)`
+/// entrypoint of the reference `SimpleAccount`. If a paymaster is configured, gas is sponsored
+/// through `pm_sponsorUserOperation`.
+pub async fn submit_user_operation(
+    rpc_url: &str,
+    config: &UserOpConfig,
+    signer: &PrivateKeySigner,
+    target: Address,
+    calldata: Bytes,
+    chain_id: u64,
+) -> Result<(B256, TransactionReceipt), ChainError> {
+    let entry_point = config.entry_point;
+    let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+    let nonce = IEntryPoint::new(entry_point, &provider)
+        .getNonce(config.smart_account, U192::ZERO)
+        .call()
+        .await?
+        .nonce;
+    let fees = provider.estimate_eip1559_fees(None).await?;
+
+    let mut user_op = UserOperation {
+        sender: config.smart_account,
+        nonce,
+        call_data: ISmartAccount::executeCall { dest: target, value: U256::ZERO, func: calldata }
+            .abi_encode()
+            .into(),
+        max_fee_per_gas: U256::from(fees.max_fee_per_gas),
+        max_priority_fee_per_gas: U256::from(fees.max_priority_fee_per_gas),
+        signature: DUMMY_SIGNATURE.parse().expect("DUMMY_SIGNATURE is valid hex"),
+        ..Default::default()
+    };
+
+    // Fill in gas limits, letting the paymaster sponsor the operation if one is configured.
+    let params = json!([user_op, entry_point]);
+    let gas_fields = match &config.paymaster_url {
+        Some(paymaster_url) => {
+            println!("   Requesting sponsorship from paymaster...");
+            json_rpc(paymaster_url, "pm_sponsorUserOperation", params).await?
+        }
+        None => json_rpc(&config.bundler_url, "eth_estimateUserOperationGas", params).await?,
+    };
+    user_op.apply_gas_fields(&gas_fields)?;
+
+    let hash = user_op.hash(entry_point, chain_id);
+    user_op.signature = signer.sign_message(hash.as_slice()).await?.as_bytes().to_vec().into();
+
+    let bundler_url = &config.bundler_url;
+    let user_op_hash: B256 = serde_json::from_value(
+        json_rpc(bundler_url, "eth_sendUserOperation", json!([user_op, entry_point])).await?,
+    )?;
+    println!("📨 User operation sent: {}", user_op_hash);
+
+    // Wait for the bundler to include the operation.
+    let deadline = tokio::time::Instant::now() + USER_OP_TIMEOUT;
+    while tokio::time::Instant::now() < deadline {
+        let receipt =
+            json_rpc(bundler_url, "eth_getUserOperationReceipt", json!([user_op_hash])).await?;
+        if !receipt.is_null() {
+            if !receipt["success"].as_bool().unwrap_or(false) {
+                return Err(ChainError::UserOpReverted(user_op_hash));
+            }
+            return Ok((user_op_hash, serde_json::from_value(receipt["receipt"].clone())?));
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    Err(ChainError::UserOpTimeout(user_op_hash, USER_OP_TIMEOUT))
+}
+
+/// Send a JSON-RPC request to a bundler or paymaster endpoint.
+async fn json_rpc(url: &str, method: &str, params: Value) -> Result<Value, ChainError> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let response: Value =
+        reqwest::Client::new().post(url).json(&request).send().await?.json().await?;
+    if let Some(error) = response.get("error") {
+        return Err(ChainError::Bundler { method: method.to_string(), error: error.to_string() });
+    }
+    Ok(response["result"].clone())
+}
@@ -0,0 +1,126 @@
+//! Fault injection for resilience testing, built with the `chaos` feature.
+//!
+//! Once a [`ChaosConfig`] is installed (with `--chaos` on `worker` and `aggregate`), prover
+//! requests time out, remote calls fail and artifact writes fail at random, each with its own
+//! probability. This lets integrators check their retry and resume logic: that the worker requeues
+//! a job whose proof timed out and picks up again after losing the job store, or that a batch can
+//! be re-run after a failed write. Never enable the feature in a production build.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A fault that can be injected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// A prover (network) request times out.
+    ProverTimeout,
+    /// A call to a remote service fails: an RPC endpoint or the job store.
+    Rpc,
+    /// Writing an artifact fails.
+    ArtifactWrite,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ProverTimeout => write!(f, "prover timeout"),
+            Self::Rpc => write!(f, "RPC failure"),
+            Self::ArtifactWrite => write!(f, "artifact write error"),
+        }
+    }
+}
+
+/// The probability of each fault, parsed from e.g. `prover=0.2,rpc=0.1,write=0.05,seed=7`.
+/// Faults left out are never injected; without a seed, every run fails differently.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ChaosConfig {
+    pub prover_timeout: f64,
+    pub rpc_failure: f64,
+    pub write_failure: f64,
+    /// Seed the fault sequence, to replay a run.
+    pub seed: Option<u64>,
+}
+
+impl ChaosConfig {
+    fn probability(&self, fault: Fault) -> f64 {
+        match fault {
+            Fault::ProverTimeout => self.prover_timeout,
+            Fault::Rpc => self.rpc_failure,
+            Fault::ArtifactWrite => self.write_failure,
+        }
+    }
+}
+
+impl FromStr for ChaosConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Self::default();
+        for entry in s.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected <fault>=<probability>, got `{}`", entry))?;
+            if key == "seed" {
+                config.seed = Some(value.parse().map_err(|_| format!("invalid seed `{}`", value))?);
+                continue;
+            }
+            let probability: f64 = value
+                .parse()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .ok_or_else(|| format!("expected a probability from 0 to 1, got `{}`", value))?;
+            match key {
+                "prover" => config.prover_timeout = probability,
+                "rpc" => config.rpc_failure = probability,
+                "write" => config.write_failure = probability,
+                _ => return Err(format!("unknown fault `{}` (prover, rpc, write or seed)", key)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The installed configuration and the state of its random number generator.
+struct Chaos {
+    config: ChaosConfig,
+    state: Mutex<u64>,
+}
+
+static CHAOS: OnceLock<Chaos> = OnceLock::new();
+
+/// Start injecting faults for the rest of the process. Only the first call has an effect.
+pub fn install(config: ChaosConfig) {
+    let seed = config.seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+    });
+    // xorshift gets stuck at zero.
+    let state = Mutex::new(seed.max(1));
+    if CHAOS.set(Chaos { config, state }).is_ok() {
+        eprintln!(
+            "🐒 Chaos mode: prover timeouts {}, RPC failures {}, write errors {} (seed {})",
+            config.prover_timeout, config.rpc_failure, config.write_failure, seed
+        );
+    }
+}
+
+/// Whether to inject `fault` now. Always false unless chaos mode is installed.
+pub fn inject(fault: Fault) -> bool {
+    let Some(chaos) = CHAOS.get() else {
+        return false;
+    };
+    let probability = chaos.config.probability(fault);
+    if probability <= 0.0 {
+        return false;
+    }
+    let mut state = chaos.state.lock().unwrap_or_else(|err| err.into_inner());
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    let injected = (*state >> 11) as f64 / (1u64 << 53) as f64 < probability;
+    if injected {
+        eprintln!("🐒 Chaos mode: injecting a {}", fault);
+    }
+    injected
+}
@@ -0,0 +1,114 @@
+//! Configuration read from the environment (and `.env`).
+
+use crate::chain::ENTRY_POINT_V06;
+use alloy_primitives::Address;
+use alloy_signer_local::PrivateKeySigner;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// The RPC endpoint used when `RPC_URL` is not set.
+pub const DEFAULT_RPC_URL: &str = "https://rpc.sepolia.succinct.xyz";
+
+/// The `FibonacciSimple` deployment used when `FIBONACCI_CONTRACT_ADDRESS` is not set.
+pub const DEFAULT_CONTRACT_ADDRESS: &str = "0x44a4c90114d64A027DB4630639153DC54eaA6224";
+
+/// Errors from missing or malformed environment variables.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{0} is not set")]
+    Missing(&'static str),
+    #[error("{name} is invalid: {reason}")]
+    Invalid { name: &'static str, reason: String },
+}
+
+/// The RPC endpoint of the chain proofs are settled on (`RPC_URL`).
+pub fn rpc_url() -> String {
+    env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string())
+}
+
+/// The `RPC_URL` endpoint, parsed for building HTTP providers.
+pub fn rpc_endpoint() -> Result<reqwest::Url, ConfigError> {
+    parse("RPC_URL", &rpc_url())
+}
+
+/// The account transactions are sent from (`PRIVATE_KEY`).
+pub fn signer() -> Result<PrivateKeySigner, ConfigError> {
+    parse("PRIVATE_KEY", &required("PRIVATE_KEY")?)
+}
+
+/// The ERC-4337 setup used to submit verifications as user operations.
+#[derive(Debug, Clone)]
+pub struct UserOpConfig {
+    /// `BUNDLER_URL`
+    pub bundler_url: String,
+    /// `SMART_ACCOUNT_ADDRESS`, which must be owned by the `PRIVATE_KEY` account.
+    pub smart_account: Address,
+    /// `PAYMASTER_URL`, if gas should be sponsored.
+    pub paymaster_url: Option<String>,
+    /// `ENTRY_POINT_ADDRESS`, defaulting to the canonical v0.6 EntryPoint.
+    pub entry_point: Address,
+}
+
+impl UserOpConfig {
+    /// Read the user operation setup from the environment.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Ok(Self {
+            bundler_url: required("BUNDLER_URL")?,
+            smart_account: parse("SMART_ACCOUNT_ADDRESS", &required("SMART_ACCOUNT_ADDRESS")?)?,
+            paymaster_url: env::var("PAYMASTER_URL").ok(),
+            entry_point: match env::var("ENTRY_POINT_ADDRESS") {
+                Ok(address) => parse("ENTRY_POINT_ADDRESS", &address)?,
+                Err(_) => ENTRY_POINT_V06,
+            },
+        })
+    }
+}
+
+/// Set `name` to `value` in a `.env` file, replacing any existing assignment and creating the file
+/// if needed.
+pub fn set_in_env_file(path: &Path, name: &str, value: &str) -> io::Result<()> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let assignment = format!("{}={}", name, value);
+    let mut replaced = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            let is_assignment = line
+                .trim_start()
+                .trim_start_matches("export ")
+                .split_once('=')
+                .is_some_and(|(key, _)| key.trim() == name);
+            if is_assignment && !replaced {
+                replaced = true;
+                assignment.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(assignment);
+    }
+    fs::write(path, lines.join("\n") + "\n")
+}
+
+/// Read a required environment variable.
+fn required(name: &'static str) -> Result<String, ConfigError> {
+    env::var(name).map_err(|_| ConfigError::Missing(name))
+}
+
+/// Parse the value of an environment variable.
+fn parse<T>(name: &'static str, value: &str) -> Result<T, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|err: T::Err| ConfigError::Invalid { name, reason: err.to_string() })
+}
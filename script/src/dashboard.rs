@@ -0,0 +1,238 @@
+//! A live dashboard of the job store in the terminal, for `cli jobs list --tui`: a table of the
+//! jobs with their phase, how long they have been in it, the cycles proved and the last error,
+//! refreshed every second, with keys to cancel or retry the selected job.
+//!
+//! It only reads and updates the store, so it can watch workers on other machines. Cancelling a
+//! running job fails it in the store, but its worker still finishes the proof and records it.
+
+use crate::jobs::{Job, JobError, JobStatus, JobStore};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// How often the jobs are reloaded from the store.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for a key before redrawing.
+const KEY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The error recorded on jobs cancelled from the dashboard.
+pub const CANCELLED: &str = "cancelled from the dashboard";
+
+/// Errors from running the dashboard.
+#[derive(Debug, Error)]
+pub enum DashboardError {
+    #[error(transparent)]
+    Jobs(#[from] JobError),
+    #[error("terminal error: {0}")]
+    Terminal(#[from] io::Error),
+}
+
+/// Show the jobs (only those with `status` or of `tenant`, if given) until the user quits.
+pub async fn run(
+    store: &JobStore,
+    status: Option<JobStatus>,
+    tenant: Option<&str>,
+) -> Result<(), DashboardError> {
+    let mut terminal = ratatui::init();
+    let dashboard = Dashboard {
+        store,
+        status,
+        tenant,
+        jobs: Vec::new(),
+        selected: TableState::default(),
+        message: String::new(),
+    };
+    let result = dashboard.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+struct Dashboard<'a> {
+    store: &'a JobStore,
+    status: Option<JobStatus>,
+    tenant: Option<&'a str>,
+    jobs: Vec<Job>,
+    selected: TableState,
+    /// The outcome of the last action, shown under the table.
+    message: String,
+}
+
+impl Dashboard<'_> {
+    async fn run(mut self, terminal: &mut DefaultTerminal) -> Result<(), DashboardError> {
+        let mut next_refresh = Instant::now();
+        loop {
+            if Instant::now() >= next_refresh {
+                self.refresh().await?;
+                next_refresh = Instant::now() + REFRESH_INTERVAL;
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Waiting for a key blocks, so let the runtime move its other tasks off this thread.
+            let event = tokio::task::block_in_place(|| -> io::Result<Option<Event>> {
+                if event::poll(KEY_POLL_INTERVAL)? {
+                    event::read().map(Some)
+                } else {
+                    Ok(None)
+                }
+            })?;
+            let Some(Event::Key(key)) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.selected.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+                KeyCode::Char('c') => {
+                    self.cancel().await?;
+                    next_refresh = Instant::now();
+                }
+                KeyCode::Char('r') => {
+                    self.retry().await?;
+                    next_refresh = Instant::now();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reload the jobs, keeping the selection on the table.
+    async fn refresh(&mut self) -> Result<(), JobError> {
+        self.jobs = self.store.list(self.status, self.tenant).await?;
+        let last = self.jobs.len().checked_sub(1);
+        let selected = match self.selected.selected() {
+            Some(selected) => last.map(|last| selected.min(last)),
+            None => last.map(|_| 0),
+        };
+        self.selected.select(selected);
+        Ok(())
+    }
+
+    /// The id and status of the selected job, if any.
+    fn selected_job(&self) -> Option<(String, JobStatus)> {
+        let job = self.jobs.get(self.selected.selected()?)?;
+        Some((job.id.clone(), job.status))
+    }
+
+    /// Fail the selected job, unless it has finished.
+    async fn cancel(&mut self) -> Result<(), JobError> {
+        let Some((id, status)) = self.selected_job() else {
+            return Ok(());
+        };
+        self.message = match status {
+            JobStatus::Completed | JobStatus::Failed => format!("Job {} has already finished", id),
+            JobStatus::Queued | JobStatus::Running | JobStatus::Interrupted => {
+                self.store.set_status(&id, JobStatus::Failed, Some(CANCELLED)).await?;
+                format!("Cancelled job {}", id)
+            }
+        };
+        Ok(())
+    }
+
+    /// Put the selected job back in the queue, if it failed or was interrupted.
+    async fn retry(&mut self) -> Result<(), JobError> {
+        let Some((id, status)) = self.selected_job() else {
+            return Ok(());
+        };
+        self.message = match status {
+            JobStatus::Failed | JobStatus::Interrupted => {
+                self.store.set_status(&id, JobStatus::Queued, None).await?;
+                format!("Requeued job {}", id)
+            }
+            JobStatus::Queued | JobStatus::Running | JobStatus::Completed => {
+                format!("Job {} is {}, not failed", id, status)
+            }
+        };
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, status_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let now = now();
+        let rows = self.jobs.iter().map(|job| {
+            Row::new([
+                Cell::from(job.id.clone()),
+                Cell::from(job.tenant.clone()),
+                Cell::from(job.input.n.to_string()),
+                Cell::from(job.system.to_string()),
+                Cell::from(job.priority.to_string()),
+                Cell::from(job.status.to_string()).style(status_style(job.status)),
+                Cell::from(format_duration(elapsed(job, now))),
+                Cell::from(job.cycles.map_or("-".to_string(), |cycles| cycles.to_string())),
+                Cell::from(job.attempts.to_string()),
+                Cell::from(job.error.clone().unwrap_or_default()),
+            ])
+        });
+        let widths = [
+            Constraint::Length(24),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ];
+        let header = [
+            "Job", "Tenant", "n", "System", "Priority", "Phase", "Elapsed", "Cycles", "Attempts",
+            "Error",
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).bold())
+            .block(Block::bordered().title(format!(" Jobs ({}) ", self.jobs.len())))
+            .row_highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(table, table_area, &mut self.selected);
+
+        let keys = "↑/↓ select   c cancel   r retry   q quit";
+        let status = match self.message.as_str() {
+            "" => keys.to_string(),
+            message => format!("{}   ({})", keys, message),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+    }
+}
+
+fn status_style(status: JobStatus) -> Style {
+    match status {
+        JobStatus::Queued => Style::new(),
+        JobStatus::Running => Style::new().fg(Color::Yellow),
+        JobStatus::Completed => Style::new().fg(Color::Green),
+        JobStatus::Failed => Style::new().fg(Color::Red),
+        JobStatus::Interrupted => Style::new().fg(Color::Magenta),
+    }
+}
+
+/// How long a job has been in its phase, in seconds: waiting for a worker, being proved, or (once
+/// it finished) how long it took overall.
+fn elapsed(job: &Job, now: u64) -> u64 {
+    match job.status {
+        JobStatus::Queued | JobStatus::Interrupted => now.saturating_sub(job.created_at),
+        // Claiming a job updates it, so this is when its worker started.
+        JobStatus::Running => now.saturating_sub(job.updated_at),
+        JobStatus::Completed | JobStatus::Failed => job.updated_at.saturating_sub(job.created_at),
+    }
+}
+
+/// Format seconds as e.g. `42s`, `3m05s` or `1h02m`.
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
@@ -0,0 +1,62 @@
+//! Time limits for proving and RPC calls, so a stuck prover or endpoint aborts the run instead of
+//! hanging it.
+
+use crate::Error;
+use std::future::Future;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Await `future`, failing with [`Error::Timeout`] if it takes longer than `limit`.
+///
+/// The future is dropped on timeout, which cancels any request it has in flight.
+pub async fn with_timeout<T, E, F>(
+    operation: &'static str,
+    limit: Option<Duration>,
+    future: F,
+) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<Error>,
+{
+    #[cfg(feature = "chaos")]
+    if crate::chaos::inject(crate::chaos::Fault::Rpc) {
+        return Err(Error::Rpc(format!("{} failed (injected by chaos mode)", operation)));
+    }
+    let Some(limit) = limit else {
+        return future.await.map_err(Into::into);
+    };
+    match tokio::time::timeout(limit, future).await {
+        Ok(result) => result.map_err(Into::into),
+        Err(_) => Err(Error::Timeout { operation, limit }),
+    }
+}
+
+/// Aborts the process with the timeout exit code if it is not dropped within its limit.
+///
+/// Blocking work such as proving cannot be interrupted from the outside, so this is how the
+/// binaries bound it.
+pub struct Watchdog {
+    _disarm: Sender<()>,
+}
+
+impl Watchdog {
+    /// Start a watchdog for `operation`, or none if there is no `limit`.
+    pub fn start(operation: &'static str, limit: Option<Duration>) -> Option<Self> {
+        let limit = limit?;
+        let (disarm, armed) = mpsc::channel::<()>();
+        thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = armed.recv_timeout(limit) {
+                let err = Error::Timeout { operation, limit };
+                eprintln!("❌ {}", err);
+                std::process::exit(err.exit_code());
+            }
+        });
+        Some(Self { _disarm: disarm })
+    }
+}
+
+/// Parse a number of seconds from the command line.
+pub fn parse_secs(secs: &str) -> Result<Duration, String> {
+    secs.parse().map(Duration::from_secs).map_err(|_| format!("expected seconds, got `{}`", secs))
+}
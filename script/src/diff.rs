@@ -0,0 +1,87 @@
+//! Comparing two proof bundles field by field: what was proved, the decoded public values, and
+//! what produced each proof. Useful when a re-proof unexpectedly commits different values.
+
+use crate::bundle::LoadedBundle;
+use alloy_primitives::Bytes;
+use fibonacci_lib::PublicValuesStruct;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Shown for a field one of the bundles does not have.
+pub const ABSENT: &str = "-";
+
+/// A field of two bundles and its value in each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// The path of the field, e.g. `public_values.fibValues`.
+    pub field: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl FieldDiff {
+    /// Whether the field differs between the bundles.
+    pub fn changed(&self) -> bool {
+        self.left != self.right
+    }
+}
+
+/// Compare two bundles. Every field either bundle has is listed, in order of its path.
+pub fn diff_bundles(left: &LoadedBundle, right: &LoadedBundle) -> Vec<FieldDiff> {
+    let (mut left_fields, mut right_fields) = (BTreeMap::new(), BTreeMap::new());
+    flatten("", describe(left), &mut left_fields);
+    flatten("", describe(right), &mut right_fields);
+
+    let mut fields: Vec<_> = left_fields.keys().chain(right_fields.keys()).cloned().collect();
+    fields.sort();
+    fields.dedup();
+    fields
+        .into_iter()
+        .map(|field| FieldDiff {
+            left: left_fields.remove(&field).unwrap_or_else(|| ABSENT.to_string()),
+            right: right_fields.remove(&field).unwrap_or_else(|| ABSENT.to_string()),
+            field,
+        })
+        .collect()
+}
+
+/// The fields of a bundle that are compared.
+fn describe(bundle: &LoadedBundle) -> Value {
+    let manifest = &bundle.manifest;
+    let public_values = match bundle.public_values() {
+        Some(bytes) => match PublicValuesStruct::from_abi_bytes(bytes) {
+            Ok(decoded) => json!(decoded),
+            // Public values of an older layout than this build's can only be compared as bytes.
+            Err(_) => json!(Bytes::copy_from_slice(bytes)),
+        },
+        None => Value::Null,
+    };
+    json!({
+        "n": manifest.n,
+        "system": manifest.system,
+        "vkey": manifest.vkey,
+        "proof_hash": manifest.proof_hash,
+        "public_values": public_values,
+        "provenance": manifest.provenance,
+        "prover": manifest.prover,
+    })
+}
+
+/// Collect the leaves of `value` by their dotted path. Arrays are compared as a whole.
+fn flatten(path: &str, value: Value, fields: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if path.is_empty() { key } else { format!("{}.{}", path, key) };
+                flatten(&path, value, fields);
+            }
+        }
+        Value::Null => {}
+        Value::String(string) => {
+            fields.insert(path.to_string(), string);
+        }
+        value => {
+            fields.insert(path.to_string(), value.to_string());
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! The domain of inputs the CLI and `service` accept, configured in `input_domain.toml` (or the
+//! file `INPUT_DOMAIN` names):
+//! ```toml
+//! min_n = 1
+//! max_n = 5000
+//! max_range_len = 100
+//! allowed_moduli = [1000000007, 998244353]
+//! max_payload_bytes = 4096
+//! ```
+//! Every constraint is optional. Inputs are checked against the program's own limits first (see
+//! [`ProgramInput::validate`]), so nothing is executed, proved or queued for an input the program
+//! would reject or panic on; the domain can only narrow those limits, e.g. to keep a public
+//! service from proving `n` near `MAX_N`.
+
+use fibonacci_lib::{ProgramInput, MAX_N, MAX_RANGE_LEN};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// The input domain file used when `INPUT_DOMAIN` is not set.
+pub const DEFAULT_INPUT_DOMAIN: &str = "input_domain.toml";
+
+/// Errors from loading an input domain or checking an input against it.
+#[derive(Debug, Error)]
+pub enum DomainError {
+    #[error("invalid input domain {path}: {reason}")]
+    InvalidConfig { path: String, reason: String },
+    #[error("input out of domain: {0}")]
+    OutOfDomain(String),
+}
+
+/// Constraints on the inputs to accept, on top of the program's own limits.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputDomain {
+    /// The smallest `n` accepted.
+    pub min_n: Option<u32>,
+    /// The largest `n` accepted, at most `MAX_N`.
+    pub max_n: Option<u32>,
+    /// The most values a range may commit, at most `MAX_RANGE_LEN`.
+    pub max_range_len: Option<u32>,
+    /// The moduli recurrences may be evaluated modulo; any if unset, none if empty.
+    pub allowed_moduli: Option<Vec<u64>>,
+    /// The largest input accepted, in bytes: as JSON for a Fibonacci input, and as given on the
+    /// command line for the inputs of other programs.
+    pub max_payload_bytes: Option<usize>,
+}
+
+impl InputDomain {
+    /// Load the input domain at `INPUT_DOMAIN`, or [`DEFAULT_INPUT_DOMAIN`] if it exists. Without
+    /// either, only the program's own limits apply.
+    pub fn from_env() -> Result<Self, DomainError> {
+        match env::var("INPUT_DOMAIN") {
+            Ok(path) => Self::load(Path::new(&path)),
+            Err(_) if Path::new(DEFAULT_INPUT_DOMAIN).exists() => {
+                Self::load(Path::new(DEFAULT_INPUT_DOMAIN))
+            }
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Load an input domain file.
+    pub fn load(path: &Path) -> Result<Self, DomainError> {
+        let invalid = |reason: String| DomainError::InvalidConfig {
+            path: path.display().to_string(),
+            reason,
+        };
+        let contents = fs::read_to_string(path).map_err(|err| invalid(err.to_string()))?;
+        let domain: Self = toml::from_str(&contents).map_err(|err| invalid(err.to_string()))?;
+        domain.check_limits().map_err(invalid)?;
+        Ok(domain)
+    }
+
+    /// Check that the domain is consistent and within the limits the program was built with, so
+    /// that a misconfiguration fails at startup rather than on every request.
+    fn check_limits(&self) -> Result<(), String> {
+        if let (Some(min_n), Some(max_n)) = (self.min_n, self.max_n) {
+            if min_n > max_n {
+                return Err(format!("min_n = {} is greater than max_n = {}", min_n, max_n));
+            }
+        }
+        if self.max_n.is_some_and(|max_n| max_n > MAX_N) {
+            return Err(format!("max_n is greater than the program's MAX_N = {}", MAX_N));
+        }
+        if self.max_range_len.is_some_and(|max| max > MAX_RANGE_LEN) {
+            return Err(format!(
+                "max_range_len is greater than the program's MAX_RANGE_LEN = {}",
+                MAX_RANGE_LEN
+            ));
+        }
+        if let Some(modulus) = self.allowed_moduli.iter().flatten().find(|modulus| **modulus < 2) {
+            return Err(format!("no recurrence takes the modulus {}", modulus));
+        }
+        Ok(())
+    }
+
+    /// Check a Fibonacci program input: first that the program accepts it, then that it is in
+    /// the domain.
+    pub fn check(&self, input: &ProgramInput) -> Result<(), DomainError> {
+        input.validate().map_err(DomainError::OutOfDomain)?;
+        let out = |reason: String| Err(DomainError::OutOfDomain(reason));
+        if let Some(min_n) = self.min_n.filter(|min_n| input.n < *min_n) {
+            return out(format!("n = {} is less than the minimum of {}", input.n, min_n));
+        }
+        if let Some(max_n) = self.max_n.filter(|max_n| input.n > *max_n) {
+            return out(format!("n = {} is greater than the maximum of {}", input.n, max_n));
+        }
+        if let (Some(start), Some(max)) = (input.range_start, self.max_range_len) {
+            let len = input.n - start + 1;
+            if len > max {
+                return out(format!("a range of {} values is over the maximum of {}", len, max));
+            }
+        }
+        if let (Some(recurrence), Some(allowed)) = (&input.recurrence, &self.allowed_moduli) {
+            if !allowed.contains(&recurrence.modulus) {
+                return out(format!("the modulus {} is not allowed", recurrence.modulus));
+            }
+        }
+        let bytes = serde_json::to_vec(input).map_or(0, |json| json.len());
+        self.check_payload(bytes)
+    }
+
+    /// Check the size of an input, in bytes.
+    pub fn check_payload(&self, bytes: usize) -> Result<(), DomainError> {
+        match self.max_payload_bytes {
+            Some(max) if bytes > max => Err(DomainError::OutOfDomain(format!(
+                "the input is {} bytes, more than the maximum of {}",
+                bytes, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
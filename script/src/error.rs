@@ -0,0 +1,197 @@
+//! The crate-wide error type, grouping the module errors into the categories the binaries report
+//! and exit with.
+
+use crate::addresses::AddressError;
+use crate::artifacts::ArtifactError;
+use crate::audit::AuditError;
+use crate::bundle::BundleError;
+use crate::chain::ChainError;
+use crate::config::ConfigError;
+use crate::domain::DomainError;
+use crate::jobs::JobError;
+use crate::notify::NotifyError;
+use crate::prover::ProverError;
+use crate::verifiers::VerifierError;
+use alloy_provider::PendingTransactionError;
+use alloy_transport::TransportError;
+use std::time::Duration;
+use thiserror::Error;
+
+/// A categorized failure of one of the scripts.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A missing or malformed setting.
+    #[error(transparent)]
+    Config(ConfigError),
+    /// The program rejected its input or failed while executing.
+    #[error(transparent)]
+    Execution(ProverError),
+    /// Generating or verifying a proof failed.
+    #[error(transparent)]
+    Proving(ProverError),
+    /// Reading or writing artifacts failed.
+    #[error(transparent)]
+    Io(ArtifactError),
+    /// The chain (or a bundler, or an HTTP API) could not be reached or returned an error.
+    #[error("{0}")]
+    Rpc(String),
+    /// Public values or a response could not be decoded.
+    #[error("failed to decode public values: {0}")]
+    Decode(#[from] alloy_sol_types::Error),
+    /// The proof was rejected, or refused before being submitted.
+    #[error("{0}")]
+    Rejected(String),
+    /// An operation ran past its time limit and was abandoned.
+    #[error("{operation} timed out after {limit:?}")]
+    Timeout { operation: &'static str, limit: Duration },
+}
+
+impl Error {
+    /// The process exit code for this error, so callers can tell failures apart without parsing
+    /// the output.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Config(_) => 3,
+            Self::Execution(_) => 4,
+            Self::Proving(_) => 5,
+            Self::Io(_) => 6,
+            Self::Rpc(_) => 7,
+            Self::Decode(_) => 8,
+            Self::Rejected(_) => 9,
+            Self::Timeout { .. } => 10,
+        }
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        Self::Config(err)
+    }
+}
+
+impl From<ProverError> for Error {
+    fn from(err: ProverError) -> Self {
+        match err {
+            ProverError::InvalidInput(_)
+            | ProverError::Execution(_)
+            | ProverError::GuestPanic(_)
+            | ProverError::Divergence(_)
+            | ProverError::Nondeterministic(_) => Self::Execution(err),
+            ProverError::Proving(_) | ProverError::Verification(_) => Self::Proving(err),
+            ProverError::Decode(err) => Self::Decode(err),
+            ProverError::ElfLock(reason) => {
+                Self::Config(ConfigError::Invalid { name: "elf.lock", reason })
+            }
+            ProverError::StaleElf(reason) => {
+                Self::Config(ConfigError::Invalid { name: "SP1_STALE_ELF", reason })
+            }
+        }
+    }
+}
+
+impl From<DomainError> for Error {
+    fn from(err: DomainError) -> Self {
+        match err {
+            DomainError::InvalidConfig { .. } => {
+                Self::Config(ConfigError::Invalid { name: "INPUT_DOMAIN", reason: err.to_string() })
+            }
+            DomainError::OutOfDomain(reason) => {
+                Self::Execution(ProverError::InvalidInput(reason))
+            }
+        }
+    }
+}
+
+impl From<NotifyError> for Error {
+    fn from(err: NotifyError) -> Self {
+        match err {
+            NotifyError::InvalidConfig { .. } => Self::Config(ConfigError::Invalid {
+                name: "NOTIFICATIONS",
+                reason: err.to_string(),
+            }),
+            NotifyError::Send { .. } => Self::Rpc(err.to_string()),
+        }
+    }
+}
+
+impl From<JobError> for Error {
+    /// The job store is a service like the chain: failing to reach or query it is an RPC error.
+    fn from(err: JobError) -> Self {
+        Self::Rpc(err.to_string())
+    }
+}
+
+impl From<ArtifactError> for Error {
+    fn from(err: ArtifactError) -> Self {
+        match err {
+            ArtifactError::Decode(err) => Self::Decode(err),
+            err => Self::Io(err),
+        }
+    }
+}
+
+impl From<AuditError> for Error {
+    fn from(err: AuditError) -> Self {
+        match err {
+            AuditError::Io(err) => err.into(),
+            err => Self::Rejected(err.to_string()),
+        }
+    }
+}
+
+impl From<BundleError> for Error {
+    fn from(err: BundleError) -> Self {
+        match err {
+            BundleError::Artifact(err) => err.into(),
+            BundleError::Fetch { .. } => Self::Rpc(err.to_string()),
+            err => Self::Rejected(err.to_string()),
+        }
+    }
+}
+
+impl From<AddressError> for Error {
+    fn from(err: AddressError) -> Self {
+        match err {
+            AddressError::BookNotFound { .. }
+            | AddressError::InvalidBook { .. }
+            | AddressError::UnknownAlias { .. }
+            | AddressError::InvalidAlias(_) => {
+                Self::Config(ConfigError::Invalid { name: "contract", reason: err.to_string() })
+            }
+            err => Self::Rpc(err.to_string()),
+        }
+    }
+}
+
+impl From<VerifierError> for Error {
+    fn from(err: VerifierError) -> Self {
+        match err {
+            VerifierError::Rpc(_) | VerifierError::Call(_) => Self::Rpc(err.to_string()),
+            err => Self::Config(ConfigError::Invalid { name: "verifier", reason: err.to_string() }),
+        }
+    }
+}
+
+impl From<ChainError> for Error {
+    fn from(err: ChainError) -> Self {
+        Self::Rpc(err.to_string())
+    }
+}
+
+impl From<TransportError> for Error {
+    fn from(err: TransportError) -> Self {
+        Self::Rpc(format!("RPC request failed: {}", err))
+    }
+}
+
+impl From<alloy_contract::Error> for Error {
+    fn from(err: alloy_contract::Error) -> Self {
+        Self::Rpc(format!("contract call failed: {}", err))
+    }
+}
+
+impl From<PendingTransactionError> for Error {
+    fn from(err: PendingTransactionError) -> Self {
+        Self::Rpc(format!("transaction was not confirmed: {}", err))
+    }
+}
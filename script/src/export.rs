@@ -0,0 +1,490 @@
+//! Converting a Groth16 proof and the SP1 Groth16 verifying key into the JSON layouts of other
+//! verification tooling: snarkjs (`proof.json`, `public.json`, `verification_key.json`) and gnark
+//! (the JSON encoding of its Go `Proof` and `VerifyingKey` structs).
+//!
+//! The proof bytes passed to the on-chain verifier are the 4-byte verifier selector followed by
+//! the uncompressed points `A`, `B` and `C`, with the coordinates of `B` in EIP-197 order
+//! (imaginary part first). The verifying key is the one `sp1-verifier` embeds, in gnark's binary
+//! format with compressed points, so its points are decompressed here.
+
+use crate::artifacts::{write_json, ArtifactError};
+use alloy_primitives::U256;
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use clap::ValueEnum;
+use fibonacci_lib::VkHash;
+use fibonacci_verifier_wasm::{groth16_vk, proof_system, ProofSystem};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The formats a proof can be exported in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Snarkjs,
+    GnarkJson,
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Snarkjs => write!(f, "snarkjs"),
+            Self::GnarkJson => write!(f, "gnark-json"),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// The files the proof, its public inputs and the verifying key are saved as, named as the
+    /// tooling names them.
+    pub fn file_names(&self) -> [&'static str; 3] {
+        match self {
+            Self::Snarkjs => ["proof.json", "public.json", "verification_key.json"],
+            Self::GnarkJson => ["proof.json", "public_witness.json", "verifying_key.json"],
+        }
+    }
+}
+
+/// Errors from exporting a proof.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("only Groth16 proofs for this SP1 version can be exported")]
+    NotGroth16,
+    #[error("the {0} is truncated")]
+    Truncated(&'static str),
+    #[error("the verifying key has an invalid point at byte {0}")]
+    InvalidPoint(usize),
+    #[error("the verifying key has {0} public inputs, expected 2")]
+    UnexpectedInputs(usize),
+}
+
+/// A point of G1, in affine coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct G1 {
+    pub x: U256,
+    pub y: U256,
+}
+
+/// A point of G2, in affine coordinates, each as `[real, imaginary]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct G2 {
+    pub x: [U256; 2],
+    pub y: [U256; 2],
+}
+
+/// A Groth16 proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub a: G1,
+    pub b: G2,
+    pub c: G1,
+}
+
+/// A Groth16 verifying key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Groth16VerifyingKey {
+    pub alpha: G1,
+    pub beta_g1: G1,
+    pub beta: G2,
+    pub gamma: G2,
+    pub delta_g1: G1,
+    pub delta: G2,
+    /// The points the public inputs are multiplied with, the first one standing alone.
+    pub ic: Vec<G1>,
+}
+
+/// A proof converted to another format: the proof, its public inputs and the verifying key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exported {
+    pub format: ExportFormat,
+    pub proof: Value,
+    pub public_inputs: Value,
+    pub verifying_key: Value,
+}
+
+impl Exported {
+    /// Save the exported files in `dir`, returning their paths.
+    pub fn save(&self, dir: &Path) -> Result<Vec<PathBuf>, ArtifactError> {
+        let values = [&self.proof, &self.public_inputs, &self.verifying_key];
+        let mut paths = Vec::new();
+        for (name, value) in self.format.file_names().into_iter().zip(values) {
+            let path = dir.join(name);
+            write_json(&path, value)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+impl Groth16Proof {
+    /// Parse the proof bytes passed to the on-chain verifier.
+    pub fn from_bytes(proof: &[u8]) -> Result<Self, ExportError> {
+        if proof_system(proof) != Some(ProofSystem::Groth16) {
+            return Err(ExportError::NotGroth16);
+        }
+        let words = proof
+            .get(4..4 + 8 * 32)
+            .ok_or(ExportError::Truncated("proof"))?
+            .as_chunks::<32>()
+            .0
+            .iter()
+            .map(|word| U256::from_be_bytes(*word))
+            .collect::<Vec<_>>();
+        Ok(Self {
+            a: G1 { x: words[0], y: words[1] },
+            b: G2 { x: [words[3], words[2]], y: [words[5], words[4]] },
+            c: G1 { x: words[6], y: words[7] },
+        })
+    }
+}
+
+impl Groth16VerifyingKey {
+    /// The Groth16 verifying key of this SP1 version.
+    pub fn sp1() -> Result<Self, ExportError> {
+        Self::from_gnark_bytes(groth16_vk())
+    }
+
+    /// Parse a verifying key in gnark's binary format.
+    pub fn from_gnark_bytes(bytes: &[u8]) -> Result<Self, ExportError> {
+        let count = bytes.get(288..292).ok_or(ExportError::Truncated("verifying key"))?;
+        let count = u32::from_be_bytes(count.try_into().expect("slice has 4 bytes")) as usize;
+        let ic = (0..count).map(|i| g1(bytes, 292 + 32 * i)).collect::<Result<Vec<_>, _>>()?;
+        if ic.len() != 3 {
+            return Err(ExportError::UnexpectedInputs(ic.len().saturating_sub(1)));
+        }
+        Ok(Self {
+            alpha: g1(bytes, 0)?,
+            beta_g1: g1(bytes, 32)?,
+            beta: g2(bytes, 64)?,
+            gamma: g2(bytes, 128)?,
+            delta_g1: g1(bytes, 192)?,
+            delta: g2(bytes, 224)?,
+            ic,
+        })
+    }
+}
+
+/// The public inputs of an SP1 Groth16 proof: the program vkey hash and the SHA-256 digest of
+/// the public values, with its top three bits cleared to fit the scalar field.
+pub fn public_inputs(vkey: &VkHash, public_values: &[u8]) -> [U256; 2] {
+    let mut digest: [u8; 32] = Sha256::digest(public_values).into();
+    digest[0] &= 0x1f;
+    [U256::from_be_bytes(vkey.0 .0), U256::from_be_bytes(digest)]
+}
+
+/// Convert a Groth16 proof of the program to `format`.
+pub fn export(
+    format: ExportFormat,
+    proof: &[u8],
+    public_values: &[u8],
+    vkey: &VkHash,
+) -> Result<Exported, ExportError> {
+    let proof = Groth16Proof::from_bytes(proof)?;
+    let vk = Groth16VerifyingKey::sp1()?;
+    let inputs = public_inputs(vkey, public_values);
+    let public_inputs = json!(inputs.map(|input| input.to_string()));
+    Ok(match format {
+        ExportFormat::Snarkjs => Exported {
+            format,
+            proof: json!({
+                "pi_a": snarkjs_g1(&proof.a),
+                "pi_b": snarkjs_g2(&proof.b),
+                "pi_c": snarkjs_g1(&proof.c),
+                "protocol": "groth16",
+                "curve": "bn128"
+            }),
+            public_inputs,
+            verifying_key: json!({
+                "protocol": "groth16",
+                "curve": "bn128",
+                "nPublic": inputs.len(),
+                "vk_alpha_1": snarkjs_g1(&vk.alpha),
+                "vk_beta_2": snarkjs_g2(&vk.beta),
+                "vk_gamma_2": snarkjs_g2(&vk.gamma),
+                "vk_delta_2": snarkjs_g2(&vk.delta),
+                "IC": vk.ic.iter().map(snarkjs_g1).collect::<Vec<_>>()
+            }),
+        },
+        ExportFormat::GnarkJson => Exported {
+            format,
+            proof: json!({
+                "Ar": gnark_g1(&proof.a),
+                "Bs": gnark_g2(&proof.b),
+                "Krs": gnark_g1(&proof.c),
+                "Commitments": [],
+                "CommitmentPok": { "X": "0", "Y": "0" }
+            }),
+            public_inputs,
+            verifying_key: json!({
+                "G1": {
+                    "Alpha": gnark_g1(&vk.alpha),
+                    "Beta": gnark_g1(&vk.beta_g1),
+                    "Delta": gnark_g1(&vk.delta_g1),
+                    "K": vk.ic.iter().map(gnark_g1).collect::<Vec<_>>()
+                },
+                "G2": {
+                    "Beta": gnark_g2(&vk.beta),
+                    "Gamma": gnark_g2(&vk.gamma),
+                    "Delta": gnark_g2(&vk.delta)
+                },
+                "PublicAndCommitmentCommitted": []
+            }),
+        },
+    })
+}
+
+/// A G1 point in snarkjs' projective layout.
+fn snarkjs_g1(point: &G1) -> Value {
+    json!([point.x.to_string(), point.y.to_string(), "1"])
+}
+
+/// A G2 point in snarkjs' projective layout.
+fn snarkjs_g2(point: &G2) -> Value {
+    json!([
+        point.x.map(|c| c.to_string()),
+        point.y.map(|c| c.to_string()),
+        ["1", "0"]
+    ])
+}
+
+fn gnark_g1(point: &G1) -> Value {
+    json!({ "X": point.x.to_string(), "Y": point.y.to_string() })
+}
+
+fn gnark_g2(point: &G2) -> Value {
+    let e2 = |c: [U256; 2]| json!({ "A0": c[0].to_string(), "A1": c[1].to_string() });
+    json!({ "X": e2(point.x), "Y": e2(point.y) })
+}
+
+/// gnark's flags in the top two bits of a compressed point.
+const FLAG_MASK: u8 = 0b11 << 6;
+const FLAG_INFINITY: u8 = 0b01 << 6;
+const FLAG_LARGEST: u8 = 0b11 << 6;
+
+/// Decompress the G1 point at `offset`.
+fn g1(bytes: &[u8], offset: usize) -> Result<G1, ExportError> {
+    let data = bytes.get(offset..offset + 32).ok_or(ExportError::Truncated("verifying key"))?;
+    let flag = data[0] & FLAG_MASK;
+    if flag == FLAG_INFINITY {
+        return Ok(G1 { x: U256::ZERO, y: U256::ZERO });
+    }
+    let x = fq(data);
+    let mut y = G1Affine::get_point_from_x_unchecked(x, false)
+        .ok_or(ExportError::InvalidPoint(offset))?
+        .y;
+    if is_largest(y) != (flag == FLAG_LARGEST) {
+        y = -y;
+    }
+    Ok(G1 { x: to_u256(x), y: to_u256(y) })
+}
+
+/// Decompress the G2 point at `offset`, whose `x` is stored imaginary part first.
+fn g2(bytes: &[u8], offset: usize) -> Result<G2, ExportError> {
+    let data = bytes.get(offset..offset + 64).ok_or(ExportError::Truncated("verifying key"))?;
+    let flag = data[0] & FLAG_MASK;
+    if flag == FLAG_INFINITY {
+        return Ok(G2 { x: [U256::ZERO; 2], y: [U256::ZERO; 2] });
+    }
+    let x = Fq2::new(fq(&data[32..]), fq(&data[..32]));
+    let mut y = G2Affine::get_point_from_x_unchecked(x, false)
+        .ok_or(ExportError::InvalidPoint(offset))?
+        .y;
+    // gnark orders elements of Fq2 by their real part, unless it is zero.
+    let largest = if y.c0.is_zero() { is_largest(y.c1) } else { is_largest(y.c0) };
+    if largest != (flag == FLAG_LARGEST) {
+        y = -y;
+    }
+    Ok(G2 { x: [to_u256(x.c0), to_u256(x.c1)], y: [to_u256(y.c0), to_u256(y.c1)] })
+}
+
+/// A big-endian field element, without the flag bits.
+fn fq(data: &[u8]) -> Fq {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&data[..32]);
+    bytes[0] &= !FLAG_MASK;
+    Fq::from_be_bytes_mod_order(&bytes)
+}
+
+/// Whether `y` is larger than `-y`, as gnark compares them.
+fn is_largest(y: Fq) -> bool {
+    y.into_bigint() > Fq::MODULUS_MINUS_ONE_DIV_TWO
+}
+
+fn to_u256(value: Fq) -> U256 {
+    U256::from_be_slice(&value.into_bigint().to_bytes_be())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fibonacci_verifier_wasm::verifier_hash;
+
+    /// The proof, public values and vkey hash of the checked-in Groth16 fixture, with the proof
+    /// prefixed with the selector of this SP1 version: the fixture predates it, but its points
+    /// are laid out the same.
+    fn fixture() -> (Vec<u8>, Vec<u8>, VkHash) {
+        let path = format!(
+            "{}/../contracts/src/fixtures/groth16-fixture.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let fixture: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let bytes = |name: &str| {
+            hex::decode(fixture[name].as_str().unwrap().trim_start_matches("0x")).unwrap()
+        };
+        let mut proof = bytes("proof");
+        proof[..4].copy_from_slice(&verifier_hash(ProofSystem::Groth16)[..4]);
+        (proof, bytes("publicValues"), fixture["vkey"].as_str().unwrap().parse().unwrap())
+    }
+
+    // The words of the fixture proof, `A`, `B` (imaginary parts first) and `C`.
+    const PROOF_WORDS: [&str; 8] = [
+        "3368483806581724465317689444020368698939935546242112327495978281682465392330",
+        "12627574437790655231247846960676361754065698615430281341965713420363859584728",
+        "7750567354316701759421141562409349644652715114489483253273095588401674943050",
+        "15827530545520619343786370830622212868641901690378764499355844446468159024730",
+        "18141768765258825375394772911544008449573352426063301270878392995188943593222",
+        "8684430188248723034225312728437303766534915681294700020778311474820647172702",
+        "3835410732109491868891377502804714573030615131141431365785086890865152830488",
+        "251604910484066635454261974288812699144587728722695311556547315198759626316",
+    ];
+    const VKEY_INPUT: &str =
+        "319999016678636370054941892603576234974363537179753055569733680612164079586";
+    const DIGEST_INPUT: &str =
+        "10824622438547753178226638683150032945337073675643207970725709517409856557841";
+
+    fn u256(value: &str) -> U256 {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn exports_the_fixture_for_snarkjs() {
+        let (proof, public_values, vkey) = fixture();
+        let exported = export(ExportFormat::Snarkjs, &proof, &public_values, &vkey).unwrap();
+        let [ax, ay, bx1, bx0, by1, by0, cx, cy] = PROOF_WORDS;
+        assert_eq!(exported.proof["pi_a"], json!([ax, ay, "1"]));
+        assert_eq!(exported.proof["pi_b"], json!([[bx0, bx1], [by0, by1], ["1", "0"]]));
+        assert_eq!(exported.proof["pi_c"], json!([cx, cy, "1"]));
+        assert_eq!(exported.public_inputs, json!([VKEY_INPUT, DIGEST_INPUT]));
+        assert_eq!(exported.verifying_key["nPublic"], 2);
+        assert_eq!(exported.verifying_key["IC"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn exports_the_fixture_for_gnark() {
+        let (proof, public_values, vkey) = fixture();
+        let exported = export(ExportFormat::GnarkJson, &proof, &public_values, &vkey).unwrap();
+        let [ax, ay, bx1, bx0, by1, by0, cx, cy] = PROOF_WORDS;
+        assert_eq!(exported.proof["Ar"], json!({ "X": ax, "Y": ay }));
+        assert_eq!(
+            exported.proof["Bs"],
+            json!({ "X": { "A0": bx0, "A1": bx1 }, "Y": { "A0": by0, "A1": by1 } })
+        );
+        assert_eq!(exported.proof["Krs"], json!({ "X": cx, "Y": cy }));
+        assert_eq!(exported.public_inputs, json!([VKEY_INPUT, DIGEST_INPUT]));
+        assert_eq!(exported.verifying_key["G1"]["K"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn public_inputs_clear_the_top_bits_of_the_digest() {
+        let (_, public_values, vkey) = fixture();
+        let [vkey_input, digest_input] = public_inputs(&vkey, &public_values);
+        assert_eq!(vkey_input, u256(VKEY_INPUT));
+        assert_eq!(digest_input, u256(DIGEST_INPUT));
+        assert!(digest_input < U256::from(1) << 253);
+    }
+
+    #[test]
+    fn decompresses_the_sp1_verifying_key() {
+        let vk = Groth16VerifyingKey::sp1().unwrap();
+        assert_eq!(
+            vk.alpha,
+            G1 {
+                x: u256(
+                    "20491192805390485299153009773594534940189261866228447918068658471970481763042"
+                ),
+                y: u256(
+                    "9383485363053290200918347156157836566562967994039712273449902621266178545958"
+                ),
+            }
+        );
+        assert_eq!(
+            vk.gamma,
+            G2 {
+                x: [
+                    u256("10857046999023057135944570762232829481370756359578518086990519993285655852781"),
+                    u256("11559732032986387107991004021392285783925812861821192530917403151452391805634"),
+                ],
+                y: [
+                    u256("8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+                    u256("4082367875863433681332203403145435568316851327593401208105741076214120093531"),
+                ],
+            }
+        );
+        assert_eq!(
+            vk.delta.y,
+            [
+                u256(
+                    "11385252965472363874004017020523979267854101512663014352368174256411716100034"
+                ),
+                u256("707821308472421780425082520239282952693670279239989952629124761519869475067"),
+            ]
+        );
+        assert_eq!(
+            vk.ic[2],
+            G1 {
+                x: u256(
+                    "2223923876691923064813371578678400285087400227347901303400514986210692294428"
+                ),
+                y: u256(
+                    "3228708299174762375496115493137156328822199374794870011715145604387710550517"
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn verifying_key_has_compressed_points_and_two_public_inputs() {
+        let bytes = groth16_vk();
+        let offsets = [0, 32, 64, 128, 192, 224].into_iter().chain((0..3).map(|i| 292 + 32 * i));
+        for offset in offsets {
+            let flag = bytes[offset] & FLAG_MASK;
+            assert!(flag == 0b10 << 6 || flag == FLAG_LARGEST, "flag {:#x} at {}", flag, offset);
+        }
+        assert_eq!(bytes[288..292], 3u32.to_be_bytes());
+    }
+
+    #[test]
+    fn the_largest_flag_picks_the_root() {
+        let mut bytes = groth16_vk().to_vec();
+        bytes[0] ^= FLAG_LARGEST ^ (0b10 << 6);
+        let alpha = Groth16VerifyingKey::sp1().unwrap().alpha;
+        let flipped = Groth16VerifyingKey::from_gnark_bytes(&bytes).unwrap().alpha;
+        assert_eq!(flipped.x, alpha.x);
+        assert_eq!(flipped.y + alpha.y, U256::from_be_slice(&Fq::MODULUS.to_bytes_be()));
+    }
+
+    #[test]
+    fn rejects_malformed_inputs() {
+        let mut bytes = groth16_vk().to_vec();
+        bytes[288..292].copy_from_slice(&2u32.to_be_bytes());
+        assert!(matches!(
+            Groth16VerifyingKey::from_gnark_bytes(&bytes),
+            Err(ExportError::UnexpectedInputs(1))
+        ));
+        assert!(matches!(
+            Groth16VerifyingKey::from_gnark_bytes(&groth16_vk()[..300]),
+            Err(ExportError::Truncated("verifying key"))
+        ));
+
+        let (proof, _, _) = fixture();
+        assert!(matches!(
+            Groth16Proof::from_bytes(&proof[..100]),
+            Err(ExportError::Truncated("proof"))
+        ));
+        let mut plonk = proof;
+        plonk[..4].copy_from_slice(&verifier_hash(ProofSystem::Plonk)[..4]);
+        assert!(matches!(Groth16Proof::from_bytes(&plonk), Err(ExportError::NotGroth16)));
+    }
+}
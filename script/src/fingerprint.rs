@@ -0,0 +1,41 @@
+//! Fingerprinting the guest sources. Shared with `build.rs`, which embeds the fingerprint of the
+//! sources the ELF was built from, so it can later be compared with the sources on disk.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The crates the program is built from, relative to `script`.
+pub const GUEST_SOURCE_DIRS: &[&str] = &["../program", "../lib"];
+
+/// Hash the `Cargo.toml` and `src/` files of each of `dirs` under `root`, or `None` if any of
+/// them cannot be read.
+pub fn source_fingerprint(root: &Path, dirs: &[&str]) -> Option<String> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        files.push(Path::new(dir).join("Cargo.toml"));
+        collect(root, &Path::new(dir).join("src"), &mut files).ok()?;
+    }
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update(fs::read(root.join(&file)).ok()?);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Collect the files under `dir`, relative to `root`.
+fn collect(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(dir))? {
+        let entry = entry?;
+        let path = dir.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+//! Choosing when to submit: the recent base fees of the settlement chain, from `eth_feeHistory`,
+//! and waiting for the base fee to drop under a threshold.
+
+use crate::chain::ChainError;
+use alloy_provider::Provider;
+use alloy_rpc_types::BlockNumberOrTag;
+use alloy_transport::Transport;
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The most blocks `eth_feeHistory` returns in one request on common nodes.
+pub const MAX_SAMPLED_BLOCKS: u64 = 1024;
+
+/// The base fees of recent blocks, in gwei.
+#[derive(Debug, Clone, Serialize)]
+pub struct BaseFeeSample {
+    /// How many blocks were sampled, up to the latest.
+    pub blocks: u64,
+    pub latest_block: u64,
+    /// The base fee of the next block, which a transaction sent now pays.
+    pub next_gwei: f64,
+    pub min_gwei: f64,
+    pub p25_gwei: f64,
+    pub median_gwei: f64,
+    pub max_gwei: f64,
+}
+
+/// Whether to submit now or wait, judged against the sampled base fees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "action")]
+pub enum Recommendation {
+    /// The base fee is in the cheapest quarter of the sampled blocks.
+    SubmitNow,
+    /// The base fee is typical: under the median, but not especially cheap.
+    Acceptable,
+    /// The base fee is above the median; waiting for it to drop to `target_gwei` is likely to
+    /// pay off.
+    Wait { target_gwei: f64 },
+}
+
+impl fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SubmitNow => write!(f, "submit now: the base fee is among the cheapest quarter"),
+            Self::Acceptable => write!(f, "fine to submit now: the base fee is under the median"),
+            Self::Wait { target_gwei } => {
+                write!(f, "wait: the base fee is above the median, aim for {:.3} gwei", target_gwei)
+            }
+        }
+    }
+}
+
+impl BaseFeeSample {
+    /// Recommend submitting now or waiting, from where the next base fee falls among the sample.
+    pub fn recommendation(&self) -> Recommendation {
+        if self.next_gwei <= self.p25_gwei {
+            Recommendation::SubmitNow
+        } else if self.next_gwei <= self.median_gwei {
+            Recommendation::Acceptable
+        } else {
+            Recommendation::Wait { target_gwei: self.median_gwei }
+        }
+    }
+}
+
+/// Sample the base fees of the last `blocks` blocks (at most [`MAX_SAMPLED_BLOCKS`]).
+pub async fn sample_base_fees<T, P>(provider: &P, blocks: u64) -> Result<BaseFeeSample, ChainError>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let blocks = blocks.clamp(1, MAX_SAMPLED_BLOCKS);
+    let history = provider.get_fee_history(blocks, BlockNumberOrTag::Latest, &[]).await?;
+    // The history ends with the base fee of the block after the latest.
+    let mut fees = history.base_fee_per_gas;
+    let next = fees.pop().unwrap_or_default();
+    if fees.is_empty() {
+        return Err(ChainError::BlockNotFound("latest".to_string()));
+    }
+    let latest_block = history.oldest_block + fees.len() as u64 - 1;
+    fees.sort_unstable();
+    Ok(BaseFeeSample {
+        blocks: fees.len() as u64,
+        latest_block,
+        next_gwei: wei_to_gwei(next),
+        min_gwei: wei_to_gwei(fees[0]),
+        p25_gwei: wei_to_gwei(percentile(&fees, 25)),
+        median_gwei: wei_to_gwei(percentile(&fees, 50)),
+        max_gwei: wei_to_gwei(fees[fees.len() - 1]),
+    })
+}
+
+/// The base fee of the next block, in gwei.
+pub async fn next_base_fee<T, P>(provider: &P) -> Result<f64, ChainError>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let history = provider.get_fee_history(1, BlockNumberOrTag::Latest, &[]).await?;
+    Ok(wei_to_gwei(history.base_fee_per_gas.last().copied().unwrap_or_default()))
+}
+
+/// Wait until the base fee of the next block is at most `max_gwei`, checking every `poll`.
+/// Returns the base fee it dropped to, or `None` if it was still higher after `within`.
+pub async fn wait_for_base_fee<T, P>(
+    provider: &P,
+    max_gwei: f64,
+    within: Duration,
+    poll: Duration,
+) -> Result<Option<f64>, ChainError>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let deadline = Instant::now() + within;
+    loop {
+        let next_gwei = next_base_fee(provider).await?;
+        if next_gwei <= max_gwei {
+            return Ok(Some(next_gwei));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        println!("⏳ Base fee {:.3} gwei is above {} gwei, waiting...", next_gwei, max_gwei);
+        tokio::time::sleep(poll.min(deadline - now)).await;
+    }
+}
+
+/// Convert an amount of wei to gwei.
+pub fn wei_to_gwei(wei: u128) -> f64 {
+    wei as f64 / 1e9
+}
+
+/// The nearest-rank `p`th percentile of sorted fees.
+fn percentile(sorted: &[u128], p: usize) -> u128 {
+    let rank = (sorted.len() * p).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied().unwrap_or_default()
+}
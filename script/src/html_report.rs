@@ -0,0 +1,312 @@
+//! A static HTML page of proving activity, for `cli report html`: charts of the prover spend and
+//! settlement gas by program and chain, the jobs of the job store with their cost and whether
+//! their proofs were settled, and the run reports found in the artifacts directory.
+//!
+//! The page is a single file without scripts or external resources, so it can be mailed or
+//! attached to a ticket for people who do not use the CLI.
+
+use crate::chain::wei_to_eth;
+use crate::jobs::{CostSummary, Job, JobStatus, Settlement};
+use crate::profile::escape;
+use crate::run_report::{RunReport, REPLAY_REPORT_FILE, RUN_REPORT_FILE};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything the page shows.
+#[derive(Debug, Clone)]
+pub struct ActivityReport {
+    /// The Unix time the report was generated at.
+    pub generated_at: u64,
+    /// The Unix time the report starts from.
+    pub since: u64,
+    /// The proving price per million cycles, to estimate the prover network spend.
+    pub price_per_mcycle: f64,
+    pub costs: Vec<CostSummary>,
+    pub jobs: Vec<Job>,
+    pub settlements: Vec<Settlement>,
+    /// The run reports, and where they were found.
+    pub runs: Vec<(PathBuf, RunReport)>,
+}
+
+impl ActivityReport {
+    /// Render the report as a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        self.write_summary(&mut body);
+        self.write_costs(&mut body);
+        self.write_jobs(&mut body);
+        self.write_runs(&mut body);
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Proving activity</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+             <h1>Proving activity</h1>\n<p>Since {}, generated {}.</p>\n{}</body>\n</html>\n",
+            STYLE,
+            format_time(self.since),
+            format_time(self.generated_at),
+            body
+        )
+    }
+
+    fn write_summary(&self, html: &mut String) {
+        let count = |status: JobStatus| self.jobs.iter().filter(|job| job.status == status).count();
+        let spend: f64 = self.costs.iter().map(|summary| self.spend(summary)).sum();
+        let gas_cost: u128 = self.costs.iter().map(|summary| summary.gas_cost_wei).sum();
+        let pending = count(JobStatus::Queued) + count(JobStatus::Running);
+        let reverted = self.settlements.iter().filter(|settlement| !settlement.success).count();
+        html.push_str("<div class=\"tiles\">\n");
+        let tiles = [
+            ("Jobs", self.jobs.len().to_string()),
+            ("Completed", count(JobStatus::Completed).to_string()),
+            ("Failed", count(JobStatus::Failed).to_string()),
+            ("Queued or running", pending.to_string()),
+            ("Settlements", self.settlements.len().to_string()),
+            ("Reverted settlements", reverted.to_string()),
+            ("Prover spend", format!("{:.4}", spend)),
+            ("Settlement gas (ETH)", format!("{:.6}", wei_to_eth(gas_cost))),
+        ];
+        for (label, value) in tiles {
+            write_html(html, format_args!("<div class=\"tile\"><b>{}</b>{}</div>\n", value, label));
+        }
+        html.push_str("</div>\n");
+    }
+
+    fn write_costs(&self, html: &mut String) {
+        html.push_str("<h2>Costs by program and chain</h2>\n");
+        if self.costs.is_empty() {
+            html.push_str("<p>No completed jobs or settlements.</p>\n");
+            return;
+        }
+        let label = |summary: &CostSummary| match summary.chain_id {
+            Some(chain_id) => format!("{} on chain {}", summary.program, chain_id),
+            None => summary.program.clone(),
+        };
+        let spend: Vec<(String, f64)> =
+            self.costs.iter().map(|summary| (label(summary), self.spend(summary))).collect();
+        let gas: Vec<(String, f64)> = self
+            .costs
+            .iter()
+            .map(|summary| (label(summary), wei_to_eth(summary.gas_cost_wei)))
+            .collect();
+        let title = format!("Prover network spend (at {} per Mcycle)", self.price_per_mcycle);
+        write_chart(html, &title, &spend, 4);
+        write_chart(html, "Settlement gas (ETH, excluding L1 data fees)", &gas, 6);
+
+        let mut per_day: BTreeMap<u64, f64> = BTreeMap::new();
+        for job in self.jobs.iter().filter(|job| job.status == JobStatus::Completed) {
+            *per_day.entry(job.updated_at / 86_400).or_default() += 1.0;
+        }
+        let per_day: Vec<(String, f64)> = per_day
+            .into_iter()
+            .map(|(day, proofs)| (format_time(day * 86_400)[..10].to_string(), proofs))
+            .collect();
+        write_chart(html, "Completed jobs per day", &per_day, 0);
+    }
+
+    fn write_jobs(&self, html: &mut String) {
+        html.push_str("<h2>Jobs</h2>\n");
+        if self.jobs.is_empty() {
+            html.push_str("<p>No jobs.</p>\n");
+            return;
+        }
+        html.push_str(
+            "<table>\n<tr><th>Job</th><th>Tenant</th><th>n</th><th>System</th><th>Status</th>\
+             <th>Created</th><th>Attempts</th><th>Prover</th><th>Cycles</th><th>Settlement</th>\
+             <th>Error</th></tr>\n",
+        );
+        for job in &self.jobs {
+            let (class, settlement) = self.settlement_status(job);
+            write_html(
+                html,
+                format_args!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td>\
+                     <td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td>\
+                     <td>{}</td></tr>\n",
+                    escape(&job.id),
+                    escape(&job.tenant),
+                    job.input.n,
+                    job.system,
+                    job.status,
+                    job.status,
+                    format_time(job.created_at),
+                    job.attempts,
+                    escape(job.prover.as_deref().unwrap_or("-")),
+                    job.cycles.map_or("-".to_string(), |cycles| cycles.to_string()),
+                    class,
+                    settlement,
+                    escape(job.error.as_deref().unwrap_or("")),
+                ),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn write_runs(&self, html: &mut String) {
+        html.push_str("<h2>Runs</h2>\n");
+        if self.runs.is_empty() {
+            html.push_str("<p>No run reports.</p>\n");
+            return;
+        }
+        for (path, run) in &self.runs {
+            let (class, status) = match (&run.outcome.error, &run.outcome.proof_hash) {
+                (Some(error), _) => ("failed", format!("failed: {}", escape(error))),
+                (None, Some(_)) => ("completed", "proved and verified locally".to_string()),
+                (None, None) => ("queued", "no proof".to_string()),
+            };
+            let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+            write_html(
+                html,
+                format_args!(
+                    "<details>\n<summary><span class=\"{}\">{}</span> {} n = {} ({}), {} \
+                     in {:.1}s</summary>\n<table>\n",
+                    class,
+                    if class == "failed" { "✗" } else { "✓" },
+                    escape(&run.command),
+                    run.input.n,
+                    run.system,
+                    format_time(run.started_at),
+                    run.duration_secs
+                ),
+            );
+            let rows = [
+                ("Report", escape(&path.display().to_string())),
+                ("Status", status),
+                ("Input", escape(&serde_json::to_string(&run.input).unwrap_or_default())),
+                ("VKey", optional(run.outcome.vkey.map(|vkey| vkey.to_string()))),
+                ("Cycles", optional(run.outcome.cycles.map(|cycles| cycles.to_string()))),
+                (
+                    "Public values",
+                    optional(run.outcome.public_values.as_ref().map(ToString::to_string)),
+                ),
+                ("Proof hash", optional(run.outcome.proof_hash.map(|hash| hash.to_string()))),
+                ("Git commit", escape(&run.provenance.git_commit)),
+                ("ELF hash", run.provenance.elf_hash.to_string()),
+                (
+                    "Environment",
+                    escape(
+                        &run.env
+                            .iter()
+                            .map(|(name, value)| format!("{}={}", name, value))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                ),
+            ];
+            for (name, value) in rows {
+                write_html(html, format_args!("<tr><th>{}</th><td>{}</td></tr>\n", name, value));
+            }
+            html.push_str("</table>\n</details>\n");
+        }
+    }
+
+    /// The estimated prover network spend of a summary.
+    fn spend(&self, summary: &CostSummary) -> f64 {
+        summary.network_cycles as f64 / 1e6 * self.price_per_mcycle
+    }
+
+    /// Whether the proof of a job was settled on-chain, as a CSS class and a description.
+    fn settlement_status(&self, job: &Job) -> (&'static str, String) {
+        let settlements: Vec<&Settlement> = self
+            .settlements
+            .iter()
+            .filter(|settlement| settlement.job_id.as_deref() == Some(job.id.as_str()))
+            .collect();
+        if let Some(settled) = settlements.iter().find(|settlement| settlement.success) {
+            let description = format!(
+                "settled on chain {} in block {} ({})",
+                settled.chain_id,
+                settled.block_number,
+                escape(&settled.tx_hash)
+            );
+            return ("completed", description);
+        }
+        match (settlements.len(), job.status) {
+            (0, JobStatus::Completed) => ("queued", "not settled".to_string()),
+            (0, _) => ("queued", "-".to_string()),
+            (reverted, _) => ("failed", format!("{} settlement(s) reverted", reverted)),
+        }
+    }
+}
+
+/// Find the run reports under `dir`, including those of replays.
+pub fn find_run_reports(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut reports = Vec::new();
+    if !dir.exists() {
+        return Ok(reports);
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            reports.extend(find_run_reports(&path)?);
+        } else if path
+            .file_name()
+            .is_some_and(|name| name == RUN_REPORT_FILE || name == REPLAY_REPORT_FILE)
+        {
+            reports.push(path);
+        }
+    }
+    reports.sort();
+    Ok(reports)
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }\n\
+    .tiles { display: flex; flex-wrap: wrap; gap: 1em; }\n\
+    .tile { border: 1px solid #ddd; border-radius: 4px; padding: 0.5em 1em; min-width: 8em; }\n\
+    .tile b { display: block; font-size: 1.5em; }\n\
+    table { border-collapse: collapse; margin: 0.5em 0 1em; }\n\
+    th, td { border: 1px solid #ddd; padding: 2px 6px; text-align: left; font-size: 0.9em; }\n\
+    .chart { margin-bottom: 1.5em; }\n\
+    .bar { display: flex; align-items: center; margin: 2px 0; }\n\
+    .bar span { width: 16em; }\n\
+    .bar div { background: #2a9d8f; height: 1em; margin-right: 0.5em; }\n\
+    .completed { color: #2a9d8f; }\n.failed { color: #e63946; }\n\
+    .running { color: #e9c46a; }\n.queued, .interrupted { color: #888; }\n\
+    details { margin: 0.5em 0; }\n";
+
+/// A horizontal bar chart of `values`, each labelled and shown with `decimals` decimals.
+fn write_chart(html: &mut String, title: &str, values: &[(String, f64)], decimals: usize) {
+    let max = values.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+    write_html(html, format_args!("<div class=\"chart\">\n<h3>{}</h3>\n", escape(title)));
+    for (label, value) in values {
+        let width = if max > 0.0 { value / max * 60.0 } else { 0.0 };
+        write_html(
+            html,
+            format_args!(
+                "<div class=\"bar\"><span>{}</span><div style=\"width:{:.2}%\"></div>{:.*}</div>\n",
+                escape(label),
+                width,
+                decimals,
+                value
+            ),
+        );
+    }
+    html.push_str("</div>\n");
+}
+
+fn write_html(html: &mut String, args: std::fmt::Arguments<'_>) {
+    html.write_fmt(args).expect("writing to a String cannot fail");
+}
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM UTC`.
+pub fn format_time(timestamp: u64) -> String {
+    let (year, month, day) = civil_date(timestamp);
+    let secs = timestamp % 86_400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, secs / 3600, secs % 3600 / 60)
+}
+
+/// The UTC year, month and day of a Unix timestamp.
+pub(crate) fn civil_date(timestamp: u64) -> (i64, i64, i64) {
+    // The civil date of a day since the epoch (Howard Hinnant's `civil_from_days`).
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
@@ -0,0 +1,1325 @@
+//! A persistent queue of proving jobs, worked through by `worker` and managed with `cli jobs`.
+//!
+//! The store is SQLite by default. With the `postgres` feature it can also be a Postgres database
+//! (`postgres://...`), so that workers on different machines share one queue. Both backends use
+//! the same schema.
+//!
+//! Jobs and API keys belong to a tenant, so one deployment can serve several projects: a tenant's
+//! requests are only coalesced with its own jobs, `service` only shows a key the jobs of its
+//! tenant, and `worker` keeps each tenant's proofs in its own directory (see
+//! [`crate::artifacts::tenant_dir`]).
+
+use crate::artifacts::input_hash;
+use crate::chain::SettlementReceipt;
+use crate::prover::{NetworkStrategy, ProofSystem};
+use alloy_primitives::{Address, B256};
+use clap::ValueEnum;
+use fibonacci_lib::ProgramInput;
+use serde::Serialize;
+use sqlx::any::{AnyPoolOptions, AnyRow};
+use sqlx::{AnyPool, Row};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// The job store used when `JOB_STORE_URL` is not set.
+pub const DEFAULT_JOB_STORE_URL: &str = "sqlite://artifacts/jobs.db?mode=rwc";
+
+/// The tenant of jobs and keys that were not given one, including all those from before tenants.
+pub const DEFAULT_TENANT: &str = "default";
+
+/// The schema shared by the SQLite and Postgres backends, as migrations applied in order. Stores
+/// record how many they have applied in `job_store_version`.
+const MIGRATIONS: &[&[&str]] = &[
+    &[
+        "CREATE TABLE IF NOT EXISTS jobs (
+            id TEXT PRIMARY KEY,
+            input TEXT NOT NULL,
+            input_hash TEXT NOT NULL,
+            system TEXT NOT NULL,
+            status TEXT NOT NULL,
+            attempts BIGINT NOT NULL DEFAULT 0,
+            worker TEXT,
+            artifact TEXT,
+            error TEXT,
+            created_at BIGINT NOT NULL,
+            updated_at BIGINT NOT NULL
+        )",
+        "CREATE INDEX IF NOT EXISTS jobs_status ON jobs (status, created_at)",
+    ],
+    &[
+        "ALTER TABLE jobs ADD COLUMN priority BIGINT NOT NULL DEFAULT 1",
+        "ALTER TABLE jobs ADD COLUMN run_after BIGINT NOT NULL DEFAULT 0",
+        "CREATE INDEX IF NOT EXISTS jobs_queue ON jobs (status, priority, run_after)",
+    ],
+    &[
+        // At most one unfinished job per input, so concurrent identical submissions coalesce.
+        "CREATE UNIQUE INDEX IF NOT EXISTS jobs_unfinished_input ON jobs (input_hash)
+        WHERE status IN ('queued', 'running', 'interrupted')",
+    ],
+    &[
+        "ALTER TABLE jobs ADD COLUMN valid_until BIGINT NOT NULL DEFAULT 0",
+        "ALTER TABLE jobs ADD COLUMN renewed_by TEXT",
+    ],
+    &[
+        "ALTER TABLE jobs ADD COLUMN proof_hash TEXT",
+        "CREATE INDEX IF NOT EXISTS jobs_proof_hash ON jobs (proof_hash)",
+        // Transactions that settled a proof, linked to the job that proved it (if any). Wei
+        // amounts may not fit a BIGINT, so they are stored as decimal text.
+        "CREATE TABLE IF NOT EXISTS settlements (
+            tx_hash TEXT PRIMARY KEY,
+            job_id TEXT,
+            proof_hash TEXT NOT NULL,
+            manifest_hash TEXT,
+            chain_id BIGINT NOT NULL,
+            contract TEXT NOT NULL,
+            block_number BIGINT NOT NULL,
+            gas_used BIGINT NOT NULL,
+            effective_gas_price TEXT NOT NULL,
+            success BIGINT NOT NULL,
+            artifact TEXT NOT NULL,
+            created_at BIGINT NOT NULL
+        )",
+        "CREATE INDEX IF NOT EXISTS settlements_job ON settlements (job_id)",
+    ],
+    &[
+        // Every job and settlement so far is of the Fibonacci program.
+        "ALTER TABLE jobs ADD COLUMN program TEXT NOT NULL DEFAULT 'fibonacci'",
+        "ALTER TABLE jobs ADD COLUMN prover TEXT",
+        "ALTER TABLE jobs ADD COLUMN cycles BIGINT",
+        "ALTER TABLE settlements ADD COLUMN program TEXT NOT NULL DEFAULT 'fibonacci'",
+    ],
+    &["ALTER TABLE jobs ADD COLUMN network_strategy TEXT"],
+    &[
+        // Keys are only stored hashed: a leaked store does not leak them.
+        "CREATE TABLE IF NOT EXISTS api_keys (
+            name TEXT PRIMARY KEY,
+            key_hash TEXT NOT NULL UNIQUE,
+            rate_limit BIGINT,
+            max_concurrent BIGINT,
+            created_at BIGINT NOT NULL,
+            revoked_at BIGINT
+        )",
+        "ALTER TABLE jobs ADD COLUMN api_key TEXT",
+        "CREATE INDEX IF NOT EXISTS jobs_api_key ON jobs (api_key, status)",
+    ],
+    &[
+        "ALTER TABLE jobs ADD COLUMN tenant TEXT NOT NULL DEFAULT 'default'",
+        "ALTER TABLE api_keys ADD COLUMN tenant TEXT NOT NULL DEFAULT 'default'",
+        // Identical requests of different tenants are separate jobs.
+        "DROP INDEX IF EXISTS jobs_unfinished_input",
+        "CREATE UNIQUE INDEX IF NOT EXISTS jobs_unfinished_tenant_input ON jobs (tenant, input_hash)
+        WHERE status IN ('queued', 'running', 'interrupted')",
+    ],
+    &[
+        // How long each phase of a job took, one row per phase run (a retried job has several).
+        "CREATE TABLE IF NOT EXISTS job_phases (
+            job_id TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            system TEXT NOT NULL,
+            duration_ms BIGINT NOT NULL,
+            recorded_at BIGINT NOT NULL
+        )",
+        "CREATE INDEX IF NOT EXISTS job_phases_recorded ON job_phases (recorded_at)",
+    ],
+    &[
+        // The nonces of requests being settled or settled, so every worker and host sharing the
+        // store settles a request once. `tx_hash` is set once the settlement is mined.
+        "CREATE TABLE IF NOT EXISTS used_nonces (
+            consumer TEXT NOT NULL,
+            chain_id BIGINT NOT NULL,
+            nonce BIGINT NOT NULL,
+            tx_hash TEXT,
+            claimed_at BIGINT NOT NULL,
+            UNIQUE (consumer, chain_id, nonce)
+        )",
+    ],
+];
+
+/// The columns a [`Job`] is read from.
+const COLUMNS: &str = concat!(
+    "id, input, input_hash, system, status, priority, run_after, attempts, worker, artifact, ",
+    "error, renewed_by, proof_hash, prover, cycles, network_strategy, api_key, tenant, ",
+    "created_at, updated_at"
+);
+
+/// The columns an [`ApiKey`] is read from.
+const API_KEY_COLUMNS: &str = "name, tenant, rate_limit, max_concurrent, created_at, revoked_at";
+
+/// Errors from the job store.
+#[derive(Debug, Error)]
+pub enum JobError {
+    #[error("job store error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("job {0} not found")]
+    NotFound(String),
+    #[error("job {id} is corrupt: {reason}")]
+    Corrupt { id: String, reason: String },
+    #[error("API key {0} not found")]
+    KeyNotFound(String),
+    #[error("API key {0} already exists")]
+    KeyExists(String),
+    #[error("invalid tenant `{0}`: use lowercase letters, digits, `-` and `_`")]
+    InvalidTenant(String),
+    #[error("API key {api_key} already has {max} unfinished jobs")]
+    TooManyJobs { api_key: String, max: u32 },
+}
+
+/// How urgently a job should be proved. Higher priorities are claimed first.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Default, ToSchema)]
+#[schema(rename_all = "lowercase")]
+pub enum Priority {
+    /// Batch work such as re-proving, done when nothing else is waiting.
+    Background,
+    #[default]
+    Normal,
+    /// Liveness-critical work, e.g. oracle updates.
+    Urgent,
+}
+
+impl Priority {
+    fn as_i64(self) -> i64 {
+        match self {
+            Self::Background => 0,
+            Self::Normal => 1,
+            Self::Urgent => 2,
+        }
+    }
+
+    fn from_i64(priority: i64) -> Option<Self> {
+        match priority {
+            0 => Some(Self::Background),
+            1 => Some(Self::Normal),
+            2 => Some(Self::Urgent),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.to_possible_value().expect("no skipped variants").get_name())
+    }
+}
+
+/// How a job is scheduled.
+#[derive(Clone, Debug, Default)]
+pub struct JobOptions {
+    pub priority: Priority,
+    /// The Unix time before which the job is not claimed, or 0 to run it as soon as possible.
+    pub run_after: u64,
+    /// How the prover network should fulfill the job, overriding the worker's default for its
+    /// priority.
+    pub network_strategy: Option<NetworkStrategy>,
+    /// The name of the API key the job was submitted with through `service`, if any.
+    pub api_key: Option<String>,
+    /// The most unfinished jobs `api_key` may have, counting this one; past it the job is not
+    /// queued (see [`JobError::TooManyJobs`]).
+    pub max_concurrent: Option<u32>,
+    /// The tenant the job belongs to, or [`DEFAULT_TENANT`].
+    pub tenant: Option<String>,
+}
+
+/// A key clients of `service` authenticate with, and its limits.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ApiKey {
+    pub name: String,
+    /// The tenant whose jobs the key submits and sees.
+    pub tenant: String,
+    /// The most requests the key may make per minute, if limited.
+    pub rate_limit: Option<u32>,
+    /// The most unfinished jobs the key may have submitted at once, if limited.
+    pub max_concurrent: Option<u32>,
+    pub created_at: u64,
+    /// When the key was revoked, if it was.
+    pub revoked_at: Option<u64>,
+}
+
+impl ApiKey {
+    fn from_row(row: &AnyRow) -> Result<Self, JobError> {
+        let limit = |column: &str| -> Result<Option<u32>, JobError> {
+            Ok(row.try_get::<Option<i64>, _>(column)?.map(|limit| limit as u32))
+        };
+        Ok(Self {
+            name: row.try_get("name")?,
+            tenant: row.try_get("tenant")?,
+            rate_limit: limit("rate_limit")?,
+            max_concurrent: limit("max_concurrent")?,
+            created_at: row.try_get::<i64, _>("created_at")? as u64,
+            revoked_at: row.try_get::<Option<i64>, _>("revoked_at")?.map(|at| at as u64),
+        })
+    }
+}
+
+/// How a job's proof was generated, recorded for cost reporting.
+#[derive(Clone, Debug)]
+pub struct ProvingRun {
+    /// The prover used (`SP1_PROVER`): only `network` proofs are paid for.
+    pub prover: String,
+    pub cycles: u64,
+}
+
+/// The proving and settlement costs of a program on a chain, as summed by [`JobStore::costs`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CostSummary {
+    pub program: String,
+    /// The chain the proofs were bound to and settled on, if any.
+    pub chain_id: Option<u64>,
+    /// Completed jobs, including those served from the cache.
+    pub proofs: u64,
+    /// Cycles proved on the prover network.
+    pub network_cycles: u64,
+    /// Cycles proved locally (`cpu`, `cuda` or `mock`), which cost nothing on the network.
+    pub local_cycles: u64,
+    /// Settlement transactions, including reverted ones.
+    pub settlements: u64,
+    pub reverted_settlements: u64,
+    pub gas_used: u128,
+    /// What the settlement transactions cost, excluding any L1 data fees.
+    pub gas_cost_wei: u128,
+}
+
+/// A settlement transaction recorded with [`JobStore::record_settlement`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Settlement {
+    pub tx_hash: String,
+    /// The job that proved the settled proof, if it was proved through the store.
+    pub job_id: Option<String>,
+    pub program: String,
+    pub chain_id: u64,
+    pub contract: String,
+    pub block_number: u64,
+    pub gas_used: u64,
+    /// Whether the transaction succeeded; a reverted one did not settle the proof.
+    pub success: bool,
+    pub created_at: u64,
+}
+
+/// A timed phase of a job, recorded with [`JobStore::record_phase`].
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum Phase {
+    /// Setting up the proving keys, recorded for the first job a worker proves.
+    Setup,
+    /// Executing the program to count the cycles proved.
+    Execute,
+    Prove,
+    /// Verifying the proof locally.
+    Verify,
+    /// Saving the proof's call data.
+    Save,
+    /// Sending the settlement transaction and waiting for its receipt.
+    Submit,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.to_possible_value().expect("no skipped variants").get_name())
+    }
+}
+
+/// The durations of a phase of the jobs of a proof system over a period, as computed by
+/// [`JobStore::phase_stats`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PhaseStats {
+    /// The Unix time the period starts at.
+    pub period: u64,
+    pub system: String,
+    pub phase: Phase,
+    pub count: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Where a job is in its lifecycle.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, ToSchema)]
+#[schema(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for a worker.
+    Queued,
+    /// Claimed by a worker.
+    Running,
+    /// Proved; the call data is at the job's `artifact`.
+    Completed,
+    /// Given up on; see the job's `error`.
+    Failed,
+    /// Stopped before it finished; picked up again like a queued job.
+    Interrupted,
+}
+
+impl JobStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Interrupted => "interrupted",
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s, false)
+    }
+}
+
+/// A proving job.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    pub input: ProgramInput,
+    /// The proof cache key of the input, see [`input_hash`].
+    pub input_hash: B256,
+    pub system: ProofSystem,
+    pub status: JobStatus,
+    pub priority: Priority,
+    /// The Unix time before which the job is not claimed (0 if it can run at any time).
+    pub run_after: u64,
+    /// How many times the job has been claimed.
+    pub attempts: u32,
+    /// The worker that last claimed the job.
+    pub worker: Option<String>,
+    /// The call data of the proof, once completed.
+    pub artifact: Option<String>,
+    /// The keccak256 hash of the proof bytes, once completed.
+    pub proof_hash: Option<B256>,
+    /// The prover (`SP1_PROVER`) that generated the proof, unless it was served from the cache.
+    pub prover: Option<String>,
+    /// How many cycles were proved, unless the proof was served from the cache.
+    pub cycles: Option<u64>,
+    /// The job's own prover network strategy (see [`Job::network_strategy`]).
+    pub network_strategy: Option<NetworkStrategy>,
+    /// The name of the API key the job was submitted with, if any.
+    pub api_key: Option<String>,
+    pub tenant: String,
+    /// Why the job last failed.
+    pub error: Option<String>,
+    /// The job that re-proved this one before its proof expired.
+    pub renewed_by: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Job {
+    fn from_row(row: &AnyRow) -> Result<Self, JobError> {
+        let id: String = row.try_get("id")?;
+        let corrupt = |reason: String| JobError::Corrupt { id: id.clone(), reason };
+        let input: String = row.try_get("input")?;
+        let input_hash: String = row.try_get("input_hash")?;
+        let system: String = row.try_get("system")?;
+        let status: String = row.try_get("status")?;
+        let priority: i64 = row.try_get("priority")?;
+        let proof_hash: Option<String> = row.try_get("proof_hash")?;
+        let network_strategy: Option<String> = row.try_get("network_strategy")?;
+        Ok(Self {
+            input: serde_json::from_str(&input).map_err(|err| corrupt(err.to_string()))?,
+            input_hash: input_hash.parse().map_err(|_| corrupt("invalid input hash".into()))?,
+            system: ProofSystem::from_str(&system, false).map_err(corrupt)?,
+            status: status.parse().map_err(corrupt)?,
+            priority: Priority::from_i64(priority)
+                .ok_or_else(|| corrupt(format!("invalid priority {}", priority)))?,
+            run_after: row.try_get::<i64, _>("run_after")? as u64,
+            attempts: row.try_get::<i64, _>("attempts")? as u32,
+            worker: row.try_get("worker")?,
+            artifact: row.try_get("artifact")?,
+            proof_hash: proof_hash
+                .map(|hash| hash.parse().map_err(|_| corrupt("invalid proof hash".into())))
+                .transpose()?,
+            error: row.try_get("error")?,
+            renewed_by: row.try_get("renewed_by")?,
+            prover: row.try_get("prover")?,
+            cycles: row.try_get::<Option<i64>, _>("cycles")?.map(|cycles| cycles as u64),
+            network_strategy: network_strategy
+                .map(|strategy| NetworkStrategy::from_str(&strategy, false).map_err(corrupt))
+                .transpose()?,
+            api_key: row.try_get("api_key")?,
+            tenant: row.try_get("tenant")?,
+            created_at: row.try_get::<i64, _>("created_at")? as u64,
+            updated_at: row.try_get::<i64, _>("updated_at")? as u64,
+            id,
+        })
+    }
+
+    /// How the prover network should fulfill the job: its own strategy, or else the one in
+    /// `defaults` for its priority. `None` leaves the choice to the network.
+    pub fn network_strategy(
+        &self,
+        defaults: &BTreeMap<Priority, NetworkStrategy>,
+    ) -> Option<NetworkStrategy> {
+        self.network_strategy.or_else(|| defaults.get(&self.priority).copied())
+    }
+}
+
+/// A connection to the job store.
+#[derive(Clone)]
+pub struct JobStore {
+    pool: AnyPool,
+    postgres: bool,
+}
+
+impl JobStore {
+    /// Connect to the store at `url` (`sqlite://...` or `postgres://...`), creating the schema if
+    /// needed.
+    pub async fn connect(url: &str) -> Result<Self, JobError> {
+        sqlx::any::install_default_drivers();
+        if let Some(path) = url.strip_prefix("sqlite://") {
+            let path = Path::new(path.split('?').next().unwrap_or_default());
+            if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(sqlx::Error::Io)?;
+            }
+        }
+        let pool = AnyPoolOptions::new().max_connections(5).connect(url).await?;
+        migrate(&pool).await?;
+        Ok(Self { pool, postgres: url.starts_with("postgres") })
+    }
+
+    /// Queue a job, or coalesce it onto the tenant's unfinished or completed job for the same
+    /// input and proof system, so identical requests are proved once and share an artifact. Returns the job
+    /// and whether the request was coalesced.
+    ///
+    /// A coalesced request raises the priority of the existing job if it is more urgent, and is
+    /// not counted against the `max_concurrent` jobs of its API key.
+    pub async fn submit(
+        &self,
+        id: &str,
+        input: &ProgramInput,
+        system: ProofSystem,
+        options: JobOptions,
+    ) -> Result<(Job, bool), JobError> {
+        let hash = input_hash(input, system);
+        let priority = options.priority;
+        let tenant = options.tenant.clone().unwrap_or_else(|| DEFAULT_TENANT.to_string());
+        if let Some(job) = self.find_by_input_hash(&tenant, hash).await? {
+            return Ok((self.raise_priority(job, priority).await?, true));
+        }
+        match self.enqueue(id, input, system, options).await {
+            Ok(job) => Ok((job, false)),
+            // An identical request was queued concurrently.
+            Err(JobError::Database(err))
+                if err.as_database_error().is_some_and(|err| err.is_unique_violation()) =>
+            {
+                match self.find_by_input_hash(&tenant, hash).await? {
+                    Some(job) => Ok((self.raise_priority(job, priority).await?, true)),
+                    None => Err(JobError::Database(err)),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The tenant's most recent unfinished or completed job for an input hash.
+    async fn find_by_input_hash(
+        &self,
+        tenant: &str,
+        hash: B256,
+    ) -> Result<Option<Job>, JobError> {
+        let sql = format!(
+            "SELECT {} FROM jobs
+            WHERE tenant = $1 AND input_hash = $2 AND status IN ($3, $4, $5, $6)
+            ORDER BY created_at DESC LIMIT 1",
+            COLUMNS
+        );
+        let row = sqlx::query(&sql)
+            .bind(tenant)
+            .bind(hash.to_string())
+            .bind(JobStatus::Queued.as_str())
+            .bind(JobStatus::Running.as_str())
+            .bind(JobStatus::Interrupted.as_str())
+            .bind(JobStatus::Completed.as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(Job::from_row).transpose()
+    }
+
+    /// Raise the priority of a job to `priority`, if that is more urgent.
+    async fn raise_priority(&self, mut job: Job, priority: Priority) -> Result<Job, JobError> {
+        if priority > job.priority {
+            sqlx::query("UPDATE jobs SET priority = $1, updated_at = $2 WHERE id = $3")
+                .bind(priority.as_i64())
+                .bind(now() as i64)
+                .bind(&job.id)
+                .execute(&self.pool)
+                .await?;
+            job.priority = priority;
+        }
+        Ok(job)
+    }
+
+    /// Add a job to the queue, even if an identical one exists (see [`JobStore::submit`]).
+    ///
+    /// With `max_concurrent`, the unfinished jobs of the API key are counted by the insert itself,
+    /// after locking the key on Postgres, so concurrent submissions of a key never exceed it.
+    pub async fn enqueue(
+        &self,
+        id: &str,
+        input: &ProgramInput,
+        system: ProofSystem,
+        options: JobOptions,
+    ) -> Result<Job, JobError> {
+        let tenant = options.tenant.unwrap_or_else(|| DEFAULT_TENANT.to_string());
+        validate_tenant(&tenant)?;
+        let now = now() as i64;
+        let input_json = serde_json::to_string(input).expect("inputs always serialize to JSON");
+        let limit = match (&options.api_key, options.max_concurrent) {
+            (Some(api_key), Some(max)) => Some((api_key.clone(), max)),
+            _ => None,
+        };
+        let mut tx = self.pool.begin().await?;
+        // SQLite runs one write at a time, but on Postgres two inserts could each count the jobs
+        // before the other is committed.
+        if let Some((api_key, _)) = limit.as_ref().filter(|_| self.postgres) {
+            sqlx::query("SELECT name FROM api_keys WHERE name = $1 FOR UPDATE")
+                .bind(api_key.as_str())
+                .fetch_optional(&mut *tx)
+                .await?;
+        }
+        let guard = match limit {
+            Some(_) => {
+                "WHERE (
+                    SELECT COUNT(*) FROM jobs WHERE api_key = $10 AND status IN ($14, $15, $16)
+                ) < $17"
+            }
+            None => "",
+        };
+        let sql = format!(
+            "INSERT INTO jobs (
+                id, input, input_hash, system, status, priority, run_after, valid_until,
+                network_strategy, api_key, tenant, created_at, updated_at
+            )
+            SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13 {}",
+            guard
+        );
+        let mut query = sqlx::query(&sql)
+            .bind(id)
+            .bind(input_json)
+            .bind(input_hash(input, system).to_string())
+            .bind(system.to_string())
+            .bind(JobStatus::Queued.as_str())
+            .bind(options.priority.as_i64())
+            .bind(options.run_after as i64)
+            .bind(input.valid_until.unwrap_or_default() as i64)
+            .bind(options.network_strategy.map(|strategy| strategy.to_string()))
+            .bind(options.api_key)
+            .bind(tenant)
+            .bind(now)
+            .bind(now);
+        if let Some((_, max)) = &limit {
+            query = query
+                .bind(JobStatus::Queued.as_str())
+                .bind(JobStatus::Running.as_str())
+                .bind(JobStatus::Interrupted.as_str())
+                .bind(i64::from(*max));
+        }
+        if query.execute(&mut *tx).await?.rows_affected() == 0 {
+            let (api_key, max) = limit.expect("only a limited insert inserts nothing");
+            return Err(JobError::TooManyJobs { api_key, max });
+        }
+        tx.commit().await?;
+        self.get(id).await
+    }
+
+    /// Claim the most urgent due job that is queued (or interrupted) for `worker`, oldest first
+    /// within a priority, if there is one.
+    ///
+    /// Priorities with `max_running` jobs already running across all workers are skipped. The
+    /// limits are checked just before claiming, so concurrent workers may briefly exceed them.
+    ///
+    /// On Postgres the claim skips rows locked by other workers, so concurrent workers never claim
+    /// the same job.
+    ///
+    /// Only jobs of `tenants` are claimed, or of any tenant if it is empty.
+    pub async fn claim(
+        &self,
+        worker: &str,
+        max_running: &BTreeMap<Priority, u32>,
+        tenants: &[String],
+    ) -> Result<Option<Job>, JobError> {
+        injected_failure()?;
+        let running = self.running_by_priority().await?;
+        let allowed: Vec<String> = [Priority::Background, Priority::Normal, Priority::Urgent]
+            .into_iter()
+            .filter(|priority| match max_running.get(priority) {
+                Some(&limit) => running.get(priority).copied().unwrap_or_default() < limit,
+                None => true,
+            })
+            .map(|priority| priority.as_i64().to_string())
+            .collect();
+        if allowed.is_empty() {
+            return Ok(None);
+        }
+
+        let lock = if self.postgres { "FOR UPDATE SKIP LOCKED" } else { "" };
+        // Tenants are bound after the fixed parameters, from $7 on.
+        let tenant_filter = match tenants.len() {
+            0 => String::new(),
+            len => {
+                let params: Vec<String> = (7..7 + len).map(|i| format!("${}", i)).collect();
+                format!("AND tenant IN ({})", params.join(", "))
+            }
+        };
+        let now = now() as i64;
+        let sql = format!(
+            "UPDATE jobs SET status = $1, worker = $2, attempts = attempts + 1, updated_at = $3
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE status IN ($4, $5) AND run_after <= $6 AND priority IN ({}) {}
+                ORDER BY priority DESC, created_at
+                LIMIT 1 {}
+            )
+            RETURNING {}",
+            allowed.join(", "),
+            tenant_filter,
+            lock,
+            COLUMNS
+        );
+        let mut query = sqlx::query(&sql)
+            .bind(JobStatus::Running.as_str())
+            .bind(worker)
+            .bind(now)
+            .bind(JobStatus::Queued.as_str())
+            .bind(JobStatus::Interrupted.as_str())
+            .bind(now);
+        for tenant in tenants {
+            query = query.bind(tenant.as_str());
+        }
+        let row = query.fetch_optional(&self.pool).await?;
+        row.as_ref().map(Job::from_row).transpose()
+    }
+
+    /// How many jobs of each priority are running.
+    async fn running_by_priority(&self) -> Result<BTreeMap<Priority, u32>, JobError> {
+        let rows = sqlx::query(
+            "SELECT priority, COUNT(*) AS running FROM jobs WHERE status = $1 GROUP BY priority",
+        )
+        .bind(JobStatus::Running.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+        let mut running = BTreeMap::new();
+        for row in rows {
+            if let Some(priority) = Priority::from_i64(row.try_get("priority")?) {
+                running.insert(priority, row.try_get::<i64, _>("running")? as u32);
+            }
+        }
+        Ok(running)
+    }
+
+    /// Mark a job as completed, with the call data of its proof (whose bytes hash to `proof_hash`)
+    /// at `artifact`. `run` is how the proof was generated, or `None` if it came from the cache.
+    pub async fn complete(
+        &self,
+        id: &str,
+        artifact: &str,
+        proof_hash: B256,
+        run: Option<&ProvingRun>,
+    ) -> Result<(), JobError> {
+        injected_failure()?;
+        sqlx::query(
+            "UPDATE jobs SET status = $1, artifact = $2, proof_hash = $3, prover = $4, cycles = $5,
+            error = NULL, updated_at = $6 WHERE id = $7",
+        )
+        .bind(JobStatus::Completed.as_str())
+        .bind(artifact)
+        .bind(proof_hash.to_string())
+        .bind(run.map(|run| run.prover.clone()))
+        .bind(run.map(|run| run.cycles as i64))
+        .bind(now() as i64)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record a failed attempt, putting the job back in the queue if `retry` is set.
+    pub async fn fail(&self, id: &str, error: &str, retry: bool) -> Result<(), JobError> {
+        let status = if retry { JobStatus::Queued } else { JobStatus::Failed };
+        self.set_status(id, status, Some(error)).await
+    }
+
+    /// Set the status of a job, e.g. to requeue it or to mark it interrupted.
+    pub async fn set_status(
+        &self,
+        id: &str,
+        status: JobStatus,
+        error: Option<&str>,
+    ) -> Result<(), JobError> {
+        let updated =
+            sqlx::query("UPDATE jobs SET status = $1, error = $2, updated_at = $3 WHERE id = $4")
+                .bind(status.as_str())
+                .bind(error)
+                .bind(now() as i64)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        if updated.rows_affected() == 0 {
+            return Err(JobError::NotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Completed jobs whose proofs expire (per their committed `validUntil`) at or before
+    /// `before` and have not been re-proved yet.
+    pub async fn expiring(&self, before: u64) -> Result<Vec<Job>, JobError> {
+        let sql = format!(
+            "SELECT {} FROM jobs
+            WHERE status = $1 AND valid_until != 0 AND valid_until <= $2 AND renewed_by IS NULL
+            ORDER BY valid_until",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(JobStatus::Completed.as_str())
+            .bind(before as i64)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(Job::from_row).collect()
+    }
+
+    /// Record that the proof of job `id` has been renewed by job `renewed_by`.
+    pub async fn mark_renewed(&self, id: &str, renewed_by: &str) -> Result<(), JobError> {
+        sqlx::query("UPDATE jobs SET renewed_by = $1, updated_at = $2 WHERE id = $3")
+            .bind(renewed_by)
+            .bind(now() as i64)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record the settlement of a proof, whose receipt is saved at `artifact`, and link it to the
+    /// job that proved it, if any. Returns the id of that job.
+    pub async fn record_settlement(
+        &self,
+        settlement: &SettlementReceipt,
+        artifact: &str,
+    ) -> Result<Option<String>, JobError> {
+        let job_id: Option<String> = sqlx::query(
+            "SELECT id FROM jobs WHERE proof_hash = $1 ORDER BY updated_at DESC LIMIT 1",
+        )
+        .bind(settlement.proof_hash.to_string())
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| row.try_get("id"))
+        .transpose()?;
+        sqlx::query(
+            "INSERT INTO settlements (
+                tx_hash, job_id, proof_hash, manifest_hash, chain_id, contract, block_number,
+                gas_used, effective_gas_price, success, artifact, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (tx_hash) DO NOTHING",
+        )
+        .bind(settlement.tx_hash.to_string())
+        .bind(job_id.clone())
+        .bind(settlement.proof_hash.to_string())
+        .bind(settlement.manifest_hash.map(|hash| hash.to_string()))
+        .bind(settlement.chain_id as i64)
+        .bind(settlement.contract.to_string())
+        .bind(settlement.block_number as i64)
+        .bind(settlement.gas_used as i64)
+        .bind(settlement.effective_gas_price_wei.to_string())
+        .bind(i64::from(settlement.success))
+        .bind(artifact)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(job_id)
+    }
+
+    /// The transactions that settled the proof of a job, oldest first.
+    pub async fn settlement_txs(&self, job_id: &str) -> Result<Vec<String>, JobError> {
+        let rows = sqlx::query(
+            "SELECT tx_hash FROM settlements WHERE job_id = $1 ORDER BY created_at, tx_hash",
+        )
+        .bind(job_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(|row| row.try_get("tx_hash").map_err(JobError::from)).collect()
+    }
+
+    /// The settlements recorded since the Unix time `since`, oldest first.
+    pub async fn settlements(&self, since: u64) -> Result<Vec<Settlement>, JobError> {
+        let rows = sqlx::query(
+            "SELECT tx_hash, job_id, program, chain_id, contract, block_number, gas_used, success,
+                created_at
+            FROM settlements WHERE created_at >= $1 ORDER BY created_at, tx_hash",
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(Settlement {
+                    tx_hash: row.try_get("tx_hash")?,
+                    job_id: row.try_get("job_id")?,
+                    program: row.try_get("program")?,
+                    chain_id: row.try_get::<i64, _>("chain_id")? as u64,
+                    contract: row.try_get("contract")?,
+                    block_number: row.try_get::<i64, _>("block_number")? as u64,
+                    gas_used: row.try_get::<i64, _>("gas_used")? as u64,
+                    success: row.try_get::<i64, _>("success")? != 0,
+                    created_at: row.try_get::<i64, _>("created_at")? as u64,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the `nonce` of a request to `consumer` on `chain_id` is being settled or has been.
+    pub async fn nonce_used(
+        &self,
+        consumer: Address,
+        chain_id: u64,
+        nonce: u64,
+    ) -> Result<bool, JobError> {
+        let row = sqlx::query(
+            "SELECT 1 AS used FROM used_nonces WHERE consumer = $1 AND chain_id = $2 AND nonce = $3",
+        )
+        .bind(consumer.to_string())
+        .bind(chain_id as i64)
+        .bind(nonce as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    /// Claim the `nonce` of a request to `consumer` on `chain_id` before settling it. Returns
+    /// `false` if it is already claimed, by this or any other worker or host: checking and
+    /// claiming is one insert, so two submissions of the same request cannot both claim it.
+    pub async fn claim_nonce(
+        &self,
+        consumer: Address,
+        chain_id: u64,
+        nonce: u64,
+    ) -> Result<bool, JobError> {
+        let claimed = sqlx::query(
+            "INSERT INTO used_nonces (consumer, chain_id, nonce, claimed_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (consumer, chain_id, nonce) DO NOTHING",
+        )
+        .bind(consumer.to_string())
+        .bind(chain_id as i64)
+        .bind(nonce as i64)
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    /// Record the transaction that settled a claimed nonce.
+    pub async fn settle_nonce(
+        &self,
+        consumer: Address,
+        chain_id: u64,
+        nonce: u64,
+        tx_hash: B256,
+    ) -> Result<(), JobError> {
+        sqlx::query(
+            "UPDATE used_nonces SET tx_hash = $1
+            WHERE consumer = $2 AND chain_id = $3 AND nonce = $4",
+        )
+        .bind(tx_hash.to_string())
+        .bind(consumer.to_string())
+        .bind(chain_id as i64)
+        .bind(nonce as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Release a claimed nonce whose settlement failed, so the request can be submitted again.
+    /// Settled nonces are kept.
+    pub async fn release_nonce(
+        &self,
+        consumer: Address,
+        chain_id: u64,
+        nonce: u64,
+    ) -> Result<(), JobError> {
+        sqlx::query(
+            "DELETE FROM used_nonces
+            WHERE consumer = $1 AND chain_id = $2 AND nonce = $3 AND tx_hash IS NULL",
+        )
+        .bind(consumer.to_string())
+        .bind(chain_id as i64)
+        .bind(nonce as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record how long a phase of a job took. Jobs not in the store (e.g. those of a NATS queue)
+    /// are not recorded.
+    pub async fn record_phase(
+        &self,
+        job_id: &str,
+        phase: Phase,
+        duration: Duration,
+    ) -> Result<(), JobError> {
+        sqlx::query(
+            "INSERT INTO job_phases (job_id, phase, system, duration_ms, recorded_at)
+            SELECT id, $1, system, $2, $3 FROM jobs WHERE id = $4",
+        )
+        .bind(phase.to_string())
+        .bind(duration.as_millis() as i64)
+        .bind(now() as i64)
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The median, 95th percentile and longest duration of each phase since the Unix time
+    /// `since`, by proof system and by period of `period_secs` seconds, oldest first.
+    pub async fn phase_stats(
+        &self,
+        since: u64,
+        period_secs: u64,
+    ) -> Result<Vec<PhaseStats>, JobError> {
+        let rows = sqlx::query(
+            "SELECT phase, system, duration_ms, recorded_at FROM job_phases
+            WHERE recorded_at >= $1",
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        let period_secs = period_secs.max(1);
+        let mut durations: BTreeMap<(u64, String, Phase), Vec<u64>> = BTreeMap::new();
+        for row in rows {
+            let phase: String = row.try_get("phase")?;
+            // Phases a newer version of the scripts records are left out.
+            let Ok(phase) = <Phase as ValueEnum>::from_str(&phase, false) else {
+                continue;
+            };
+            let recorded_at = row.try_get::<i64, _>("recorded_at")? as u64;
+            let period = recorded_at - recorded_at % period_secs;
+            durations
+                .entry((period, row.try_get("system")?, phase))
+                .or_default()
+                .push(row.try_get::<i64, _>("duration_ms")? as u64);
+        }
+        Ok(durations
+            .into_iter()
+            .map(|((period, system, phase), mut durations)| {
+                durations.sort_unstable();
+                PhaseStats {
+                    period,
+                    system,
+                    phase,
+                    count: durations.len() as u64,
+                    p50_ms: percentile(&durations, 50),
+                    p95_ms: percentile(&durations, 95),
+                    max_ms: durations.last().copied().unwrap_or_default(),
+                }
+            })
+            .collect())
+    }
+
+    /// The proving and settlement costs since the Unix time `since`, by program and chain. Jobs
+    /// count by when they completed, and are attributed to the chain their input is bound to.
+    pub async fn costs(&self, since: u64) -> Result<Vec<CostSummary>, JobError> {
+        let mut summaries = BTreeMap::new();
+
+        let jobs = sqlx::query(
+            "SELECT id, program, input, prover, cycles FROM jobs
+            WHERE status = $1 AND updated_at >= $2",
+        )
+        .bind(JobStatus::Completed.as_str())
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        for row in jobs {
+            let id: String = row.try_get("id")?;
+            let input: String = row.try_get("input")?;
+            let input: ProgramInput = serde_json::from_str(&input)
+                .map_err(|err| JobError::Corrupt { id, reason: err.to_string() })?;
+            let cycles = row.try_get::<Option<i64>, _>("cycles")?.unwrap_or_default() as u64;
+            let prover: Option<String> = row.try_get("prover")?;
+            let summary = summary_for(&mut summaries, row.try_get("program")?, input.chain_id);
+            summary.proofs += 1;
+            match prover.as_deref() {
+                Some("network") => summary.network_cycles += cycles,
+                _ => summary.local_cycles += cycles,
+            }
+        }
+
+        let settlements = sqlx::query(
+            "SELECT program, chain_id, gas_used, effective_gas_price, success FROM settlements
+            WHERE created_at >= $1",
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        for row in settlements {
+            let chain_id = row.try_get::<i64, _>("chain_id")? as u64;
+            let gas_used = row.try_get::<i64, _>("gas_used")? as u128;
+            let gas_price: String = row.try_get("effective_gas_price")?;
+            let summary = summary_for(&mut summaries, row.try_get("program")?, Some(chain_id));
+            summary.settlements += 1;
+            if row.try_get::<i64, _>("success")? == 0 {
+                summary.reverted_settlements += 1;
+            }
+            summary.gas_used += gas_used;
+            summary.gas_cost_wei += gas_used * gas_price.parse::<u128>().unwrap_or_default();
+        }
+        Ok(summaries.into_values().collect())
+    }
+
+    /// Look up a job.
+    pub async fn get(&self, id: &str) -> Result<Job, JobError> {
+        let sql = format!("SELECT {} FROM jobs WHERE id = $1", COLUMNS);
+        let row = sqlx::query(&sql).bind(id).fetch_optional(&self.pool).await?;
+        match row {
+            Some(row) => Job::from_row(&row),
+            None => Err(JobError::NotFound(id.to_string())),
+        }
+    }
+
+    /// List jobs, oldest first, optionally only those with `status` or of `tenant`.
+    pub async fn list(
+        &self,
+        status: Option<JobStatus>,
+        tenant: Option<&str>,
+    ) -> Result<Vec<Job>, JobError> {
+        // A NULL filter matches every job.
+        let sql = format!(
+            "SELECT {} FROM jobs
+            WHERE ($1 IS NULL OR status = $1) AND ($2 IS NULL OR tenant = $2)
+            ORDER BY created_at, id",
+            COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(status.map(JobStatus::as_str))
+            .bind(tenant)
+            .fetch_all(&self.pool)
+            .await?;
+        rows.iter().map(Job::from_row).collect()
+    }
+
+    /// How many queued, running or interrupted jobs were submitted with an API key.
+    pub async fn unfinished_jobs(&self, api_key: &str) -> Result<u64, JobError> {
+        let count: i64 = sqlx::query(
+            "SELECT COUNT(*) AS count FROM jobs WHERE api_key = $1 AND status IN ($2, $3, $4)",
+        )
+        .bind(api_key)
+        .bind(JobStatus::Queued.as_str())
+        .bind(JobStatus::Running.as_str())
+        .bind(JobStatus::Interrupted.as_str())
+        .fetch_one(&self.pool)
+        .await?
+        .try_get("count")?;
+        Ok(count as u64)
+    }
+
+    /// Add an API key of `tenant`, of which only the hash is stored (see
+    /// [`crate::auth::key_hash`]).
+    pub async fn create_api_key(
+        &self,
+        name: &str,
+        tenant: &str,
+        key_hash: B256,
+        rate_limit: Option<u32>,
+        max_concurrent: Option<u32>,
+    ) -> Result<ApiKey, JobError> {
+        validate_tenant(tenant)?;
+        let created = sqlx::query(
+            "INSERT INTO api_keys (name, tenant, key_hash, rate_limit, max_concurrent, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (name) DO NOTHING",
+        )
+        .bind(name)
+        .bind(tenant)
+        .bind(key_hash.to_string())
+        .bind(rate_limit.map(i64::from))
+        .bind(max_concurrent.map(i64::from))
+        .bind(now() as i64)
+        .execute(&self.pool)
+        .await?;
+        if created.rows_affected() == 0 {
+            return Err(JobError::KeyExists(name.to_string()));
+        }
+        self.api_key(name).await
+    }
+
+    /// Look up an API key by name.
+    pub async fn api_key(&self, name: &str) -> Result<ApiKey, JobError> {
+        let sql = format!("SELECT {} FROM api_keys WHERE name = $1", API_KEY_COLUMNS);
+        let row = sqlx::query(&sql).bind(name).fetch_optional(&self.pool).await?;
+        match row {
+            Some(row) => ApiKey::from_row(&row),
+            None => Err(JobError::KeyNotFound(name.to_string())),
+        }
+    }
+
+    /// The unrevoked API key with the given hash, if any.
+    pub async fn find_api_key(&self, key_hash: B256) -> Result<Option<ApiKey>, JobError> {
+        let sql = format!(
+            "SELECT {} FROM api_keys WHERE key_hash = $1 AND revoked_at IS NULL",
+            API_KEY_COLUMNS
+        );
+        let row = sqlx::query(&sql).bind(key_hash.to_string()).fetch_optional(&self.pool).await?;
+        row.as_ref().map(ApiKey::from_row).transpose()
+    }
+
+    /// List API keys, including revoked ones, by name.
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>, JobError> {
+        let sql = format!("SELECT {} FROM api_keys ORDER BY name", API_KEY_COLUMNS);
+        let rows = sqlx::query(&sql).fetch_all(&self.pool).await?;
+        rows.iter().map(ApiKey::from_row).collect()
+    }
+
+    /// Revoke an API key. Its jobs are kept.
+    pub async fn revoke_api_key(&self, name: &str) -> Result<(), JobError> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET revoked_at = $1 WHERE name = $2 AND revoked_at IS NULL",
+        )
+        .bind(now() as i64)
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            self.api_key(name).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Bring the schema up to date by applying the migrations the store has not seen yet.
+async fn migrate(pool: &AnyPool) -> Result<(), JobError> {
+    sqlx::query("CREATE TABLE IF NOT EXISTS job_store_version (version BIGINT NOT NULL)")
+        .execute(pool)
+        .await?;
+    let applied: Option<i64> = sqlx::query("SELECT MAX(version) AS version FROM job_store_version")
+        .fetch_one(pool)
+        .await?
+        .try_get("version")?;
+    let applied = applied.unwrap_or_default() as usize;
+    for (version, statements) in MIGRATIONS.iter().enumerate().skip(applied) {
+        let mut tx = pool.begin().await?;
+        for statement in *statements {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        sqlx::query("INSERT INTO job_store_version (version) VALUES ($1)")
+            .bind(version as i64 + 1)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// Check that a tenant name is safe to use as a directory name.
+pub fn validate_tenant(tenant: &str) -> Result<(), JobError> {
+    let valid =
+        |byte: u8| byte.is_ascii_lowercase() || byte.is_ascii_digit() || b"-_".contains(&byte);
+    if tenant.is_empty() || tenant.len() > 64 || !tenant.bytes().all(valid) {
+        return Err(JobError::InvalidTenant(tenant.to_string()));
+    }
+    Ok(())
+}
+
+/// The summary of `program` on `chain_id`, starting from an empty one.
+fn summary_for(
+    summaries: &mut BTreeMap<(String, Option<u64>), CostSummary>,
+    program: String,
+    chain_id: Option<u64>,
+) -> &mut CostSummary {
+    summaries.entry((program.clone(), chain_id)).or_insert_with(|| CostSummary {
+        program,
+        chain_id,
+        ..Default::default()
+    })
+}
+
+/// The current Unix time in seconds.
+/// The nearest-rank `p`th percentile of sorted durations.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (sorted.len() * p).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied().unwrap_or_default()
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Fail like a lost connection to the store if chaos mode says so.
+fn injected_failure() -> Result<(), JobError> {
+    #[cfg(feature = "chaos")]
+    if crate::chaos::inject(crate::chaos::Fault::Rpc) {
+        let err = std::io::Error::other("job store unreachable (injected by chaos mode)");
+        return Err(JobError::Database(sqlx::Error::Io(err)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh SQLite store in the temporary directory.
+    async fn store(name: &str) -> JobStore {
+        let path = std::env::temp_dir().join(format!("jobs-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        JobStore::connect(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap()
+    }
+
+    /// Submit a job for `n` with an API key limited to `max` unfinished jobs.
+    async fn submit(store: &JobStore, id: &str, n: u32, max: u32) -> Result<(Job, bool), JobError> {
+        let options = JobOptions {
+            api_key: Some("alice".to_string()),
+            max_concurrent: Some(max),
+            ..Default::default()
+        };
+        store.submit(id, &ProgramInput::new(n), ProofSystem::Groth16, options).await
+    }
+
+    #[tokio::test]
+    async fn submit_keeps_a_key_within_its_unfinished_jobs() {
+        let store = store("limit").await;
+        submit(&store, "job-1", 1, 2).await.unwrap();
+        submit(&store, "job-2", 2, 2).await.unwrap();
+        let err = submit(&store, "job-3", 3, 2).await.unwrap_err();
+        assert!(matches!(err, JobError::TooManyJobs { max: 2, .. }), "{}", err);
+        assert!(matches!(store.get("job-3").await, Err(JobError::NotFound(_))));
+
+        // An identical request is coalesced rather than counted.
+        let (job, coalesced) = submit(&store, "job-4", 1, 2).await.unwrap();
+        assert!(coalesced && job.id == "job-1");
+
+        // Completed jobs no longer count.
+        store.complete("job-1", "artifact", B256::ZERO, None).await.unwrap();
+        submit(&store, "job-3", 3, 2).await.unwrap();
+        assert_eq!(store.unfinished_jobs("alice").await.unwrap(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_submissions_stay_within_the_limit() {
+        let store = store("concurrent").await;
+        let handles: Vec<_> = (1..=10)
+            .map(|n| {
+                let store = store.clone();
+                tokio::spawn(async move { submit(&store, &format!("job-{}", n), n, 3).await })
+            })
+            .collect();
+        let mut queued = 0;
+        for handle in handles {
+            match handle.await.unwrap() {
+                Ok(_) => queued += 1,
+                Err(JobError::TooManyJobs { .. }) => {}
+                Err(err) => panic!("{}", err),
+            }
+        }
+        assert_eq!(queued, 3);
+        assert_eq!(store.unfinished_jobs("alice").await.unwrap(), 3);
+    }
+}
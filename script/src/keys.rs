@@ -0,0 +1,85 @@
+//! Proving and verifying keys of the programs a [`crate::prover::FibonacciProver`] proves with,
+//! each set up once, on first use.
+//!
+//! The cache keeps the keys of the `capacity` most recently used programs and evicts the least
+//! recently used one past that, so memory stays bounded. A worker only sets up the Fibonacci
+//! program when it starts (see [`crate::prover::FibonacciProver::warm_up`]): it never proves
+//! batches or recursive proofs, so it never holds the other programs' keys.
+
+use sp1_sdk::{EnvProver, SP1ProvingKey, SP1VerifyingKey};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// How many programs' keys are kept by default: the Fibonacci, aggregator and recursion programs.
+pub const DEFAULT_KEY_CACHE_SIZE: usize = 3;
+
+/// The proving and verifying keys of a program.
+pub type Keys = (SP1ProvingKey, SP1VerifyingKey);
+
+/// A cached program: its name, and its keys once set up.
+type Entry = (&'static str, Arc<OnceLock<Arc<Keys>>>);
+
+/// The keys of the most recently used programs, by program name.
+pub struct KeyCache {
+    capacity: usize,
+    /// The cached programs, least recently used first. Each entry is set up outside the lock, so
+    /// a setup never holds up the lookups of other programs, and only once however many threads
+    /// need it at the same time.
+    entries: Mutex<VecDeque<Entry>>,
+}
+
+impl Default for KeyCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_KEY_CACHE_SIZE)
+    }
+}
+
+impl KeyCache {
+    /// Create a cache keeping the keys of at most `capacity` programs (at least one).
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), entries: Mutex::new(VecDeque::new()) }
+    }
+
+    /// How many programs' keys are kept at most.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// How many programs' keys are kept.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no program's keys are kept.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Whether the keys of the program `name` are kept.
+    pub fn contains(&self, name: &str) -> bool {
+        self.lock().iter().any(|(cached, _)| *cached == name)
+    }
+
+    /// The keys of the program `name`, set up from `elf` with `client` unless they are kept
+    /// already. Keeping them evicts the least recently used program once the cache is full; keys
+    /// still held by a prover stay valid after their eviction.
+    pub fn get(&self, name: &'static str, elf: &[u8], client: &EnvProver) -> Arc<Keys> {
+        let entry = {
+            let mut entries = self.lock();
+            let entry = match entries.iter().position(|(cached, _)| *cached == name) {
+                Some(index) => entries.remove(index).map(|(_, entry)| entry).unwrap_or_default(),
+                None => Arc::default(),
+            };
+            entries.push_back((name, entry.clone()));
+            while entries.len() > self.capacity {
+                entries.pop_front();
+            }
+            entry
+        };
+        entry.get_or_init(|| Arc::new(client.setup(elf))).clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<Entry>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,58 @@
+//! Proving, artifact handling and on-chain settlement for the Fibonacci program.
+//!
+//! The binaries in `src/bin` are thin wrappers around this crate; other Rust services can depend
+//! on it directly:
+//! ```ignore
+//! use fibonacci_lib::ProgramInput;
+//! use fibonacci_script::prover::{FibonacciProver, ProofSystem};
+//!
+//! let prover = FibonacciProver::new();
+//! let proof = prover.prove(&ProgramInput::new(20), ProofSystem::Groth16)?;
+//! ```
+
+pub mod addresses;
+pub mod artifact_store;
+pub mod artifacts;
+pub mod audit;
+pub mod auth;
+pub mod balance;
+pub mod batch;
+pub mod bundle;
+pub mod chain;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod config;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+pub mod deadline;
+pub mod diff;
+pub mod domain;
+pub mod error;
+pub mod export;
+pub mod fingerprint;
+pub mod gas;
+pub mod html_report;
+pub mod jobs;
+pub mod keys;
+pub mod migrate;
+pub mod mock;
+pub mod notify;
+pub mod openapi;
+pub mod profile;
+pub mod programs;
+pub mod provenance;
+pub mod prover;
+pub mod public_values;
+pub mod regex;
+pub mod revert;
+pub mod rpc;
+pub mod run_report;
+pub mod scaffold;
+pub mod shutdown;
+pub mod state;
+pub mod stream;
+pub mod symbols;
+pub mod trace;
+pub mod verifiers;
+
+pub use error::Error;
@@ -0,0 +1,212 @@
+//! Upgrading saved proof bundles to the current [`Manifest`] format, so that archives written by
+//! older versions of the scripts stay readable.
+//!
+//! Two kinds of bundles are upgraded in place:
+//! - loose files from before manifests existed: a manifest is written for them, listing the files
+//!   found for the proof's `n`;
+//! - manifests of an older version, which are rewritten with the fields added since.
+//!
+//! The files a manifest lists are never modified, so they keep matching the hashes it records.
+
+use crate::artifacts::{
+    compressed_path, guest_stdout_path, manifest_path, read_artifact, to_canonical_json,
+    write_json, ArtifactError, CallData, Compression, Manifest, MANIFEST_VERSION,
+};
+use alloy_primitives::{keccak256, B256};
+use fibonacci_lib::VkHash;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The proof systems a saved proof may have been generated with, as in its file name.
+const SYSTEMS: [&str; 2] = ["groth16", "plonk"];
+
+/// Errors from migrating a bundle.
+#[derive(Debug, Error)]
+pub enum MigrateError {
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+    #[error(
+        "{}: manifest version {version} is newer than this tool supports ({MANIFEST_VERSION})",
+        path.display()
+    )]
+    Unsupported { path: PathBuf, version: u64 },
+    #[error("no saved proof matches the call data for n = {0}")]
+    MissingProof(u32),
+    #[error("{}: invalid vkey: {reason}", path.display())]
+    InvalidVkey { path: PathBuf, reason: String },
+}
+
+/// What was done to the bundle of one proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Migration {
+    /// The manifest is already in the current format.
+    UpToDate { n: u32 },
+    /// A manifest was written for loose files saved before manifests existed.
+    Created { n: u32, manifest_hash: B256 },
+    /// The manifest was upgraded from an older version. Its hash changes, so settlement receipts
+    /// saved before keep referring to `old_hash`.
+    Upgraded { n: u32, from: u64, old_hash: B256, new_hash: B256 },
+}
+
+/// Upgrade every proof bundle in `dir` to the current manifest format. Bundles are found by their
+/// call data (`contract_call_data_n<n>.json`), which every version of the scripts saved. With
+/// `dry_run`, nothing is written, but the migrations that would be made are returned.
+pub fn migrate_artifacts(dir: &Path, dry_run: bool) -> Result<Vec<Migration>, MigrateError> {
+    let mut migrations = Vec::new();
+    for n in saved_inputs(dir)? {
+        let path = manifest_path(dir, n);
+        let migration = if path.exists() {
+            upgrade_manifest(&path, n, dry_run)?
+        } else {
+            create_manifest(dir, n, dry_run)?
+        };
+        migrations.push(migration);
+    }
+    Ok(migrations)
+}
+
+/// Bring an existing manifest up to the current version.
+fn upgrade_manifest(path: &Path, n: u32, dry_run: bool) -> Result<Migration, MigrateError> {
+    let old_contents = read(path)?;
+    let mut manifest: Value = serde_json::from_slice(&old_contents)
+        .map_err(|source| ArtifactError::Json { path: path.to_path_buf(), source })?;
+    let from = manifest["version"]
+        .as_u64()
+        .ok_or_else(|| ArtifactError::InvalidField { path: path.to_path_buf(), field: "version" })?;
+    if from > u64::from(MANIFEST_VERSION) {
+        return Err(MigrateError::Unsupported { path: path.to_path_buf(), version: from });
+    }
+    if from == u64::from(MANIFEST_VERSION) {
+        return Ok(Migration::UpToDate { n });
+    }
+    // Each version only adds fields; fill in what the files were saved with before them.
+    if from < 2 {
+        manifest["compression"] = Compression::None.to_string().into();
+    }
+    if from < 3 {
+        // What built an older proof was not recorded, and cannot be told from its files.
+        manifest["provenance"] = Value::Null;
+    }
+    if from < 4 {
+        // Nor was who proved it.
+        manifest["prover"] = Value::Null;
+    }
+    manifest["version"] = MANIFEST_VERSION.into();
+    let manifest: Manifest = serde_json::from_value(manifest)
+        .map_err(|source| ArtifactError::Json { path: path.to_path_buf(), source })?;
+    if !dry_run {
+        write_json(path, &manifest)?;
+    }
+    Ok(Migration::Upgraded {
+        n,
+        from,
+        old_hash: keccak256(&old_contents),
+        new_hash: manifest_hash(&manifest),
+    })
+}
+
+/// Write a manifest for the loose files saved for `n`.
+fn create_manifest(dir: &Path, n: u32, dry_run: bool) -> Result<Migration, MigrateError> {
+    let call_data_file = existing(&dir.join(format!("contract_call_data_n{}.json", n)));
+    let call_data = CallData::load(&call_data_file)?;
+
+    // The saved proof whose bytes are those of the call data (both systems may have been saved).
+    let mut proof = None;
+    for system in SYSTEMS {
+        let path = existing(&dir.join(format!("proof_{}_n{}.bin", system, n)));
+        if path.exists() && read_artifact(&path)? == call_data.proof_bytes.as_ref() {
+            proof = Some((system, path));
+            break;
+        }
+    }
+    let (system, proof_file) = proof.ok_or(MigrateError::MissingProof(n))?;
+
+    let vkey_file = dir.join("verification_key.txt");
+    let vkey: VkHash = String::from_utf8_lossy(&read(&vkey_file)?)
+        .parse()
+        .map_err(|reason| MigrateError::InvalidVkey { path: vkey_file.clone(), reason })?;
+
+    let mut files = BTreeMap::new();
+    for path in [
+        proof_file,
+        dir.join(format!("public_values_n{}.bin", n)),
+        vkey_file,
+        call_data_file,
+        existing(&dir.join(format!("summary_n{}.txt", n))),
+        existing(&guest_stdout_path(dir, n)),
+    ] {
+        if path.exists() {
+            files.insert(file_name(&path), keccak256(read(&path)?));
+        }
+    }
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        n,
+        system: system.to_string(),
+        vkey,
+        proof_hash: keccak256(&call_data.proof_bytes),
+        compression: compression_of(&files),
+        files,
+        provenance: None,
+        prover: None,
+    };
+    let path = manifest_path(dir, n);
+    if !dry_run {
+        write_json(&path, &manifest)?;
+    }
+    Ok(Migration::Created { n, manifest_hash: manifest_hash(&manifest) })
+}
+
+/// The inputs a proof was saved for in `dir`, from the names of their call data files.
+fn saved_inputs(dir: &Path) -> Result<Vec<u32>, ArtifactError> {
+    let io_err = |source| ArtifactError::Io { path: dir.to_path_buf(), source };
+    let mut inputs = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io_err)? {
+        let name = entry.map_err(io_err)?.file_name();
+        let n = name
+            .to_str()
+            .and_then(|name| name.strip_prefix("contract_call_data_n"))
+            .and_then(|rest| rest.strip_suffix(".json").or_else(|| rest.strip_suffix(".json.zst")))
+            .and_then(|n| n.parse().ok());
+        inputs.extend(n);
+    }
+    inputs.sort_unstable();
+    inputs.dedup();
+    Ok(inputs)
+}
+
+/// How loose files were compressed. zstd frames do not record their level, so the default one is
+/// assumed.
+fn compression_of(files: &BTreeMap<String, B256>) -> Compression {
+    if files.keys().any(|name| name.ends_with(".zst")) {
+        Compression::Zstd(Compression::DEFAULT_ZSTD_LEVEL)
+    } else {
+        Compression::None
+    }
+}
+
+/// The hash of the manifest file written for `manifest`.
+fn manifest_hash(manifest: &Manifest) -> B256 {
+    keccak256(to_canonical_json(manifest))
+}
+
+/// `path`, or its compressed sibling if only that exists.
+fn existing(path: &Path) -> PathBuf {
+    let compressed = compressed_path(path);
+    if !path.exists() && compressed.exists() {
+        compressed
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, ArtifactError> {
+    fs::read(path).map_err(|source| ArtifactError::Io { path: path.to_path_buf(), source })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string()
+}
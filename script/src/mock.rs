@@ -0,0 +1,116 @@
+//! Simulated prover network behaviour for mock proofs.
+//!
+//! Mock proofs (`SP1_PROVER=mock`) come back instantly and never fail, which hides how the worker
+//! and the services around it behave under real proving times. With these variables set, every
+//! mock proof request first waits as long as a real one might, then fails at the given rate:
+//!
+//! - `SP1_MOCK_LATENCY`: seconds to wait, fixed (`30`) or drawn from a range (`10..120`).
+//! - `SP1_MOCK_FAILURE_RATE`: the probability from 0 to 1 that a request fails.
+//! - `SP1_MOCK_SEED`: seed the delays and failures, to replay a run.
+//!
+//! Other provers ignore them.
+
+use crate::config::ConfigError;
+use std::env;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The latency and failure rate of simulated prover network requests.
+#[derive(Debug)]
+pub struct MockNetwork {
+    /// The shortest and longest delay of a request.
+    pub latency: (Duration, Duration),
+    pub failure_rate: f64,
+    state: Mutex<u64>,
+}
+
+impl MockNetwork {
+    /// Read the simulated behaviour from the environment. `None` unless the prover is `mock` and
+    /// a latency or failure rate is set.
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        if env::var("SP1_PROVER").as_deref() != Ok("mock") {
+            return Ok(None);
+        }
+        let latency = env::var("SP1_MOCK_LATENCY").ok();
+        let failure_rate = env::var("SP1_MOCK_FAILURE_RATE").ok();
+        if latency.is_none() && failure_rate.is_none() {
+            return Ok(None);
+        }
+        let invalid = |name, reason| ConfigError::Invalid { name, reason };
+        let latency = match latency {
+            Some(latency) => {
+                parse_latency(&latency).map_err(|err| invalid("SP1_MOCK_LATENCY", err))?
+            }
+            None => (Duration::ZERO, Duration::ZERO),
+        };
+        let failure_rate = match failure_rate {
+            Some(rate) => rate
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| {
+                    invalid("SP1_MOCK_FAILURE_RATE", format!("expected 0 to 1, got `{}`", rate))
+                })?,
+            None => 0.0,
+        };
+        let seed = match env::var("SP1_MOCK_SEED") {
+            Ok(seed) => seed.parse().map_err(|_| {
+                invalid("SP1_MOCK_SEED", format!("expected a number, got `{}`", seed))
+            })?,
+            Err(_) => {
+                SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+            }
+        };
+        Ok(Some(Self::new(latency, failure_rate, seed)))
+    }
+
+    /// Simulate requests taking between `latency.0` and `latency.1`, failing at `failure_rate`.
+    pub fn new(latency: (Duration, Duration), failure_rate: f64, seed: u64) -> Self {
+        // xorshift gets stuck at zero.
+        Self { latency, failure_rate, state: Mutex::new(seed.max(1)) }
+    }
+
+    /// Wait like a prover network request, then fail if this request is one of those that do.
+    pub fn request(&self) -> Result<(), String> {
+        let (min, max) = self.latency;
+        let delay = min + (max - min).mul_f64(self.next_unit());
+        if !delay.is_zero() {
+            eprintln!("🎭 Simulating a {:.1}s prover network request", delay.as_secs_f64());
+            thread::sleep(delay);
+        }
+        if self.next_unit() < self.failure_rate {
+            return Err("simulated prover network failure (SP1_MOCK_FAILURE_RATE)".to_string());
+        }
+        Ok(())
+    }
+
+    /// A uniform sample from [0, 1).
+    fn next_unit(&self) -> f64 {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Parse `<secs>` or `<min>..<max>` seconds.
+fn parse_latency(latency: &str) -> Result<(Duration, Duration), String> {
+    let secs = |secs: &str| {
+        secs.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(Duration::from_secs_f64)
+            .ok_or_else(|| format!("expected seconds, got `{}`", secs))
+    };
+    let (min, max) = match latency.split_once("..") {
+        Some((min, max)) => (secs(min)?, secs(max)?),
+        None => (secs(latency)?, secs(latency)?),
+    };
+    if min > max {
+        return Err(format!("the range `{}` is empty", latency));
+    }
+    Ok((min, max))
+}
@@ -0,0 +1,164 @@
+//! The OpenAPI document of the `service` JSON-RPC interface (see [`crate::rpc`]), from which
+//! client SDKs in other languages can be generated. The service serves it at `/openapi.json`
+//! and as `sp1_openApi`, and `service --openapi` prints it.
+//!
+//! JSON-RPC has a single endpoint, but the service accepts calls on any path, so each method is
+//! documented as a `POST` to its own path (`/sp1_prove`, ...) taking the JSON-RPC request and
+//! returning the JSON-RPC response. Generated clients then get one typed operation per method.
+
+use crate::artifacts::ProgramProof;
+use crate::jobs::{JobStatus, Priority};
+use crate::prover::ProofSystem;
+use crate::rpc::{
+    ProofResponse, ProveRequest, ProveResponse, VerifyResponse, CHAIN_ERROR, JOB_NOT_FOUND,
+    PROOF_NOT_READY,
+};
+use fibonacci_lib::recurrence::Recurrence;
+use fibonacci_lib::ProgramInput;
+use utoipa::openapi::path::{HttpMethod, OperationBuilder, PathItem};
+use utoipa::openapi::request_body::RequestBodyBuilder;
+use utoipa::openapi::response::ResponseBuilder;
+use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder, OneOfBuilder, Schema, Type};
+use utoipa::openapi::{ContentBuilder, OpenApi, PathsBuilder, Ref, RefOr, Required};
+use utoipa::OpenApi as _;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    info(
+        title = "SP1 Fibonacci prover",
+        description = "JSON-RPC 2.0 interface of the proving service. Proofs are requested with \
+            sp1_prove, fetched with sp1_getProof and checked against the consumer contract with \
+            sp1_verifyOnchain."
+    ),
+    components(schemas(
+        ProgramInput,
+        Recurrence,
+        ProofSystem,
+        Priority,
+        JobStatus,
+        ProveRequest,
+        ProveResponse,
+        ProofResponse,
+        VerifyResponse,
+        ProgramProof,
+    ))
+)]
+struct ApiDoc;
+
+/// A method of the interface: its name, summary, parameter schemas and result schema.
+struct Method {
+    name: &'static str,
+    summary: &'static str,
+    params: &'static [(&'static str, fn() -> RefOr<Schema>)],
+    result: &'static str,
+}
+
+const METHODS: &[Method] = &[
+    Method {
+        name: "sp1_prove",
+        summary: "Queue a proof of an input, coalesced onto any identical job",
+        params: &[("request", || schema_ref("ProveRequest"))],
+        result: "ProveResponse",
+    },
+    Method {
+        name: "sp1_getProof",
+        summary: "Get a job's status, and its proof once completed",
+        params: &[("jobId", string)],
+        result: "ProofResponse",
+    },
+    Method {
+        name: "sp1_verifyOnchain",
+        summary: "Simulate verifying a job's proof on the consumer contract (the service's by \
+            default)",
+        params: &[("jobId", string), ("contract", string)],
+        result: "VerifyResponse",
+    },
+];
+
+/// The OpenAPI document of the service.
+pub fn openapi() -> OpenApi {
+    let mut doc = ApiDoc::openapi();
+    let mut paths = PathsBuilder::new();
+    for method in METHODS {
+        let request = RequestBodyBuilder::new()
+            .content("application/json", json_content(request_schema(method)))
+            .required(Some(Required::True))
+            .build();
+        let response = ResponseBuilder::new()
+            .description("The JSON-RPC response: the result, or an error")
+            .content("application/json", json_content(response_schema(method.result)))
+            .build();
+        let operation = OperationBuilder::new()
+            .operation_id(Some(method.name))
+            .summary(Some(method.summary))
+            .request_body(Some(request))
+            .response("200", response);
+        paths = paths.path(format!("/{}", method.name), PathItem::new(HttpMethod::Post, operation));
+    }
+    doc.paths = paths.build();
+    if let Some(components) = doc.components.as_mut() {
+        components.schemas.insert("RpcError".to_string(), error_schema().into());
+    }
+    doc
+}
+
+/// The JSON-RPC request calling `method`, with its parameters by position.
+fn request_schema(method: &Method) -> RefOr<Schema> {
+    let params: Vec<Schema> = method
+        .params
+        .iter()
+        .map(|(name, schema)| OneOfBuilder::new().item(schema()).description(Some(*name)).into())
+        .collect();
+    envelope()
+        .property("method", constant(method.name))
+        .required("method")
+        .property("params", ArrayBuilder::new().prefix_items(params).min_items(Some(1)))
+        .required("params")
+        .into()
+}
+
+/// The JSON-RPC response: `result` of type `result`, or an `error`.
+fn response_schema(result: &str) -> RefOr<Schema> {
+    let success = envelope().property("result", schema_ref(result)).required("result");
+    let failure = envelope().property("error", schema_ref("RpcError")).required("error");
+    OneOfBuilder::new().item(success).item(failure).into()
+}
+
+/// The fields every JSON-RPC request and response has.
+fn envelope() -> ObjectBuilder {
+    ObjectBuilder::new()
+        .property("jsonrpc", constant("2.0"))
+        .required("jsonrpc")
+        .property("id", ObjectBuilder::new().schema_type(Type::Integer))
+        .required("id")
+}
+
+/// A JSON-RPC error, with the service's own codes.
+fn error_schema() -> ObjectBuilder {
+    let codes = format!(
+        "{} for an unknown job, {} for a job without a proof yet, {} for an unreachable chain, \
+        or a standard JSON-RPC code",
+        JOB_NOT_FOUND, PROOF_NOT_READY, CHAIN_ERROR
+    );
+    ObjectBuilder::new()
+        .property("code", ObjectBuilder::new().schema_type(Type::Integer).description(Some(codes)))
+        .required("code")
+        .property("message", ObjectBuilder::new().schema_type(Type::String))
+        .required("message")
+}
+
+fn json_content(schema: RefOr<Schema>) -> utoipa::openapi::Content {
+    ContentBuilder::new().schema(Some(schema)).build()
+}
+
+fn constant(value: &str) -> ObjectBuilder {
+    ObjectBuilder::new().schema_type(Type::String).enum_values(Some([value]))
+}
+
+fn string() -> RefOr<Schema> {
+    ObjectBuilder::new().schema_type(Type::String).into()
+}
+
+fn schema_ref(name: &str) -> RefOr<Schema> {
+    Ref::from_schema_name(name).into()
+}
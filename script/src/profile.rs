@@ -0,0 +1,87 @@
+//! Cycle profiles of an execution, broken down by the `cycle-tracker-report` regions the program
+//! marks (reading the input, computing, encoding, ...).
+//!
+//! A profile is a tree of `{name, value, children}` nodes, the JSON format flame graph viewers
+//! such as d3-flame-graph read, and can also be rendered as a standalone HTML page.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The node the cycles outside every tracked region are attributed to.
+pub const UNTRACKED: &str = "(untracked)";
+
+/// A region of the execution and the cycles spent in it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileNode {
+    pub name: String,
+    pub value: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ProfileNode>,
+}
+
+impl ProfileNode {
+    /// A node without children.
+    pub fn leaf(name: impl Into<String>, value: u64) -> Self {
+        Self { name: name.into(), value, children: Vec::new() }
+    }
+
+    /// The profile of an execution of `program` that ran `total` cycles, where `regions` are the
+    /// cycles of each tracked region (`ExecutionReport::cycle_tracker`). The regions are assumed
+    /// not to nest; they are listed by cycles, largest first.
+    pub fn from_regions(program: &str, total: u64, regions: &HashMap<String, u64>) -> Self {
+        let mut children: Vec<_> =
+            regions.iter().map(|(name, &cycles)| Self::leaf(name.as_str(), cycles)).collect();
+        children.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
+        let tracked: u64 = children.iter().map(|child| child.value).sum();
+        if total > tracked {
+            children.push(Self::leaf(UNTRACKED, total - tracked));
+        }
+        Self { name: program.to_string(), value: total.max(tracked), children }
+    }
+
+    /// Render the profile as a flame graph: one row per level of the tree, each region as wide
+    /// as its share of the root's cycles.
+    pub fn to_html(&self) -> String {
+        let mut rows = String::new();
+        let mut level = vec![self];
+        while !level.is_empty() {
+            rows.push_str("<div class=\"row\">");
+            for node in &level {
+                let share = node.value as f64 * 100.0 / self.value.max(1) as f64;
+                write!(
+                    rows,
+                    "<div class=\"node\" style=\"width:{:.3}%\" title=\"{} - {} cycles ({:.1}%)\">\
+                     {}</div>",
+                    share,
+                    escape(&node.name),
+                    node.value,
+                    share,
+                    escape(&node.name)
+                )
+                .expect("writing to a String cannot fail");
+            }
+            rows.push_str("</div>\n");
+            level = level.iter().flat_map(|node| &node.children).collect();
+        }
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} cycle profile\
+             </title>\n<style>\nbody {{ font-family: monospace; }}\n.row {{ display: flex; }}\n\
+             .node {{ box-sizing: border-box; overflow: hidden; white-space: nowrap; \
+             padding: 2px; border: 1px solid #fff; background: #f4a261; }}\n</style>\n</head>\n\
+             <body>\n<h1>{}: {} cycles</h1>\n{}</body>\n</html>\n",
+            escape(&self.name),
+            escape(&self.name),
+            self.value,
+            rows
+        )
+    }
+}
+
+/// Escape text for HTML content and attributes.
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
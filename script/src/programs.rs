@@ -0,0 +1,569 @@
+//! The registry of guest programs the scripts can be pointed at with `--program <name>`.
+//!
+//! To add a guest, add it to the workspace, build it in `build.rs`, and register its ELF (from
+//! `include_elf!`) and input parser in [`PROGRAMS`]; `cli init-program` does all of this (see
+//! [`crate::scaffold`]).
+//!
+//! Reproducible builds of the guests are pinned in [`ELF_LOCK_PATH`]; proving refuses to use an
+//! embedded ELF that does not match its pinned hash. Separately, [`check_fresh_elf`] catches an
+//! ELF that was built from older guest sources than the ones on disk.
+
+use crate::artifacts::{read_json, write_json, ArtifactError};
+use crate::config::ConfigError;
+use crate::fingerprint::{source_fingerprint, GUEST_SOURCE_DIRS};
+use crate::prover::{to_stdin, ProverError, FIBONACCI_ELF};
+use crate::regex::compile_dfa;
+use alloy_primitives::{Bytes, B256};
+use fibonacci_lib::bls::BlsInput;
+use fibonacci_lib::dfa::RegexInput;
+use fibonacci_lib::ed25519::Ed25519Input;
+use fibonacci_lib::headers::HeaderChainInput;
+use fibonacci_lib::json::JsonInput;
+use fibonacci_lib::jwt::JwtInput;
+use fibonacci_lib::stats::StatsInput;
+use fibonacci_lib::storage::StorageProofInput;
+use fibonacci_lib::{ProgramInput, VkHash};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sp1_sdk::SP1Stdin;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Where the vkey of every registered program is recorded, by program name.
+pub const VKEYS_PATH: &str = "artifacts/vkeys.json";
+
+/// Where the hashes of reproducibly built ELFs are pinned.
+pub const ELF_LOCK_PATH: &str = "elf.lock";
+
+/// Where `cli build-all` records what it last built of every program, by program name.
+pub const BUILDS_PATH: &str = "artifacts/builds.json";
+
+/// The fingerprint of the guest sources the embedded ELF was built from, or empty if unknown.
+const GUEST_SOURCE_FINGERPRINT: &str = env!("GUEST_SOURCE_FINGERPRINT");
+
+/// Set on the restarted process after a rebuild, so a rebuild that did not help is not repeated.
+const REBUILT_ENV: &str = "SP1_STALE_ELF_REBUILT";
+
+/// A guest program built by `build.rs`.
+#[derive(Clone, Copy)]
+pub struct Program {
+    /// The name it is selected by.
+    pub name: &'static str,
+    /// The crate of the guest, relative to `script`.
+    pub dir: &'static str,
+    /// The package name of the guest, as passed to `include_elf!`.
+    pub package: &'static str,
+    /// Its ELF, as embedded by `include_elf!`.
+    pub elf: &'static [u8],
+    /// Parse an input given on the command line into the program's stdin.
+    pub parse_input: fn(&str) -> Result<SP1Stdin, ProverError>,
+}
+
+impl fmt::Debug for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Program").field("name", &self.name).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name)
+    }
+}
+
+/// The hashing program, with the vanilla hash crates.
+#[cfg(feature = "hash-bench")]
+pub const HASHING_ELF: &[u8] = sp1_sdk::include_elf!("hashing-program");
+
+/// The hashing program, with the hash crates patched to use the SP1 precompiles.
+#[cfg(feature = "hash-bench")]
+pub const HASHING_PATCHED_ELF: &[u8] = sp1_sdk::include_elf!("hashing-program-patched");
+
+/// The JSON program, which extracts a field from a private JSON document.
+pub const JSON_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-json-program");
+
+/// The Ed25519 program, which verifies an Ed25519 signature with the SP1 precompiles.
+pub const ED25519_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-ed25519-program");
+
+/// The storage program, which verifies an Ethereum storage proof against a state root.
+pub const STORAGE_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-storage-program");
+
+/// The header chain program, which verifies that Ethereum block headers link by parent hash.
+pub const HEADER_CHAIN_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-header-chain-program");
+
+/// The statistics program, which computes statistics over a private dataset.
+pub const STATS_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-stats-program");
+
+/// The JWT program, which verifies an RS256 or ES256 token and its expiry.
+pub const JWT_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-jwt-program");
+
+/// The regex program, which matches private text against a public regular expression.
+pub const REGEX_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-regex-program");
+
+/// The BLS program, which verifies an aggregate BLS12-381 signature with the SP1 precompiles.
+pub const BLS_ELF: &[u8] = sp1_sdk::include_elf!("fibonacci-bls-program");
+
+/// Every program the scripts know about.
+pub const PROGRAMS: &[Program] = &[
+    Program {
+        name: "fibonacci",
+        dir: "../program",
+        package: "fibonacci-program",
+        elf: FIBONACCI_ELF,
+        parse_input: parse_fibonacci_input,
+    },
+    Program {
+        name: "json",
+        dir: "../json-program",
+        package: "fibonacci-json-program",
+        elf: JSON_ELF,
+        parse_input: parse_json_input,
+    },
+    Program {
+        name: "ed25519",
+        dir: "../ed25519-program",
+        package: "fibonacci-ed25519-program",
+        elf: ED25519_ELF,
+        parse_input: parse_ed25519_input,
+    },
+    Program {
+        name: "storage",
+        dir: "../storage-program",
+        package: "fibonacci-storage-program",
+        elf: STORAGE_ELF,
+        parse_input: parse_storage_input,
+    },
+    Program {
+        name: "header-chain",
+        dir: "../header-chain-program",
+        package: "fibonacci-header-chain-program",
+        elf: HEADER_CHAIN_ELF,
+        parse_input: parse_header_chain_input,
+    },
+    Program {
+        name: "stats",
+        dir: "../stats-program",
+        package: "fibonacci-stats-program",
+        elf: STATS_ELF,
+        parse_input: parse_stats_input,
+    },
+    Program {
+        name: "jwt",
+        dir: "../jwt-program",
+        package: "fibonacci-jwt-program",
+        elf: JWT_ELF,
+        parse_input: parse_jwt_input,
+    },
+    Program {
+        name: "regex",
+        dir: "../regex-program",
+        package: "fibonacci-regex-program",
+        elf: REGEX_ELF,
+        parse_input: parse_regex_input,
+    },
+    Program {
+        name: "bls",
+        dir: "../bls-program",
+        package: "fibonacci-bls-program",
+        elf: BLS_ELF,
+        parse_input: parse_bls_input,
+    },
+    #[cfg(feature = "hash-bench")]
+    Program {
+        name: "hashing",
+        dir: "../hashing-program",
+        package: "hashing-program",
+        elf: HASHING_ELF,
+        parse_input: parse_hashing_input,
+    },
+    #[cfg(feature = "hash-bench")]
+    Program {
+        name: "hashing-patched",
+        dir: "../hashing-program-patched",
+        package: "hashing-program-patched",
+        elf: HASHING_PATCHED_ELF,
+        parse_input: parse_hashing_input,
+    },
+];
+
+/// Look up a program by name; usable as a clap `value_parser`.
+pub fn program(name: &str) -> Result<&'static Program, String> {
+    PROGRAMS.iter().find(|program| program.name == name).ok_or_else(|| {
+        let names: Vec<_> = PROGRAMS.iter().map(|program| program.name).collect();
+        format!("unknown program `{}` (expected one of: {})", name, names.join(", "))
+    })
+}
+
+/// Parse a Fibonacci input: either just `n`, or a JSON [`ProgramInput`].
+pub fn parse_program_input(input: &str) -> Result<ProgramInput, ProverError> {
+    match input.trim().parse() {
+        Ok(n) => Ok(ProgramInput::new(n)),
+        Err(_) => serde_json::from_str(input)
+            .map_err(|err| ProverError::InvalidInput(format!("expected n or JSON: {}", err))),
+    }
+}
+
+/// Parse a Fibonacci input (see [`parse_program_input`]) into the program's stdin.
+fn parse_fibonacci_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    to_stdin(&parse_program_input(input)?)
+}
+
+/// Parse a JSON program input: `{"document": <any JSON>, "path": "<JSON Pointer>"}`. The document
+/// is hashed as its compact serialization; use the `json_field` binary to hash a file as it is.
+fn parse_json_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    #[derive(Deserialize)]
+    struct Input {
+        document: serde_json::Value,
+        path: String,
+    }
+    let input: Input = serde_json::from_str(input).map_err(|err| {
+        ProverError::InvalidInput(format!("expected a document and a path: {}", err))
+    })?;
+    let document = serde_json::to_vec(&input.document).expect("JSON values always serialize");
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&JsonInput { document, path: input.path });
+    Ok(stdin)
+}
+
+/// Parse an Ed25519 program input: `{"public_key": "0x...", "signature": "0x...", "message":
+/// "0x..."}`, checking the signature so an invalid one fails here rather than in the program.
+fn parse_ed25519_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    #[derive(Deserialize)]
+    struct Input {
+        public_key: B256,
+        signature: Bytes,
+        message: Bytes,
+    }
+    let input: Input = serde_json::from_str(input).map_err(|err| {
+        ProverError::InvalidInput(format!("expected a public key, signature and message: {}", err))
+    })?;
+    let input = Ed25519Input {
+        public_key: input.public_key.0,
+        signature: input.signature.to_vec(),
+        message: input.message.to_vec(),
+    };
+    input.verify().map_err(ProverError::InvalidInput)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+    Ok(stdin)
+}
+
+/// Parse a storage program input: a JSON [`StorageProofInput`], with the trie nodes as hex,
+/// checking the proofs so an invalid one fails here rather than in the program.
+fn parse_storage_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    let input: StorageProofInput = serde_json::from_str(input)
+        .map_err(|err| ProverError::InvalidInput(format!("expected a storage proof: {}", err)))?;
+    input.verify().map_err(ProverError::InvalidInput)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+    Ok(stdin)
+}
+
+/// Parse a header chain program input: a JSON array of RLP-encoded headers in hex, oldest first,
+/// checking that they form a chain so a broken one fails here rather than in the program.
+fn parse_header_chain_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    let headers = serde_json::from_str(input)
+        .map_err(|err| ProverError::InvalidInput(format!("expected hex headers: {}", err)))?;
+    let input = HeaderChainInput { headers };
+    input.verify().map_err(ProverError::InvalidInput)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+    Ok(stdin)
+}
+
+/// Parse a statistics program input: the values, comma-separated.
+fn parse_stats_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    let values = input
+        .split(',')
+        .map(|value| value.trim().parse())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|err| ProverError::InvalidInput(format!("expected integers: {}", err)))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&StatsInput { values });
+    Ok(stdin)
+}
+
+/// Parse a JWT program input: `{"token": "...", "public_key": "0x<DER>", "timestamp": ...,
+/// "claims": [...]}`, checking the token so an invalid one fails here rather than in the program.
+fn parse_jwt_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    #[derive(Deserialize)]
+    struct Input {
+        token: String,
+        public_key: Bytes,
+        timestamp: u64,
+        #[serde(default)]
+        claims: Vec<String>,
+    }
+    let input: Input = serde_json::from_str(input).map_err(|err| {
+        ProverError::InvalidInput(format!("expected a token, public key and timestamp: {}", err))
+    })?;
+    let input = JwtInput {
+        token: input.token,
+        public_key: input.public_key.to_vec(),
+        timestamp: input.timestamp,
+        claims: input.claims,
+    };
+    input.verify().map_err(ProverError::InvalidInput)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+    Ok(stdin)
+}
+
+/// Parse a regex program input: `{"pattern": "...", "text": "..."}`, compiling the pattern into
+/// the DFA the program runs.
+fn parse_regex_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    #[derive(Deserialize)]
+    struct Input {
+        pattern: String,
+        text: String,
+    }
+    let input: Input = serde_json::from_str(input).map_err(|err| {
+        ProverError::InvalidInput(format!("expected a pattern and a text: {}", err))
+    })?;
+    let dfa =
+        compile_dfa(&input.pattern).map_err(|err| ProverError::InvalidInput(err.to_string()))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&RegexInput { pattern: input.pattern, dfa, text: input.text.into_bytes() });
+    Ok(stdin)
+}
+
+/// Parse a BLS program input: `{"public_keys": ["0x...", ...], "signature": "0x...", "message":
+/// "0x..."}` with the aggregate signature, checking it so an invalid one fails here rather than
+/// in the program.
+fn parse_bls_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    #[derive(Deserialize)]
+    struct Input {
+        public_keys: Vec<Bytes>,
+        signature: Bytes,
+        message: Bytes,
+    }
+    let input: Input = serde_json::from_str(input).map_err(|err| {
+        let message = format!("expected public keys, a signature and a message: {}", err);
+        ProverError::InvalidInput(message)
+    })?;
+    let input = BlsInput {
+        public_keys: input.public_keys.iter().map(|key| key.to_vec()).collect(),
+        signature: input.signature.to_vec(),
+        message: input.message.to_vec(),
+    };
+    input.verify().map_err(ProverError::InvalidInput)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input);
+    Ok(stdin)
+}
+
+/// Parse a hashing input: the number of bytes to hash.
+#[cfg(feature = "hash-bench")]
+fn parse_hashing_input(input: &str) -> Result<SP1Stdin, ProverError> {
+    let len: usize = input
+        .trim()
+        .parse()
+        .map_err(|err| ProverError::InvalidInput(format!("expected a byte count: {}", err)))?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write_vec(hashing_data(len));
+    Ok(stdin)
+}
+
+/// The deterministic `len` bytes the hashing program is benchmarked on.
+#[cfg(feature = "hash-bench")]
+pub fn hashing_data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+/// Record the vkey of a program in the registry at `path`, keeping the other programs' vkeys.
+pub fn save_vkey(path: &Path, program: &Program, vkey_hash: &VkHash) -> Result<(), ArtifactError> {
+    let mut vkeys: BTreeMap<String, VkHash> =
+        if path.exists() { read_json(path)? } else { BTreeMap::new() };
+    vkeys.insert(program.name.to_string(), *vkey_hash);
+    write_json(path, &vkeys)
+}
+
+/// What `cli build-all` last built of a program, recorded in [`BUILDS_PATH`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuiltElf {
+    /// The hex SHA-256 of the ELF.
+    pub sha256: String,
+    /// The vkey of the ELF.
+    pub vkey: VkHash,
+    /// The SP1 Docker image tag it was built with, if it was built reproducibly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<String>,
+}
+
+/// Read the builds recorded at `path`, or none if nothing was recorded yet.
+pub fn read_builds(path: &Path) -> Result<BTreeMap<String, BuiltElf>, ArtifactError> {
+    if path.exists() {
+        read_json(path)
+    } else {
+        Ok(BTreeMap::new())
+    }
+}
+
+/// The hash of a reproducibly built ELF.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedElf {
+    /// The program name.
+    pub name: String,
+    /// The SP1 Docker image tag it was built with.
+    pub toolchain: String,
+    /// The hex SHA-256 of the ELF.
+    pub sha256: String,
+}
+
+/// The contents of [`ELF_LOCK_PATH`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElfLock {
+    #[serde(default, rename = "program")]
+    pub programs: Vec<LockedElf>,
+}
+
+impl ElfLock {
+    /// Load the lock file, if there is one.
+    pub fn load(path: &Path) -> Result<Option<Self>, ProverError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let invalid =
+            |reason: String| ProverError::ElfLock(format!("{}: {}", path.display(), reason));
+        let contents = fs::read_to_string(path).map_err(|err| invalid(err.to_string()))?;
+        toml::from_str(&contents).map(Some).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Pin `locked`, replacing any previous pin of the same program.
+    pub fn pin(&mut self, locked: LockedElf) {
+        self.programs.retain(|program| program.name != locked.name);
+        self.programs.push(locked);
+        self.programs.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Write the lock file.
+    pub fn save(&self, path: &Path) -> Result<(), ArtifactError> {
+        let contents = toml::to_string(self).expect("the lock file always serializes to TOML");
+        fs::write(path, contents)
+            .map_err(|source| ArtifactError::Io { path: path.to_path_buf(), source })
+    }
+}
+
+/// The hex SHA-256 of an ELF.
+pub fn elf_sha256(elf: &[u8]) -> String {
+    hex::encode(Sha256::digest(elf))
+}
+
+/// Check that the embedded ELF of `program` matches its pin in [`ELF_LOCK_PATH`], if it has one.
+pub fn check_elf_lock(program: &Program) -> Result<(), ProverError> {
+    let Some(lock) = ElfLock::load(Path::new(ELF_LOCK_PATH))? else {
+        return Ok(());
+    };
+    let Some(locked) = lock.programs.iter().find(|locked| locked.name == program.name) else {
+        return Ok(());
+    };
+    let sha256 = elf_sha256(program.elf);
+    if sha256 != locked.sha256 {
+        return Err(ProverError::ElfLock(format!(
+            "the embedded {} ELF has SHA-256 {}, but {} pins {} (built with {}); rebuild the \
+             scripts with SP1_DOCKER_TAG={}",
+            program.name, sha256, ELF_LOCK_PATH, locked.sha256, locked.toolchain, locked.toolchain
+        )));
+    }
+    Ok(())
+}
+
+/// What to do when the embedded ELF is older than the guest sources (`SP1_STALE_ELF`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaleElfPolicy {
+    /// Print a warning and carry on (the default).
+    Warn,
+    /// Refuse to execute or prove.
+    Error,
+    /// Rebuild the scripts (and with them the guest), then restart the command.
+    Rebuild,
+}
+
+impl StaleElfPolicy {
+    /// Read the policy from `SP1_STALE_ELF`.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        match env::var("SP1_STALE_ELF").as_deref() {
+            Err(_) | Ok("warn") => Ok(Self::Warn),
+            Ok("error") => Ok(Self::Error),
+            Ok("rebuild") => Ok(Self::Rebuild),
+            Ok(other) => Err(ConfigError::Invalid {
+                name: "SP1_STALE_ELF",
+                reason: format!("expected `warn`, `error` or `rebuild`, got `{}`", other),
+            }),
+        }
+    }
+}
+
+/// Check, once per process, that the embedded ELF was built from the guest sources on disk.
+///
+/// The sources are looked up next to the crate the binary was built from; if they are not there
+/// (e.g. the binary was copied elsewhere) or the build was skipped, there is nothing to compare.
+pub fn check_fresh_elf() -> Result<(), ProverError> {
+    static CHECKED: OnceLock<Result<(), String>> = OnceLock::new();
+    CHECKED.get_or_init(check_fresh_elf_uncached).clone().map_err(ProverError::StaleElf)
+}
+
+/// [`check_fresh_elf`], failing with the reason.
+fn check_fresh_elf_uncached() -> Result<(), String> {
+    if GUEST_SOURCE_FINGERPRINT.is_empty() {
+        return Ok(());
+    }
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let Some(fingerprint) = source_fingerprint(root, GUEST_SOURCE_DIRS) else {
+        return Ok(());
+    };
+    if fingerprint == GUEST_SOURCE_FINGERPRINT {
+        return Ok(());
+    }
+    let stale = "the guest sources changed since the embedded ELF was built";
+    match StaleElfPolicy::from_env().map_err(|err| err.to_string())? {
+        StaleElfPolicy::Warn => {
+            eprintln!("⚠️  {}; rebuild the scripts (or set SP1_STALE_ELF=rebuild)", stale);
+            Ok(())
+        }
+        StaleElfPolicy::Error => Err(format!("{}; rebuild the scripts", stale)),
+        StaleElfPolicy::Rebuild if env::var_os(REBUILT_ENV).is_some() => {
+            Err(format!("{}, and rebuilding the scripts did not pick the changes up", stale))
+        }
+        StaleElfPolicy::Rebuild => {
+            eprintln!("🔨 {}; rebuilding...", stale);
+            Err(rebuild_and_restart(root))
+        }
+    }
+}
+
+/// Rebuild the binaries of this crate and replace the current process with the rebuilt one,
+/// returning only if that fails.
+fn rebuild_and_restart(root: &Path) -> String {
+    let mut build = Command::new("cargo");
+    build.args(["build", "--bins"]).current_dir(root);
+    if !cfg!(debug_assertions) {
+        build.arg("--release");
+    }
+    match build.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => return format!("rebuilding the scripts failed with {}", status),
+        Err(err) => return format!("failed to run cargo: {}", err),
+    }
+    let exe = match env::current_exe() {
+        Ok(exe) => exe,
+        Err(err) => return format!("failed to locate the current binary: {}", err),
+    };
+    let mut restart = Command::new(exe);
+    restart.args(env::args_os().skip(1)).env(REBUILT_ENV, "1");
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        format!("failed to restart: {}", restart.exec())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = restart;
+        "the scripts were rebuilt; run the command again".to_string()
+    }
+}
@@ -0,0 +1,129 @@
+//! What produced a proof: the commit and toolchain the scripts were built from, the versions of
+//! the SP1 and alloy crates they link, the host they ran on and the hash of the program ELF.
+//! Recorded in the manifest, call data and summary of every saved proof, so that auditors can
+//! tell exactly which build generated it.
+//!
+//! The manifest and summary also record the [`ProverIdentity`]: the prover mode, machine and
+//! build features a proof was generated with, and for the prover network the request and the
+//! prover that fulfilled it, so that a bad proof can be traced back to where it was made.
+
+use alloy_primitives::{keccak256, Address, B256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The commit the scripts were built from, with a `-dirty` suffix if the workspace had local
+/// changes, or empty outside a git checkout (embedded by `build.rs`).
+const BUILD_GIT_COMMIT: &str = env!("BUILD_GIT_COMMIT");
+
+/// `rustc --version` of the compiler that built the scripts (embedded by `build.rs`).
+const BUILD_RUSTC_VERSION: &str = env!("BUILD_RUSTC_VERSION");
+
+/// The versions `Cargo.lock` resolved the SP1 and alloy crates to, as `name=version` pairs
+/// separated by `;` (embedded by `build.rs`).
+const BUILD_PACKAGES: &str = env!("BUILD_PACKAGES");
+
+/// The cargo features the scripts were built with, separated by `,` (embedded by `build.rs`).
+const BUILD_FEATURES: &str = env!("BUILD_FEATURES");
+
+/// The build and host a proof was produced by.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The git commit of the workspace, or `unknown`.
+    pub git_commit: String,
+    pub rustc: String,
+    /// The versions of the scripts and of the SP1 and alloy crates, by package name. A package
+    /// resolved at several versions lists all of them, separated by commas.
+    pub packages: BTreeMap<String, String>,
+    /// The host's operating system and architecture, as in `std::env::consts`.
+    pub os: String,
+    pub arch: String,
+    /// The keccak256 hash of the program ELF that was proved.
+    pub elf_hash: B256,
+}
+
+impl Provenance {
+    /// The provenance of proofs of `elf` generated by this build on this host.
+    pub fn current(elf: &[u8]) -> Self {
+        let mut packages: BTreeMap<String, String> = BUILD_PACKAGES
+            .split(';')
+            .filter_map(|package| package.split_once('='))
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect();
+        packages.insert(env!("CARGO_PKG_NAME").to_string(), env!("CARGO_PKG_VERSION").to_string());
+        Self {
+            git_commit: or_unknown(BUILD_GIT_COMMIT),
+            rustc: or_unknown(BUILD_RUSTC_VERSION),
+            packages,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            elf_hash: keccak256(elf),
+        }
+    }
+
+    /// The `name version` pairs of the recorded packages, for display.
+    pub fn package_list(&self) -> String {
+        self.packages
+            .iter()
+            .map(|(name, version)| format!("{} {}", name, version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Who generated a proof, and how.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverIdentity {
+    /// The prover (`SP1_PROVER`): `cpu`, `cuda`, `network` or `mock`.
+    pub mode: String,
+    /// The machine that proved, or requested the proof from the prover network.
+    pub hostname: String,
+    /// The cargo features the scripts were built with, including those that enable SP1 SDK
+    /// features (`profiling`).
+    pub features: Vec<String>,
+    /// The prover network request that was fulfilled, with `SP1_PROVER=network`.
+    #[serde(default)]
+    pub request_id: Option<B256>,
+    /// The network prover that fulfilled the request, if the network reported it.
+    #[serde(default)]
+    pub fulfiller: Option<Address>,
+}
+
+impl ProverIdentity {
+    /// The identity of a proof generated on this host now, without a prover network request.
+    pub fn current() -> Self {
+        Self {
+            mode: std::env::var("SP1_PROVER").unwrap_or_else(|_| "cpu".to_string()),
+            hostname: or_unknown(&gethostname::gethostname().to_string_lossy()),
+            features: BUILD_FEATURES
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_string)
+                .collect(),
+            request_id: None,
+            fulfiller: None,
+        }
+    }
+
+    /// A one-line description, for summaries and logs.
+    pub fn describe(&self) -> String {
+        let mut description = format!("{} on {}", self.mode, self.hostname);
+        if let Some(request_id) = self.request_id {
+            description.push_str(&format!(", request {}", request_id));
+        }
+        if let Some(fulfiller) = self.fulfiller {
+            description.push_str(&format!(" fulfilled by {}", fulfiller));
+        }
+        if !self.features.is_empty() {
+            description.push_str(&format!(" (features: {})", self.features.join(", ")));
+        }
+        description
+    }
+}
+
+fn or_unknown(value: &str) -> String {
+    if value.is_empty() {
+        "unknown".to_string()
+    } else {
+        value.to_string()
+    }
+}
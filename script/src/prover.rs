@@ -0,0 +1,729 @@
+//! Executing and proving the Fibonacci program.
+
+use crate::config::ConfigError;
+use crate::keys::{KeyCache, Keys};
+use crate::mock::MockNetwork;
+use crate::programs::{check_elf_lock, check_fresh_elf, Program, PROGRAMS};
+use crate::provenance::ProverIdentity;
+use alloy_primitives::Address;
+use clap::ValueEnum;
+use fibonacci_lib::batch::AggregationInput;
+use fibonacci_lib::recursion::RecursionInput;
+use fibonacci_lib::{
+    compute_public_values, run_program, ProgramInput, PublicValuesStruct, VkHash, GUEST_LOG_FD,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sp1_sdk::network::FulfillmentStrategy;
+use sp1_sdk::{
+    include_elf, EnvProver, HashableKey, NetworkProver, ProverClient, SP1Proof,
+    SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+};
+use std::fmt;
+use std::future::Future;
+use std::panic;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::runtime::RuntimeFlavor;
+use utoipa::ToSchema;
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const FIBONACCI_ELF: &[u8] = include_elf!("fibonacci-program");
+
+/// The ELF of the aggregator program, which verifies a batch of Fibonacci proofs in one proof.
+pub const AGGREGATOR_ELF: &[u8] = include_elf!("fibonacci-aggregator-program");
+
+/// The ELF of the recursion program, which verifies one compressed Fibonacci proof.
+pub const RECURSION_ELF: &[u8] = include_elf!("fibonacci-recursion-program");
+
+/// Errors from executing or proving the program.
+#[derive(Debug, Error)]
+pub enum ProverError {
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("execution failed: {0}")]
+    Execution(String),
+    #[error("{0}")]
+    GuestPanic(GuestPanic),
+    #[error("failed to generate proof: {0}")]
+    Proving(String),
+    #[error("failed to verify proof: {0}")]
+    Verification(String),
+    #[error("failed to decode public values: {0}")]
+    Decode(#[from] alloy_sol_types::Error),
+    #[error("ELF is not the pinned build: {0}")]
+    ElfLock(String),
+    #[error("ELF is stale: {0}")]
+    StaleElf(String),
+    #[error("the program committed other public values than the host computes: {}", .0.join("; "))]
+    Divergence(Vec<String>),
+    #[error("the program is not deterministic: the same input gave {}", .0.join(" and "))]
+    Nondeterministic(Vec<String>),
+}
+
+/// Why the program halted instead of committing its public values. The executor does not report
+/// the program counter or cycle count it halted at, so only the message is known.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GuestPanic {
+    /// The panic message.
+    pub message: String,
+}
+
+impl fmt::Display for GuestPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "program panicked: {}", self.message)
+    }
+}
+
+impl GuestPanic {
+    /// Explain a failed execution of the program on `input`.
+    ///
+    /// The SDK only reports that the program halted with a non-zero exit code; its panic message
+    /// goes to the executor's log. Since the program runs [`run_program`], the message is
+    /// recovered by replaying that natively. The replay's panic is caught on this thread only;
+    /// the process-wide panic hook is left alone, so it reports the panic like any other.
+    pub fn from_execution_error(input: &ProgramInput, err: impl fmt::Display) -> Option<Self> {
+        let replayed = panic::catch_unwind(|| run_program(input));
+        let message = match replayed {
+            Err(payload) => payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string())),
+            Ok(_) => None,
+        };
+        let err = err.to_string();
+        let message = match message {
+            Some(message) => message,
+            None if err.contains("exit code") => err,
+            None => return None,
+        };
+        Some(Self { message })
+    }
+}
+
+/// Enum representing the available proof systems
+#[derive(
+    Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+impl fmt::Display for ProofSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plonk => write!(f, "plonk"),
+            Self::Groth16 => write!(f, "groth16"),
+        }
+    }
+}
+
+/// How the prover network fulfills a proof request. Only applies with `SP1_PROVER=network`.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkStrategy {
+    /// Succinct's hosted provers, the network's default.
+    Hosted,
+    /// Prover capacity reserved for the requester, for latency-sensitive proofs.
+    Reserved,
+    /// The open auction, cheapest for proofs that can wait.
+    Auction,
+}
+
+impl fmt::Display for NetworkStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.to_possible_value().expect("no skipped variants").get_name())
+    }
+}
+
+impl From<NetworkStrategy> for FulfillmentStrategy {
+    fn from(strategy: NetworkStrategy) -> Self {
+        match strategy {
+            NetworkStrategy::Hosted => Self::Hosted,
+            NetworkStrategy::Reserved => Self::Reserved,
+            NetworkStrategy::Auction => Self::Auction,
+        }
+    }
+}
+
+/// The outputs of executing the program without proving it.
+#[derive(Debug, Clone)]
+pub struct Execution {
+    pub public_values: PublicValuesStruct,
+    pub cycles: u64,
+    /// What the program logged, kept apart from the host's output.
+    pub guest_stdout: String,
+}
+
+/// Collects what a program writes to [`GUEST_LOG_FD`] during an execution.
+#[derive(Debug, Clone, Default)]
+pub struct GuestLog(Arc<Mutex<Vec<u8>>>);
+
+impl GuestLog {
+    /// The execution hook to register for [`GUEST_LOG_FD`].
+    pub fn hook<E>(&self) -> impl FnMut(E, &[u8]) -> Vec<Vec<u8>> + Send + Sync + 'static {
+        let log = Arc::clone(&self.0);
+        move |_, line| {
+            log.lock().expect("guest log lock poisoned").extend_from_slice(line);
+            Vec::new()
+        }
+    }
+
+    /// Everything logged so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().expect("guest log lock poisoned")).into_owned()
+    }
+}
+
+/// A prover for the Fibonacci program, configured from the environment (`SP1_PROVER`, ...).
+///
+/// The proving and verifying keys are only set up on first use, so executing the program stays
+/// cheap, unless [`Self::warm_up`] sets them up ahead of time. They are then kept in a
+/// [`KeyCache`] for every later proof: a worker or batch proving many inputs sets each program up
+/// once. The prover is `Sync`, so threads proving concurrently can share one (behind an `Arc` if
+/// they outlive it); the first to need a key sets it up while the others wait for it.
+pub struct FibonacciProver {
+    client: EnvProver,
+    /// The keys of the Fibonacci program, held for the life of the prover even once the cache
+    /// evicts them.
+    keys: OnceLock<Arc<Keys>>,
+    /// The keys of the Fibonacci program, and of the aggregator program for [`Self::prove_batch`]
+    /// and the recursion program for [`Self::prove_recursive`] once they are needed.
+    key_cache: Arc<KeyCache>,
+    /// The prover network client, for requests with a [`NetworkStrategy`].
+    network: OnceLock<NetworkProver>,
+    /// The simulated network behaviour of mock proof requests.
+    mock: Result<Option<MockNetwork>, ConfigError>,
+    allow_rejected: bool,
+}
+
+impl Default for FibonacciProver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FibonacciProver {
+    /// Create a prover from the environment.
+    pub fn new() -> Self {
+        Self {
+            client: ProverClient::from_env(),
+            keys: OnceLock::new(),
+            key_cache: Arc::default(),
+            network: OnceLock::new(),
+            mock: MockNetwork::from_env(),
+            allow_rejected: false,
+        }
+    }
+
+    /// Run the program on inputs it rejects too, so the committed rejection (see
+    /// [`fibonacci_lib::Status`]) can be proven, instead of failing with
+    /// [`ProverError::InvalidInput`] up front.
+    pub fn allow_rejected(mut self) -> Self {
+        self.allow_rejected = true;
+        self
+    }
+
+    /// Set up the keys of the Fibonacci program ahead of its first proof, and return how long it
+    /// took. The aggregator and recursion programs are still set up on first use, as only
+    /// [`Self::prove_batch`] and [`Self::prove_recursive`] need them.
+    pub fn warm_up(&self) -> Duration {
+        let started = Instant::now();
+        self.keys();
+        started.elapsed()
+    }
+
+    /// The program's stdin for `input`, validated unless rejections are allowed.
+    fn stdin(&self, input: &ProgramInput) -> Result<SP1Stdin, ProverError> {
+        if !self.allow_rejected {
+            return to_stdin(input);
+        }
+        let mut stdin = SP1Stdin::new();
+        stdin.write(input);
+        Ok(stdin)
+    }
+
+    /// The proving and verifying keys of the program.
+    fn keys(&self) -> &Keys {
+        self.keys.get_or_init(|| self.key_cache.get("fibonacci", FIBONACCI_ELF, &self.client))
+    }
+
+    /// The verifying key of the program.
+    pub fn vk(&self) -> &SP1VerifyingKey {
+        &self.keys().1
+    }
+
+    /// The program vkey hash the verifier contracts are deployed with.
+    pub fn vkey_hash(&self) -> VkHash {
+        vk_hash(self.vk())
+    }
+
+    /// Execute the program without generating a proof.
+    pub fn execute(&self, input: &ProgramInput) -> Result<Execution, ProverError> {
+        check_fresh_elf()?;
+        let stdin = self.stdin(input)?;
+        let guest_log = GuestLog::default();
+        let (output, report) = self
+            .client
+            .execute(FIBONACCI_ELF, &stdin)
+            .with_hook(GUEST_LOG_FD, guest_log.hook())
+            .run()
+            .map_err(|err| match GuestPanic::from_execution_error(input, &err) {
+                Some(guest_panic) => ProverError::GuestPanic(guest_panic),
+                None => ProverError::Execution(err.to_string()),
+            })?;
+        let public_values = PublicValuesStruct::from_abi_bytes(output.as_slice())?;
+        cross_check(input, &public_values)?;
+        Ok(Execution {
+            public_values,
+            cycles: report.total_instruction_count(),
+            guest_stdout: guest_log.contents(),
+        })
+    }
+
+    /// Execute the program twice on `input` and check that both executions commit the same public
+    /// values in the same number of cycles, returning the first.
+    pub fn check_determinism(&self, input: &ProgramInput) -> Result<Execution, ProverError> {
+        let first = self.execute(input)?;
+        let second = self.execute(input)?;
+        check_same_execution(
+            (&first.public_values.to_abi_bytes(), first.cycles),
+            (&second.public_values.to_abi_bytes(), second.cycles),
+        )?;
+        Ok(first)
+    }
+
+    /// Generate a core proof.
+    pub fn prove_core(
+        &self,
+        input: &ProgramInput,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        check_fresh_elf()?;
+        check_elf_lock(&PROGRAMS[0])?;
+        self.before_request()?;
+        let stdin = self.stdin(input)?;
+        let proof = self
+            .client
+            .prove(&self.keys().0, &stdin)
+            .run()
+            .map_err(|err| ProverError::Proving(err.to_string()))?;
+        cross_check(input, &decode_public_values(&proof)?)?;
+        Ok(proof)
+    }
+
+    /// Generate an EVM-compatible proof.
+    pub fn prove(
+        &self,
+        input: &ProgramInput,
+        system: ProofSystem,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        check_fresh_elf()?;
+        check_elf_lock(&PROGRAMS[0])?;
+        self.before_request()?;
+        let stdin = self.stdin(input)?;
+        let pk = &self.keys().0;
+        let proof = match system {
+            ProofSystem::Plonk => self.client.prove(pk, &stdin).plonk().run(),
+            ProofSystem::Groth16 => self.client.prove(pk, &stdin).groth16().run(),
+        }
+        .map_err(|err| ProverError::Proving(err.to_string()))?;
+        cross_check(input, &decode_public_values(&proof)?)?;
+        Ok(proof)
+    }
+
+    /// Generate an EVM-compatible proof, fulfilled by the prover network with `strategy`. With
+    /// any other prover than `network` the strategy does not apply, and this is [`Self::prove`].
+    pub fn prove_with_strategy(
+        &self,
+        input: &ProgramInput,
+        system: ProofSystem,
+        strategy: NetworkStrategy,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        self.prove_traced(input, system, Some(strategy)).map(|(proof, _)| proof)
+    }
+
+    /// Generate an EVM-compatible proof as [`Self::prove_with_strategy`] does (with the network's
+    /// default strategy if none is given), along with who generated it. Prover network requests
+    /// are sent and awaited separately, so the identity records the request and, if the network
+    /// reports it, the prover that fulfilled it.
+    pub fn prove_traced(
+        &self,
+        input: &ProgramInput,
+        system: ProofSystem,
+        strategy: Option<NetworkStrategy>,
+    ) -> Result<(SP1ProofWithPublicValues, ProverIdentity), ProverError> {
+        let mut identity = ProverIdentity::current();
+        if std::env::var("SP1_PROVER").as_deref() != Ok("network") {
+            return Ok((self.prove(input, system)?, identity));
+        }
+        check_fresh_elf()?;
+        check_elf_lock(&PROGRAMS[0])?;
+        self.before_request()?;
+        let stdin = self.stdin(input)?;
+        let pk = &self.keys().0;
+        let network = self.network.get_or_init(|| ProverClient::builder().network().build());
+        let strategy = strategy.unwrap_or(NetworkStrategy::Hosted);
+        let request = network.prove(pk, &stdin).strategy(strategy.into());
+        let request_id = match system {
+            ProofSystem::Plonk => request.plonk().request(),
+            ProofSystem::Groth16 => request.groth16().request(),
+        }
+        .map_err(|err| ProverError::Proving(err.to_string()))?;
+        let proof: SP1ProofWithPublicValues = block_on(network.wait_proof(request_id, None, None))
+            .map_err(|err| ProverError::Proving(format!("request {}: {}", request_id, err)))?;
+        cross_check(input, &decode_public_values(&proof)?)?;
+
+        identity.request_id = Some(request_id);
+        // Which prover fulfilled the request is only informative, so failing to look it up does
+        // not fail the proof.
+        identity.fulfiller = block_on(network.get_proof_request(request_id))
+            .ok()
+            .flatten()
+            .and_then(|request| request.fulfiller)
+            .filter(|fulfiller| fulfiller.len() == 20)
+            .map(|fulfiller| Address::from_slice(&fulfiller));
+        Ok((proof, identity))
+    }
+
+    /// Generate a compressed proof, which other programs can verify (see [`Self::prove_batch`]).
+    pub fn prove_compressed(
+        &self,
+        input: &ProgramInput,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        check_fresh_elf()?;
+        check_elf_lock(&PROGRAMS[0])?;
+        self.before_request()?;
+        let stdin = self.stdin(input)?;
+        let proof = self
+            .client
+            .prove(&self.keys().0, &stdin)
+            .compressed()
+            .run()
+            .map_err(|err| ProverError::Proving(err.to_string()))?;
+        cross_check(input, &decode_public_values(&proof)?)?;
+        Ok(proof)
+    }
+
+    /// Prove a batch of inputs with a single EVM-compatible proof: each input is proved as a
+    /// compressed proof, then the aggregator program verifies them all and commits their public
+    /// values, or only the Merkle root of them with `merkle_root`.
+    pub fn prove_batch(
+        &self,
+        inputs: &[ProgramInput],
+        system: ProofSystem,
+        merkle_root: bool,
+    ) -> Result<BatchProof, ProverError> {
+        let mut aggregation = AggregationInput {
+            vkey_digest: self.vk().hash_u32(),
+            public_values: Vec::new(),
+            merkle_root,
+        };
+        let mut proofs = Vec::new();
+        for input in inputs {
+            let proof = self.prove_compressed(input)?;
+            let SP1Proof::Compressed(compressed) = proof.proof else {
+                return Err(ProverError::Proving("expected a compressed proof".to_string()));
+            };
+            aggregation.public_values.push(proof.public_values.to_vec());
+            proofs.push(compressed);
+        }
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&aggregation);
+        for proof in proofs {
+            stdin.write_proof(*proof, self.vk().vk.clone());
+        }
+        let keys = self.key_cache.get("aggregator", AGGREGATOR_ELF, &self.client);
+        let (pk, vk) = &*keys;
+        self.before_request()?;
+        let proof = match system {
+            ProofSystem::Plonk => self.client.prove(pk, &stdin).plonk().run(),
+            ProofSystem::Groth16 => self.client.prove(pk, &stdin).groth16().run(),
+        }
+        .map_err(|err| ProverError::Proving(err.to_string()))?;
+        Ok(BatchProof {
+            proof,
+            aggregator_vkey: vk_hash(vk),
+            input: aggregation,
+            inputs: inputs.to_vec(),
+        })
+    }
+
+    /// Prove an input with two levels of recursion: it is proved as a compressed proof, then the
+    /// recursion program verifies that proof and commits its public values in an EVM-compatible
+    /// proof.
+    pub fn prove_recursive(
+        &self,
+        input: &ProgramInput,
+        system: ProofSystem,
+    ) -> Result<RecursiveProof, ProverError> {
+        let inner = self.prove_compressed(input)?;
+        let SP1Proof::Compressed(compressed) = inner.proof.clone() else {
+            return Err(ProverError::Proving("expected a compressed proof".to_string()));
+        };
+        let recursion = RecursionInput {
+            vkey_digest: self.vk().hash_u32(),
+            public_values: inner.public_values.to_vec(),
+        };
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&recursion);
+        stdin.write_proof(*compressed, self.vk().vk.clone());
+        let keys = self.key_cache.get("recursion", RECURSION_ELF, &self.client);
+        let (pk, vk) = &*keys;
+        self.before_request()?;
+        let proof = match system {
+            ProofSystem::Plonk => self.client.prove(pk, &stdin).plonk().run(),
+            ProofSystem::Groth16 => self.client.prove(pk, &stdin).groth16().run(),
+        }
+        .map_err(|err| ProverError::Proving(err.to_string()))?;
+        self.client.verify(&proof, vk).map_err(|err| ProverError::Verification(err.to_string()))?;
+        Ok(RecursiveProof { proof, inner, recursion_vkey: vk_hash(vk), input: recursion })
+    }
+
+    /// Fail like a timed-out prover request if chaos mode says so, and simulate the latency and
+    /// failures of the prover network for mock proofs (see [`crate::mock`]).
+    fn before_request(&self) -> Result<(), ProverError> {
+        #[cfg(feature = "chaos")]
+        if crate::chaos::inject(crate::chaos::Fault::ProverTimeout) {
+            return Err(ProverError::Proving("request timed out (injected by chaos mode)".into()));
+        }
+        match &self.mock {
+            Ok(Some(mock)) => mock.request().map_err(ProverError::Proving),
+            Ok(None) => Ok(()),
+            Err(err) => Err(ProverError::Proving(err.to_string())),
+        }
+    }
+
+    /// Verify a proof of the program.
+    pub fn verify(&self, proof: &SP1ProofWithPublicValues) -> Result<(), ProverError> {
+        self.client
+            .verify(proof, self.vk())
+            .map_err(|err| ProverError::Verification(err.to_string()))
+    }
+}
+
+/// A proof of a batch of inputs, generated by [`FibonacciProver::prove_batch`].
+#[derive(Debug, Clone)]
+pub struct BatchProof {
+    /// The proof of the aggregator program.
+    pub proof: SP1ProofWithPublicValues,
+    /// The vkey hash of the aggregator program, which the batch contract is deployed with.
+    pub aggregator_vkey: VkHash,
+    /// What the aggregator program was given, including the public values of every input.
+    pub input: AggregationInput,
+    /// The Fibonacci program inputs, in the order of their public values.
+    pub inputs: Vec<ProgramInput>,
+}
+
+/// A proof of a proof, generated by [`FibonacciProver::prove_recursive`].
+#[derive(Debug, Clone)]
+pub struct RecursiveProof {
+    /// The proof of the recursion program.
+    pub proof: SP1ProofWithPublicValues,
+    /// The compressed proof of the Fibonacci program it verified.
+    pub inner: SP1ProofWithPublicValues,
+    /// The vkey hash of the recursion program, which its verifier contract is deployed with.
+    pub recursion_vkey: VkHash,
+    /// What the recursion program was given.
+    pub input: RecursionInput,
+}
+
+/// A prover for any registered program, for the guests other than Fibonacci: the caller writes
+/// the program's input to its stdin and decodes its public values.
+pub struct ProgramProver {
+    program: &'static Program,
+    client: EnvProver,
+    keys: OnceLock<Keys>,
+}
+
+impl ProgramProver {
+    /// Create a prover of `program` from the environment.
+    pub fn new(program: &'static Program) -> Self {
+        Self { program, client: ProverClient::from_env(), keys: OnceLock::new() }
+    }
+
+    /// The proving and verifying keys of the program.
+    fn keys(&self) -> &Keys {
+        self.keys.get_or_init(|| self.client.setup(self.program.elf))
+    }
+
+    /// The verifying key of the program.
+    pub fn vk(&self) -> &SP1VerifyingKey {
+        &self.keys().1
+    }
+
+    /// The program vkey hash its verifier contracts are deployed with.
+    pub fn vkey_hash(&self) -> VkHash {
+        vk_hash(self.vk())
+    }
+
+    /// Execute the program without generating a proof, returning its public values and cycles.
+    pub fn execute(&self, stdin: &SP1Stdin) -> Result<(Vec<u8>, u64), ProverError> {
+        let (output, report) = self
+            .client
+            .execute(self.program.elf, stdin)
+            .run()
+            .map_err(|err| ProverError::Execution(err.to_string()))?;
+        Ok((output.to_vec(), report.total_instruction_count()))
+    }
+
+    /// Execute the program twice and check that both executions commit the same public values in
+    /// the same number of cycles, returning the first.
+    pub fn check_determinism(&self, stdin: &SP1Stdin) -> Result<(Vec<u8>, u64), ProverError> {
+        let first = self.execute(stdin)?;
+        let second = self.execute(stdin)?;
+        check_same_execution((&first.0, first.1), (&second.0, second.1))?;
+        Ok(first)
+    }
+
+    /// Generate an EVM-compatible proof.
+    pub fn prove(
+        &self,
+        stdin: &SP1Stdin,
+        system: ProofSystem,
+    ) -> Result<SP1ProofWithPublicValues, ProverError> {
+        check_elf_lock(self.program)?;
+        let pk = &self.keys().0;
+        match system {
+            ProofSystem::Plonk => self.client.prove(pk, stdin).plonk().run(),
+            ProofSystem::Groth16 => self.client.prove(pk, stdin).groth16().run(),
+        }
+        .map_err(|err| ProverError::Proving(err.to_string()))
+    }
+
+    /// Verify a proof of the program.
+    pub fn verify(&self, proof: &SP1ProofWithPublicValues) -> Result<(), ProverError> {
+        self.client
+            .verify(proof, self.vk())
+            .map_err(|err| ProverError::Verification(err.to_string()))
+    }
+}
+
+/// The hash of a verifying key, as the verifier contracts are deployed with.
+pub fn vk_hash(vk: &SP1VerifyingKey) -> VkHash {
+    vk.bytes32().parse().expect("SP1 vkey hashes are 32 bytes of hex")
+}
+
+/// Validate the input and write it to the program's stdin.
+pub fn to_stdin(input: &ProgramInput) -> Result<SP1Stdin, ProverError> {
+    input.validate().map_err(ProverError::InvalidInput)?;
+    let mut stdin = SP1Stdin::new();
+    stdin.write(input);
+    Ok(stdin)
+}
+
+/// Check the public values the program committed for `input` against the ones `fibonacci-lib`
+/// computes natively, field by field, so a bug in the program is caught before a proof of it
+/// reaches a contract.
+pub fn cross_check(
+    input: &ProgramInput,
+    committed: &PublicValuesStruct,
+) -> Result<(), ProverError> {
+    let (Value::Object(committed), Value::Object(expected)) =
+        (json!(committed), json!(compute_public_values(input)))
+    else {
+        unreachable!("public values serialize as objects");
+    };
+    let fields: Vec<_> = committed
+        .iter()
+        .filter(|(field, value)| expected.get(*field) != Some(*value))
+        .map(|(field, value)| {
+            let expected = expected.get(field).cloned().unwrap_or_default();
+            format!("`{}` is {}, expected {}", field, value, expected)
+        })
+        .collect();
+    if fields.is_empty() {
+        Ok(())
+    } else {
+        Err(ProverError::Divergence(fields))
+    }
+}
+
+/// Check that two executions of a program on the same input committed the same public values in
+/// the same number of cycles. A program that differs reads something besides its input, such as
+/// the time, randomness or the iteration order of a `HashMap`, and its proofs cannot be
+/// reproduced.
+pub fn check_same_execution(
+    (first, first_cycles): (&[u8], u64),
+    (second, second_cycles): (&[u8], u64),
+) -> Result<(), ProverError> {
+    let mut differences = Vec::new();
+    if first != second {
+        differences.push(format!(
+            "public values 0x{} then 0x{}",
+            hex::encode(first),
+            hex::encode(second)
+        ));
+    }
+    if first_cycles != second_cycles {
+        differences.push(format!("{} cycles then {}", first_cycles, second_cycles));
+    }
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(ProverError::Nondeterministic(differences))
+    }
+}
+
+/// Decode the public values committed by a proof.
+pub fn decode_public_values(
+    proof: &SP1ProofWithPublicValues,
+) -> Result<PublicValuesStruct, ProverError> {
+    Ok(PublicValuesStruct::from_abi_bytes(proof.public_values.as_slice())?)
+}
+
+/// Wait for a prover network (or other network) call from synchronous code, on the current runtime
+/// if it is multi-threaded (as the SDK's blocking calls do) and on a new one otherwise.
+pub(crate) fn block_on<T: Send>(future: impl Future<Output = T> + Send) -> T {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        // `block_in_place` panics on a current-thread runtime, such as the one of
+        // `#[tokio::test]`, so the call gets a runtime of its own on another thread.
+        Ok(_) => std::thread::scope(|scope| {
+            scope
+                .spawn(|| block_on_new_runtime(future))
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload))
+        }),
+        Err(_) => block_on_new_runtime(future),
+    }
+}
+
+fn block_on_new_runtime<T>(future: impl Future<Output = T>) -> T {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start a runtime")
+        .block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn sleep_then(value: u32) -> u32 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        value
+    }
+
+    #[test]
+    fn block_on_starts_a_runtime_outside_of_one() {
+        assert_eq!(block_on(sleep_then(1)), 1);
+    }
+
+    #[tokio::test]
+    async fn block_on_works_on_a_current_thread_runtime() {
+        assert_eq!(block_on(sleep_then(2)), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn block_on_works_on_a_multi_thread_runtime() {
+        assert_eq!(block_on(sleep_then(3)), 3);
+    }
+}
@@ -0,0 +1,545 @@
+//! Decoding public values of every layout the program has committed.
+//!
+//! `PublicValuesStruct` has grown fields over time, so public values read from an old settlement
+//! or artifact may not decode as the current struct. Each layout after the first is a dynamic
+//! struct whose head has one word per field, so the offset of `fibValues` (the first dynamic
+//! field) tells the layouts apart:
+//!
+//! | Layout | Fields |
+//! |--------|--------|
+//! | 1 | `n`, `a`, `b` |
+//! | 2 | + `fibValues` |
+//! | 3 | + `chainId`, `consumer` |
+//! | 4 | + `nonce` |
+//! | 5 | + `validUntil` |
+//! | 6 | + `status` |
+//! | 7 | + `rangeHash` |
+//! | 8 | + `coefficients`, `initialValues`, `modulus`, `result` (current) |
+
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::{sol, SolType};
+use fibonacci_lib::recurrence::Recurrence;
+use fibonacci_lib::{compute_public_values, ProgramInput, PublicValuesStruct, Sequence};
+use thiserror::Error;
+
+/// The layout of the current `PublicValuesStruct`.
+pub const CURRENT_LAYOUT: u8 = 8;
+
+/// The fields of the current layout, in order. Layout `v` has the first `field_count(v)`.
+pub const FIELDS: [&str; 14] = [
+    "n",
+    "a",
+    "b",
+    "fibValues",
+    "chainId",
+    "consumer",
+    "nonce",
+    "validUntil",
+    "status",
+    "rangeHash",
+    "coefficients",
+    "initialValues",
+    "modulus",
+    "result",
+];
+
+sol! {
+    struct PublicValuesV1 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+    }
+
+    struct PublicValuesV2 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+        uint32[] fibValues;
+    }
+
+    struct PublicValuesV3 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+        uint32[] fibValues;
+        uint64 chainId;
+        address consumer;
+    }
+
+    struct PublicValuesV4 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+        uint32[] fibValues;
+        uint64 chainId;
+        address consumer;
+        uint64 nonce;
+    }
+
+    struct PublicValuesV5 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+        uint32[] fibValues;
+        uint64 chainId;
+        address consumer;
+        uint64 nonce;
+        uint64 validUntil;
+    }
+
+    struct PublicValuesV6 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+        uint32[] fibValues;
+        uint64 chainId;
+        address consumer;
+        uint64 nonce;
+        uint64 validUntil;
+        uint8 status;
+    }
+
+    struct PublicValuesV7 {
+        uint32 n;
+        uint32 a;
+        uint32 b;
+        uint32[] fibValues;
+        uint64 chainId;
+        address consumer;
+        uint64 nonce;
+        uint64 validUntil;
+        uint8 status;
+        bytes32 rangeHash;
+    }
+}
+
+/// Errors from public values that no layout can decode.
+#[derive(Debug, Error)]
+pub enum LayoutError {
+    #[error("{0} bytes is too short for any public values layout")]
+    TooShort(usize),
+    #[error("the struct is at offset {0}, expected 32")]
+    StructOffset(U256),
+    #[error("`fibValues` is at offset {0}, which matches no layout")]
+    UnknownLayout(U256),
+    #[error("invalid layout {layout} encoding: {source}")]
+    Invalid { layout: u8, source: alloy_sol_types::Error },
+}
+
+/// Public values decoded with the layout they were encoded with.
+#[derive(Debug, Clone)]
+pub struct DecodedPublicValues {
+    pub layout: u8,
+    /// The values, with the fields the layout does not have left at zero.
+    pub values: PublicValuesStruct,
+    /// What is wrong with the encoding or the values, if anything.
+    pub issues: Vec<String>,
+}
+
+impl DecodedPublicValues {
+    /// The fields of the layout, with their values for display.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let values = &self.values;
+        let all = [
+            values.n.to_string(),
+            values.a.to_string(),
+            values.b.to_string(),
+            format!("{:?}", values.fibValues),
+            values.chainId.to_string(),
+            values.consumer.to_string(),
+            values.nonce.to_string(),
+            values.validUntil.to_string(),
+            format!("{} ({})", values.status, values.status_name()),
+            values.rangeHash.to_string(),
+            format!("{:?}", values.coefficients),
+            format!("{:?}", values.initialValues),
+            values.modulus.to_string(),
+            values.result.to_string(),
+        ];
+        FIELDS.into_iter().zip(all).take(field_count(self.layout)).collect()
+    }
+}
+
+/// The number of fields of a layout.
+pub fn field_count(layout: u8) -> usize {
+    match layout {
+        1 => 3,
+        2 => 4,
+        8.. => 14,
+        layout => layout as usize + 3,
+    }
+}
+
+/// Decode public values of any layout, flagging non-canonical encodings and values the program
+/// would not have committed.
+pub fn decode_any(bytes: &[u8]) -> Result<DecodedPublicValues, LayoutError> {
+    let layout = detect_layout(bytes)?;
+    let mut issues = Vec::new();
+    let values = match layout {
+        1 => {
+            let v = decode::<PublicValuesV1>(bytes, layout, &mut issues)?;
+            base(v.n, v.a, v.b)
+        }
+        2 => {
+            let v = decode::<PublicValuesV2>(bytes, layout, &mut issues)?;
+            PublicValuesStruct { fibValues: v.fibValues, ..base(v.n, v.a, v.b) }
+        }
+        3 => {
+            let v = decode::<PublicValuesV3>(bytes, layout, &mut issues)?;
+            PublicValuesStruct {
+                fibValues: v.fibValues,
+                chainId: v.chainId,
+                consumer: v.consumer,
+                ..base(v.n, v.a, v.b)
+            }
+        }
+        4 => {
+            let v = decode::<PublicValuesV4>(bytes, layout, &mut issues)?;
+            PublicValuesStruct {
+                fibValues: v.fibValues,
+                chainId: v.chainId,
+                consumer: v.consumer,
+                nonce: v.nonce,
+                ..base(v.n, v.a, v.b)
+            }
+        }
+        5 => {
+            let v = decode::<PublicValuesV5>(bytes, layout, &mut issues)?;
+            PublicValuesStruct {
+                fibValues: v.fibValues,
+                chainId: v.chainId,
+                consumer: v.consumer,
+                nonce: v.nonce,
+                validUntil: v.validUntil,
+                ..base(v.n, v.a, v.b)
+            }
+        }
+        6 => {
+            let v = decode::<PublicValuesV6>(bytes, layout, &mut issues)?;
+            PublicValuesStruct {
+                fibValues: v.fibValues,
+                chainId: v.chainId,
+                consumer: v.consumer,
+                nonce: v.nonce,
+                validUntil: v.validUntil,
+                status: v.status,
+                ..base(v.n, v.a, v.b)
+            }
+        }
+        7 => {
+            let v = decode::<PublicValuesV7>(bytes, layout, &mut issues)?;
+            PublicValuesStruct {
+                fibValues: v.fibValues,
+                chainId: v.chainId,
+                consumer: v.consumer,
+                nonce: v.nonce,
+                validUntil: v.validUntil,
+                status: v.status,
+                rangeHash: v.rangeHash,
+                ..base(v.n, v.a, v.b)
+            }
+        }
+        _ => decode::<PublicValuesStruct>(bytes, layout, &mut issues)?,
+    };
+    check_values(layout, &values, &mut issues);
+    Ok(DecodedPublicValues { layout, values, issues })
+}
+
+/// Tell the layout from the offset of `fibValues`.
+fn detect_layout(bytes: &[u8]) -> Result<u8, LayoutError> {
+    // Layout 1 is a static struct of three words; the others take at least six.
+    if bytes.len() == 96 {
+        return Ok(1);
+    }
+    let word = |index: usize| {
+        bytes
+            .get(index * 32..(index + 1) * 32)
+            .map(U256::from_be_slice)
+            .ok_or(LayoutError::TooShort(bytes.len()))
+    };
+    let offset = word(0)?;
+    if offset != U256::from(32) {
+        return Err(LayoutError::StructOffset(offset));
+    }
+    // The struct's fourth field, `fibValues`, points past the head of the struct.
+    let array_offset = word(4)?;
+    match usize::try_from(array_offset).unwrap_or(0) {
+        128 => Ok(2),
+        192 => Ok(3),
+        224 => Ok(4),
+        256 => Ok(5),
+        288 => Ok(6),
+        320 => Ok(7),
+        448 => Ok(8),
+        _ => Err(LayoutError::UnknownLayout(array_offset)),
+    }
+}
+
+/// Decode `bytes` as `T`, leniently if need be, noting any deviation from the canonical
+/// encoding.
+fn decode<T>(bytes: &[u8], layout: u8, issues: &mut Vec<String>) -> Result<T, LayoutError>
+where
+    T: SolType<RustType = T>,
+{
+    let values = match T::abi_decode(bytes, true) {
+        Ok(values) => values,
+        Err(strict) => {
+            let values = T::abi_decode(bytes, false)
+                .map_err(|source| LayoutError::Invalid { layout, source })?;
+            issues.push(format!("non-canonical encoding: {}", strict));
+            values
+        }
+    };
+    let canonical = T::abi_encode(&values);
+    if canonical != bytes {
+        issues.push(format!(
+            "the {} bytes differ from the canonical {}-byte encoding (padding, offsets or \
+             trailing data)",
+            bytes.len(),
+            canonical.len()
+        ));
+    }
+    Ok(values)
+}
+
+/// The current struct with only the fields of the first layout set.
+fn base(n: u32, a: u32, b: u32) -> PublicValuesStruct {
+    PublicValuesStruct {
+        n,
+        a,
+        b,
+        fibValues: Vec::new(),
+        chainId: 0,
+        consumer: Address::ZERO,
+        nonce: 0,
+        validUntil: 0,
+        status: 0,
+        rangeHash: B256::ZERO,
+        coefficients: Vec::new(),
+        initialValues: Vec::new(),
+        modulus: 0,
+        result: 0,
+    }
+}
+
+/// Flag values that differ from what the current program computes for the same input.
+fn check_values(layout: u8, values: &PublicValuesStruct, issues: &mut Vec<String>) {
+    if layout >= 6 && values.status().is_none() {
+        issues.push(format!("unknown status code {}", values.status));
+        return;
+    }
+    // A rejection carries no values to check.
+    if layout >= 6 && values.is_rejected() {
+        return;
+    }
+    if values.fibValues.len() > values.n as usize + 1 {
+        issues.push(format!("{} fibValues cannot end at n = {}", values.fibValues.len(), values.n));
+        return;
+    }
+    let input = ProgramInput {
+        n: values.n,
+        range_start: values.range_start(),
+        // The start of a hashed range is not committed, so the hash cannot be checked.
+        range_hash: false,
+        chain_id: Some(values.chainId),
+        consumer: Some(values.consumer),
+        nonce: Some(values.nonce),
+        valid_until: Some(values.validUntil),
+        recurrence: values.is_recurrence().then(|| Recurrence {
+            coefficients: values.coefficients.clone(),
+            initial: values.initialValues.clone(),
+            modulus: values.modulus,
+        }),
+        seeds: values.sequence().and_then(Sequence::input_seeds),
+    };
+    let expected = compute_public_values(&input);
+    if expected.is_rejected() {
+        issues.push(format!("the program now rejects this input ({})", expected.status_name()));
+        return;
+    }
+    if layout >= 6 && expected.status != values.status {
+        issues.push(format!(
+            "status is {}, but the program commits {} for n = {}",
+            values.status_name(),
+            expected.status_name(),
+            values.n
+        ));
+    }
+    if (values.a, values.b) != (expected.a, expected.b) {
+        issues.push(format!(
+            "a and b are {} and {}, but the program computes {} and {} for n = {}",
+            values.a, values.b, expected.a, expected.b, values.n
+        ));
+    }
+    if values.fibValues != expected.fibValues {
+        issues.push(format!(
+            "fibValues are not the {} numbers up to n = {} (expected {:?})",
+            values.sequence().unwrap_or(Sequence::Fibonacci),
+            values.n,
+            expected.fibValues
+        ));
+    }
+    if values.result != expected.result {
+        issues.push(format!(
+            "result is {}, but the recurrence evaluates to {} at n = {}",
+            values.result, expected.result, values.n
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    /// The public values the current program commits for a range ending at n = 10, bound to a
+    /// consumer.
+    fn current() -> PublicValuesStruct {
+        let input = ProgramInput {
+            range_start: Some(7),
+            chain_id: Some(11155111),
+            consumer: Some(address!("00000000000000000000000000000000000000aa")),
+            nonce: Some(7),
+            valid_until: Some(2_000_000_000),
+            ..ProgramInput::new(10)
+        };
+        compute_public_values(&input)
+    }
+
+    fn encode<T: SolType<RustType = T>>(values: &T) -> Vec<u8> {
+        T::abi_encode(values)
+    }
+
+    /// `values` encoded in each layout, oldest first.
+    fn encodings(v: &PublicValuesStruct) -> Vec<(u8, Vec<u8>)> {
+        let (n, a, b, fib) = (v.n, v.a, v.b, v.fibValues.clone());
+        let (chain_id, consumer, nonce, valid_until) =
+            (v.chainId, v.consumer, v.nonce, v.validUntil);
+        vec![
+            (1, encode(&PublicValuesV1 { n, a, b })),
+            (2, encode(&PublicValuesV2 { n, a, b, fibValues: fib.clone() })),
+            (
+                3,
+                encode(&PublicValuesV3 {
+                    n,
+                    a,
+                    b,
+                    fibValues: fib.clone(),
+                    chainId: chain_id,
+                    consumer,
+                }),
+            ),
+            (
+                4,
+                encode(&PublicValuesV4 {
+                    n,
+                    a,
+                    b,
+                    fibValues: fib.clone(),
+                    chainId: chain_id,
+                    consumer,
+                    nonce,
+                }),
+            ),
+            (
+                5,
+                encode(&PublicValuesV5 {
+                    n,
+                    a,
+                    b,
+                    fibValues: fib.clone(),
+                    chainId: chain_id,
+                    consumer,
+                    nonce,
+                    validUntil: valid_until,
+                }),
+            ),
+            (
+                6,
+                encode(&PublicValuesV6 {
+                    n,
+                    a,
+                    b,
+                    fibValues: fib.clone(),
+                    chainId: chain_id,
+                    consumer,
+                    nonce,
+                    validUntil: valid_until,
+                    status: v.status,
+                }),
+            ),
+            (
+                7,
+                encode(&PublicValuesV7 {
+                    n,
+                    a,
+                    b,
+                    fibValues: fib,
+                    chainId: chain_id,
+                    consumer,
+                    nonce,
+                    validUntil: valid_until,
+                    status: v.status,
+                    rangeHash: v.rangeHash,
+                }),
+            ),
+            (CURRENT_LAYOUT, v.to_abi_bytes()),
+        ]
+    }
+
+    #[test]
+    fn detects_every_layout() {
+        let values = current();
+        let expected = DecodedPublicValues { layout: CURRENT_LAYOUT, values, issues: Vec::new() };
+        for (layout, bytes) in encodings(&expected.values) {
+            let decoded = decode_any(&bytes).unwrap();
+            assert_eq!(decoded.layout, layout);
+            assert!(decoded.issues.is_empty(), "layout {}: {:?}", layout, decoded.issues);
+            let fields = expected.fields();
+            assert_eq!(decoded.fields(), fields[..field_count(layout)], "layout {}", layout);
+        }
+    }
+
+    #[test]
+    fn every_layout_has_its_own_fields() {
+        let counts: Vec<_> = (1..=CURRENT_LAYOUT).map(field_count).collect();
+        assert_eq!(counts, [3, 4, 6, 7, 8, 9, 10, 14]);
+        assert_eq!(field_count(CURRENT_LAYOUT), FIELDS.len());
+    }
+
+    #[test]
+    fn flags_values_the_program_would_not_commit() {
+        for (layout, _) in encodings(&current()) {
+            let mut values = current();
+            values.b += 1;
+            let (_, bytes) = encodings(&values).remove(layout as usize - 1);
+            let decoded = decode_any(&bytes).unwrap();
+            assert!(
+                decoded.issues.iter().any(|issue| issue.starts_with("a and b are")),
+                "layout {}: {:?}",
+                layout,
+                decoded.issues
+            );
+        }
+    }
+
+    #[test]
+    fn flags_trailing_bytes() {
+        let mut bytes = current().to_abi_bytes();
+        bytes.extend_from_slice(&[0; 32]);
+        let decoded = decode_any(&bytes).unwrap();
+        assert_eq!(decoded.layout, CURRENT_LAYOUT);
+        assert!(decoded.issues.iter().any(|issue| issue.contains("canonical")));
+    }
+
+    #[test]
+    fn rejects_unknown_encodings() {
+        assert!(matches!(decode_any(&[0; 16]), Err(LayoutError::TooShort(16))));
+        let mut bytes = current().to_abi_bytes();
+        bytes[31] = 64;
+        assert!(matches!(decode_any(&bytes), Err(LayoutError::StructOffset(_))));
+        let mut bytes = current().to_abi_bytes();
+        bytes[32 * 5 - 2] = 0x7f;
+        assert!(matches!(decode_any(&bytes), Err(LayoutError::UnknownLayout(_))));
+    }
+}
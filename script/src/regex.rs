@@ -0,0 +1,61 @@
+//! Compiling a regular expression into the [`Dfa`] the regex program runs.
+//!
+//! The pattern is compiled with `regex-automata` (the engine behind `regex`), so it matches as
+//! `regex::bytes::Regex::is_match` would: anywhere in the text, unless anchored with `^` or `$`.
+//! Only the states reachable from the start are kept, numbered in the order they are first
+//! reached, so compiling the same pattern with the same version always gives the same DFA and
+//! hash. Anyone checking a proof compiles the pattern again and compares the `dfaHash`.
+
+use fibonacci_lib::dfa::Dfa;
+use regex_automata::dfa::{dense, Automaton};
+use regex_automata::util::primitives::StateID;
+use regex_automata::Input;
+use std::collections::{HashMap, VecDeque};
+use thiserror::Error;
+
+/// The largest number of states a compiled pattern may have. Each state is a row of 256
+/// transitions the program reads and hashes, so this bounds its cycles.
+pub const MAX_DFA_STATES: usize = 1024;
+
+/// Errors from compiling a pattern.
+#[derive(Debug, Error)]
+pub enum RegexError {
+    #[error("invalid pattern: {0}")]
+    Build(#[from] dense::BuildError),
+    #[error("the pattern cannot be matched by a DFA: {0}")]
+    Unsupported(String),
+    #[error("the pattern compiles to more than {MAX_DFA_STATES} states")]
+    TooLarge,
+}
+
+/// Compile `pattern` into the DFA the regex program runs.
+pub fn compile_dfa(pattern: &str) -> Result<Dfa, RegexError> {
+    let automaton = dense::DFA::new(pattern)?;
+    let start = automaton
+        .start_state_forward(&Input::new(""))
+        .map_err(|err| RegexError::Unsupported(err.to_string()))?;
+
+    let mut ids: HashMap<StateID, u32> = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    let mut dfa = Dfa::default();
+    while let Some(state) = queue.pop_front() {
+        if automaton.is_quit_state(state) {
+            return Err(RegexError::Unsupported("it gives up on some bytes".into()));
+        }
+        for byte in 0..=u8::MAX {
+            let next = automaton.next_state(state, byte);
+            let count = ids.len();
+            let id = *ids.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                count as u32
+            });
+            dfa.transitions.push(id);
+        }
+        dfa.accepting.push(automaton.is_match_state(state));
+        dfa.accepting_at_end.push(automaton.is_match_state(automaton.next_eoi_state(state)));
+        if ids.len() > MAX_DFA_STATES {
+            return Err(RegexError::TooLarge);
+        }
+    }
+    Ok(dfa)
+}
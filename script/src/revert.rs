@@ -0,0 +1,113 @@
+//! Decoding of revert data from failed verification calls.
+
+use crate::chain::FibonacciSimple;
+use alloy_primitives::Bytes;
+use alloy_sol_types::{sol, SolInterface};
+
+sol! {
+    /// Errors raised by the SP1 verifier gateway and the versioned Groth16/PLONK verifiers.
+    interface ISP1VerifierErrors {
+        error RouteNotFound(bytes4 selector);
+        error RouteIsFrozen(bytes4 selector);
+        error WrongVerifierSelector(bytes4 receivedSelector, bytes4 expectedSelector);
+        error InvalidProof();
+        error ProofInvalid();
+        error PublicInputNotInField();
+    }
+}
+
+/// A decoded revert, with a hint at what the caller got wrong.
+#[derive(Debug, Clone)]
+pub struct DecodedRevert {
+    /// The error name and arguments, e.g. `WrongChain(chainId: 1)`.
+    pub error: String,
+    /// What most likely caused the error.
+    pub hint: &'static str,
+}
+
+/// Extract the raw revert data from a failed contract call, if the RPC returned any.
+pub fn revert_data(err: &alloy_contract::Error) -> Option<Bytes> {
+    match err {
+        alloy_contract::Error::TransportError(err) => {
+            err.as_error_resp().and_then(|payload| payload.as_revert_data())
+        }
+        _ => None,
+    }
+}
+
+/// Decode revert data raised by the SP1 verifier gateway or verifiers.
+pub fn decode_sp1_revert(data: &[u8]) -> Option<DecodedRevert> {
+    use ISP1VerifierErrors::ISP1VerifierErrorsErrors as E;
+
+    let decoded = match E::abi_decode(data, true).ok()? {
+        E::RouteNotFound(e) => DecodedRevert {
+            error: format!("RouteNotFound(selector: {})", e.selector),
+            hint: "the gateway has no verifier for this proof's version; regenerate the proof \
+                   with the SP1 version the gateway supports",
+        },
+        E::RouteIsFrozen(e) => DecodedRevert {
+            error: format!("RouteIsFrozen(selector: {})", e.selector),
+            hint: "the verifier for this proof's version has been frozen; regenerate the proof \
+                   with a newer SP1 version",
+        },
+        E::WrongVerifierSelector(e) => DecodedRevert {
+            error: format!(
+                "WrongVerifierSelector(receivedSelector: {}, expectedSelector: {})",
+                e.receivedSelector, e.expectedSelector
+            ),
+            hint: "the proof was routed to a verifier for a different SP1 version or proof system",
+        },
+        E::InvalidProof(_) | E::ProofInvalid(_) => DecodedRevert {
+            error: "InvalidProof()".to_string(),
+            hint: "the proof does not verify against the contract's program vkey and these \
+                   public values; check FIBONACCI_PROGRAM_VKEY matches `cargo run --bin vkey`",
+        },
+        E::PublicInputNotInField(_) => DecodedRevert {
+            error: "PublicInputNotInField()".to_string(),
+            hint: "the vkey or public values digest is malformed",
+        },
+    };
+    Some(decoded)
+}
+
+/// Decode revert data raised by the `FibonacciSimple` contract itself.
+pub fn decode_consumer_revert(data: &[u8]) -> Option<DecodedRevert> {
+    use FibonacciSimple::FibonacciSimpleErrors as E;
+
+    let decoded = match E::abi_decode(data, true).ok()? {
+        E::WrongChain(e) => DecodedRevert {
+            error: format!("WrongChain(chainId: {})", e.chainId),
+            hint: "the public values are bound to a different chain",
+        },
+        E::WrongConsumer(e) => DecodedRevert {
+            error: format!("WrongConsumer(consumer: {})", e.consumer),
+            hint: "the public values are bound to a different contract",
+        },
+        E::ProofExpired(e) => DecodedRevert {
+            error: format!("ProofExpired(validUntil: {})", e.validUntil),
+            hint: "the proof's validUntil timestamp has passed; generate a new proof",
+        },
+        E::NotFibonacci(_) => DecodedRevert {
+            error: "NotFibonacci()".to_string(),
+            hint: "the public values are of another sequence or a recurrence, not the Fibonacci \
+                   sequence",
+        },
+    };
+    Some(decoded)
+}
+
+/// Decode revert data from a verification call: the consumer contract's errors first, then the
+/// SP1 verifier's, then a standard revert reason.
+pub fn decode_revert(data: &[u8]) -> Option<DecodedRevert> {
+    decode_consumer_revert(data)
+        .or_else(|| decode_sp1_revert(data))
+        .or_else(|| decode_standard_revert(data))
+}
+
+/// Decode a standard `Error(string)` or `Panic(uint256)` revert.
+pub fn decode_standard_revert(data: &[u8]) -> Option<DecodedRevert> {
+    alloy_sol_types::decode_revert_reason(data).map(|reason| DecodedRevert {
+        error: reason,
+        hint: "the contract reverted with a plain reason string or panic",
+    })
+}
@@ -0,0 +1,405 @@
+//! A JSON-RPC 2.0 interface to the prover, served by the `service` binary, so clients built on
+//! Ethereum tooling can request and check proofs with the conventions they already use.
+//!
+//! Proving requests are queued in the job store and proved by `worker`, like `cli jobs submit`:
+//! - `sp1_prove(request)` queues a proof of `request.input` (a [`ProgramInput`]) with
+//!   `request.system` (`groth16` by default) and `request.priority`, and returns the job id.
+//!   Identical requests are coalesced onto the same job.
+//! - `sp1_getProof(jobId)` returns the job's status, and its public values and proof once it is
+//!   completed.
+//! - `sp1_verifyOnchain(jobId, contract?)` simulates `verifyFibonacciProof` with the job's proof
+//!   on the consumer contract (the service's by default) and returns whether it verifies, with
+//!   the decoded revert if it does not. Nothing is sent: the service holds no keys.
+//! - `sp1_openApi()` returns the OpenAPI document of these methods (see [`crate::openapi`]).
+//!
+//! Every method but `sp1_openApi` needs an API key (see [`crate::auth`]) unless the service
+//! allows anonymous requests. `sp1_prove` and `sp1_verifyOnchain` calls are recorded in the audit
+//! log under the key's name.
+//!
+//! A key only submits and sees the jobs of its tenant; anonymous requests are of the
+//! [`DEFAULT_TENANT`]. The jobs of other tenants are reported as not found.
+
+use crate::artifact_store::ArtifactStore;
+use crate::artifacts::CallData;
+use crate::audit::AuditLog;
+use crate::auth::{key_hash, PresentedKey, RateLimiter};
+use crate::chain::FibonacciSimple;
+use crate::domain::InputDomain;
+use crate::jobs::{
+    ApiKey, Job, JobError, JobOptions, JobStatus, JobStore, Priority, DEFAULT_TENANT,
+};
+use crate::openapi::openapi;
+use crate::prover::ProofSystem;
+use crate::revert::{decode_revert, revert_data};
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_provider::ProviderBuilder;
+use clap::ValueEnum;
+use fibonacci_lib::ProgramInput;
+use jsonrpsee::types::{ErrorObjectOwned, Params};
+use jsonrpsee::{Extensions, RpcModule};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
+
+/// The address the service listens on when `SERVICE_ADDR` is not set.
+pub const DEFAULT_SERVICE_ADDR: &str = "127.0.0.1:8645";
+
+/// The error code of invalid method parameters, as defined by JSON-RPC 2.0.
+pub const INVALID_PARAMS: i32 = -32602;
+/// The error code of an internal error, as defined by JSON-RPC 2.0.
+pub const INTERNAL_ERROR: i32 = -32603;
+/// The error code of an unknown job id.
+pub const JOB_NOT_FOUND: i32 = -32001;
+/// The error code of a job that has no proof yet.
+pub const PROOF_NOT_READY: i32 = -32002;
+/// The error code of a chain that could not be reached.
+pub const CHAIN_ERROR: i32 = -32003;
+/// The error code of a missing, unknown or revoked API key.
+pub const UNAUTHORIZED: i32 = -32004;
+/// The error code of a key over its per-minute request limit.
+pub const RATE_LIMITED: i32 = -32005;
+/// The error code of a key with as many unfinished jobs as it may have.
+pub const TOO_MANY_JOBS: i32 = -32006;
+
+/// What the methods share.
+pub struct RpcContext {
+    pub store: JobStore,
+    /// Where workers cache the proofs of completed jobs.
+    pub artifacts: Box<dyn ArtifactStore>,
+    /// The endpoint `sp1_verifyOnchain` calls the contract through.
+    pub rpc_endpoint: reqwest::Url,
+    /// The consumer contract `sp1_verifyOnchain` verifies against by default.
+    pub contract: Address,
+    /// Whether requests without an API key are served.
+    pub allow_anonymous: bool,
+    pub rate_limiter: RateLimiter,
+    /// The inputs `sp1_prove` accepts.
+    pub domain: InputDomain,
+    /// The log requests are attributed in, behind a lock so concurrent entries chain in order.
+    pub audit: Mutex<AuditLog>,
+}
+
+/// The parameter of `sp1_prove`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ProveRequest {
+    pub input: ProgramInput,
+    /// `groth16` by default.
+    #[serde(default = "default_system")]
+    #[schema(default = "groth16")]
+    pub system: ProofSystem,
+    /// `normal` by default.
+    #[serde(default)]
+    #[schema(value_type = Option<Priority>)]
+    pub priority: Option<String>,
+}
+
+/// The result of `sp1_prove`.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProveResponse {
+    pub job_id: String,
+    #[schema(value_type = JobStatus)]
+    pub status: String,
+    /// Whether the request was coalesced onto an existing job for the same input.
+    pub coalesced: bool,
+}
+
+/// The result of `sp1_getProof`: a job, with its proof once completed.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofResponse {
+    pub job_id: String,
+    #[schema(value_type = JobStatus)]
+    pub status: String,
+    pub system: ProofSystem,
+    #[schema(value_type = String)]
+    pub input_hash: B256,
+    /// Why the job failed, if it did.
+    pub error: Option<String>,
+    /// The public values, for `verifyFibonacciProof`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub public_values: Option<Bytes>,
+    /// The proof, for `verifyFibonacciProof`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub proof: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub proof_hash: Option<B256>,
+}
+
+/// The result of `sp1_verifyOnchain`.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResponse {
+    pub verified: bool,
+    #[schema(value_type = String)]
+    pub contract: Address,
+    /// The raw revert data, if the proof was rejected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub revert_data: Option<Bytes>,
+    /// The decoded revert, e.g. `WrongChain(chainId: 1)`, if it is a known error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// What most likely caused the revert.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+fn default_system() -> ProofSystem {
+    ProofSystem::Groth16
+}
+
+/// The JSON-RPC methods of the service.
+pub fn rpc_module(context: RpcContext) -> RpcModule<RpcContext> {
+    let mut module = RpcModule::new(context);
+    module
+        .register_async_method("sp1_prove", |params, context, extensions| async move {
+            prove(params, &context, &extensions).await
+        })
+        .expect("method names are unique");
+    module
+        .register_async_method("sp1_getProof", |params, context, extensions| async move {
+            get_proof(params, &context, &extensions).await
+        })
+        .expect("method names are unique");
+    module
+        .register_async_method("sp1_verifyOnchain", |params, context, extensions| async move {
+            verify_onchain(params, &context, &extensions).await
+        })
+        .expect("method names are unique");
+    module
+        .register_method("sp1_openApi", |_, _, _| {
+            serde_json::to_value(openapi()).map_err(|err| error(INTERNAL_ERROR, err.to_string()))
+        })
+        .expect("method names are unique");
+    module
+}
+
+/// `sp1_prove`: queue a proving job.
+async fn prove(
+    params: Params<'static>,
+    context: &RpcContext,
+    extensions: &Extensions,
+) -> Result<ProveResponse, ErrorObjectOwned> {
+    let key = authenticate(context, extensions).await?;
+    let request: ProveRequest = params.one()?;
+    let response = submit(&request, key.as_ref(), context).await;
+    let details = json!({
+        "method": "sp1_prove",
+        "tenant": tenant(key.as_ref()),
+        "input": request.input,
+        "system": request.system,
+        "jobId": response.as_ref().ok().map(|response| &response.job_id),
+        "coalesced": response.as_ref().ok().map(|response| response.coalesced),
+    });
+    audit(context, key.as_ref(), "request", details, &response)?;
+    response
+}
+
+/// Queue the job of a `sp1_prove` request, within the key's limit of unfinished jobs.
+async fn submit(
+    request: &ProveRequest,
+    key: Option<&ApiKey>,
+    context: &RpcContext,
+) -> Result<ProveResponse, ErrorObjectOwned> {
+    context.domain.check(&request.input).map_err(|err| error(INVALID_PARAMS, err.to_string()))?;
+    let priority = match &request.priority {
+        Some(priority) => <Priority as ValueEnum>::from_str(priority, true)
+            .map_err(|err| error(INVALID_PARAMS, err))?,
+        None => Priority::default(),
+    };
+    let options = JobOptions {
+        priority,
+        api_key: key.map(|key| key.name.clone()),
+        max_concurrent: key.and_then(|key| key.max_concurrent),
+        tenant: Some(tenant(key).to_string()),
+        ..Default::default()
+    };
+    let (job, coalesced) = context
+        .store
+        .submit(&new_job_id(), &request.input, request.system, options)
+        .await
+        .map_err(job_error)?;
+    Ok(ProveResponse { job_id: job.id, status: job.status.to_string(), coalesced })
+}
+
+/// `sp1_getProof`: the status of a job, and its proof once completed.
+async fn get_proof(
+    params: Params<'static>,
+    context: &RpcContext,
+    extensions: &Extensions,
+) -> Result<ProofResponse, ErrorObjectOwned> {
+    let key = authenticate(context, extensions).await?;
+    let id: String = params.one()?;
+    let job = tenant_job(context, key.as_ref(), &id).await?;
+    let call_data = call_data(context, &job)?;
+    Ok(ProofResponse {
+        status: job.status.to_string(),
+        system: job.system,
+        input_hash: job.input_hash,
+        public_values: call_data.as_ref().map(|call_data| call_data.public_values.clone()),
+        proof: call_data.map(|call_data| call_data.proof_bytes),
+        proof_hash: job.proof_hash,
+        error: job.error,
+        job_id: job.id,
+    })
+}
+
+/// `sp1_verifyOnchain`: simulate the verification of a job's proof on a consumer contract.
+async fn verify_onchain(
+    params: Params<'static>,
+    context: &RpcContext,
+    extensions: &Extensions,
+) -> Result<VerifyResponse, ErrorObjectOwned> {
+    let key = authenticate(context, extensions).await?;
+    let mut params = params.sequence();
+    let id: String = params.next()?;
+    let contract: Option<Address> = params.optional_next()?;
+    let contract = contract.unwrap_or(context.contract);
+    let response = simulate_verification(&id, key.as_ref(), contract, context).await;
+    let details = json!({
+        "method": "sp1_verifyOnchain",
+        "tenant": tenant(key.as_ref()),
+        "jobId": id,
+        "contract": contract,
+        "verified": response.as_ref().ok().map(|response| response.verified),
+    });
+    audit(context, key.as_ref(), "verify", details, &response)?;
+    response
+}
+
+/// Call `verifyFibonacciProof` with the proof of job `id` on `contract`, without sending.
+async fn simulate_verification(
+    id: &str,
+    key: Option<&ApiKey>,
+    contract: Address,
+    context: &RpcContext,
+) -> Result<VerifyResponse, ErrorObjectOwned> {
+    let job = tenant_job(context, key, id).await?;
+    let call_data = call_data(context, &job)?.ok_or_else(|| {
+        error(PROOF_NOT_READY, format!("job {} is {}, not completed", job.id, job.status))
+    })?;
+    let provider = ProviderBuilder::new().on_http(context.rpc_endpoint.clone());
+    let call = FibonacciSimple::new(contract, &provider)
+        .verifyFibonacciProof(call_data.public_values, call_data.proof_bytes);
+    let mut response = VerifyResponse {
+        verified: true,
+        contract,
+        revert_data: None,
+        error: None,
+        hint: None,
+    };
+    if let Err(err) = call.call().await {
+        let data = revert_data(&err).ok_or_else(|| error(CHAIN_ERROR, err.to_string()))?;
+        let revert = decode_revert(&data);
+        response.verified = false;
+        response.error = revert.as_ref().map(|revert| revert.error.clone());
+        response.hint = revert.map(|revert| revert.hint.to_string());
+        response.revert_data = Some(data);
+    }
+    Ok(response)
+}
+
+/// The key a request was made with, checked against the store and its rate limit, or `None` for
+/// an anonymous request if those are allowed.
+async fn authenticate(
+    context: &RpcContext,
+    extensions: &Extensions,
+) -> Result<Option<ApiKey>, ErrorObjectOwned> {
+    let Some(PresentedKey(presented)) = extensions.get::<PresentedKey>() else {
+        if context.allow_anonymous {
+            return Ok(None);
+        }
+        return Err(error(UNAUTHORIZED, "an API key is required"));
+    };
+    let key = context
+        .store
+        .find_api_key(key_hash(presented))
+        .await
+        .map_err(job_error)?
+        .ok_or_else(|| error(UNAUTHORIZED, "unknown or revoked API key"))?;
+    if !context.rate_limiter.check(&key) {
+        let limit = key.rate_limit.unwrap_or_default();
+        let message = format!("API key {} is over its {} requests per minute", key.name, limit);
+        return Err(error(RATE_LIMITED, message));
+    }
+    Ok(Some(key))
+}
+
+/// The tenant of requests made with `key`.
+fn tenant(key: Option<&ApiKey>) -> &str {
+    key.map_or(DEFAULT_TENANT, |key| key.tenant.as_str())
+}
+
+/// Look up a job of the tenant of `key`. Jobs of other tenants are not found, so their ids do not
+/// leak.
+async fn tenant_job(
+    context: &RpcContext,
+    key: Option<&ApiKey>,
+    id: &str,
+) -> Result<Job, ErrorObjectOwned> {
+    match context.store.get(id).await {
+        Ok(job) if job.tenant == tenant(key) => Ok(job),
+        Ok(_) => Err(job_error(JobError::NotFound(id.to_string()))),
+        Err(err) => Err(job_error(err)),
+    }
+}
+
+/// Record a request in the audit log, attributed to the key it was made with.
+fn audit<T>(
+    context: &RpcContext,
+    key: Option<&ApiKey>,
+    operation: &str,
+    details: Value,
+    result: &Result<T, ErrorObjectOwned>,
+) -> Result<(), ErrorObjectOwned> {
+    let actor = key.map_or("anonymous".to_string(), |key| format!("api-key:{}", key.name));
+    let outcome = match result {
+        Ok(_) => "ok".to_string(),
+        Err(err) => format!("failed: {}", err.message()),
+    };
+    let log = context.audit.lock().expect("the audit log is never poisoned");
+    log.append_as(&actor, operation, details, outcome)
+        .map(|_| ())
+        .map_err(|err| error(INTERNAL_ERROR, format!("failed to write the audit log: {}", err)))
+}
+
+/// The call data of a completed job, or `None` if it has no proof yet.
+fn call_data(context: &RpcContext, job: &Job) -> Result<Option<CallData>, ErrorObjectOwned> {
+    match (&job.artifact, job.status) {
+        (Some(artifact), JobStatus::Completed) => {
+            let key = context.artifacts.key_of(artifact).ok_or_else(|| {
+                error(INTERNAL_ERROR, format!("{} is not in the artifact store", artifact))
+            })?;
+            CallData::load_stored(context.artifacts.as_ref(), &key)
+                .map(Some)
+                .map_err(|err| error(INTERNAL_ERROR, err.to_string()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// A unique id for a job submitted over RPC.
+fn new_job_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    format!("job-{}-{}", millis, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+fn job_error(err: JobError) -> ErrorObjectOwned {
+    match err {
+        JobError::NotFound(_) => error(JOB_NOT_FOUND, err.to_string()),
+        JobError::TooManyJobs { .. } => error(TOO_MANY_JOBS, err.to_string()),
+        err => error(INTERNAL_ERROR, err.to_string()),
+    }
+}
+
+fn error(code: i32, message: impl Into<String>) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(code, message.into(), None::<()>)
+}
@@ -0,0 +1,162 @@
+//! Structured reports of proving runs, from which a run can be replayed.
+//!
+//! `network_evm` writes `run_report.json` next to its artifacts whether the run succeeds or not:
+//! its parsed arguments, the program input, the configuration it read from the environment, the
+//! build it ran on and what came out. `--replay` rebuilds the run from the report, so a proving
+//! issue can be reproduced from the report alone, and compares the new outcome with the old one.
+
+use crate::artifacts::{read_json, write_json, ArtifactError};
+use crate::prover::{ProofSystem, FIBONACCI_ELF};
+use crate::provenance::Provenance;
+use alloy_primitives::{Bytes, B256};
+use fibonacci_lib::{ProgramInput, VkHash};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The file name of the report in the output directory.
+pub const RUN_REPORT_FILE: &str = "run_report.json";
+
+/// The file name of the report of a replay, which leaves the original report in place.
+pub const REPLAY_REPORT_FILE: &str = "replay_report.json";
+
+/// The environment variables that configure a run and are restored on replay. Secrets such as
+/// `NETWORK_PRIVATE_KEY` are deliberately left out, so the replaying user's own are used.
+pub const REPLAYED_VARS: &[&str] = &[
+    "SP1_PROVER",
+    "NETWORK_RPC_URL",
+    "SP1_STALE_ELF",
+    "SP1_MOCK_LATENCY",
+    "SP1_MOCK_FAILURE_RATE",
+    "SP1_MOCK_SEED",
+];
+
+/// What a run did and how it was set up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReport {
+    /// The binary that ran, e.g. `network_evm`.
+    pub command: String,
+    /// Its parsed command line arguments, including defaults and those read from the environment.
+    pub args: Value,
+    pub input: ProgramInput,
+    pub system: ProofSystem,
+    /// The [`REPLAYED_VARS`] that were set.
+    pub env: BTreeMap<String, String>,
+    pub provenance: Provenance,
+    /// Unix timestamp of the start of the run.
+    pub started_at: u64,
+    pub duration_secs: f64,
+    pub outcome: RunOutcome,
+}
+
+/// What came out of a run, as far as it got.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunOutcome {
+    pub vkey: Option<VkHash>,
+    pub cycles: Option<u64>,
+    pub public_values: Option<Bytes>,
+    /// The keccak256 hash of the proof bytes.
+    pub proof_hash: Option<B256>,
+    /// The error the run failed with.
+    pub error: Option<String>,
+}
+
+impl RunReport {
+    /// Start the report of a `command` run with `args`, recording the environment now.
+    pub fn start<A: Serialize>(
+        command: &str,
+        args: &A,
+        input: ProgramInput,
+        system: ProofSystem,
+    ) -> Self {
+        let env = REPLAYED_VARS
+            .iter()
+            .filter_map(|name| Some((name.to_string(), env::var(name).ok()?)))
+            .collect();
+        Self {
+            command: command.to_string(),
+            args: serde_json::to_value(args).expect("arguments serialize to JSON"),
+            input,
+            system,
+            env,
+            provenance: Provenance::current(FIBONACCI_ELF),
+            started_at: now_secs(),
+            duration_secs: 0.0,
+            outcome: RunOutcome::default(),
+        }
+    }
+
+    /// Record how long the run took and the error it failed with, if any.
+    pub fn finish(&mut self, elapsed: Duration, error: Option<String>) {
+        self.duration_secs = elapsed.as_secs_f64();
+        self.outcome.error = error;
+    }
+
+    /// Save the report in `dir`, as [`REPLAY_REPORT_FILE`] for a replay and [`RUN_REPORT_FILE`]
+    /// otherwise.
+    pub fn save(&self, dir: &Path, replay: bool) -> Result<PathBuf, ArtifactError> {
+        let path = dir.join(if replay { REPLAY_REPORT_FILE } else { RUN_REPORT_FILE });
+        write_json(&path, self)?;
+        Ok(path)
+    }
+
+    /// Load a saved report.
+    pub fn load(path: &Path) -> Result<Self, ArtifactError> {
+        read_json(path)
+    }
+
+    /// Restore the recorded environment: set the recorded variables and unset the others, so the
+    /// replay does not pick up a different local configuration. Returns the variables changed,
+    /// with their recorded values (`None` if unset).
+    pub fn restore_env(&self) -> Vec<(&'static str, Option<&str>)> {
+        let mut changed = Vec::new();
+        for &name in REPLAYED_VARS {
+            let recorded = self.env.get(name).map(String::as_str);
+            if env::var(name).ok().as_deref() == recorded {
+                continue;
+            }
+            match recorded {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+            changed.push((name, recorded));
+        }
+        changed
+    }
+
+    /// What differs between this run's outcome and `replayed`, the outcome of its replay. Cycles
+    /// are only compared when both runs got that far; proof bytes are not compared at all, since
+    /// proving is randomized.
+    pub fn differences(&self, replayed: &RunOutcome) -> Vec<String> {
+        let original = &self.outcome;
+        let mut differences = Vec::new();
+        let mut compare = |field: &str, original: String, replayed: String| {
+            if original != replayed {
+                differences.push(format!("{}: {} -> {}", field, original, replayed));
+            }
+        };
+        let show = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+        compare(
+            "vkey",
+            show(original.vkey.map(|vkey| vkey.to_string())),
+            show(replayed.vkey.map(|vkey| vkey.to_string())),
+        );
+        if let (Some(original), Some(replayed)) = (original.cycles, replayed.cycles) {
+            compare("cycles", original.to_string(), replayed.to_string());
+        }
+        compare(
+            "public values",
+            show(original.public_values.as_ref().map(|values| values.to_string())),
+            show(replayed.public_values.as_ref().map(|values| values.to_string())),
+        );
+        compare("error", show(original.error.clone()), show(replayed.error.clone()));
+        differences
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
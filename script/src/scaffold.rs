@@ -0,0 +1,271 @@
+//! Scaffolding a new guest program, for `cli init-program <name>`.
+//!
+//! A program named `my-thing` gets a guest crate in `my-thing-program`, its input and public
+//! values in `fibonacci_lib::my_thing`, a host driver in `src/bin/my_thing.rs`, and the wiring
+//! every program needs: a workspace member, a build in `build.rs`, and an entry in
+//! [`crate::programs::PROGRAMS`]. The generated program commits the hash of its input; fill in
+//! the TODOs to make it prove something.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const GUEST_CARGO_TOML: &str = include_str!("../templates/program/guest_cargo.toml.tmpl");
+const GUEST_MAIN: &str = include_str!("../templates/program/guest_main.rs.tmpl");
+const LIB_MODULE: &str = include_str!("../templates/program/lib.rs.tmpl");
+const HOST_BIN: &str = include_str!("../templates/program/host.rs.tmpl");
+
+/// Errors from scaffolding a program.
+#[derive(Debug, Error)]
+pub enum ScaffoldError {
+    #[error("invalid program name `{0}`: use lowercase letters, digits and single `-`s")]
+    InvalidName(String),
+    #[error("{} already exists", .0.display())]
+    Exists(PathBuf),
+    #[error("failed to access {}: {source}", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("could not find where to add {what} in {}; add it by hand", path.display())]
+    NoAnchor { path: PathBuf, what: &'static str },
+}
+
+/// The names a program is known by in the different places it is wired into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramNames {
+    /// The name it is selected by, e.g. `my-thing`.
+    pub name: String,
+    /// Its Rust module and binary name, e.g. `my_thing`.
+    pub snake: String,
+    /// The prefix of its types, e.g. `MyThing`.
+    pub camel: String,
+    /// Its crate directory, e.g. `my-thing-program`.
+    pub dir: String,
+    /// Its package name, e.g. `fibonacci-my-thing-program`.
+    pub package: String,
+}
+
+impl ProgramNames {
+    /// The names of the program `name`.
+    pub fn new(name: &str) -> Result<Self, ScaffoldError> {
+        let valid = name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !name.ends_with('-')
+            && !name.contains("--")
+            && !name.ends_with("-program");
+        if !valid {
+            return Err(ScaffoldError::InvalidName(name.to_string()));
+        }
+        let camel = name
+            .split('-')
+            .map(|word| {
+                let (first, rest) = word.split_at(1);
+                format!("{}{}", first.to_ascii_uppercase(), rest)
+            })
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            snake: name.replace('-', "_"),
+            camel,
+            dir: format!("{}-program", name),
+            package: format!("fibonacci-{}-program", name),
+        })
+    }
+
+    /// Fill the placeholders of a template.
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("__PACKAGE__", &self.package)
+            .replace("__NAME__", &self.name)
+            .replace("__SNAKE__", &self.snake)
+            .replace("__CAMEL__", &self.camel)
+            .replace("__TITLE__", &self.name.replace('-', " "))
+            .replace("__ELF__", &format!("{}_ELF", self.snake.to_ascii_uppercase()))
+    }
+}
+
+/// The files a scaffold writes, and whether each is new.
+#[derive(Debug, Default)]
+pub struct Scaffold {
+    files: Vec<(PathBuf, String, bool)>,
+}
+
+impl Scaffold {
+    /// The files, relative to the repository root, and whether each is new.
+    pub fn files(&self) -> impl Iterator<Item = (&Path, bool)> {
+        self.files.iter().map(|(path, _, created)| (path.as_path(), *created))
+    }
+
+    fn create(
+        &mut self,
+        root: &Path,
+        path: PathBuf,
+        contents: String,
+    ) -> Result<(), ScaffoldError> {
+        if root.join(&path).exists() {
+            return Err(ScaffoldError::Exists(path));
+        }
+        self.files.push((path, contents, true));
+        Ok(())
+    }
+
+    fn edit(
+        &mut self,
+        root: &Path,
+        path: &str,
+        edit: impl FnOnce(&str) -> Option<String>,
+        what: &'static str,
+    ) -> Result<(), ScaffoldError> {
+        let path = PathBuf::from(path);
+        let contents = read(&root.join(&path))?;
+        let edited =
+            edit(&contents).ok_or_else(|| ScaffoldError::NoAnchor { path: path.clone(), what })?;
+        self.files.push((path, edited, false));
+        Ok(())
+    }
+
+    /// Write the files under `root`.
+    pub fn write(&self, root: &Path) -> Result<(), ScaffoldError> {
+        for (path, contents, _) in &self.files {
+            let path = root.join(path);
+            let io_err = |source| ScaffoldError::Io { path: path.clone(), source };
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(io_err)?;
+            }
+            fs::write(&path, contents).map_err(io_err)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plan the scaffold of the program `names` in the repository at `root`, without writing
+/// anything. Fails if any file it would create exists, or if a file it edits has changed so much
+/// that it cannot tell where to add the program.
+pub fn plan(root: &Path, names: &ProgramNames) -> Result<Scaffold, ScaffoldError> {
+    let mut scaffold = Scaffold::default();
+    let guest = Path::new(&names.dir);
+    scaffold.create(root, guest.join("Cargo.toml"), names.render(GUEST_CARGO_TOML))?;
+    scaffold.create(root, guest.join("src/main.rs"), names.render(GUEST_MAIN))?;
+    let module = PathBuf::from(format!("lib/src/{}.rs", names.snake));
+    scaffold.create(root, module, names.render(LIB_MODULE))?;
+    let host = PathBuf::from(format!("script/src/bin/{}.rs", names.snake));
+    scaffold.create(root, host, names.render(HOST_BIN))?;
+
+    let module = format!("pub mod {};", names.snake);
+    scaffold.edit(
+        root,
+        "lib/src/lib.rs",
+        |lib| insert_sorted(lib, "pub mod ", &module),
+        "the module",
+    )?;
+    let member = format!("    \"{}\",", names.dir);
+    scaffold.edit(
+        root,
+        "Cargo.toml",
+        |manifest| {
+            let members = manifest.find("members = [\n")? + "members = [\n".len();
+            let end = members + manifest[members..].find(']')?;
+            let block = insert_sorted(&manifest[members..end], "    \"", &member)?;
+            Some(format!("{}{}{}", &manifest[..members], block, &manifest[end..]))
+        },
+        "the workspace member",
+    )?;
+    let build = format!(
+        "    build_program_with_args(\"../{}\", BuildArgs::default());\n",
+        names.dir
+    );
+    scaffold.edit(
+        root,
+        "script/build.rs",
+        |script| {
+            let anchors = [
+                "    if std::env::var_os(\"CARGO_FEATURE_HASH_BENCH\")",
+                "    embed_source_fingerprint();",
+            ];
+            insert_before_any(script, &anchors, &build)
+        },
+        "the program build",
+    )?;
+    let bin = format!("[[bin]]\nname = \"{0}\"\npath = \"src/bin/{0}.rs\"\n\n", names.snake);
+    scaffold.edit(
+        root,
+        "script/Cargo.toml",
+        |manifest| insert_before_any(manifest, &["[features]"], &bin),
+        "the host binary",
+    )?;
+    scaffold.edit(
+        root,
+        "script/src/programs.rs",
+        |registry| register(registry, names),
+        "the program registry entry",
+    )?;
+    Ok(scaffold)
+}
+
+/// Add the ELF, registry entry and input parser of the program to `programs.rs`.
+fn register(registry: &str, names: &ProgramNames) -> Option<String> {
+    let import = format!("use fibonacci_lib::{}::{}Input;", names.snake, names.camel);
+    let registry = insert_sorted(registry, "use fibonacci_lib::", &import)?;
+    let elf = names.render(
+        "/// The __TITLE__ program.\n\
+        pub const __ELF__: &[u8] = sp1_sdk::include_elf!(\"__PACKAGE__\");\n\n",
+    );
+    let anchors = ["/// Every program the scripts know about."];
+    let registry = insert_before_any(&registry, &anchors, &elf)?;
+    let entry = names.render(
+        "    Program {\n        name: \"__NAME__\",\n        \
+        dir: \"../__NAME__-program\",\n        package: \"__PACKAGE__\",\n        \
+        elf: __ELF__,\n        \
+        parse_input: parse___SNAKE___input,\n    },\n",
+    );
+    let anchors =
+        ["    #[cfg(feature = \"hash-bench\")]\n    Program {", "];\n\n/// Look up a program"];
+    let registry = insert_before_any(&registry, &anchors, &entry)?;
+    let parser = names.render(
+        "/// Parse a __TITLE__ program input: a JSON [`__CAMEL__Input`], checked so that an \
+        invalid\n/// one fails here rather than in the program.\n\
+        fn parse___SNAKE___input(input: &str) -> Result<SP1Stdin, ProverError> {\n    \
+        let input: __CAMEL__Input = serde_json::from_str(input)\n        \
+        .map_err(|err| ProverError::InvalidInput(format!(\"invalid input: {}\", err)))?;\n    \
+        input.public_values().map_err(ProverError::InvalidInput)?;\n    \
+        let mut stdin = SP1Stdin::new();\n    stdin.write(&input);\n    Ok(stdin)\n}\n\n",
+    );
+    let anchors = ["/// Parse a hashing input", "/// Record the vkey of a program"];
+    insert_before_any(&registry, &anchors, &parser)
+}
+
+/// Insert `text` before the first of `anchors` found in `contents`.
+fn insert_before_any(contents: &str, anchors: &[&str], text: &str) -> Option<String> {
+    let at = anchors.iter().find_map(|anchor| contents.find(anchor))?;
+    Some(format!("{}{}{}", &contents[..at], text, &contents[at..]))
+}
+
+/// Insert the line `line` among the lines of `contents` starting with `prefix`, keeping them in
+/// order: before the first such line that sorts after it (and any attributes on that line), or
+/// after the last one.
+fn insert_sorted(contents: &str, prefix: &str, line: &str) -> Option<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let matching: Vec<usize> =
+        (0..lines.len()).filter(|&i| lines[i].starts_with(prefix)).collect();
+    let at = match matching.iter().find(|&&i| lines[i] > line) {
+        Some(&i) => {
+            let mut i = i;
+            while i > 0 && lines[i - 1].trim_start().starts_with("#[") {
+                i -= 1;
+            }
+            i
+        }
+        None => matching.last()? + 1,
+    };
+    let mut edited: Vec<&str> = lines[..at].to_vec();
+    edited.push(line);
+    edited.extend_from_slice(&lines[at..]);
+    let mut edited = edited.join("\n");
+    if contents.ends_with('\n') {
+        edited.push('\n');
+    }
+    Some(edited)
+}
+
+fn read(path: &Path) -> Result<String, ScaffoldError> {
+    fs::read_to_string(path)
+        .map_err(|source| ScaffoldError::Io { path: path.to_path_buf(), source })
+}
@@ -0,0 +1,70 @@
+//! Graceful shutdown on SIGINT/SIGTERM for the long-running binaries.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// The exit code used when a second signal forces an immediate exit.
+pub const FORCED_EXIT_CODE: i32 = 130;
+
+/// Tracks whether the process has been asked to stop.
+///
+/// The first SIGINT/SIGTERM only sets the flag, so the current job can finish or checkpoint; a
+/// second one exits immediately.
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    requested: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl Shutdown {
+    /// Start listening for signals. Must be called from within a Tokio runtime.
+    pub fn listen() -> Self {
+        let shutdown = Self::default();
+        let listener = shutdown.clone();
+        tokio::spawn(async move {
+            loop {
+                if signal().await.is_err() {
+                    return;
+                }
+                if listener.requested.swap(true, Ordering::SeqCst) {
+                    eprintln!("🛑 Second signal received, exiting immediately");
+                    std::process::exit(FORCED_EXIT_CODE);
+                }
+                eprintln!("🛑 Shutting down after the current job (signal again to force)");
+                listener.notify.notify_waiters();
+            }
+        });
+        shutdown
+    }
+
+    /// Whether a shutdown has been requested.
+    pub fn requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Wait until a shutdown is requested.
+    pub async fn wait(&self) {
+        let notified = self.notify.notified();
+        if !self.requested() {
+            notified.await;
+        }
+    }
+}
+
+/// Wait for the next SIGINT or SIGTERM.
+#[cfg(unix)]
+async fn signal() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result,
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+/// Wait for the next Ctrl-C.
+#[cfg(not(unix))]
+async fn signal() -> std::io::Result<()> {
+    tokio::signal::ctrl_c().await
+}
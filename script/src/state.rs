@@ -0,0 +1,178 @@
+//! Fetching Ethereum state over RPC, for the programs that verify it.
+
+use crate::chain::ChainError;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256, U64};
+use alloy_provider::{Provider, ProviderBuilder};
+use fibonacci_lib::headers::BASE_HEADER_FIELDS;
+use fibonacci_lib::storage::StorageProofInput;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The fields of a header in the order of its RLP encoding, and whether each is an integer. The
+/// first [`BASE_HEADER_FIELDS`] are in every header; the others were added by later forks
+/// (London, Shanghai, Cancun, Prague), in this order, and are only encoded when present.
+const HEADER_FIELDS: [(&str, bool); 21] = [
+    ("parentHash", false),
+    ("sha3Uncles", false),
+    ("miner", false),
+    ("stateRoot", false),
+    ("transactionsRoot", false),
+    ("receiptsRoot", false),
+    ("logsBloom", false),
+    ("difficulty", true),
+    ("number", true),
+    ("gasLimit", true),
+    ("gasUsed", true),
+    ("timestamp", true),
+    ("extraData", false),
+    ("mixHash", false),
+    ("nonce", false),
+    ("baseFeePerGas", true),
+    ("withdrawalsRoot", false),
+    ("blobGasUsed", true),
+    ("excessBlobGas", true),
+    ("parentBeaconBlockRoot", false),
+    ("requestsHash", false),
+];
+
+/// A block, as far as the programs need it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub number: U64,
+    pub hash: B256,
+    pub state_root: B256,
+}
+
+/// A storage proof fetched with `eth_getProof`, and the block it was fetched at.
+#[derive(Debug, Clone)]
+pub struct FetchedStorageProof {
+    pub block: BlockHeader,
+    pub input: StorageProofInput,
+    /// The value the node claims the slot holds, which the proof should confirm.
+    pub value: U256,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountProof {
+    account_proof: Vec<Bytes>,
+    storage_proof: Vec<SlotProof>,
+}
+
+#[derive(Deserialize)]
+struct SlotProof {
+    value: U256,
+    proof: Vec<Bytes>,
+}
+
+/// Fetch the header of `block` (`None` for the latest).
+pub async fn fetch_block(rpc_url: &str, block: Option<u64>) -> Result<BlockHeader, ChainError> {
+    let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+    let tag = block_tag(block);
+    let header: Option<BlockHeader> =
+        provider.raw_request("eth_getBlockByNumber".into(), (&tag, false)).await?;
+    header.ok_or(ChainError::BlockNotFound(tag))
+}
+
+/// Fetch the proof of `slot` of `account` at `block` (`None` for the latest), as the input of the
+/// storage program.
+pub async fn fetch_storage_proof(
+    rpc_url: &str,
+    account: Address,
+    slot: B256,
+    block: Option<u64>,
+) -> Result<FetchedStorageProof, ChainError> {
+    let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+    // Resolve the block first, so the proof is fetched at the state root it is checked against.
+    let block = fetch_block(rpc_url, block).await?;
+    let proof: AccountProof = provider
+        .raw_request("eth_getProof".into(), (account, [slot], block_tag(Some(block.number.to()))))
+        .await?;
+    let slot_proof = proof.storage_proof.into_iter().next().unwrap_or(SlotProof {
+        value: U256::ZERO,
+        proof: Vec::new(),
+    });
+    Ok(FetchedStorageProof {
+        input: StorageProofInput {
+            state_root: block.state_root,
+            account,
+            slot,
+            account_proof: proof.account_proof,
+            storage_proof: slot_proof.proof,
+        },
+        value: slot_proof.value,
+        block,
+    })
+}
+
+/// Fetch the RLP-encoded headers of the blocks `from..=to`, as the input of the header chain
+/// program.
+///
+/// Nodes return headers as JSON, so each is re-encoded from its fields and checked against the
+/// hash the node reports, which catches fields of a fork this encoding does not know.
+pub async fn fetch_raw_headers(
+    rpc_url: &str,
+    from: u64,
+    to: u64,
+) -> Result<Vec<Bytes>, ChainError> {
+    let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
+    let mut headers = Vec::new();
+    for number in from..=to {
+        let tag = block_tag(Some(number));
+        let block: Option<Value> =
+            provider.raw_request("eth_getBlockByNumber".into(), (&tag, false)).await?;
+        let block = block.ok_or(ChainError::BlockNotFound(tag))?;
+        let encoded = encode_header(&block).ok_or(ChainError::HeaderEncoding(number))?;
+        let hash = block["hash"].as_str().and_then(|hash| hash.parse::<B256>().ok());
+        if hash != Some(keccak256(&encoded)) {
+            return Err(ChainError::HeaderEncoding(number));
+        }
+        headers.push(encoded.into());
+    }
+    Ok(headers)
+}
+
+/// RLP-encode a header from the fields of a JSON-RPC block, or `None` if a field is missing or
+/// malformed.
+fn encode_header(block: &Value) -> Option<Vec<u8>> {
+    let mut payload = Vec::new();
+    for (index, (field, integer)) in HEADER_FIELDS.into_iter().enumerate() {
+        let Some(value) = block.get(field).and_then(Value::as_str) else {
+            if index < BASE_HEADER_FIELDS {
+                return None;
+            }
+            // The fields of a fork are only there if those of the previous forks are.
+            break;
+        };
+        if integer {
+            encode_string(&value.parse::<U256>().ok()?.to_be_bytes_trimmed_vec(), &mut payload);
+        } else {
+            encode_string(&hex::decode(value.strip_prefix("0x")?).ok()?, &mut payload);
+        }
+    }
+    let mut encoded = Vec::new();
+    alloy_rlp::Header { list: true, payload_length: payload.len() }.encode(&mut encoded);
+    encoded.extend(payload);
+    Some(encoded)
+}
+
+/// RLP-encode a string.
+fn encode_string(bytes: &[u8], out: &mut Vec<u8>) {
+    if let [byte] = bytes {
+        if *byte < 0x80 {
+            out.push(*byte);
+            return;
+        }
+    }
+    alloy_rlp::Header { list: false, payload_length: bytes.len() }.encode(out);
+    out.extend_from_slice(bytes);
+}
+
+/// The JSON-RPC block parameter for `block`.
+fn block_tag(block: Option<u64>) -> String {
+    match block {
+        Some(number) => format!("{:#x}", number),
+        None => "latest".to_string(),
+    }
+}
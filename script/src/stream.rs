@@ -0,0 +1,43 @@
+//! Writing a large payload to a program's stdin in chunks, for a program that reads it with
+//! [`fibonacci_lib::stream::ChunkReader`].
+
+use alloy_primitives::{Keccak256, B256};
+use fibonacci_lib::stream::StreamHeader;
+use sp1_sdk::SP1Stdin;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// Write the `len` bytes of `payload` to `stdin` as a [`StreamHeader`] followed by chunks of
+/// `chunk_size` bytes, and return the keccak256 hash the program computes of them. The payload
+/// is read one chunk at a time, e.g. from a file.
+pub fn write_chunked(
+    stdin: &mut SP1Stdin,
+    mut payload: impl Read,
+    len: u64,
+    chunk_size: usize,
+) -> io::Result<B256> {
+    let header = StreamHeader::new(len, chunk_size);
+    stdin.write(&header);
+    let mut hasher = Keccak256::new();
+    let mut remaining = len;
+    for _ in 0..header.chunks {
+        let mut chunk = vec![0; remaining.min(chunk_size as u64) as usize];
+        payload.read_exact(&mut chunk)?;
+        remaining -= chunk.len() as u64;
+        hasher.update(&chunk);
+        stdin.write_vec(chunk);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Write a file to `stdin` in chunks (see [`write_chunked`]).
+pub fn write_file_chunked(
+    stdin: &mut SP1Stdin,
+    path: &Path,
+    chunk_size: usize,
+) -> io::Result<B256> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    write_chunked(stdin, BufReader::new(file), len, chunk_size)
+}
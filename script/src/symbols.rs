@@ -0,0 +1,108 @@
+//! The function symbols of a guest ELF, for mapping program counters from traces and panics back
+//! to the functions they are in.
+
+/// A function in the ELF's symbol table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// The demangled name, without the trailing hash.
+    pub name: String,
+    pub address: u32,
+    pub size: u32,
+}
+
+impl Symbol {
+    /// Whether `pc` is inside the function.
+    pub fn contains(&self, pc: u32) -> bool {
+        pc >= self.address && pc - self.address < self.size.max(1)
+    }
+}
+
+/// `SHT_SYMTAB`.
+const SECTION_SYMTAB: u32 = 2;
+/// `STT_FUNC`.
+const SYMBOL_FUNC: u8 = 2;
+
+/// Read the function symbols of a 32-bit little-endian ELF (as built for the SP1 zkVM), sorted by
+/// address.
+pub fn function_symbols(elf: &[u8]) -> Result<Vec<Symbol>, String> {
+    if elf.get(..6) != Some(b"\x7fELF\x01\x01".as_slice()) {
+        return Err("not a 32-bit little-endian ELF".to_string());
+    }
+    let section_offset = read_u32(elf, 0x20)? as usize;
+    let section_size = read_u16(elf, 0x2e)? as usize;
+    let section_count = read_u16(elf, 0x30)? as usize;
+    let section = |index: usize| section_offset + index * section_size;
+
+    let mut symbols = Vec::new();
+    for index in 0..section_count {
+        let header = section(index);
+        if read_u32(elf, header + 4)? != SECTION_SYMTAB {
+            continue;
+        }
+        let (offset, size) = (read_u32(elf, header + 16)?, read_u32(elf, header + 20)?);
+        let strings = section(read_u32(elf, header + 24)? as usize);
+        let strings_offset = read_u32(elf, strings + 16)? as usize;
+        for entry in (offset..offset + size).step_by(16) {
+            let entry = entry as usize;
+            if elf.get(entry + 12).is_some_and(|info| info & 0xf == SYMBOL_FUNC) {
+                let name = read_str(elf, strings_offset + read_u32(elf, entry)? as usize)?;
+                symbols.push(Symbol {
+                    name: demangle(name),
+                    address: read_u32(elf, entry + 4)?,
+                    size: read_u32(elf, entry + 8)?,
+                });
+            }
+        }
+    }
+    symbols.sort_by_key(|symbol| symbol.address);
+    Ok(symbols)
+}
+
+/// The function containing `pc`, if any.
+pub fn symbolize(symbols: &[Symbol], pc: u32) -> Option<&Symbol> {
+    symbols.iter().rev().find(|symbol| symbol.contains(pc))
+}
+
+/// Demangle a legacy Rust symbol (`_ZN3foo3bar17h0123456789abcdefE` is `foo::bar`), leaving other
+/// names as they are.
+pub fn demangle(name: &str) -> String {
+    let Some(mut rest) = name.strip_prefix("_ZN") else {
+        return name.to_string();
+    };
+    let mut segments = Vec::new();
+    while let Some(len_end) = rest.find(|c: char| !c.is_ascii_digit()).filter(|&end| end > 0) {
+        let Ok(len) = rest[..len_end].parse::<usize>() else {
+            break;
+        };
+        let Some(segment) = rest.get(len_end..len_end + len) else {
+            break;
+        };
+        segments.push(segment);
+        rest = &rest[len_end + len..];
+    }
+    if rest != "E" || segments.is_empty() {
+        return name.to_string();
+    }
+    if segments.last().is_some_and(|hash| hash.len() == 17 && hash.starts_with('h')) {
+        segments.pop();
+    }
+    segments.join("::")
+}
+
+fn read_u32(elf: &[u8], offset: usize) -> Result<u32, String> {
+    elf.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("slice has 4 bytes")))
+        .ok_or_else(|| format!("truncated ELF at offset {:#x}", offset))
+}
+
+fn read_u16(elf: &[u8], offset: usize) -> Result<u16, String> {
+    elf.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes(bytes.try_into().expect("slice has 2 bytes")))
+        .ok_or_else(|| format!("truncated ELF at offset {:#x}", offset))
+}
+
+fn read_str(elf: &[u8], offset: usize) -> Result<&str, String> {
+    let bytes = elf.get(offset..).ok_or_else(|| format!("truncated ELF at offset {:#x}", offset))?;
+    let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..end]).map_err(|_| format!("invalid symbol name at {:#x}", offset))
+}
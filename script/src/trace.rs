@@ -0,0 +1,80 @@
+//! Summaries of `debug_traceCall` traces (geth's `callTracer` format) for failed calls.
+
+use alloy_primitives::{Address, Bytes};
+use serde::Deserialize;
+
+/// A call frame as reported by `callTracer`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// The call type, e.g. `CALL` or `STATICCALL`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub from: Address,
+    #[serde(default)]
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub input: Bytes,
+    #[serde(default)]
+    pub output: Option<Bytes>,
+    /// Why the frame failed, e.g. `execution reverted` or `invalid opcode: INVALID`.
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub revert_reason: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+}
+
+/// The `callTracer` options passed to `debug_traceCall`.
+pub fn call_tracer_options() -> serde_json::Value {
+    serde_json::json!({ "tracer": "callTracer" })
+}
+
+impl CallFrame {
+    /// Whether the frame failed.
+    pub fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// The 4-byte selector of the called function, if any.
+    pub fn selector(&self) -> Option<&[u8]> {
+        self.input.get(..4)
+    }
+
+    /// The innermost failed frame on the failing path, i.e. where the revert originated.
+    ///
+    /// A failed child whose failure was caught by its parent is ignored, since only the last
+    /// failed child of a failed frame can have caused its failure.
+    pub fn failing_frame(&self) -> Option<&CallFrame> {
+        if !self.failed() {
+            return None;
+        }
+        match self.calls.iter().rev().find(|call| call.failed()) {
+            Some(child) if self.reverted_with_child(child) => child.failing_frame(),
+            _ => Some(self),
+        }
+    }
+
+    /// The first frame (in call order) that calls `to`, including this one.
+    pub fn find_call_to(&self, to: Address) -> Option<&CallFrame> {
+        if self.to == Some(to) {
+            return Some(self);
+        }
+        self.calls.iter().find_map(|call| call.find_call_to(to))
+    }
+
+    /// Whether `frame` is this frame or one of its descendants.
+    pub fn contains(&self, frame: &CallFrame) -> bool {
+        std::ptr::eq(self, frame) || self.calls.iter().any(|call| call.contains(frame))
+    }
+
+    /// Whether the failed `child` is what made this frame fail: either the revert data was
+    /// bubbled up unchanged, or the child failed without any data (e.g. out of gas).
+    fn reverted_with_child(&self, child: &CallFrame) -> bool {
+        match (&self.output, &child.output) {
+            (Some(output), Some(child_output)) => output == child_output,
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Discovery of the SP1 verifier gateway proofs are verified through on each chain.
+//!
+//! Succinct deploys one gateway per proof system on each supported chain. The built-in table
+//! lists the official deployments by chain id and SP1 version; a verifier book (`verifiers.toml`
+//! by default, overridable with `VERIFIER_BOOK`) adds chains or replaces entries:
+//!
+//! ```toml
+//! [31337]
+//! v5-groth16 = "0x397A5f7f3dBd538f23DE225B51f532c34448dA9B"
+//! v5-plonk = "0x3B6041173B80E77f038f3F2C0f9744f04837185e"
+//! ```
+//!
+//! Whichever way a gateway is found, it is checked on-chain before use: it must route the
+//! verifier selector of this SP1 version to a live verifier whose `VERIFIER_HASH()` and
+//! `VERSION()` match.
+
+use crate::prover::ProofSystem;
+use alloy_primitives::{address, Address, FixedBytes, B256};
+use alloy_provider::Provider;
+use alloy_sol_types::sol;
+use alloy_transport::{Transport, TransportError};
+use fibonacci_verifier_wasm::verifier_hash;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// The SP1 major version proofs are generated with (that of `sp1-sdk` in `Cargo.toml`).
+pub const SP1_VERSION: &str = "v5";
+
+/// The verifier book used when `VERIFIER_BOOK` is not set.
+const DEFAULT_VERIFIER_BOOK: &str = "verifiers.toml";
+
+const GROTH16_GATEWAY: Address = address!("397A5f7f3dBd538f23DE225B51f532c34448dA9B");
+const PLONK_GATEWAY: Address = address!("3B6041173B80E77f038f3F2C0f9744f04837185e");
+
+/// The official gateway deployments, by chain id and SP1 version. The gateways are deployed at
+/// the same addresses on every chain.
+const DEPLOYMENTS: &[(u64, &str, ProofSystem, Address)] = &[
+    (1, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (1, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (10, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (10, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (8453, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (8453, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (42161, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (42161, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (84532, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (84532, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (421614, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (421614, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (11155111, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (11155111, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+    (11155420, "v5", ProofSystem::Groth16, GROTH16_GATEWAY),
+    (11155420, "v5", ProofSystem::Plonk, PLONK_GATEWAY),
+];
+
+sol! {
+    #[sol(rpc)]
+    interface ISP1VerifierGateway {
+        function routes(bytes4 selector) external view returns (address verifier, bool frozen);
+    }
+
+    #[sol(rpc)]
+    interface ISP1VerifierWithHash {
+        function VERIFIER_HASH() external pure returns (bytes32);
+        function VERSION() external pure returns (string memory);
+    }
+}
+
+/// Errors from resolving or checking a verifier gateway.
+#[derive(Debug, Error)]
+pub enum VerifierError {
+    #[error("RPC request failed: {0}")]
+    Rpc(#[from] TransportError),
+    #[error("verifier query failed: {0}")]
+    Call(#[from] alloy_contract::Error),
+    #[error("failed to read verifier book {path}: {reason}")]
+    InvalidBook { path: String, reason: String },
+    #[error(
+        "no {system} verifier gateway is known for SP1 {version} on chain {chain_id}; add one to \
+         the verifier book or pass it explicitly"
+    )]
+    UnknownChain { chain_id: u64, version: &'static str, system: ProofSystem },
+    #[error("gateway {gateway} has no verifier for selector {selector}")]
+    NoRoute { gateway: Address, selector: FixedBytes<4> },
+    #[error("gateway {gateway} has frozen its verifier {verifier}")]
+    Frozen { gateway: Address, verifier: Address },
+    #[error("verifier {verifier} has VERIFIER_HASH {actual}, expected {expected}")]
+    WrongHash { verifier: Address, expected: B256, actual: B256 },
+    #[error("verifier {verifier} is version {version}, expected {}", SP1_VERSION)]
+    WrongVersion { verifier: Address, version: String },
+}
+
+/// Where a gateway address came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GatewaySource {
+    /// Given on the command line or in the environment.
+    Explicit,
+    /// An entry of the verifier book at this path.
+    Book(String),
+    /// The built-in table of official deployments.
+    BuiltIn,
+}
+
+impl fmt::Display for GatewaySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Explicit => write!(f, "explicit"),
+            Self::Book(path) => write!(f, "verifier book {}", path),
+            Self::BuiltIn => write!(f, "official deployments"),
+        }
+    }
+}
+
+/// A gateway checked on-chain, and the verifier it routes this SP1 version's proofs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedGateway {
+    pub chain_id: u64,
+    pub system: ProofSystem,
+    pub gateway: Address,
+    pub source: GatewaySource,
+    pub selector: FixedBytes<4>,
+    pub verifier: Address,
+    /// The verifier's `VERSION()`, e.g. `v5.0.0`.
+    pub version: String,
+}
+
+/// Gateway entries by chain id (as a string, since TOML keys are strings) and
+/// `<version>-<system>`.
+type VerifierBook = BTreeMap<String, BTreeMap<String, Address>>;
+
+/// Find the `system` gateway for the chain served by `provider` (unless `explicit` is given) and
+/// check that it routes this SP1 version's proofs to a matching verifier.
+pub async fn resolve_gateway<T, P>(
+    provider: &P,
+    system: ProofSystem,
+    explicit: Option<Address>,
+) -> Result<ResolvedGateway, VerifierError>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let chain_id = provider.get_chain_id().await?;
+    let (gateway, source) = match explicit {
+        Some(gateway) => (gateway, GatewaySource::Explicit),
+        None => lookup_gateway(chain_id, system)?,
+    };
+    let expected = B256::from(verifier_hash(wasm_system(system)));
+    let selector = FixedBytes::<4>::from_slice(&expected[..4]);
+
+    let route = ISP1VerifierGateway::new(gateway, provider).routes(selector).call().await?;
+    if route.verifier.is_zero() {
+        return Err(VerifierError::NoRoute { gateway, selector });
+    }
+    // A frozen route keeps its verifier but rejects every proof.
+    if route.frozen {
+        return Err(VerifierError::Frozen { gateway, verifier: route.verifier });
+    }
+    let verifier = ISP1VerifierWithHash::new(route.verifier, provider);
+    let actual = verifier.VERIFIER_HASH().call().await?._0;
+    if actual != expected {
+        return Err(VerifierError::WrongHash { verifier: route.verifier, expected, actual });
+    }
+    let version = verifier.VERSION().call().await?._0;
+    if version != SP1_VERSION && !version.starts_with(&format!("{}.", SP1_VERSION)) {
+        return Err(VerifierError::WrongVersion { verifier: route.verifier, version });
+    }
+    Ok(ResolvedGateway {
+        chain_id,
+        system,
+        gateway,
+        source,
+        selector,
+        verifier: route.verifier,
+        version,
+    })
+}
+
+/// Look up the gateway for a chain in the verifier book, then in the built-in table.
+fn lookup_gateway(
+    chain_id: u64,
+    system: ProofSystem,
+) -> Result<(Address, GatewaySource), VerifierError> {
+    let path = env::var("VERIFIER_BOOK").unwrap_or_else(|_| DEFAULT_VERIFIER_BOOK.to_string());
+    if Path::new(&path).exists() {
+        let invalid = |reason: String| VerifierError::InvalidBook { path: path.clone(), reason };
+        let contents = fs::read_to_string(&path).map_err(|err| invalid(err.to_string()))?;
+        let book: VerifierBook =
+            toml::from_str(&contents).map_err(|err| invalid(err.to_string()))?;
+        let key = format!("{}-{}", SP1_VERSION, system);
+        if let Some(gateway) = book.get(&chain_id.to_string()).and_then(|entries| entries.get(&key))
+        {
+            return Ok((*gateway, GatewaySource::Book(path)));
+        }
+    }
+    DEPLOYMENTS
+        .iter()
+        .find(|(chain, version, kind, _)| {
+            *chain == chain_id && *version == SP1_VERSION && *kind == system
+        })
+        .map(|(.., gateway)| (*gateway, GatewaySource::BuiltIn))
+        .ok_or(VerifierError::UnknownChain { chain_id, version: SP1_VERSION, system })
+}
+
+/// The proof system of proof bytes, from the verifier selector they are prefixed with.
+pub fn proof_system(proof: &[u8]) -> Option<ProofSystem> {
+    match fibonacci_verifier_wasm::proof_system(proof)? {
+        fibonacci_verifier_wasm::ProofSystem::Groth16 => Some(ProofSystem::Groth16),
+        fibonacci_verifier_wasm::ProofSystem::Plonk => Some(ProofSystem::Plonk),
+    }
+}
+
+fn wasm_system(system: ProofSystem) -> fibonacci_verifier_wasm::ProofSystem {
+    match system {
+        ProofSystem::Groth16 => fibonacci_verifier_wasm::ProofSystem::Groth16,
+        ProofSystem::Plonk => fibonacci_verifier_wasm::ProofSystem::Plonk,
+    }
+}
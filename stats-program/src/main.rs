@@ -0,0 +1,14 @@
+//! The statistics program: computes the count, sum, mean, minimum and maximum of a private
+//! dataset and commits them with the hash of the dataset, without revealing its values.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::stats::StatsInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<StatsInput>();
+
+    let public_values = input.statistics().expect("the dataset must not be empty");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
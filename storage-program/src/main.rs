@@ -0,0 +1,14 @@
+//! The storage program: verifies an Ethereum storage proof against a state root and commits the
+//! state root, the account, the slot and the value it holds.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use fibonacci_lib::storage::StorageProofInput;
+
+pub fn main() {
+    let input = sp1_zkvm::io::read::<StorageProofInput>();
+
+    let public_values = input.verify().expect("the storage proof must be valid");
+    sp1_zkvm::io::commit_slice(&public_values.to_abi_bytes());
+}
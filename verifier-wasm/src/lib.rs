@@ -0,0 +1,137 @@
+//! Off-chain verification of the Fibonacci program's Groth16 and PLONK proofs.
+//!
+//! Used natively by `cli verify-wrapped`, and compiled to WebAssembly so dapp frontends can check
+//! a proof client-side before submitting it:
+//! ```shell
+//! wasm-pack build verifier-wasm --target web
+//! ```
+
+use sha2::{Digest, Sha256};
+use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// The proof systems whose wrapped proofs can be verified off-chain.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ProofSystem {
+    Plonk,
+    Groth16,
+}
+
+/// Why a proof could not be verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The proof is not prefixed with the selector of a supported verifier.
+    UnknownVerifier,
+    /// The proof does not verify against the public values and vkey hash.
+    Invalid(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownVerifier => {
+                write!(f, "proof is not a Groth16 or PLONK proof for this SP1 version")
+            }
+            Self::Invalid(reason) => write!(f, "proof is invalid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// The Groth16 verifying key for this SP1 version, in gnark's binary format (`cli export` reads
+/// its points).
+pub fn groth16_vk() -> &'static [u8] {
+    &GROTH16_VK_BYTES
+}
+
+/// The PLONK verifying key for this SP1 version, in gnark's binary format.
+pub fn plonk_vk() -> &'static [u8] {
+    &PLONK_VK_BYTES
+}
+
+/// The hash identifying the on-chain verifier of a proof system for this SP1 version (its
+/// `VERIFIER_HASH()`), whose first 4 bytes prefix every proof as the verifier selector.
+pub fn verifier_hash(system: ProofSystem) -> [u8; 32] {
+    let vk = match system {
+        ProofSystem::Groth16 => groth16_vk(),
+        ProofSystem::Plonk => plonk_vk(),
+    };
+    Sha256::digest(vk).into()
+}
+
+/// The proof system of a proof, from the 4-byte verifier selector it is prefixed with.
+pub fn proof_system(proof: &[u8]) -> Option<ProofSystem> {
+    let selector = proof.get(..4)?;
+    if selector == &verifier_hash(ProofSystem::Groth16)[..4] {
+        Some(ProofSystem::Groth16)
+    } else if selector == &verifier_hash(ProofSystem::Plonk)[..4] {
+        Some(ProofSystem::Plonk)
+    } else {
+        None
+    }
+}
+
+/// Verify the proof bytes (as passed to the on-chain verifier) against the ABI-encoded public
+/// values and the `0x`-prefixed program vkey hash.
+pub fn verify_proof(
+    proof: &[u8],
+    public_values: &[u8],
+    vkey_hash: &str,
+) -> Result<ProofSystem, VerifyError> {
+    let system = proof_system(proof).ok_or(VerifyError::UnknownVerifier)?;
+    let vkey_hash = format!("0x{}", vkey_hash.trim().trim_start_matches("0x"));
+    match system {
+        ProofSystem::Groth16 => {
+            Groth16Verifier::verify(proof, public_values, &vkey_hash, groth16_vk())
+                .map_err(|err| VerifyError::Invalid(err.to_string()))?
+        }
+        ProofSystem::Plonk => {
+            PlonkVerifier::verify(proof, public_values, &vkey_hash, plonk_vk())
+                .map_err(|err| VerifyError::Invalid(err.to_string()))?
+        }
+    }
+    Ok(system)
+}
+
+/// Verify a proof from JavaScript. Returns `"groth16"` or `"plonk"`, and throws if the proof is
+/// invalid.
+#[wasm_bindgen]
+pub fn verify(proof: &[u8], public_values: &[u8], vkey_hash: &str) -> Result<String, JsError> {
+    match verify_proof(proof, public_values, vkey_hash) {
+        Ok(ProofSystem::Groth16) => Ok("groth16".to_string()),
+        Ok(ProofSystem::Plonk) => Ok("plonk".to_string()),
+        Err(err) => Err(JsError::new(&err.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The proof, public values and vkey hash of a fixture the Solidity tests use.
+    fn fixture(system: &str) -> (Vec<u8>, Vec<u8>, String) {
+        let path = format!(
+            "{}/../contracts/src/fixtures/{}-fixture.json",
+            env!("CARGO_MANIFEST_DIR"),
+            system
+        );
+        let fixture: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let field = |name: &str| fixture[name].as_str().unwrap().to_string();
+        let bytes = |name: &str| hex::decode(field(name).trim_start_matches("0x")).unwrap();
+        (bytes("proof"), bytes("publicValues"), field("vkey"))
+    }
+
+    // The Solidity tests mock the SP1 verifier, so they pass with stale fixtures too.
+    #[test]
+    #[ignore = "the fixtures predate SP1 v5 and the current public values layout: regenerate them \
+                with `cargo run --release --bin evm -- --system <groth16|plonk>`"]
+    fn fixtures_are_valid_proofs() {
+        for (name, system) in [("groth16", ProofSystem::Groth16), ("plonk", ProofSystem::Plonk)] {
+            let (proof, public_values, vkey) = fixture(name);
+            assert_eq!(verify_proof(&proof, &public_values, &vkey), Ok(system), "{}", name);
+        }
+    }
+}